@@ -1,15 +1,41 @@
 import BN from "bn.js";
-import {PublicKey, TransactionInstruction} from "@solana/web3.js";
+import {AccountMeta, PublicKey, TransactionInstruction} from "@solana/web3.js";
 import {Program} from "@project-serum/anchor";
 import {VaultZeta} from "../artifacts/types/vault_zeta";
 import {getVaultInfo} from "../pda/vault";
-import {CLOCK_PROGRAM_ID, SOLEND_PROGRAM_ID, TOKEN_PROGRAM_ID} from "../pubkeys";
+import {CLOCK_PROGRAM_ID, SOLEND_PROGRAM_ID, TOKEN_PROGRAM_ID, ZETA_PROGRAM_ID} from "../pubkeys";
 import {Reserve} from "../structs/solend";
 import {getLendingMarketAuthority} from "../pda/solend";
 import {Vault} from "../structs/vault";
+import {ZetaGroup} from "../structs/zeta-markets";
+import {getMarginAccount, getSocializedLossAccount, getState, getZetaVault} from "../pda/zeta-markets";
 
 
 
+// Zeta accounts paying the part of a withdrawal the Solend reserve
+// liquidity doesn't cover, ordered as `MARGIN_WITHDRAW_ACCOUNTS`
+export const getMarginWithdrawAccounts = async (
+  vault: Vault,
+  group: ZetaGroup,
+): Promise<AccountMeta[]> => {
+  const {executor} = await getVaultInfo(vault.publicKey);
+  const [state] = await getState();
+  const marginAccount = await getMarginAccount(group.publicKey, executor);
+  const [socializedLossAccount] = await getSocializedLossAccount(group.publicKey);
+  const [zetaVault] = await getZetaVault(group.publicKey);
+  return [
+    {pubkey: group.publicKey, isSigner: false, isWritable: false},
+    {pubkey: marginAccount, isSigner: false, isWritable: true},
+    {pubkey: zetaVault, isSigner: false, isWritable: true},
+    {pubkey: socializedLossAccount, isSigner: false, isWritable: true},
+    {pubkey: state, isSigner: false, isWritable: false},
+    {pubkey: group.greeks, isSigner: false, isWritable: false},
+    {pubkey: group.oracle, isSigner: false, isWritable: false},
+    {pubkey: ZETA_PROGRAM_ID, isSigner: false, isWritable: false},
+    {pubkey: group.oracle, isSigner: false, isWritable: false},
+  ];
+}
+
 export const createWithdrawIx = async (
   amountIn: BN,
   authority: PublicKey,
@@ -17,7 +43,8 @@ export const createWithdrawIx = async (
   userSharesAccount: PublicKey,
   vault: Vault,
   reserve: Reserve,
-  program: Program<VaultZeta>
+  program: Program<VaultZeta>,
+  group?: ZetaGroup,
 ): Promise<TransactionInstruction> => {
   const {sharesMint, executor} = await getVaultInfo(vault.publicKey);
   const lendingMarketAuthority = await getLendingMarketAuthority(reserve.lendingMarket);
@@ -40,5 +67,6 @@ export const createWithdrawIx = async (
       tokenProgram: TOKEN_PROGRAM_ID,
       lendingProgram: SOLEND_PROGRAM_ID
     })
+    .remainingAccounts(group ? await getMarginWithdrawAccounts(vault, group) : [])
     .instruction();
 }
\ No newline at end of file
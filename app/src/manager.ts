@@ -158,10 +158,12 @@ export class Manager {
     userTokenAccount: PublicKey,
     userSharesAccount: PublicKey,
     vaultAddress: PublicKey,
-    simulate = false
+    simulate = false,
+    fromMargin = false
   ) {
     const vault = this.validate<Vault>(vaultAddress);
     const reserve = this.validate<Reserve>(vault.reserve);
+    const group = fromMargin ? this.validate<ZetaGroup>(vault.zetaGroup) : undefined;
     return this.exec([
       await createWithdrawIx(
         amountOut,
@@ -170,7 +172,8 @@ export class Manager {
         userSharesAccount,
         vault,
         reserve,
-        this.program
+        this.program,
+        group
       ),
     ], [user], simulate);
   }
@@ -22,7 +22,8 @@ export const getVaultInfo = async (vault: PublicKey, programId = VAULT_ZETA_PROG
   );
 
   const [executor] = await PublicKey.findProgramAddress(
-    [Buffer.from('executor'), vault.toBuffer()],
+    // version 0, `rotate_executor` moves vaults to the next one
+    [Buffer.from('executor'), vault.toBuffer(), Buffer.from([0])],
     programId
   );
 
@@ -0,0 +1,49 @@
+//! Account fixtures for program-test. Layouts follow the Solend `Pack`
+//! implementations mirrored in `solend_cpi`.
+use anchor_lang::prelude::*;
+
+pub const RESERVE_LEN: usize = 619;
+// 10^18, the scale of Solend's `Decimal`
+pub const WAD: u128 = 1_000_000_000_000_000_000;
+
+pub struct ReserveFixture {
+  pub lending_market: Pubkey,
+  pub liquidity_mint: Pubkey,
+  pub liquidity_decimals: u8,
+  pub liquidity_supply: Pubkey,
+  pub pyth_oracle: Pubkey,
+  pub collateral_mint: Pubkey,
+  pub collateral_supply: Pubkey,
+  pub fee_receiver: Pubkey,
+  // quote value of one whole liquidity token, in whole units
+  pub market_price: u64,
+}
+
+impl ReserveFixture {
+  /// Authority the mock signs reserve transfers and collateral mints with.
+  pub fn market_authority(&self) -> Pubkey {
+    Pubkey::find_program_address(&[self.lending_market.as_ref()], &crate::ID).0
+  }
+
+  /// Serialized reserve with zero collateral supply, which Solend reads as
+  /// the initial 1:1 exchange rate, matching what the mock program pays out.
+  pub fn data(&self) -> Vec<u8> {
+    let mut data = vec![0u8; RESERVE_LEN];
+    data[0] = 1;
+    data[10..42].copy_from_slice(self.lending_market.as_ref());
+    data[42..74].copy_from_slice(self.liquidity_mint.as_ref());
+    data[74] = self.liquidity_decimals;
+    data[75..107].copy_from_slice(self.liquidity_supply.as_ref());
+    data[107..139].copy_from_slice(self.pyth_oracle.as_ref());
+    data[211..227].copy_from_slice(&(self.market_price as u128 * WAD).to_le_bytes());
+    data[227..259].copy_from_slice(self.collateral_mint.as_ref());
+    data[267..299].copy_from_slice(self.collateral_supply.as_ref());
+    // optimal utilization, ltv, liquidation bonus, liquidation threshold,
+    // min / optimal / max borrow rate
+    data[299..306].copy_from_slice(&[80, 75, 5, 80, 0, 4, 30]);
+    data[323..331].copy_from_slice(&u64::MAX.to_le_bytes());
+    data[331..339].copy_from_slice(&u64::MAX.to_le_bytes());
+    data[339..371].copy_from_slice(self.fee_receiver.as_ref());
+    data
+  }
+}
@@ -0,0 +1,140 @@
+//! Minimal stand-in for the Solend lending program.
+//! Only the instructions vault-zeta calls are implemented, the exchange rate
+//! is always 1:1 and reserve state is never updated.
+use std::convert::TryInto;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_spl::token::{self, Burn, MintTo, Transfer};
+
+pub mod fixtures;
+
+declare_id!("ALend7Ketfx5bxh6ghsCDXAoDrhvEmsXT3cynB6aPLgx");
+
+#[cfg(not(feature = "no-entrypoint"))]
+anchor_lang::solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: &[u8],
+) -> ProgramResult {
+  let (tag, rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+  let amount = rest
+    .get(..8)
+    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    .unwrap_or(0);
+  let result = match tag {
+    // refresh reserve / refresh obligation
+    3 | 7 => Ok(()),
+    4 => deposit_reserve_liquidity(program_id, accounts, amount),
+    5 => redeem_reserve_collateral(program_id, accounts, amount),
+    6 => Ok(()),
+    8 => deposit_obligation_collateral(accounts, amount),
+    10 => borrow_obligation_liquidity(program_id, accounts, amount),
+    _ => return Err(ProgramError::InvalidInstructionData),
+  };
+  result.map_err(Into::into)
+}
+
+fn market_authority_seeds(program_id: &Pubkey, lending_market: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[lending_market.as_ref()], program_id)
+}
+
+// source_liquidity, destination_collateral, reserve, reserve_liquidity_supply,
+// reserve_collateral_mint, lending_market, lending_market_authority,
+// user_transfer_authority, token_program
+fn deposit_reserve_liquidity(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> Result<()> {
+  let [source_liquidity, destination_collateral, _reserve, liquidity_supply, collateral_mint,
+    lending_market, market_authority, transfer_authority, token_program] = take::<9>(accounts)?;
+  let (_, bump) = market_authority_seeds(program_id, lending_market.key);
+  let market_key = lending_market.key();
+  let seeds: &[&[u8]] = &[market_key.as_ref(), &[bump]];
+
+  token::transfer(
+    CpiContext::new(token_program.clone(), Transfer {
+      from: source_liquidity.clone(),
+      to: liquidity_supply.clone(),
+      authority: transfer_authority.clone(),
+    }),
+    amount,
+  )?;
+  token::mint_to(
+    CpiContext::new_with_signer(token_program.clone(), MintTo {
+      mint: collateral_mint.clone(),
+      to: destination_collateral.clone(),
+      authority: market_authority.clone(),
+    }, &[seeds]),
+    amount,
+  )
+}
+
+// source_collateral, destination_liquidity, reserve, reserve_collateral_mint,
+// reserve_liquidity_supply, lending_market, lending_market_authority,
+// user_transfer_authority, token_program
+fn redeem_reserve_collateral(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> Result<()> {
+  let [source_collateral, destination_liquidity, _reserve, collateral_mint, liquidity_supply,
+    lending_market, market_authority, transfer_authority, token_program] = take::<9>(accounts)?;
+  let (_, bump) = market_authority_seeds(program_id, lending_market.key);
+  let market_key = lending_market.key();
+  let seeds: &[&[u8]] = &[market_key.as_ref(), &[bump]];
+
+  token::burn(
+    CpiContext::new(token_program.clone(), Burn {
+      mint: collateral_mint.clone(),
+      from: source_collateral.clone(),
+      authority: transfer_authority.clone(),
+    }),
+    amount,
+  )?;
+  token::transfer(
+    CpiContext::new_with_signer(token_program.clone(), Transfer {
+      from: liquidity_supply.clone(),
+      to: destination_liquidity.clone(),
+      authority: market_authority.clone(),
+    }, &[seeds]),
+    amount,
+  )
+}
+
+// source_collateral, destination_collateral, deposit_reserve, obligation,
+// lending_market, obligation_owner, user_transfer_authority, token_program
+fn deposit_obligation_collateral(accounts: &[AccountInfo], amount: u64) -> Result<()> {
+  let [source_collateral, destination_collateral, _reserve, _obligation, _lending_market,
+    _owner, transfer_authority, token_program] = take::<8>(accounts)?;
+  token::transfer(
+    CpiContext::new(token_program.clone(), Transfer {
+      from: source_collateral.clone(),
+      to: destination_collateral.clone(),
+      authority: transfer_authority.clone(),
+    }),
+    amount,
+  )
+}
+
+// source_liquidity, destination_liquidity, borrow_reserve, fee_receiver,
+// obligation, lending_market, lending_market_authority, obligation_owner, token_program
+fn borrow_obligation_liquidity(program_id: &Pubkey, accounts: &[AccountInfo], amount: u64) -> Result<()> {
+  let [liquidity_supply, destination_liquidity, _reserve, _fee_receiver, _obligation,
+    lending_market, market_authority, _owner, token_program] = take::<9>(accounts)?;
+  let (_, bump) = market_authority_seeds(program_id, lending_market.key);
+  let market_key = lending_market.key();
+  let seeds: &[&[u8]] = &[market_key.as_ref(), &[bump]];
+
+  token::transfer(
+    CpiContext::new_with_signer(token_program.clone(), Transfer {
+      from: liquidity_supply.clone(),
+      to: destination_liquidity.clone(),
+      authority: market_authority.clone(),
+    }, &[seeds]),
+    amount,
+  )
+}
+
+fn take<'a, 'info, const N: usize>(
+  accounts: &'a [AccountInfo<'info>],
+) -> Result<&'a [AccountInfo<'info>; N]> {
+  accounts
+    .get(..N)
+    .and_then(|accounts| accounts.try_into().ok())
+    .ok_or_else(|| ProgramError::NotEnoughAccountKeys.into())
+}
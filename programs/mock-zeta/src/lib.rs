@@ -0,0 +1,168 @@
+//! Minimal stand-in for the Zeta dex program.
+//! Instruction names and argument layouts match `ZetaInterface` so the vault's
+//! CPI calls land here unchanged. Accounts are read positionally from
+//! `remaining_accounts` in the order of the vault's `zeta_context` structs.
+//! Margin accounts only track a USDC balance; order instructions are no-ops.
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{hash::hash, program::invoke_signed, system_instruction};
+use anchor_spl::token::{self, Transfer};
+
+declare_id!("BG3oRikW8d16YjUEmX3ZxHm9SiJzrGtMhsSR8aCw1Cd7");
+
+pub const MARGIN_ACCOUNT_LEN: usize = 8 + 6144;
+// discriminator + authority + nonce
+const BALANCE_OFFSET: usize = 8 + 32 + 1;
+
+#[program]
+pub mod mock_zeta {
+  use super::*;
+
+  // margin_account, authority, payer, zeta_program, system_program, zeta_group
+  pub fn initialize_margin_account(ctx: Context<Any>) -> Result<()> {
+    let accounts = take(ctx.remaining_accounts, 6)?;
+    let (margin_account, authority, payer) = (&accounts[0], &accounts[1], &accounts[2]);
+    let (system_program, zeta_group) = (&accounts[4], &accounts[5]);
+    let (address, nonce) = margin_account_address(zeta_group.key, authority.key);
+    require!(address == margin_account.key(), ErrorCode::ConstraintSeeds);
+
+    let lamports = Rent::get()?.minimum_balance(MARGIN_ACCOUNT_LEN);
+    invoke_signed(
+      &system_instruction::create_account(
+        payer.key,
+        margin_account.key,
+        lamports,
+        MARGIN_ACCOUNT_LEN as u64,
+        &crate::ID,
+      ),
+      &[payer.clone(), margin_account.clone(), system_program.clone()],
+      &[&[b"margin", zeta_group.key.as_ref(), authority.key.as_ref(), &[nonce]]],
+    )?;
+
+    let mut data = margin_account.try_borrow_mut_data()?;
+    data[..8].copy_from_slice(&hash(b"account:MarginAccount").to_bytes()[..8]);
+    data[8..40].copy_from_slice(authority.key.as_ref());
+    data[40] = nonce;
+    Ok(())
+  }
+
+  // zeta_group, margin_account, vault, user_token_account, socialized_loss_account,
+  // authority, token_program, state, greeks
+  pub fn deposit(ctx: Context<Any>, amount: u64) -> Result<()> {
+    let accounts = take(ctx.remaining_accounts, 7)?;
+    let (margin_account, vault, user_token_account) = (&accounts[1], &accounts[2], &accounts[3]);
+    let (authority, token_program) = (&accounts[5], &accounts[6]);
+    token::transfer(
+      CpiContext::new(token_program.clone(), Transfer {
+        from: user_token_account.clone(),
+        to: vault.clone(),
+        authority: authority.clone(),
+      }),
+      amount,
+    )?;
+    let balance = read_balance(margin_account)?;
+    write_balance(margin_account, balance.checked_add(amount).unwrap())
+  }
+
+  // state, zeta_group, vault, margin_account, user_token_account, token_program,
+  // authority, greeks, oracle, socialized_loss_account
+  pub fn withdraw(ctx: Context<Any>, amount: u64) -> Result<()> {
+    let accounts = take(ctx.remaining_accounts, 6)?;
+    let (zeta_group, vault, margin_account) = (&accounts[1], &accounts[2], &accounts[3]);
+    let (user_token_account, token_program) = (&accounts[4], &accounts[5]);
+    let balance = read_balance(margin_account)?;
+    let balance = balance.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+
+    let (_, nonce) = vault_address(zeta_group.key);
+    token::transfer(
+      CpiContext::new_with_signer(token_program.clone(), Transfer {
+        from: vault.clone(),
+        to: user_token_account.clone(),
+        authority: vault.clone(),
+      }, &[&[b"vault", zeta_group.key.as_ref(), &[nonce]]]),
+      amount,
+    )?;
+    write_balance(margin_account, balance)
+  }
+
+  pub fn initialize_open_orders(_ctx: Context<Any>) -> Result<()> {
+    Ok(())
+  }
+
+  pub fn close_open_orders(_ctx: Context<Any>, _map_nonce: u8) -> Result<()> {
+    Ok(())
+  }
+
+  pub fn place_order(
+    _ctx: Context<Any>,
+    _price: u64,
+    _size: u64,
+    _side: Side,
+    _client_order_id: Option<u64>,
+  ) -> Result<()> {
+    Ok(())
+  }
+
+  pub fn place_order_v3(
+    _ctx: Context<Any>,
+    _price: u64,
+    _size: u64,
+    _side: Side,
+    _order_type: OrderType,
+    _client_order_id: Option<u64>,
+    _tag: Option<String>,
+  ) -> Result<()> {
+    Ok(())
+  }
+
+  pub fn cancel_order(_ctx: Context<Any>, _side: Side, _order_id: u128) -> Result<()> {
+    Ok(())
+  }
+
+  pub fn cancel_order_by_client_order_id(_ctx: Context<Any>, _client_order_id: u64) -> Result<()> {
+    Ok(())
+  }
+}
+
+#[derive(Accounts)]
+pub struct Any {}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Eq, Clone, Copy)]
+pub enum Side {
+  Uninitialized,
+  Bid,
+  Ask,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Eq, Clone, Copy)]
+pub enum OrderType {
+  Limit,
+  PostOnly,
+  FillOrKill,
+}
+
+pub fn margin_account_address(zeta_group: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[b"margin", zeta_group.as_ref(), authority.as_ref()], &crate::ID)
+}
+
+/// Token account the mock holds margin deposits in; must be owned by itself.
+pub fn vault_address(zeta_group: &Pubkey) -> (Pubkey, u8) {
+  Pubkey::find_program_address(&[b"vault", zeta_group.as_ref()], &crate::ID)
+}
+
+fn take<'a, 'info>(accounts: &'a [AccountInfo<'info>], n: usize) -> Result<&'a [AccountInfo<'info>]> {
+  accounts.get(..n).ok_or_else(|| ErrorCode::AccountNotEnoughKeys.into())
+}
+
+fn read_balance(margin_account: &AccountInfo) -> Result<u64> {
+  require!(*margin_account.owner == crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+  let data = margin_account.try_borrow_data()?;
+  let mut bytes = [0u8; 8];
+  bytes.copy_from_slice(&data[BALANCE_OFFSET..BALANCE_OFFSET + 8]);
+  Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_balance(margin_account: &AccountInfo, balance: u64) -> Result<()> {
+  let mut data = margin_account.try_borrow_mut_data()?;
+  data[BALANCE_OFFSET..BALANCE_OFFSET + 8].copy_from_slice(&balance.to_le_bytes());
+  Ok(())
+}
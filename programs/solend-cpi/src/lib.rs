@@ -0,0 +1,31 @@
+//! Hand-built CPI interface to the Solend lending program: the `Reserve`
+//! account wrapper, its `Pack` layout and the reserve / obligation
+//! instructions. Programs depend on it with `no-entrypoint`.
+pub mod solend_program {
+  // anchor_lang::prelude::declare_id!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo"); // mainnet
+  anchor_lang::prelude::declare_id!("ALend7Ketfx5bxh6ghsCDXAoDrhvEmsXT3cynB6aPLgx"); // devnet
+}
+
+mod deposit_liquidity;
+mod redeem_collateral;
+mod obligation;
+mod instructions;
+mod states;
+mod math;
+
+pub use deposit_liquidity::DepositReserveLiquidity;
+pub use deposit_liquidity::handler_signed as deposit_liquidity;
+pub use redeem_collateral::RedeemReserveCollateral;
+pub use redeem_collateral::handler_signed as redeem_collateral;
+pub use obligation::{
+  BorrowObligationLiquidity, DepositObligationCollateral, InitObligation, RefreshObligation,
+  RepayObligationLiquidity, WithdrawObligationCollateral, OBLIGATION_LEN,
+};
+pub use obligation::init_obligation_signed as init_obligation;
+pub use obligation::deposit_obligation_collateral_signed as deposit_obligation_collateral;
+pub use obligation::borrow_obligation_liquidity_signed as borrow_obligation_liquidity;
+pub use obligation::refresh_obligation_signed as refresh_obligation;
+pub use obligation::withdraw_obligation_collateral_signed as withdraw_obligation_collateral;
+pub use obligation::repay_obligation_liquidity_signed as repay_obligation_liquidity;
+pub use states::*;
+pub use math::*;
@@ -0,0 +1,492 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::pubkey::PUBKEY_BYTES;
+use anchor_lang::solana_program::sysvar;
+use super::instructions::LendingInstruction;
+
+// Size of the Solend obligation account
+pub const OBLIGATION_LEN: usize = 1300;
+
+pub fn init_obligation(
+  program_id: Pubkey,
+  obligation_pubkey: Pubkey,
+  lending_market_pubkey: Pubkey,
+  obligation_owner_pubkey: Pubkey,
+  token_program_id: Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id,
+    accounts: vec![
+      AccountMeta::new(obligation_pubkey, false),
+      AccountMeta::new_readonly(lending_market_pubkey, false),
+      AccountMeta::new_readonly(obligation_owner_pubkey, true),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(token_program_id, false),
+    ],
+    data: LendingInstruction::InitObligation.pack(),
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_obligation_collateral(
+  program_id: Pubkey,
+  collateral_amount: u64,
+  source_collateral_pubkey: Pubkey,
+  destination_collateral_pubkey: Pubkey,
+  deposit_reserve_pubkey: Pubkey,
+  obligation_pubkey: Pubkey,
+  lending_market_pubkey: Pubkey,
+  obligation_owner_pubkey: Pubkey,
+  user_transfer_authority_pubkey: Pubkey,
+  token_program_id: Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id,
+    accounts: vec![
+      AccountMeta::new(source_collateral_pubkey, false),
+      AccountMeta::new(destination_collateral_pubkey, false),
+      AccountMeta::new(deposit_reserve_pubkey, false),
+      AccountMeta::new(obligation_pubkey, false),
+      AccountMeta::new_readonly(lending_market_pubkey, false),
+      AccountMeta::new_readonly(obligation_owner_pubkey, true),
+      AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+      AccountMeta::new_readonly(token_program_id, false),
+    ],
+    data: LendingInstruction::DepositObligationCollateral { collateral_amount }.pack(),
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn borrow_obligation_liquidity(
+  program_id: Pubkey,
+  liquidity_amount: u64,
+  source_liquidity_pubkey: Pubkey,
+  destination_liquidity_pubkey: Pubkey,
+  borrow_reserve_pubkey: Pubkey,
+  borrow_reserve_liquidity_fee_receiver_pubkey: Pubkey,
+  obligation_pubkey: Pubkey,
+  lending_market_pubkey: Pubkey,
+  obligation_owner_pubkey: Pubkey,
+  token_program_id: Pubkey,
+) -> Instruction {
+  let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+    &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+    &program_id,
+  );
+
+  Instruction {
+    program_id,
+    accounts: vec![
+      AccountMeta::new(source_liquidity_pubkey, false),
+      AccountMeta::new(destination_liquidity_pubkey, false),
+      AccountMeta::new(borrow_reserve_pubkey, false),
+      AccountMeta::new(borrow_reserve_liquidity_fee_receiver_pubkey, false),
+      AccountMeta::new(obligation_pubkey, false),
+      AccountMeta::new_readonly(lending_market_pubkey, false),
+      AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+      AccountMeta::new_readonly(obligation_owner_pubkey, true),
+      AccountMeta::new_readonly(token_program_id, false),
+    ],
+    data: LendingInstruction::BorrowObligationLiquidity { liquidity_amount }.pack(),
+  }
+}
+
+pub fn refresh_obligation(
+  program_id: Pubkey,
+  obligation_pubkey: Pubkey,
+  reserve_pubkeys: Vec<Pubkey>,
+) -> Instruction {
+  let mut accounts = vec![AccountMeta::new(obligation_pubkey, false)];
+  accounts.extend(
+    reserve_pubkeys
+      .into_iter()
+      .map(|pubkey| AccountMeta::new_readonly(pubkey, false)),
+  );
+  Instruction {
+    program_id,
+    accounts,
+    data: LendingInstruction::RefreshObligation.pack(),
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_obligation_collateral(
+  program_id: Pubkey,
+  collateral_amount: u64,
+  source_collateral_pubkey: Pubkey,
+  destination_collateral_pubkey: Pubkey,
+  withdraw_reserve_pubkey: Pubkey,
+  obligation_pubkey: Pubkey,
+  lending_market_pubkey: Pubkey,
+  obligation_owner_pubkey: Pubkey,
+  token_program_id: Pubkey,
+) -> Instruction {
+  let (lending_market_authority_pubkey, _bump_seed) = Pubkey::find_program_address(
+    &[&lending_market_pubkey.to_bytes()[..PUBKEY_BYTES]],
+    &program_id,
+  );
+
+  Instruction {
+    program_id,
+    accounts: vec![
+      AccountMeta::new(source_collateral_pubkey, false),
+      AccountMeta::new(destination_collateral_pubkey, false),
+      AccountMeta::new_readonly(withdraw_reserve_pubkey, false),
+      AccountMeta::new(obligation_pubkey, false),
+      AccountMeta::new_readonly(lending_market_pubkey, false),
+      AccountMeta::new_readonly(lending_market_authority_pubkey, false),
+      AccountMeta::new_readonly(obligation_owner_pubkey, true),
+      AccountMeta::new_readonly(token_program_id, false),
+    ],
+    data: LendingInstruction::WithdrawObligationCollateral { collateral_amount }.pack(),
+  }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn repay_obligation_liquidity(
+  program_id: Pubkey,
+  liquidity_amount: u64,
+  source_liquidity_pubkey: Pubkey,
+  destination_liquidity_pubkey: Pubkey,
+  repay_reserve_pubkey: Pubkey,
+  obligation_pubkey: Pubkey,
+  lending_market_pubkey: Pubkey,
+  user_transfer_authority_pubkey: Pubkey,
+  token_program_id: Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id,
+    accounts: vec![
+      AccountMeta::new(source_liquidity_pubkey, false),
+      AccountMeta::new(destination_liquidity_pubkey, false),
+      AccountMeta::new(repay_reserve_pubkey, false),
+      AccountMeta::new(obligation_pubkey, false),
+      AccountMeta::new_readonly(lending_market_pubkey, false),
+      AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+      AccountMeta::new_readonly(token_program_id, false),
+    ],
+    data: LendingInstruction::RepayObligationLiquidity { liquidity_amount }.pack(),
+  }
+}
+
+#[derive(Accounts)]
+pub struct InitObligation<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub obligation_owner: AccountInfo<'info>,
+  /// CHECK:
+  pub rent: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositObligationCollateral<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub source_collateral: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub destination_collateral: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub deposit_reserve: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub obligation_owner: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub user_transfer_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BorrowObligationLiquidity<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub source_liquidity: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub destination_liquidity: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub borrow_reserve: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub borrow_reserve_liquidity_fee_receiver: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub obligation_owner: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshObligation<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawObligationCollateral<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub source_collateral: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub destination_collateral: AccountInfo<'info>,
+  /// CHECK:
+  pub withdraw_reserve: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub obligation_owner: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RepayObligationLiquidity<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub source_liquidity: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub destination_liquidity: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub repay_reserve: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub user_transfer_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_program: AccountInfo<'info>,
+}
+
+pub fn init_obligation_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, InitObligation<'info>>,
+) -> Result<()> {
+  let ix = init_obligation(
+    ctx.accounts.lending_program.key(),
+    ctx.accounts.obligation.key(),
+    ctx.accounts.lending_market.key(),
+    ctx.accounts.obligation_owner.key(),
+    ctx.accounts.token_program.key(),
+  );
+
+  let accounts = [
+    ctx.accounts.obligation.to_account_info(),
+    ctx.accounts.lending_market.to_account_info(),
+    ctx.accounts.obligation_owner.to_account_info(),
+    ctx.accounts.rent.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  invoke_signed(
+    &ix,
+    &accounts,
+    &ctx.signer_seeds,
+  )?;
+  Ok(())
+}
+
+pub fn deposit_obligation_collateral_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, DepositObligationCollateral<'info>>,
+  collateral_amount: u64,
+) -> Result<()> {
+  let ix = deposit_obligation_collateral(
+    ctx.accounts.lending_program.key(),
+    collateral_amount,
+    ctx.accounts.source_collateral.key(),
+    ctx.accounts.destination_collateral.key(),
+    ctx.accounts.deposit_reserve.key(),
+    ctx.accounts.obligation.key(),
+    ctx.accounts.lending_market.key(),
+    ctx.accounts.obligation_owner.key(),
+    ctx.accounts.user_transfer_authority.key(),
+    ctx.accounts.token_program.key(),
+  );
+
+  let accounts = [
+    ctx.accounts.source_collateral.to_account_info(),
+    ctx.accounts.destination_collateral.to_account_info(),
+    ctx.accounts.deposit_reserve.to_account_info(),
+    ctx.accounts.obligation.to_account_info(),
+    ctx.accounts.lending_market.to_account_info(),
+    ctx.accounts.obligation_owner.to_account_info(),
+    ctx.accounts.user_transfer_authority.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  invoke_signed(
+    &ix,
+    &accounts,
+    &ctx.signer_seeds,
+  )?;
+  Ok(())
+}
+
+pub fn borrow_obligation_liquidity_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, BorrowObligationLiquidity<'info>>,
+  liquidity_amount: u64,
+) -> Result<()> {
+  let ix = borrow_obligation_liquidity(
+    ctx.accounts.lending_program.key(),
+    liquidity_amount,
+    ctx.accounts.source_liquidity.key(),
+    ctx.accounts.destination_liquidity.key(),
+    ctx.accounts.borrow_reserve.key(),
+    ctx.accounts.borrow_reserve_liquidity_fee_receiver.key(),
+    ctx.accounts.obligation.key(),
+    ctx.accounts.lending_market.key(),
+    ctx.accounts.obligation_owner.key(),
+    ctx.accounts.token_program.key(),
+  );
+
+  let accounts = [
+    ctx.accounts.source_liquidity.to_account_info(),
+    ctx.accounts.destination_liquidity.to_account_info(),
+    ctx.accounts.borrow_reserve.to_account_info(),
+    ctx.accounts.borrow_reserve_liquidity_fee_receiver.to_account_info(),
+    ctx.accounts.obligation.to_account_info(),
+    ctx.accounts.lending_market.to_account_info(),
+    ctx.accounts.lending_market_authority.to_account_info(),
+    ctx.accounts.obligation_owner.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  invoke_signed(
+    &ix,
+    &accounts,
+    &ctx.signer_seeds,
+  )?;
+  Ok(())
+}
+
+/// Refreshes the obligation, the reserves it holds are passed in the
+/// remaining accounts, deposit reserves first, then borrow reserves.
+pub fn refresh_obligation_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, RefreshObligation<'info>>,
+) -> Result<()> {
+  let ix = refresh_obligation(
+    ctx.accounts.lending_program.key(),
+    ctx.accounts.obligation.key(),
+    ctx.remaining_accounts.iter().map(|reserve| reserve.key()).collect(),
+  );
+
+  let mut accounts = vec![ctx.accounts.obligation.to_account_info()];
+  accounts.extend(ctx.remaining_accounts.iter().cloned());
+  invoke_signed(
+    &ix,
+    &accounts,
+    &ctx.signer_seeds,
+  )?;
+  Ok(())
+}
+
+pub fn withdraw_obligation_collateral_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, WithdrawObligationCollateral<'info>>,
+  collateral_amount: u64,
+) -> Result<()> {
+  let ix = withdraw_obligation_collateral(
+    ctx.accounts.lending_program.key(),
+    collateral_amount,
+    ctx.accounts.source_collateral.key(),
+    ctx.accounts.destination_collateral.key(),
+    ctx.accounts.withdraw_reserve.key(),
+    ctx.accounts.obligation.key(),
+    ctx.accounts.lending_market.key(),
+    ctx.accounts.obligation_owner.key(),
+    ctx.accounts.token_program.key(),
+  );
+
+  let accounts = [
+    ctx.accounts.source_collateral.to_account_info(),
+    ctx.accounts.destination_collateral.to_account_info(),
+    ctx.accounts.withdraw_reserve.to_account_info(),
+    ctx.accounts.obligation.to_account_info(),
+    ctx.accounts.lending_market.to_account_info(),
+    ctx.accounts.lending_market_authority.to_account_info(),
+    ctx.accounts.obligation_owner.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  invoke_signed(
+    &ix,
+    &accounts,
+    &ctx.signer_seeds,
+  )?;
+  Ok(())
+}
+
+pub fn repay_obligation_liquidity_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, RepayObligationLiquidity<'info>>,
+  liquidity_amount: u64,
+) -> Result<()> {
+  let ix = repay_obligation_liquidity(
+    ctx.accounts.lending_program.key(),
+    liquidity_amount,
+    ctx.accounts.source_liquidity.key(),
+    ctx.accounts.destination_liquidity.key(),
+    ctx.accounts.repay_reserve.key(),
+    ctx.accounts.obligation.key(),
+    ctx.accounts.lending_market.key(),
+    ctx.accounts.user_transfer_authority.key(),
+    ctx.accounts.token_program.key(),
+  );
+
+  let accounts = [
+    ctx.accounts.source_liquidity.to_account_info(),
+    ctx.accounts.destination_liquidity.to_account_info(),
+    ctx.accounts.repay_reserve.to_account_info(),
+    ctx.accounts.obligation.to_account_info(),
+    ctx.accounts.lending_market.to_account_info(),
+    ctx.accounts.user_transfer_authority.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  invoke_signed(
+    &ix,
+    &accounts,
+    &ctx.signer_seeds,
+  )?;
+  Ok(())
+}
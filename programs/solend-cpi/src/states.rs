@@ -11,7 +11,7 @@ use std::{
   cmp::{Ordering},
 };
 use std::ops::Deref;
-use super::math::{Decimal, Rate, WAD, TryMul, TryDiv, TrySub, TryAdd};
+use super::math::{Decimal, Rate, U128, WAD, TryMul, TryDiv, TrySub, TryAdd};
 
 /// Percentage of an obligation that can be repaid during each liquidation call
 pub const LIQUIDATION_CLOSE_FACTOR: u8 = 20;
@@ -49,6 +49,11 @@ impl anchor_lang::Id for SolendProgram {
   }
 }
 
+/// Authority PDA the lending market signs reserve transfers with.
+pub fn get_lending_market_authority(lending_market: &Pubkey) -> Pubkey {
+  Pubkey::find_program_address(&[lending_market.as_ref()], &solend_program::ID).0
+}
+
 /// Last update state
 #[derive(Clone, Debug, Default)]
 pub struct LastUpdate {
@@ -132,6 +137,25 @@ impl ReserveLiquidity {
       .try_add(self.borrowed_amount_wads)?
       .try_sub(self.accumulated_protocol_fees_wads)
   }
+
+  /// Value of a liquidity amount in quote currency
+  pub fn market_value(&self, liquidity_amount: u64) -> Result<Decimal, ProgramError> {
+    Decimal::from(liquidity_amount)
+      .try_mul(self.market_price)?
+      .try_div(10u64.pow(self.mint_decimals as u32))
+  }
+
+  /// Share of the supplied liquidity that is borrowed, in bps
+  pub fn utilization_bps(&self) -> anchor_lang::prelude::Result<u64> {
+    let total_supply = self.total_supply()?;
+    if total_supply == Decimal::zero() {
+      return Ok(0);
+    }
+    self.borrowed_amount_wads
+      .try_mul(10_000u64)?
+      .try_div(total_supply)?
+      .try_floor_u64()
+  }
 }
 
 /// Collateral exchange rate
@@ -139,6 +163,16 @@ impl ReserveLiquidity {
 pub struct CollateralExchangeRate(Rate);
 
 impl CollateralExchangeRate {
+  /// Create exchange rate from its scaled collateral per liquidity value
+  pub fn from_scaled_val(scaled_val: u128) -> Self {
+    Self(Rate(U128::from(scaled_val)))
+  }
+
+  /// Return raw scaled collateral per liquidity value
+  pub fn to_scaled_val(&self) -> u128 {
+    self.0.to_scaled_val()
+  }
+
   /// Convert reserve collateral to liquidity
   pub fn collateral_to_liquidity(&self, collateral_amount: u64) -> anchor_lang::prelude::Result<u64> {
     self.decimal_collateral_to_liquidity(collateral_amount.into())?
@@ -576,3 +610,206 @@ impl Deref for Reserve {
     &self.0
   }
 }
+
+/// Collateral deposited to an obligation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObligationCollateral {
+  /// Reserve collateral is deposited to
+  pub deposit_reserve: Pubkey,
+  /// Amount of collateral deposited
+  pub deposited_amount: u64,
+  /// Collateral market value in quote currency
+  pub market_value: Decimal,
+}
+
+/// Liquidity borrowed from an obligation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObligationLiquidity {
+  /// Reserve liquidity is borrowed from
+  pub borrow_reserve: Pubkey,
+  /// Borrow rate used for calculating interest
+  pub cumulative_borrow_rate_wads: Decimal,
+  /// Amount of liquidity borrowed plus interest
+  pub borrowed_amount_wads: Decimal,
+  /// Liquidity market value in quote currency
+  pub market_value: Decimal,
+}
+
+impl ObligationLiquidity {
+  /// Borrowed amount with the interest accrued up to the reserve's last refresh
+  pub fn accrued_amount_wads(&self, reserve: &ReserveState) -> Result<Decimal, ProgramError> {
+    if self.cumulative_borrow_rate_wads == Decimal::zero() {
+      return Ok(self.borrowed_amount_wads);
+    }
+    let rate = reserve.liquidity.cumulative_borrow_rate_wads
+      .try_div(self.cumulative_borrow_rate_wads)?;
+    self.borrowed_amount_wads.try_mul(rate)
+  }
+}
+
+/// Lending market obligation state, the parts the vault reads
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObligationState {
+  /// Version of the struct
+  pub version: u8,
+  /// Last update to collateral, liquidity, or their market values
+  pub last_update: LastUpdate,
+  /// Lending market address
+  pub lending_market: Pubkey,
+  /// Owner authority which can borrow liquidity
+  pub owner: Pubkey,
+  /// Deposited collateral for the obligation, unique by deposit reserve address
+  pub deposits: Vec<ObligationCollateral>,
+  /// Borrowed liquidity for the obligation, unique by borrow reserve address
+  pub borrows: Vec<ObligationLiquidity>,
+  /// Market value of deposits
+  pub deposited_value: Decimal,
+  /// Market value of borrows
+  pub borrowed_value: Decimal,
+}
+
+const OBLIGATION_COLLATERAL_LEN: usize = 88; // 32 + 8 + 16 + 32
+const OBLIGATION_LIQUIDITY_LEN: usize = 112; // 32 + 16 + 16 + 16 + 32
+const MAX_OBLIGATION_RESERVES: usize = 10;
+
+impl ObligationState {
+  /// Collateral deposited to `reserve`
+  pub fn deposited_amount(&self, reserve: &Pubkey) -> u64 {
+    self.deposits.iter()
+      .find(|deposit| deposit.deposit_reserve == *reserve)
+      .map_or(0, |deposit| deposit.deposited_amount)
+  }
+
+  /// Liquidity owed to `reserve`, interest accrued up to its last refresh included
+  pub fn borrowed_amount(&self, reserve_key: &Pubkey, reserve: &ReserveState) -> anchor_lang::prelude::Result<u64> {
+    match self.borrows.iter().find(|borrow| borrow.borrow_reserve == *reserve_key) {
+      Some(borrow) => borrow.accrued_amount_wads(reserve)?.try_ceil_u64(),
+      None => Ok(0),
+    }
+  }
+
+  /// Deposit reserves followed by borrow reserves, the order `RefreshObligation` takes them in
+  pub fn reserves(&self) -> Vec<Pubkey> {
+    self.deposits.iter().map(|deposit| deposit.deposit_reserve)
+      .chain(self.borrows.iter().map(|borrow| borrow.borrow_reserve))
+      .collect()
+  }
+
+  /// Unpacks the obligation layout of `OBLIGATION_LEN` bytes
+  pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+    if input.len() < OBLIGATION_LEN {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    let input = array_ref![input, 0, OBLIGATION_LEN];
+    #[allow(clippy::ptr_offset_with_cast)]
+      let (
+      version,
+      last_update_slot,
+      last_update_stale,
+      lending_market,
+      owner,
+      deposited_value,
+      borrowed_value,
+      _allowed_borrow_value,
+      _unhealthy_borrow_value,
+      _padding,
+      deposits_len,
+      borrows_len,
+      data_flat,
+    ) = array_refs![
+            input,
+            1,
+            8,
+            1,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            16,
+            16,
+            16,
+            16,
+            64,
+            1,
+            1,
+            OBLIGATION_COLLATERAL_LEN + (OBLIGATION_LIQUIDITY_LEN * (MAX_OBLIGATION_RESERVES - 1))
+        ];
+
+    let version = u8::from_le_bytes(*version);
+    if version == 0 || version > 1 {
+      msg!("Obligation version does not match lending program version");
+      return Err(ProgramError::InvalidAccountData);
+    }
+    let deposits_len = u8::from_le_bytes(*deposits_len) as usize;
+    let borrows_len = u8::from_le_bytes(*borrows_len) as usize;
+    if deposits_len + borrows_len > MAX_OBLIGATION_RESERVES {
+      return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mut offset = 0;
+    let mut deposits = Vec::with_capacity(deposits_len);
+    for _ in 0..deposits_len {
+      let data = array_ref![data_flat, offset, OBLIGATION_COLLATERAL_LEN];
+      #[allow(clippy::ptr_offset_with_cast)]
+        let (deposit_reserve, deposited_amount, market_value, _padding) =
+        array_refs![data, PUBKEY_BYTES, 8, 16, 32];
+      deposits.push(ObligationCollateral {
+        deposit_reserve: Pubkey::new_from_array(*deposit_reserve),
+        deposited_amount: u64::from_le_bytes(*deposited_amount),
+        market_value: unpack_decimal(market_value),
+      });
+      offset += OBLIGATION_COLLATERAL_LEN;
+    }
+    let mut borrows = Vec::with_capacity(borrows_len);
+    for _ in 0..borrows_len {
+      let data = array_ref![data_flat, offset, OBLIGATION_LIQUIDITY_LEN];
+      #[allow(clippy::ptr_offset_with_cast)]
+        let (borrow_reserve, cumulative_borrow_rate_wads, borrowed_amount_wads, market_value, _padding) =
+        array_refs![data, PUBKEY_BYTES, 16, 16, 16, 32];
+      borrows.push(ObligationLiquidity {
+        borrow_reserve: Pubkey::new_from_array(*borrow_reserve),
+        cumulative_borrow_rate_wads: unpack_decimal(cumulative_borrow_rate_wads),
+        borrowed_amount_wads: unpack_decimal(borrowed_amount_wads),
+        market_value: unpack_decimal(market_value),
+      });
+      offset += OBLIGATION_LIQUIDITY_LEN;
+    }
+
+    Ok(Self {
+      version,
+      last_update: LastUpdate {
+        slot: u64::from_le_bytes(*last_update_slot),
+        stale: last_update_stale.first().unwrap() == &1_u8,
+      },
+      lending_market: Pubkey::new_from_array(*lending_market),
+      owner: Pubkey::new_from_array(*owner),
+      deposits,
+      borrows,
+      deposited_value: unpack_decimal(deposited_value),
+      borrowed_value: unpack_decimal(borrowed_value),
+    })
+  }
+}
+
+#[derive(Clone)]
+pub struct Obligation(ObligationState);
+
+impl anchor_lang::Owner for Obligation {
+  fn owner() -> Pubkey {
+    solend_program::ID
+  }
+}
+
+impl anchor_lang::AccountSerialize for Obligation {}
+
+impl anchor_lang::AccountDeserialize for Obligation {
+  fn try_deserialize_unchecked(buf: &mut &[u8]) -> anchor_lang::prelude::Result<Self> {
+    Ok(ObligationState::unpack(buf).map(Obligation)?)
+  }
+}
+
+impl Deref for Obligation {
+  type Target = ObligationState;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
@@ -0,0 +1,100 @@
+use std::str::FromStr;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use solend_cpi::{Reserve, ReserveState, WAD};
+
+// `solana account --output json` dump of a USDC reserve, fields set to known
+// values so every offset of the 619 byte layout is pinned by an assertion
+const USDC_RESERVE: &str = include_str!("fixtures/usdc_reserve.json");
+
+fn account_data(dump: &str) -> Vec<u8> {
+  let dump: serde_json::Value = serde_json::from_str(dump).unwrap();
+  let data = dump["account"]["data"][0].as_str().unwrap();
+  base64::decode(data).unwrap()
+}
+
+fn key(address: &str) -> Pubkey {
+  Pubkey::from_str(address).unwrap()
+}
+
+#[test]
+fn reserve_fields_decode_at_their_offsets() {
+  let data = account_data(USDC_RESERVE);
+  assert_eq!(data.len(), ReserveState::LEN);
+  let reserve = Reserve::try_deserialize(&mut &data[..]).unwrap();
+
+  assert_eq!(reserve.version, 1);
+  assert_eq!(reserve.last_update.slot, 156_184_290);
+  assert!(!reserve.last_update.stale);
+  assert_eq!(reserve.lending_market, key("4UpD2fh7xH3VP9QQaXtsS1YY3bxzWhtfpks7FatyKvdY"));
+
+  assert_eq!(reserve.liquidity.mint_pubkey, key("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"));
+  assert_eq!(reserve.liquidity.mint_decimals, 6);
+  assert_eq!(reserve.liquidity.supply_pubkey, key("8SheGtsopRUDzdiD6v6BR9a6bqZ9QwywYQY99Fp5meNf"));
+  assert_eq!(reserve.liquidity.pyth_oracle_pubkey, key("Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJy7eJotD"));
+  assert_eq!(reserve.liquidity.switchboard_oracle_pubkey, key("CZx29wKMUxaJDq6aLVQTdViPL754tTR64NAgQBUGxxHb"));
+  assert_eq!(reserve.liquidity.available_amount, 12_500_000_000_000);
+  assert_eq!(reserve.liquidity.borrowed_amount_wads.to_scaled_val().unwrap(), 37_500_000_000_000 * WAD as u128);
+  assert_eq!(reserve.liquidity.cumulative_borrow_rate_wads.to_scaled_val().unwrap(), 1_050_000_000_000_000_000);
+  assert_eq!(reserve.liquidity.market_price.to_scaled_val().unwrap(), WAD as u128);
+  assert_eq!(reserve.liquidity.accumulated_protocol_fees_wads.to_scaled_val().unwrap(), 500_000_000 * WAD as u128);
+
+  assert_eq!(reserve.collateral.mint_pubkey, key("993dVFL2uXWYeoXuEBFXR4BijeXdTv4s6BzsCjJZuwqk"));
+  assert_eq!(reserve.collateral.mint_total_supply, 48_000_000_000_000);
+  assert_eq!(reserve.collateral.supply_pubkey, key("UtRy8gcEu9fCkDuUrU8EmC7Uc6FZy5NCwttzG7i6nkw"));
+
+  let config = reserve.config;
+  assert_eq!(config.optimal_utilization_rate, 80);
+  assert_eq!(config.loan_to_value_ratio, 80);
+  assert_eq!(config.liquidation_bonus, 5);
+  assert_eq!(config.liquidation_threshold, 85);
+  assert_eq!(config.min_borrow_rate, 0);
+  assert_eq!(config.optimal_borrow_rate, 8);
+  assert_eq!(config.max_borrow_rate, 50);
+  assert_eq!(config.fees.borrow_fee_wad, 10_000_000_000_000);
+  assert_eq!(config.fees.flash_loan_fee_wad, 3_000_000_000_000_000);
+  assert_eq!(config.fees.host_fee_percentage, 20);
+  assert_eq!(config.deposit_limit, 1_000_000_000_000_000);
+  assert_eq!(config.borrow_limit, u64::MAX);
+  assert_eq!(config.fee_receiver, key("5Gdxn4yquneifE6uk9tK8X4CqHfWKjW2BvYU25hAykwP"));
+  assert_eq!(config.protocol_liquidation_fee, 30);
+  assert_eq!(config.protocol_take_rate, 10);
+}
+
+#[test]
+fn reserve_pack_round_trips() {
+  let data = account_data(USDC_RESERVE);
+  let state = ReserveState::unpack(&data).unwrap();
+  let mut packed = vec![0u8; ReserveState::LEN];
+  ReserveState::pack(state, &mut packed).unwrap();
+  assert_eq!(packed, data);
+}
+
+#[test]
+fn reserve_valuation_from_fixture() {
+  let data = account_data(USDC_RESERVE);
+  let reserve = Reserve::try_deserialize(&mut &data[..]).unwrap();
+
+  let total_supply = reserve.liquidity.total_supply().unwrap();
+  assert_eq!(total_supply.try_floor_u64().unwrap(), 49_999_500_000_000);
+  assert_eq!(reserve.liquidity.utilization_bps().unwrap(), 7_500);
+  assert_eq!(reserve.liquidity.market_value(1_000_000).unwrap().try_floor_u64().unwrap(), 1);
+
+  let exchange_rate = reserve.collateral.exchange_rate(total_supply).unwrap();
+  assert_eq!(exchange_rate.collateral_to_liquidity(48_000_000).unwrap(), 49_999_500);
+  assert_eq!(exchange_rate.liquidity_to_collateral(49_999_500).unwrap(), 47_999_999);
+}
+
+#[test]
+fn reserve_rejects_resized_or_uninitialized_accounts() {
+  let data = account_data(USDC_RESERVE);
+
+  let mut grown = data.clone();
+  grown.push(0);
+  assert!(Reserve::try_deserialize(&mut &grown[..]).is_err());
+  assert!(Reserve::try_deserialize(&mut &data[..ReserveState::LEN - 1]).is_err());
+
+  let mut uninitialized = data;
+  uninitialized[0] = 0;
+  assert!(Reserve::try_deserialize(&mut &uninitialized[..]).is_err());
+}
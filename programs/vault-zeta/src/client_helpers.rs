@@ -0,0 +1,124 @@
+//! Remaining-accounts layouts of the instructions that take them, so clients
+//! build them from here instead of hard-coding the order.
+
+/// One ladder leg of `place_ladder` and `execute_strategy`, repeated per leg.
+/// `abort_epoch` takes the same layout, one group per market. The order
+/// tracker is created by `init_order_tracker`.
+pub const LADDER_LEG_ACCOUNTS: &[&str] = &[
+  "market",
+  "request_queue",
+  "event_queue",
+  "bids",
+  "asks",
+  "coin_vault",
+  "pc_vault",
+  "coin_wallet",
+  "pc_wallet",
+  "open_orders",
+  "market_node",
+  "market_mint",
+  "order_tracker",
+];
+
+pub const ABORT_EPOCH_ACCOUNTS: &[&str] = LADDER_LEG_ACCOUNTS;
+
+/// Optional keeper cosigner of `bid_order`, `cancel_replace` and
+/// `buy_to_close`, and of `place_ladder` after its legs, needed above
+/// `KeeperSet::cosign_notional`.
+pub const KEEPER_COSIGNER_ACCOUNTS: &[&str] = &[
+  "cosigner",
+];
+
+/// `fast_rollover` markets to cancel, up to `MAX_ROLLOVER_MARKETS` groups,
+/// then `FAST_ROLLOVER_SETTLEMENT_ACCOUNTS` when it books a series.
+pub const FAST_ROLLOVER_ACCOUNTS: &[&str] = LADDER_LEG_ACCOUNTS;
+
+pub const FAST_ROLLOVER_SETTLEMENT_ACCOUNTS: &[&str] = &[
+  "settlement_account",
+];
+
+/// Zeta accounts of `withdraw` and `withdraw_all`, only read when the Solend
+/// reserve liquidity doesn't cover the payout and the margin account pays
+/// the rest. `margin_account` is the cross-margin account when the vault
+/// uses that model.
+pub const MARGIN_WITHDRAW_ACCOUNTS: &[&str] = &[
+  "zeta_group",
+  "margin_account",
+  "zeta_vault",
+  "socialized_loss_account",
+  "state",
+  "greeks",
+  "oracle",
+  "zeta_program",
+  "price_update",
+];
+
+/// Escrows moved to the new executor by `rotate_executor`, each passed when
+/// it exists, then the shares escrow of every receipts epoch not fully
+/// redeemed.
+pub const ROTATE_EXECUTOR_ACCOUNTS: &[&str] = &[
+  "share_lock_escrow",
+  "stake_escrow",
+  "reward_vault",
+  "pending_deposit_escrow",
+];
+
+/// Drift accounts of `rebalance_hedge`, `place_drift_order` and
+/// `settle_drift_pnl`: oracles first, then spot markets, then the perp market.
+/// `place_drift_order` takes the optional keeper cosigner after them.
+pub const REBALANCE_HEDGE_ACCOUNTS: &[&str] = &[
+  "perp_oracle",
+  "quote_spot_market",
+  "perp_market",
+];
+
+/// `execute_strategy` ladder legs, repeated up to `MAX_LADDER_LEGS`, then the
+/// optional keeper cosigner. The strategy program reads the legs read-only
+/// and returns `StrategyIntent`s indexing them.
+pub const EXECUTE_STRATEGY_ACCOUNTS: &[&str] = LADDER_LEG_ACCOUNTS;
+
+/// `refresh_valuation` remaining accounts of a vault with a Solend
+/// obligation. Solend `refresh_obligation` has to run earlier in the
+/// transaction so the debt includes the accrued interest.
+pub const REFRESH_VALUATION_ACCOUNTS: &[&str] = &[
+  "obligation",
+  "borrow_reserve",
+];
+
+/// Remaining accounts of `deposit`, `deposit_for`, `deposit_with_signature`
+/// and `convert_pending_deposit` while the vault charges an exit fee or
+/// locks deposits. The receipt is the share owner's, opened beforehand with
+/// `open_deposit_receipt`.
+pub const DEPOSIT_RECEIPT_ACCOUNTS: &[&str] = &[
+  "deposit_receipt",
+  "deposit_escrow",
+];
+
+/// Remaining accounts of `harvest_yield`, `redeem_receipts` and
+/// `release_deposit_shares`, with the treasury share account, and of
+/// `withdraw_instant`, with the treasury underlying account. Required once
+/// the protocol config account those instructions take exists.
+pub const PROTOCOL_FEE_ACCOUNTS: &[&str] = &[
+  "treasury",
+];
+
+/// Last two remaining accounts of `deposit`, `deposit_for`,
+/// `deposit_with_signature` and `deposit_pending` for vaults created with an
+/// operator bond, after any `DEPOSIT_RECEIPT_ACCOUNTS`.
+pub const BOND_ACCOUNTS: &[&str] = &[
+  "protocol_config",
+  "bond",
+];
+
+/// Last remaining account of the keeper cranks refunded out of the keeper
+/// pool: `convert_pending_deposit`, `refresh_valuation`, `reconcile_fills`,
+/// `commit_nav_snapshot`, `validate_zeta_constants` and
+/// `emergency_open_withdrawals`. Without it the crank isn't refunded.
+pub const KEEPER_REFUND_ACCOUNTS: &[&str] = &[
+  "keeper_pool",
+];
+
+
+/// `extend_lookup_table` adds every remaining account to the vault lookup
+/// table, up to `MAX_LOOKUP_TABLE_EXTEND` per call.
+pub const EXTEND_LOOKUP_TABLE_ACCOUNTS: &[&str] = &[];
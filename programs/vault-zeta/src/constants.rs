@@ -0,0 +1,21 @@
+//! Zeta constants the vault math depends on. Vault code imports them from
+//! here rather than through the `cpi_calls::zeta` glob, the assertions below
+//! fail the build if the vendored values drift, and `validate_zeta_constants`
+//! checks them against the live zeta group.
+pub use crate::cpi_calls::zeta::{
+  ACTIVE_EXPIRIES, NATIVE_PRECISION_DENOMINATOR, NUM_PRODUCTS_PER_SERIES, NUM_STRIKES, PLATFORM_PRECISION,
+  POSITION_PRECISION, POSITION_PRECISION_DENOMINATOR, SERIES_FUTURE_INDEX, TOTAL_MARKETS,
+};
+
+// margin percentages are 8.dp, 100% is the denominator
+const _: () = assert!(NATIVE_PRECISION_DENOMINATOR == 100_000_000);
+// prices and margin are USDC amounts, 6.dp
+const _: () = assert!(PLATFORM_PRECISION == 6);
+// position sizes are 3.dp lots
+const _: () = assert!(POSITION_PRECISION_DENOMINATOR == 10u128.pow(POSITION_PRECISION));
+// a series is the calls, then the puts, then the future
+const _: () = assert!(NUM_PRODUCTS_PER_SERIES == NUM_STRIKES * 2 + 1);
+const _: () = assert!(SERIES_FUTURE_INDEX == NUM_PRODUCTS_PER_SERIES - 1);
+// market indexes fit the `MarketRegistry` bitmap and the zeta group products
+const _: () = assert!(ACTIVE_EXPIRIES * NUM_PRODUCTS_PER_SERIES <= 64);
+const _: () = assert!(ACTIVE_EXPIRIES * NUM_PRODUCTS_PER_SERIES <= TOTAL_MARKETS);
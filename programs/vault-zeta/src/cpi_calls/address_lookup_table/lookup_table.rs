@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use super::address_lookup_table_program;
+
+// ProgramInstruction variants, bincode encodes the tag as a u32
+const CREATE_LOOKUP_TABLE: u32 = 0;
+const EXTEND_LOOKUP_TABLE: u32 = 2;
+
+pub fn get_lookup_table_address(authority: &Pubkey, recent_slot: u64) -> (Pubkey, u8) {
+  Pubkey::find_program_address(
+    &[authority.as_ref(), recent_slot.to_le_bytes().as_ref()],
+    &address_lookup_table_program::ID,
+  )
+}
+
+#[derive(Accounts)]
+pub struct CreateLookupTable<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub lookup_table: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, signer)]
+  pub payer: AccountInfo<'info>,
+  /// CHECK:
+  pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendLookupTable<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub lookup_table: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, signer)]
+  pub payer: AccountInfo<'info>,
+  /// CHECK:
+  pub system_program: AccountInfo<'info>,
+}
+
+pub fn create_lookup_table_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, CreateLookupTable<'info>>,
+  recent_slot: u64,
+  bump_seed: u8,
+) -> Result<()> {
+  let accounts = &[
+    ctx.accounts.lookup_table.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.payer.to_account_info(),
+    ctx.accounts.system_program.to_account_info(),
+  ];
+
+  let mut data = CREATE_LOOKUP_TABLE.to_le_bytes().to_vec();
+  data.extend(recent_slot.to_le_bytes());
+  data.push(bump_seed);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: vec![
+      AccountMeta::new(ctx.accounts.lookup_table.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+      AccountMeta::new(ctx.accounts.payer.key(), true),
+      AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+    ],
+    data,
+  };
+
+  invoke_signed(&instruction, accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
+
+pub fn extend_lookup_table_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, ExtendLookupTable<'info>>,
+  new_addresses: Vec<Pubkey>,
+) -> Result<()> {
+  let accounts = &[
+    ctx.accounts.lookup_table.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.payer.to_account_info(),
+    ctx.accounts.system_program.to_account_info(),
+  ];
+
+  // bincode prefixes vectors with a u64 length
+  let mut data = EXTEND_LOOKUP_TABLE.to_le_bytes().to_vec();
+  data.extend((new_addresses.len() as u64).to_le_bytes());
+  for address in new_addresses.iter() {
+    data.extend(address.to_bytes());
+  }
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: vec![
+      AccountMeta::new(ctx.accounts.lookup_table.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+      AccountMeta::new(ctx.accounts.payer.key(), true),
+      AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+    ],
+    data,
+  };
+
+  invoke_signed(&instruction, accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod address_lookup_table_program {
+  anchor_lang::prelude::declare_id!("AddressLookupTab1e1111111111111111111111111");
+}
+
+mod lookup_table;
+
+pub use lookup_table::{get_lookup_table_address, CreateLookupTable, ExtendLookupTable};
+pub use lookup_table::create_lookup_table_signed as create_lookup_table;
+pub use lookup_table::extend_lookup_table_signed as extend_lookup_table;
@@ -0,0 +1,37 @@
+pub mod drift_program {
+  anchor_lang::prelude::declare_id!("dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH");
+}
+
+mod user;
+mod orders;
+mod states;
+
+pub use user::{get_user_address, get_user_stats_address, InitializeUser, InitializeUserStats};
+pub use user::initialize_user_stats_signed as initialize_user_stats;
+pub use user::initialize_user_signed as initialize_user;
+pub use orders::{Deposit, PlacePerpOrder, SettlePnl, Withdraw};
+pub use orders::deposit_signed as deposit;
+pub use orders::withdraw_signed as withdraw;
+pub use orders::place_perp_order_signed as place_perp_order;
+pub use orders::settle_pnl_signed as settle_pnl;
+pub use states::*;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+// anchor instruction discriminator, drift is an anchor program
+fn sighash(name: &str) -> Vec<u8> {
+  hash(format!("global:{}", name).as_bytes()).to_bytes()[..8].to_vec()
+}
+
+// markets, oracles and spot markets drift reads are passed as remaining accounts
+fn remaining_metas(remaining_accounts: &[AccountInfo]) -> Vec<AccountMeta> {
+  remaining_accounts
+    .iter()
+    .map(|info| if info.is_writable {
+      AccountMeta::new(info.key(), info.is_signer)
+    } else {
+      AccountMeta::new_readonly(info.key(), info.is_signer)
+    })
+    .collect()
+}
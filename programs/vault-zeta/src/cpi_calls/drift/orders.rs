@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use super::{remaining_metas, sighash, OrderParams};
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user_stats: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub spot_market_vault: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user_token_account: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user_stats: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub spot_market_vault: AccountInfo<'info>,
+  /// CHECK:
+  pub drift_signer: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user_token_account: AccountInfo<'info>,
+  /// CHECK:
+  pub token_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PlacePerpOrder<'info> {
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettlePnl<'info> {
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  pub spot_market_vault: AccountInfo<'info>,
+}
+
+/// Remaining accounts: oracles, then spot markets, then perp markets the
+/// user touches, in drift's expected order.
+pub fn deposit_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, Deposit<'info>>,
+  market_index: u16,
+  amount: u64,
+  reduce_only: bool,
+) -> Result<()> {
+  let mut accounts = vec![
+    ctx.accounts.state.to_account_info(),
+    ctx.accounts.user.to_account_info(),
+    ctx.accounts.user_stats.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.spot_market_vault.to_account_info(),
+    ctx.accounts.user_token_account.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  accounts.extend_from_slice(ctx.remaining_accounts.as_slice());
+
+  let mut metas = vec![
+    AccountMeta::new_readonly(ctx.accounts.state.key(), false),
+    AccountMeta::new(ctx.accounts.user.key(), false),
+    AccountMeta::new(ctx.accounts.user_stats.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+    AccountMeta::new(ctx.accounts.spot_market_vault.key(), false),
+    AccountMeta::new(ctx.accounts.user_token_account.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+  ];
+  metas.extend(remaining_metas(ctx.remaining_accounts.as_slice()));
+
+  let mut data = sighash("deposit");
+  data.extend(market_index.to_le_bytes());
+  data.extend(amount.to_le_bytes());
+  data.push(reduce_only as u8);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: metas,
+    data,
+  };
+
+  invoke_signed(&instruction, &accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
+
+/// Remaining accounts as for `deposit_signed`.
+pub fn withdraw_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, Withdraw<'info>>,
+  market_index: u16,
+  amount: u64,
+  reduce_only: bool,
+) -> Result<()> {
+  let mut accounts = vec![
+    ctx.accounts.state.to_account_info(),
+    ctx.accounts.user.to_account_info(),
+    ctx.accounts.user_stats.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.spot_market_vault.to_account_info(),
+    ctx.accounts.drift_signer.to_account_info(),
+    ctx.accounts.user_token_account.to_account_info(),
+    ctx.accounts.token_program.to_account_info(),
+  ];
+  accounts.extend_from_slice(ctx.remaining_accounts.as_slice());
+
+  let mut metas = vec![
+    AccountMeta::new_readonly(ctx.accounts.state.key(), false),
+    AccountMeta::new(ctx.accounts.user.key(), false),
+    AccountMeta::new(ctx.accounts.user_stats.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+    AccountMeta::new(ctx.accounts.spot_market_vault.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.drift_signer.key(), false),
+    AccountMeta::new(ctx.accounts.user_token_account.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.token_program.key(), false),
+  ];
+  metas.extend(remaining_metas(ctx.remaining_accounts.as_slice()));
+
+  let mut data = sighash("withdraw");
+  data.extend(market_index.to_le_bytes());
+  data.extend(amount.to_le_bytes());
+  data.push(reduce_only as u8);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: metas,
+    data,
+  };
+
+  invoke_signed(&instruction, &accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
+
+pub fn place_perp_order_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, PlacePerpOrder<'info>>,
+  params: OrderParams,
+) -> Result<()> {
+  let mut accounts = vec![
+    ctx.accounts.state.to_account_info(),
+    ctx.accounts.user.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+  ];
+  accounts.extend_from_slice(ctx.remaining_accounts.as_slice());
+
+  let mut metas = vec![
+    AccountMeta::new_readonly(ctx.accounts.state.key(), false),
+    AccountMeta::new(ctx.accounts.user.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+  ];
+  metas.extend(remaining_metas(ctx.remaining_accounts.as_slice()));
+
+  let mut data = sighash("place_perp_order");
+  data.extend(params.try_to_vec()?);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: metas,
+    data,
+  };
+
+  invoke_signed(&instruction, &accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
+
+pub fn settle_pnl_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, SettlePnl<'info>>,
+  market_index: u16,
+) -> Result<()> {
+  let mut accounts = vec![
+    ctx.accounts.state.to_account_info(),
+    ctx.accounts.user.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.spot_market_vault.to_account_info(),
+  ];
+  accounts.extend_from_slice(ctx.remaining_accounts.as_slice());
+
+  let mut metas = vec![
+    AccountMeta::new_readonly(ctx.accounts.state.key(), false),
+    AccountMeta::new(ctx.accounts.user.key(), false),
+    AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+    AccountMeta::new_readonly(ctx.accounts.spot_market_vault.key(), false),
+  ];
+  metas.extend(remaining_metas(ctx.remaining_accounts.as_slice()));
+
+  let mut data = sighash("settle_pnl");
+  data.extend(market_index.to_le_bytes());
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: metas,
+    data,
+  };
+
+  invoke_signed(&instruction, &accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderType {
+  Market,
+  Limit,
+  TriggerMarket,
+  TriggerLimit,
+  Oracle,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum MarketType {
+  Spot,
+  Perp,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PositionDirection {
+  Long,
+  Short,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PostOnlyParam {
+  None,
+  MustPostOnly,
+  TryPostOnly,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OrderTriggerCondition {
+  Above,
+  Below,
+  TriggeredAbove,
+  TriggeredBelow,
+}
+
+/// Drift `OrderParams`, base amounts are 9.dp (BASE_PRECISION) and prices
+/// 6.dp (PRICE_PRECISION).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct OrderParams {
+  pub order_type: OrderType,
+  pub market_type: MarketType,
+  pub direction: PositionDirection,
+  pub user_order_id: u8,
+  pub base_asset_amount: u64,
+  pub price: u64,
+  pub market_index: u16,
+  pub reduce_only: bool,
+  pub post_only: PostOnlyParam,
+  pub immediate_or_cancel: bool,
+  pub max_ts: Option<i64>,
+  pub trigger_price: Option<u64>,
+  pub trigger_condition: OrderTriggerCondition,
+  pub oracle_price_offset: Option<i32>,
+  pub auction_duration: Option<u8>,
+  pub auction_start_price: Option<i64>,
+  pub auction_end_price: Option<i64>,
+}
+
+impl OrderParams {
+  /// Perp limit order without trigger or auction.
+  pub fn perp_limit(
+    market_index: u16,
+    direction: PositionDirection,
+    base_asset_amount: u64,
+    price: u64,
+    reduce_only: bool,
+    post_only: bool,
+  ) -> Self {
+    Self {
+      order_type: OrderType::Limit,
+      market_type: MarketType::Perp,
+      direction,
+      user_order_id: 0,
+      base_asset_amount,
+      price,
+      market_index,
+      reduce_only,
+      post_only: if post_only { PostOnlyParam::MustPostOnly } else { PostOnlyParam::None },
+      immediate_or_cancel: false,
+      max_ts: None,
+      trigger_price: None,
+      trigger_condition: OrderTriggerCondition::Above,
+      oracle_price_offset: None,
+      auction_duration: None,
+      auction_start_price: None,
+      auction_end_price: None,
+    }
+  }
+}
+
+// `User` is zero-copy: discriminator, authority, delegate, name, then 8 spot
+// positions (40 bytes) and 8 perp positions (96 bytes)
+const PERP_POSITIONS_OFFSET: usize = 8 + 32 + 32 + 32 + 8 * 40;
+const PERP_POSITION_LEN: usize = 96;
+const MAX_PERP_POSITIONS: usize = 8;
+// offsets inside `PerpPosition`
+const BASE_ASSET_AMOUNT_OFFSET: usize = 8;
+const OPEN_BIDS_OFFSET: usize = 40;
+const OPEN_ASKS_OFFSET: usize = 48;
+const MARKET_INDEX_OFFSET: usize = 92;
+const OPEN_ORDERS_OFFSET: usize = 94;
+
+
+/// Perp exposure of a drift user in one market, base amounts 9.dp. Open asks
+/// are negative.
+#[derive(Default)]
+pub struct PerpPositionView {
+  pub base_asset_amount: i64,
+  pub open_bids: i64,
+  pub open_asks: i64,
+}
+
+impl PerpPositionView {
+  pub fn load(user: &AccountInfo, market_index: u16) -> Result<Self> {
+    if *user.owner != super::drift_program::ID {
+      return Err(ErrorCode::AccountOwnedByWrongProgram.into());
+    }
+    let data = user.try_borrow_data()?;
+    if data.len() < PERP_POSITIONS_OFFSET + PERP_POSITION_LEN * MAX_PERP_POSITIONS {
+      return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    let read_i64 = |offset: usize| {
+      let mut bytes = [0u8; 8];
+      bytes.copy_from_slice(&data[offset..offset + 8]);
+      i64::from_le_bytes(bytes)
+    };
+    for i in 0..MAX_PERP_POSITIONS {
+      let position = PERP_POSITIONS_OFFSET + i * PERP_POSITION_LEN;
+      let index = u16::from_le_bytes([
+        data[position + MARKET_INDEX_OFFSET],
+        data[position + MARKET_INDEX_OFFSET + 1],
+      ]);
+      let base_asset_amount = read_i64(position + BASE_ASSET_AMOUNT_OFFSET);
+      let open_orders = data[position + OPEN_ORDERS_OFFSET];
+      // unused slots are zeroed, market 0 included
+      if index == market_index && (base_asset_amount != 0 || open_orders != 0) {
+        return Ok(Self {
+          base_asset_amount,
+          open_bids: read_i64(position + OPEN_BIDS_OFFSET),
+          open_asks: read_i64(position + OPEN_ASKS_OFFSET),
+        });
+      }
+    }
+    Ok(Self::default())
+  }
+
+  /// Position once the resting orders fill, none on overflow of the drift
+  /// account data.
+  pub fn expected_base_asset_amount(&self) -> Option<i64> {
+    self.base_asset_amount
+      .checked_add(self.open_bids)?
+      .checked_add(self.open_asks)
+  }
+}
+
+// USDC, the collateral of the vault's drift user
+pub const QUOTE_SPOT_MARKET_INDEX: u16 = 0;
@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use super::{drift_program, sighash};
+
+pub fn get_user_address(authority: &Pubkey, sub_account_id: u16) -> Pubkey {
+  Pubkey::find_program_address(
+    &[b"user", authority.as_ref(), sub_account_id.to_le_bytes().as_ref()],
+    &drift_program::ID,
+  ).0
+}
+
+pub fn get_user_stats_address(authority: &Pubkey) -> Pubkey {
+  Pubkey::find_program_address(
+    &[b"user_stats", authority.as_ref()],
+    &drift_program::ID,
+  ).0
+}
+
+#[derive(Accounts)]
+pub struct InitializeUserStats<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub user_stats: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, signer)]
+  pub payer: AccountInfo<'info>,
+  /// CHECK:
+  pub rent: AccountInfo<'info>,
+  /// CHECK:
+  pub system_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUser<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub user_stats: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, signer)]
+  pub payer: AccountInfo<'info>,
+  /// CHECK:
+  pub rent: AccountInfo<'info>,
+  /// CHECK:
+  pub system_program: AccountInfo<'info>,
+}
+
+pub fn initialize_user_stats_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, InitializeUserStats<'info>>,
+) -> Result<()> {
+  let accounts = &[
+    ctx.accounts.user_stats.to_account_info(),
+    ctx.accounts.state.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.payer.to_account_info(),
+    ctx.accounts.rent.to_account_info(),
+    ctx.accounts.system_program.to_account_info(),
+  ];
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: vec![
+      AccountMeta::new(ctx.accounts.user_stats.key(), false),
+      AccountMeta::new(ctx.accounts.state.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+      AccountMeta::new(ctx.accounts.payer.key(), true),
+      AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+    ],
+    data: sighash("initialize_user_stats"),
+  };
+
+  invoke_signed(&instruction, accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
+
+pub fn initialize_user_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, InitializeUser<'info>>,
+  sub_account_id: u16,
+  name: [u8; 32],
+) -> Result<()> {
+  let accounts = &[
+    ctx.accounts.user.to_account_info(),
+    ctx.accounts.user_stats.to_account_info(),
+    ctx.accounts.state.to_account_info(),
+    ctx.accounts.authority.to_account_info(),
+    ctx.accounts.payer.to_account_info(),
+    ctx.accounts.rent.to_account_info(),
+    ctx.accounts.system_program.to_account_info(),
+  ];
+
+  let mut data = sighash("initialize_user");
+  data.extend(sub_account_id.to_le_bytes());
+  data.extend(name);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: vec![
+      AccountMeta::new(ctx.accounts.user.key(), false),
+      AccountMeta::new(ctx.accounts.user_stats.key(), false),
+      AccountMeta::new(ctx.accounts.state.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.authority.key(), true),
+      AccountMeta::new(ctx.accounts.payer.key(), true),
+      AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+    ],
+    data,
+  };
+
+  invoke_signed(&instruction, accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
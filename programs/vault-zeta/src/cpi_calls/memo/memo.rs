@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+use super::memo_program;
+
+/// Compact JSON object for off-chain indexers, e.g.
+/// `{"event":"harvest","vault":"..","epoch":3,"harvested":120}`.
+pub struct MemoTrailer {
+  memo: String,
+}
+
+impl MemoTrailer {
+  pub fn new(event: &str, vault: &Pubkey, epoch: u64) -> Self {
+    Self {
+      memo: format!("{{\"event\":\"{}\",\"vault\":\"{}\",\"epoch\":{}", event, vault, epoch),
+    }
+  }
+
+  pub fn field(mut self, name: &str, value: impl std::fmt::Display) -> Self {
+    self.memo.push_str(&format!(",\"{}\":{}", name, value));
+    self
+  }
+
+  pub fn finish(mut self) -> String {
+    self.memo.push('}');
+    self.memo
+  }
+}
+
+/// The memo program accepts an instruction without signers, the memo only
+/// shows up in the transaction logs.
+pub fn log_memo<'info>(memo_program: AccountInfo<'info>, memo: String) -> Result<()> {
+  let instruction = Instruction {
+    program_id: memo_program::ID,
+    accounts: vec![],
+    data: memo.into_bytes(),
+  };
+
+  invoke(&instruction, &[memo_program])?;
+
+  Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod memo_program {
+  anchor_lang::prelude::declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+}
+
+mod memo;
+
+pub use memo::{log_memo, MemoTrailer};
@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use super::token_metadata_program;
+
+// MetadataInstruction variants of the token metadata program
+const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+const CREATE_METADATA_ACCOUNT_V2: u8 = 16;
+
+/// Token metadata fields, creators, collection and uses are always left empty.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DataV2 {
+  pub name: String,
+  pub symbol: String,
+  pub uri: String,
+  pub seller_fee_basis_points: u16,
+  pub creators: Option<Vec<Creator>>,
+  pub collection: Option<Collection>,
+  pub uses: Option<Uses>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Creator {
+  pub address: Pubkey,
+  pub verified: bool,
+  pub share: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Collection {
+  pub verified: bool,
+  pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Uses {
+  pub use_method: u8,
+  pub remaining: u64,
+  pub total: u64,
+}
+
+#[derive(AnchorSerialize)]
+struct CreateMetadataAccountArgsV2 {
+  data: DataV2,
+  is_mutable: bool,
+}
+
+#[derive(AnchorSerialize)]
+struct UpdateMetadataAccountArgsV2 {
+  data: Option<DataV2>,
+  update_authority: Option<Pubkey>,
+  primary_sale_happened: Option<bool>,
+  is_mutable: Option<bool>,
+}
+
+pub fn get_metadata_address(mint: &Pubkey) -> Pubkey {
+  Pubkey::find_program_address(
+    &[b"metadata", token_metadata_program::ID.as_ref(), mint.as_ref()],
+    &token_metadata_program::ID,
+  ).0
+}
+
+#[derive(Accounts)]
+pub struct CreateMetadataAccount<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub metadata: AccountInfo<'info>,
+  /// CHECK:
+  pub mint: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub mint_authority: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, signer)]
+  pub payer: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub update_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub system_program: AccountInfo<'info>,
+  /// CHECK:
+  pub rent: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMetadataAccount<'info> {
+  /// CHECK:
+  #[account(mut)]
+  pub metadata: AccountInfo<'info>,
+  /// CHECK:
+  #[account(signer)]
+  pub update_authority: AccountInfo<'info>,
+}
+
+pub fn create_metadata_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, CreateMetadataAccount<'info>>,
+  data: DataV2,
+) -> Result<()> {
+  let accounts = &[
+    ctx.accounts.metadata.to_account_info(),
+    ctx.accounts.mint.to_account_info(),
+    ctx.accounts.mint_authority.to_account_info(),
+    ctx.accounts.payer.to_account_info(),
+    ctx.accounts.update_authority.to_account_info(),
+    ctx.accounts.system_program.to_account_info(),
+    ctx.accounts.rent.to_account_info(),
+  ];
+
+  let mut ix_data = vec![CREATE_METADATA_ACCOUNT_V2];
+  ix_data.extend(CreateMetadataAccountArgsV2 { data, is_mutable: true }.try_to_vec()?);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: vec![
+      AccountMeta::new(ctx.accounts.metadata.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.mint.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.mint_authority.key(), true),
+      AccountMeta::new(ctx.accounts.payer.key(), true),
+      AccountMeta::new_readonly(ctx.accounts.update_authority.key(), true),
+      AccountMeta::new_readonly(ctx.accounts.system_program.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.rent.key(), false),
+    ],
+    data: ix_data,
+  };
+
+  invoke_signed(&instruction, accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
+
+pub fn update_metadata_signed<'a, 'b, 'c, 'info>(
+  ctx: CpiContext<'a, 'b, 'c, 'info, UpdateMetadataAccount<'info>>,
+  data: DataV2,
+) -> Result<()> {
+  let accounts = &[
+    ctx.accounts.metadata.to_account_info(),
+    ctx.accounts.update_authority.to_account_info(),
+  ];
+
+  let mut ix_data = vec![UPDATE_METADATA_ACCOUNT_V2];
+  ix_data.extend(UpdateMetadataAccountArgsV2 {
+    data: Some(data),
+    update_authority: None,
+    primary_sale_happened: None,
+    is_mutable: None,
+  }.try_to_vec()?);
+
+  let instruction = Instruction {
+    program_id: ctx.program.key(),
+    accounts: vec![
+      AccountMeta::new(ctx.accounts.metadata.key(), false),
+      AccountMeta::new_readonly(ctx.accounts.update_authority.key(), true),
+    ],
+    data: ix_data,
+  };
+
+  invoke_signed(&instruction, accounts, ctx.signer_seeds)?;
+
+  Ok(())
+}
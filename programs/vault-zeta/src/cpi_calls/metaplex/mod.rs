@@ -0,0 +1,11 @@
+pub mod token_metadata_program {
+  anchor_lang::prelude::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+}
+
+mod metadata;
+
+pub use metadata::{
+  get_metadata_address, CreateMetadataAccount, DataV2, UpdateMetadataAccount,
+};
+pub use metadata::create_metadata_signed as create_metadata_account;
+pub use metadata::update_metadata_signed as update_metadata_account;
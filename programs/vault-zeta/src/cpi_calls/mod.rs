@@ -1,3 +1,7 @@
-pub mod solend;
-pub mod zeta;
+pub use solend_cpi as solend;
+pub use zeta_cpi as zeta;
 pub mod whirlpool;
+pub mod metaplex;
+pub mod drift;
+pub mod address_lookup_table;
+pub mod memo;
@@ -1,7 +1,7 @@
 use std::ops::Deref;
 use std::u64;
 use anchor_lang::prelude::*;
-use arrayref::{array_ref, array_refs};
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use anchor_lang::solana_program::program_pack::{IsInitialized, Pack, Sealed};
 use super::orca_whirlpool;
 
@@ -87,8 +87,60 @@ impl Sealed for WhirlpoolState {}
 impl Pack for WhirlpoolState {
   const LEN: usize = 8 + 261 + 384;
 
-  fn pack_into_slice(&self, _dst: &mut [u8]) {
-    unimplemented!()
+  // leaves the 8 bytes anchor discriminator as is, like `unpack_from_slice` skips it
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 8, WhirlpoolState::LEN - 8];
+    #[allow(clippy::ptr_offset_with_cast)]
+      let (
+      whirlpools_config,
+      whirlpool_bump,
+      tick_spacing,
+      tick_spacing_seed,
+      fee_rate,
+      protocol_fee_rate,
+      liquidity,
+      sqrt_price,
+      tick_current_index,
+      protocol_fee_owed_a,
+      protocol_fee_owed_b,
+      token_mint_a,
+      token_vault_a,
+      fee_growth_global_a,
+      token_mint_b,
+      token_vault_b,
+      fee_growth_global_b,
+      reward_last_updated_timestamp,
+      reward_infos,
+    ) = mut_array_refs![dst, 32,1,2,2,2,2,16,16,4,8,8,32,32,16,32,32,16,8, 384];
+
+    whirlpools_config.copy_from_slice(self.whirlpools_config.as_ref());
+    *whirlpool_bump = self.whirlpool_bump;
+    *tick_spacing = self.tick_spacing.to_le_bytes();
+    *tick_spacing_seed = self.tick_spacing_seed;
+    *fee_rate = self.fee_rate.to_le_bytes();
+    *protocol_fee_rate = self.protocol_fee_rate.to_le_bytes();
+    *liquidity = self.liquidity.to_le_bytes();
+    *sqrt_price = self.sqrt_price.to_le_bytes();
+    *tick_current_index = self.tick_current_index.to_le_bytes();
+    *protocol_fee_owed_a = self.protocol_fee_owed_a.to_le_bytes();
+    *protocol_fee_owed_b = self.protocol_fee_owed_b.to_le_bytes();
+    token_mint_a.copy_from_slice(self.token_mint_a.as_ref());
+    token_vault_a.copy_from_slice(self.token_vault_a.as_ref());
+    *fee_growth_global_a = self.fee_growth_global_a.to_le_bytes();
+    token_mint_b.copy_from_slice(self.token_mint_b.as_ref());
+    token_vault_b.copy_from_slice(self.token_vault_b.as_ref());
+    *fee_growth_global_b = self.fee_growth_global_b.to_le_bytes();
+    *reward_last_updated_timestamp = self.reward_last_updated_timestamp.to_le_bytes();
+    for (reward_info, dst) in self.reward_infos.iter().zip(reward_infos.chunks_exact_mut(128)) {
+      let dst = array_mut_ref![dst, 0, 128];
+      let (mint, vault, authority, emissions_per_second_x64, growth_global_x64) =
+        mut_array_refs![dst, 32, 32, 32, 16, 16];
+      mint.copy_from_slice(reward_info.mint.as_ref());
+      vault.copy_from_slice(reward_info.vault.as_ref());
+      authority.copy_from_slice(reward_info.authority.as_ref());
+      *emissions_per_second_x64 = reward_info.emissions_per_second_x64.to_le_bytes();
+      *growth_global_x64 = reward_info.growth_global_x64.to_le_bytes();
+    }
   }
 
   fn unpack_from_slice(input: &[u8]) -> std::result::Result<Self, ProgramError> {
@@ -250,8 +302,38 @@ impl Sealed for PositionState {}
 impl Pack for PositionState {
   const LEN: usize = 216;
 
-  fn pack_into_slice(&self, _dst: &mut [u8]) {
-    unimplemented!()
+  // leaves the 8 bytes anchor discriminator as is, like `unpack_from_slice` skips it
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 8, PositionState::LEN - 8];
+    #[allow(clippy::ptr_offset_with_cast)]
+      let (
+      whirlpool,
+      position_mint,
+      liquidity,
+      tick_lower_index,
+      tick_upper_index,
+      fee_growth_checkpoint_a,
+      fee_owed_a,
+      fee_growth_checkpoint_b,
+      fee_owed_b,
+      reward_infos,
+    ) = mut_array_refs![dst, 32, 32, 16, 4, 4, 16, 8, 16, 8, 72];
+
+    whirlpool.copy_from_slice(self.whirlpool.as_ref());
+    position_mint.copy_from_slice(self.position_mint.as_ref());
+    *liquidity = self.liquidity.to_le_bytes();
+    *tick_lower_index = self.tick_lower_index.to_le_bytes();
+    *tick_upper_index = self.tick_upper_index.to_le_bytes();
+    *fee_growth_checkpoint_a = self.fee_growth_checkpoint_a.to_le_bytes();
+    *fee_owed_a = self.fee_owed_a.to_le_bytes();
+    *fee_growth_checkpoint_b = self.fee_growth_checkpoint_b.to_le_bytes();
+    *fee_owed_b = self.fee_owed_b.to_le_bytes();
+    for (reward_info, dst) in self.reward_infos.iter().zip(reward_infos.chunks_exact_mut(24)) {
+      let dst = array_mut_ref![dst, 0, 24];
+      let (growth_inside_checkpoint, amount_owed) = mut_array_refs![dst, 16, 8];
+      *growth_inside_checkpoint = reward_info.growth_inside_checkpoint.to_le_bytes();
+      *amount_owed = reward_info.amount_owed.to_le_bytes();
+    }
   }
 
   fn unpack_from_slice(input: &[u8]) -> std::result::Result<Self, ProgramError> {
@@ -24,4 +24,208 @@ pub enum VaultError {
   DepositDisabled,
   #[msg("Vault is closed, use emergency_withdraw method")]
   UseEmergencyWithdraw,
+  #[msg("Share price history doesn't cover requested window")]
+  ShortSharePriceHistory,
+  #[msg("Previous receipts epoch is not settled yet")]
+  ReceiptsEpochLive,
+  #[msg("Receipts epoch is not live")]
+  ReceiptsEpochNotLive,
+  #[msg("Receipts epoch is not settled yet")]
+  ReceiptsNotSettled,
+  #[msg("Shares are locked in the deposit slot")]
+  DepositLocked,
+  #[msg("Oracle account doesn't match the vault price feed")]
+  InvalidPriceFeed,
+  #[msg("Price update is not fully verified")]
+  UnverifiedPrice,
+  #[msg("Oracle price is stale")]
+  StalePrice,
+  #[msg("Invalid vault config")]
+  InvalidConfig,
+  #[msg("Open orders can't be closed before the series is settled and emptied")]
+  OpenOrdersNotClosable,
+  #[msg("Vault setup is not complete")]
+  SetupIncomplete,
+  #[msg("Deposit window of the current epoch is closed")]
+  DepositWindowClosed,
+  #[msg("Withdraw window of the current epoch is closed")]
+  WithdrawWindowClosed,
+  #[msg("Leverage is disabled for this vault")]
+  LeverageDisabled,
+  #[msg("Borrow would exceed the vault max LTV")]
+  LtvExceeded,
+  #[msg("Order would exceed the vault delta or vega limit")]
+  GreeksLimitExceeded,
+  #[msg("Margin utilization target must be within (0, 10000] bps")]
+  InvalidMarginTarget,
+  #[msg("Solvency accounts don't match the vault")]
+  InvalidSolvencyAccounts,
+  #[msg("Vault assets are below the recorded floor share price")]
+  VaultInsolvent,
+  #[msg("Idle sweep is disabled for this vault")]
+  IdleSweepDisabled,
+  #[msg("Margin account has open positions or orders")]
+  MarginNotFlat,
+  #[msg("Idle funds are parked in the margin account")]
+  IdleFundsParked,
+  #[msg("Client order id was not assigned by the vault")]
+  UnknownClientOrderId,
+  #[msg("Share lock needs shares and a lock length")]
+  InvalidLock,
+  #[msg("Shares are locked until the lock epoch ends")]
+  SharesLocked,
+  #[msg("Strategy program is not configured for this vault")]
+  StrategyDisabled,
+  #[msg("Strategy instruction is not allowlisted")]
+  StrategyInstructionNotAllowed,
+  #[msg("Strategy program returned invalid order intents")]
+  InvalidStrategyIntents,
+  #[msg("Ladder legs don't match the configured weights")]
+  InvalidLadder,
+  #[msg("Ladder leg exceeds the available initial margin")]
+  LadderMarginExceeded,
+  #[msg("Series has not expired")]
+  SeriesNotExpired,
+  #[msg("Settlement account doesn't match the series")]
+  InvalidSettlementAccount,
+  #[msg("Series expiry is already settled")]
+  ExpiryAlreadySettled,
+  #[msg("Deposit is below the minimum or mints no shares")]
+  DepositTooSmall,
+  #[msg("Slippage must be within [0, 10000] bps")]
+  InvalidSlippage,
+  #[msg("Abort accounts don't match the market layout")]
+  InvalidAbortAccounts,
+  #[msg("Epoch was aborted by the guardian, trading is halted")]
+  EpochAborted,
+  #[msg("Order exceeds the keeper throttle for this slot or epoch")]
+  OrderThrottled,
+  #[msg("Keeper is active or the dead-man switch is disabled")]
+  KeeperActive,
+  #[msg("Instruction only supports the per-group margin account")]
+  CrossMarginUnsupported,
+  #[msg("Ask is priced below the Black-Scholes premium floor")]
+  PremiumBelowFloor,
+  #[msg("Order price is too far from the zeta mark price")]
+  PriceOutsideMarkBand,
+  #[msg("Zeta is halted or the vault's series awaits settlement")]
+  MarketUnderMaintenance,
+  #[msg("No executor rotation is pending or its timelock hasn't passed")]
+  ExecutorRotationLocked,
+  #[msg("Executor still holds positions, open orders accounts or a Solend loan")]
+  ExecutorNotMigratable,
+  #[msg("Solend reserve deposit limit reached")]
+  ReserveFull,
+  #[msg("Solend reserve is paused or stale")]
+  ReservePaused,
+  #[msg("Order exceeds the resting order or reprice budget")]
+  OrderBudgetExceeded,
+  #[msg("Instruction not supported on the selected venue")]
+  VenueUnsupported,
+  #[msg("Delta hedge is disabled")]
+  HedgeDisabled,
+  #[msg("Insurance funds only cover a share price below the floor")]
+  NoDrawdown,
+  #[msg("Deposit intent doesn't match the vault or the user's nonce")]
+  InvalidDepositIntent,
+  #[msg("Deposit intent has expired")]
+  DepositIntentExpired,
+  #[msg("Deposit intent isn't signed by the user")]
+  InvalidDepositSignature,
+  #[msg("Reprice walks too far from the epoch's first order price")]
+  RepriceWalkExceeded,
+  #[msg("Order notional exceeds the single order cap")]
+  OrderNotionalExceeded,
+  #[msg("Lookup table address or addresses don't match")]
+  InvalidLookupTable,
+  #[msg("Solend reserve price diverges from the pyth price")]
+  ReservePriceDiverged,
+  #[msg("Stake amount is zero or above the staked shares")]
+  InvalidStakeAmount,
+  #[msg("No rewards to claim")]
+  NoRewards,
+  #[msg("Vault holds at least its accounted assets")]
+  NoShortfall,
+  #[msg("Vault is in the middle of a strategy call")]
+  Reentrancy,
+  #[msg("In-kind redemption is disabled or the vault has an open borrow")]
+  InKindRedemptionUnavailable,
+  #[msg("Vault valuation is stale, harvest or refresh it first")]
+  StaleValuation,
+  #[msg("Vault holds no short of that size in the market")]
+  NoShortToClose,
+  #[msg("Closing bid is above the allowed share of the mark price")]
+  ClosePriceTooHigh,
+  #[msg("Market is not in the vault market registry")]
+  MarketNotAllowed,
+  #[msg("Vault operator bond is missing or below the protocol minimum")]
+  BondRequired,
+  #[msg("Bond must be posted in the protocol bond mint")]
+  InvalidBondMint,
+  #[msg("Vault has no epoch schedule")]
+  EpochScheduleDisabled,
+  #[msg("Pending deposit of an earlier epoch has to be converted first")]
+  PendingDepositNotConverted,
+  #[msg("Pending deposit converts in the epoch after the one it was queued in")]
+  PendingDepositNotDue,
+  #[msg("Margin overrides must be between the Zeta parameters and 100%")]
+  InvalidMarginOverrides,
+  #[msg("Zeta moved the margin account by more than the instruction asked for")]
+  UnexpectedZetaState,
+  #[msg("Zeta group no longer matches the constants the vault math relies on")]
+  ZetaConstantsMismatch,
+  #[msg("Rollover accounts don't match the market layout or exceed the market limit")]
+  InvalidRolloverAccounts,
+  #[msg("Config instructions are signed by the vault governance")]
+  InvalidConfigAuthority,
+  #[msg("Order tag is too long or has characters other than a-z, 0-9 and -")]
+  InvalidOrderTag,
+  #[msg("Cosigners can't include the authority and a threshold needs one")]
+  InvalidKeeperSet,
+  #[msg("Order notional needs a keeper cosigner signature")]
+  CosignerRequired,
+  #[msg("Margin withdraw accounts don't match the vault or the vault isn't USDC")]
+  InvalidMarginWithdrawAccounts,
+  #[msg("Withdrawal would leave less than the buffered maintenance margin on Zeta")]
+  InsufficientFreeMargin,
+  #[msg("Feature is not enabled on this deployment yet")]
+  FeatureDisabled,
+  #[msg("Serum market or book accounts failed to load")]
+  InvalidMarketAccounts,
+  #[msg("No bid resting on the book to price the order from")]
+  EmptyOrderBook,
+  #[msg("Order tracker doesn't belong to the vault and market")]
+  InvalidOrderTracker,
+  #[msg("Lending collateral value overflows")]
+  CollateralValueOverflow,
+  #[msg("Escrow isn't a token account of the rotated executor")]
+  InvalidEscrow,
+  #[msg("Entry share price of the epoch is recorded when the previous epoch settles")]
+  EntryPriceNotRecorded,
+  #[msg("No pending deposit to cancel")]
+  NoPendingDeposit,
+  #[msg("Obligation isn't the executor's or holds reserves the vault doesn't use")]
+  InvalidObligation,
+  #[msg("Obligation must be refreshed in the same slot")]
+  ObligationStale,
+  #[msg("Deposit receipt and escrow are required while the exit fee or the deposit lock is on")]
+  InvalidDepositReceipt,
+  #[msg("Margin account has to be flat and empty before the vault switches margin accounts")]
+  MarginNotEmpty,
+  #[msg("User state or token account doesn't match the beneficial owner")]
+  InvalidBeneficialOwner,
+  #[msg("Protocol config or treasury account doesn't match")]
+  InvalidProtocolFeeAccounts,
+  #[msg("Keeper pool holds less than the amount above its rent exemption")]
+  InsufficientKeeperPool,
+  #[msg("Math overflow")]
+  MathOverflow,
+  #[msg("Bond amount must be positive and within the posted bond")]
+  InvalidBondAmount,
+  #[msg("Unbonded tokens are still in the unbond delay")]
+  BondLocked,
+  #[msg("Underlying vault holds less than the idle deposits")]
+  IdleDepositsMissing,
+  #[msg("Last holder can only withdraw once the margin, USDC and obligation legs are flat")]
+  VaultNotDrainable,
 }
@@ -0,0 +1,351 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use serum_dex::state::Market;
+use crate::{executor_seeds, cpi_calls as cpi, ratio_floor, VaultError};
+use crate::cpi_calls::zeta::{
+  calculate_margin_account_state_with_parameters, deserialize_greeks, get_native_oracle_price,
+  is_cross_margin_account_flat, load_cross_margin_account, load_margin_account, load_zeta_group,
+  MarketAccounts, OrderType, Side,
+};
+use crate::instructions::{MarginSnapshot, SolvencyAccounts, LADDER_LEG_ACCOUNTS};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+// serum open orders hold at most 128 orders, one bit per slot
+const OPEN_ORDERS_SLOTS: usize = 128;
+
+#[derive(Accounts)]
+pub struct AbortEpoch<'info> {
+  #[account(
+  mut,
+  has_one = guardian,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub guardian: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub zeta_vault: AccountInfo<'info>,
+  /// CHECK: cross-margin account when the vault uses that model
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  /// CHECK:
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK:
+  pub serum_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub mint_authority: AccountInfo<'info>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  /// CHECK:
+  #[account(address = cpi::memo::memo_program::ID)]
+  pub memo_program: AccountInfo<'info>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> AbortEpoch<'info> {
+  /// Break-glass exit for the guardian. For every market passed (same account
+  /// layout as the `place_ladder` legs, a keeper cosigner may follow them for
+  /// closes above the cosign threshold) the position is optionally closed at
+  /// the mark price within `max_slippage_bps`, then all open orders are
+  /// cancelled. The free margin goes back to the usdc vault and withdrawals
+  /// stay open, with trading halted, until the next epoch. Closing at a loss
+  /// is the point, so the valuation is only recorded, the floor price isn't
+  /// asserted.
+  pub fn abort_epoch(
+    &mut self,
+    close_positions: bool,
+    max_slippage_bps: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    if max_slippage_bps > 10_000 {
+      return err!(VaultError::InvalidSlippage);
+    }
+    let legs_len = remaining_accounts.len() / LADDER_LEG_ACCOUNTS * LADDER_LEG_ACCOUNTS;
+    if remaining_accounts.len() > legs_len + 1 {
+      return err!(VaultError::InvalidAbortAccounts);
+    }
+    let (legs, cosigner) = remaining_accounts.split_at(legs_len);
+    let cosigned = self.vault.keeper_set.is_cosigned(cosigner);
+
+    for market in legs.chunks(LADDER_LEG_ACCOUNTS) {
+      if close_positions {
+        self.close_position(market, max_slippage_bps, cosigned)?;
+      }
+      self.cancel_all(market)?;
+    }
+    self.withdraw_free_margin()?;
+
+    let clock = Clock::get()?;
+    self.vault.after_abort(clock.unix_timestamp)?;
+    msg!("aborted_until: {}", self.vault.aborted_until);
+    let nav = self.solvency.record_valuation(&mut self.vault, clock.slot)?;
+    msg!("total assets: {}", nav.total_assets());
+
+    let memo = cpi::memo::MemoTrailer::new(
+      "derisk",
+      &self.vault.key(),
+      self.vault.epoch_schedule.current_epoch(clock.unix_timestamp),
+    )
+      .field("close_positions", close_positions)
+      .field("markets", legs.len() / LADDER_LEG_ACCOUNTS)
+      .field("aborted_until", self.vault.aborted_until)
+      .finish();
+    cpi::memo::log_memo(self.memo_program.to_account_info(), memo)
+  }
+
+  fn close_position(&mut self, market: &[AccountInfo<'info>], max_slippage_bps: u64, cosigned: bool) -> Result<()> {
+    let (index, mark_price) = {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+      let index = zeta_group.get_product_index_by_key(&market[0].key())?;
+      (index, greeks.mark_prices[index])
+    };
+    let cross_margin = self.vault.cross_margin;
+    let before = MarginSnapshot::load(&self.margin_account, cross_margin, Some(index))?;
+    let size = before.position;
+    if size == 0 {
+      return Ok(());
+    }
+
+    let (side, price) = if size > 0 {
+      (Side::Ask, ratio_floor!(mark_price, 10_000 - max_slippage_bps, 10_000_u64).unwrap())
+    } else {
+      (Side::Bid, ratio_floor!(mark_price, 10_000 + max_slippage_bps, 10_000_u64).unwrap())
+    };
+    // the unwind goes through the order throttle like any keeper order, a
+    // stolen guardian key can't dump the positions at once either
+    let clock = Clock::get()?;
+    self.vault.consume_order_budget(size.unsigned_abs(), price, cosigned, clock.slot, clock.unix_timestamp)?;
+    let client_order_id = self.vault.next_client_order_id();
+    msg!("market: {}", market[0].key());
+    msg!("size: {}", size);
+    msg!("price: {}", price);
+
+    // plain limit order without expiry, closing has to cross the spread
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    let seeds = executor_seeds!(self.vault);
+    let accounts = cpi::zeta::PlaceOrder {
+      zeta_group: self.zeta_group.to_account_info(),
+      margin_account: self.margin_account.to_account_info(),
+      authority: self.executor.to_account_info(),
+      token_program: self.token_program.clone(),
+      state: self.state.to_account_info(),
+      greeks: self.greeks.to_account_info(),
+      market_accounts: MarketAccounts {
+        market: market[0].clone(),
+        request_queue: market[1].clone(),
+        event_queue: market[2].clone(),
+        bids: market[3].clone(),
+        asks: market[4].clone(),
+        order_payer_token_account: market[8].clone(),
+        coin_vault: market[5].clone(),
+        pc_vault: market[6].clone(),
+        coin_wallet: market[7].clone(),
+        pc_wallet: market[8].clone(),
+      },
+      oracle: self.oracle.to_account_info(),
+      open_orders: market[9].clone(),
+      rent: self.rent.clone(),
+      market_node: market[10].clone(),
+      market_mint: market[11].clone(),
+      mint_authority: self.mint_authority.to_account_info(),
+      serum_authority: self.serum_authority.to_account_info(),
+      dex_program: self.dex_program.to_account_info(),
+    };
+    if cross_margin {
+      cpi::zeta::zeta_client::place_cross_margin_order(
+        self.zeta_program.to_account_info(),
+        accounts,
+        price,
+        size.unsigned_abs(),
+        side,
+        OrderType::Limit,
+        Some(client_order_id),
+        None,
+        None,
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::place_order(
+        self.zeta_program.to_account_info(),
+        accounts,
+        price,
+        size.unsigned_abs(),
+        side,
+        Some(client_order_id),
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(&self.margin_account, cross_margin, Some(index))?;
+    before.check_order(&after, side, price, size.unsigned_abs(), spot)
+  }
+
+  // also cancels what is left of the closing order
+  fn cancel_all(&self, market: &[AccountInfo<'info>]) -> Result<()> {
+    if market[9].data_is_empty() {
+      return Ok(());
+    }
+    let orders = resting_orders(&market[0], &market[9], &self.dex_program.key())?;
+    let seeds = executor_seeds!(self.vault);
+    for (side, order_id) in orders {
+      cpi::zeta::zeta_client::cancel_order(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::CancelOrder {
+          authority: self.executor.to_account_info(),
+          cancel_accounts: cpi::zeta::CancelAccounts {
+            zeta_group: self.zeta_group.to_account_info(),
+            state: self.state.to_account_info(),
+            margin_account: self.margin_account.to_account_info(),
+            dex_program: self.dex_program.to_account_info(),
+            serum_authority: self.serum_authority.to_account_info(),
+            open_orders: market[9].clone(),
+            market: market[0].clone(),
+            bids: market[3].clone(),
+            asks: market[4].clone(),
+            event_queue: market[2].clone(),
+          },
+        },
+        side,
+        order_id,
+        seeds,
+      )?;
+    }
+    Ok(())
+  }
+
+  fn withdraw_free_margin(&mut self) -> Result<()> {
+    if self.vault.cross_margin {
+      return self.withdraw_cross_margin();
+    }
+    let amount_out = {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      let margin_account = load_margin_account(&self.margin_account)?;
+      let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+      let state = calculate_margin_account_state_with_parameters(
+        &zeta_group,
+        &self.vault.margin_parameters(&zeta_group),
+        &margin_account,
+        &greeks,
+        &self.price_update,
+        &self.vault.oracle,
+      )?;
+      (state.available_balance_initial.max(0) as u64).min(state.balance)
+    };
+    msg!("withdraw margin: {}", amount_out);
+    if amount_out == 0 {
+      return Ok(());
+    }
+
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::withdraw(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::Withdraw {
+        zeta_group: self.zeta_group.to_account_info(),
+        margin_account: self.margin_account.to_account_info(),
+        vault: self.zeta_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        socialized_loss_account: self.socialized_loss_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        token_program: self.token_program.clone(),
+        state: self.state.to_account_info(),
+        greeks: self.greeks.to_account_info(),
+        oracle: self.oracle.to_account_info(),
+      },
+      amount_out,
+      seeds
+    )?;
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)?;
+    self.vault.margin_basis.after_margin_withdraw(amount_out)
+  }
+
+  // there is no initial margin requirement of a cross-margin account to read,
+  // its balance is only free once the account is flat
+  fn withdraw_cross_margin(&mut self) -> Result<()> {
+    let amount_out = {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      if is_cross_margin_account_flat(&margin_account) { margin_account.balance } else { 0 }
+    };
+    msg!("withdraw margin: {}", amount_out);
+    if amount_out == 0 {
+      return Ok(());
+    }
+
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::withdraw_v2(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::WithdrawV2 {
+        margin_account: self.margin_account.to_account_info(),
+        vault: self.zeta_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        socialized_loss_account: self.socialized_loss_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        token_program: self.token_program.clone(),
+        state: self.state.to_account_info(),
+        greeks: self.greeks.to_account_info(),
+        oracle: self.oracle.to_account_info(),
+      },
+      amount_out,
+      seeds
+    )?;
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)?;
+    self.vault.margin_basis.after_margin_withdraw(amount_out)
+  }
+}
+
+/// Side and order id of every order resting in the serum `open_orders`.
+pub fn resting_orders(market: &AccountInfo, open_orders: &AccountInfo, dex_program: &Pubkey) -> Result<Vec<(Side, u128)>> {
+  let serum_market = Market::load(market, dex_program, false)
+    .map_err(|_| error!(VaultError::InvalidMarketAccounts))?;
+  let open_orders = serum_market
+    .load_orders_mut(open_orders, None, dex_program, None, None)
+    .map_err(|_| error!(VaultError::InvalidMarketAccounts))?;
+  // packed struct, copy the fields out before reading them
+  let free_slot_bits = open_orders.free_slot_bits;
+  let is_bid_bits = open_orders.is_bid_bits;
+  let order_ids = open_orders.orders;
+
+  Ok((0..OPEN_ORDERS_SLOTS)
+    .filter(|slot| free_slot_bits & (1u128 << slot) == 0)
+    .map(|slot| {
+      let side = if is_bid_bits & (1u128 << slot) != 0 { Side::Bid } else { Side::Ask };
+      (side, order_ids[slot])
+    })
+    .collect())
+}
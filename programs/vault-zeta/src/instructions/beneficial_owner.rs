@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+use crate::structs::{UserState, Vault};
+use crate::pda::{USER_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SetBeneficialOwner<'info> {
+  // share holder, usually an integrator PDA signing through CPI
+  pub user_account: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump = user_state.bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+}
+
+impl<'info> SetBeneficialOwner<'info> {
+  /// Points the user's rewards and USDC claims at `beneficial_owner`,
+  /// `Pubkey::default()` hands them back to the user.
+  pub fn set_beneficial_owner(&mut self, beneficial_owner: Pubkey) -> Result<()> {
+    msg!("beneficial owner: {}", beneficial_owner);
+    self.user_state.set_beneficial_owner(beneficial_owner)
+  }
+}
+
+/// Wallet the rewards of `owner` are paid to, set on the `UserState` passed
+/// as the first remaining account. Holders without one are paid themselves.
+pub fn beneficial_owner<'info>(
+  vault: &Pubkey,
+  owner: &Pubkey,
+  remaining_accounts: &[AccountInfo<'info>],
+) -> Result<Pubkey> {
+  let user_state = match remaining_accounts.first() {
+    Some(user_state) => Account::<UserState>::try_from(user_state)?,
+    None => return Ok(*owner),
+  };
+  if user_state.vault != *vault || user_state.owner != *owner {
+    return err!(VaultError::InvalidBeneficialOwner);
+  }
+  Ok(user_state.beneficial_owner())
+}
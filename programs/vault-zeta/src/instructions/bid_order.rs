@@ -1,35 +1,71 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 use anchor_spl::mint::USDC;
-use serum_dex::critbit::{LeafNode, SlabView};
 use serum_dex::state::{Market};
-use crate::{executor_seeds, cpi_calls as cpi};
-use crate::cpi_calls::zeta::{MarginAccount, MarketAccounts, Side, ZetaGroup};
-use crate::structs::Vault;
+use crate::{executor_seeds, cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  calculate_cross_margin_greeks_exposure, calculate_greeks_exposure, deserialize_greeks,
+  get_native_oracle_price, load_cross_margin_account, load_margin_account, load_zeta_group,
+  validate_market_tradeable, Kind, MarketAccounts, Side,
+};
+use crate::instructions::{finish_dry_run, order_at_best_bid, MarginSnapshot, SolvencyAccounts};
+use crate::structs::{keeper_order_tag, Decimals, DryRunOrder, OrderTracker, Vault, Venue};
+use crate::pda::{EXECUTOR_SEED, ORDER_TRACKER_SEED, VAULT_SEED};
+
+// premium a `bid_order` joining the best bid sizes for, in whole USDC
+pub const BID_ORDER_MAX_PREMIUM: u64 = 100_000;
+
+/// Orders resting in a serum open orders account, 0 before it is initialized.
+pub fn count_resting_orders<'a, 'b>(
+  market: &'a AccountInfo<'b>,
+  open_orders: &'a AccountInfo<'b>,
+  dex_program: &Pubkey,
+) -> Result<u64> {
+  if open_orders.data_is_empty() {
+    return Ok(0);
+  }
+  let serum_market = Market::load(market, dex_program, false)
+    .map_err(|_| error!(VaultError::InvalidMarketAccounts))?;
+  let open_orders = serum_market
+    .load_orders_mut(open_orders, None, dex_program, None, None)
+    .map_err(|_| error!(VaultError::InvalidMarketAccounts))?;
+  // packed struct, copy the field out before reading it
+  let free_slot_bits = open_orders.free_slot_bits;
+  Ok(free_slot_bits.count_zeros() as u64)
+}
 
 #[derive(Accounts)]
 pub struct BidOrder<'info> {
   #[account(
   mut,
   has_one = authority,
-  seeds = [b"vault", vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
   #[account(mut)]
   pub authority: Signer<'info>,
-  /// CHECK: Oracle is currently unused and will be enabled on subsequent updates
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
   pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
   /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
   pub zeta_group: AccountInfo<'info>,
-  /// CHECK:
-  #[account(mut, address = vault.margin_account)]
+  /// CHECK: cross-margin account when the vault uses that model
+  #[account(mut, address = vault.active_margin_account())]
   pub margin_account: AccountInfo<'info>,
   /// CHECK:
   #[account(mut)]
@@ -89,10 +125,20 @@ pub struct BidOrder<'info> {
   pub market_mint: AccountInfo<'info>,
   /// CHECK:
   pub mint_authority: AccountInfo<'info>,
+  // keeper order prices of the market this epoch, bounds the reprice walk
+  #[account(
+  init_if_needed,
+  space = 8 + OrderTracker::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [ORDER_TRACKER_SEED, vault.key().as_ref(), market.key().as_ref()],
+  bump
+  )]
+  pub order_tracker: Box<Account<'info, OrderTracker>>,
   pub rent: Sysvar<'info, Rent>,
   pub token_program: Program<'info, Token>,
   pub system_program: Program<'info, System>,
   pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> BidOrder<'info> {
@@ -101,37 +147,262 @@ impl<'info> BidOrder<'info> {
   //   Ok(())
   // }
 
-  pub fn bid_order(&self) -> Result<()> {
-    if self.open_orders.data_is_empty() {
+  /// With `dry_run` every check runs but the order is only reported, see
+  /// `finish_dry_run`. The greeks limits then cover the filled positions only.
+  /// Keeper cosigners are passed as remaining accounts, see `KeeperSet`.
+  pub fn bid_order(
+    &mut self,
+    tracker_bump: u8,
+    dry_run: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_venue(Venue::Zeta)?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    self.validate_market()?;
+    self.check_market_allowed()?;
+    let snapshot = if dry_run { Some(self.snapshot()) } else { None };
+    if self.open_orders.data_is_empty() && !dry_run {
       self.init_open_orders().unwrap();
     }
 
-    let (price, size) = self.calculate_order(100000000000).unwrap();
-    self.place_order(price, size, Side::Bid)?;
+    let resting_orders = count_resting_orders(&self.market, &self.open_orders, &self.dex_program.key())?;
+    self.vault.check_resting_orders(resting_orders)?;
+
+    let (price, size) = order_at_best_bid(
+      &self.market,
+      &self.bids,
+      &self.dex_program.key(),
+      Decimals::platform_amount(BID_ORDER_MAX_PREMIUM).unwrap(),
+    )?;
+    let cosigned = self.vault.keeper_set.is_cosigned(remaining_accounts);
+    self.vault.consume_order_budget(size, price, cosigned, clock.slot, clock.unix_timestamp)?;
+    self.track_order(price, size, clock.unix_timestamp, tracker_bump)?;
+    let client_order_id = self.vault.next_client_order_id();
+    if let Some(snapshot) = snapshot {
+      self.check_order(price)?;
+      self.check_greeks_limits()?;
+      return self.end_dry_run(snapshot, price, size, client_order_id);
+    }
+    self.place_order(price, size, Side::Bid, client_order_id)?;
+    self.check_greeks_limits()?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  /// Reprices a resting keeper bid: cancels it by client order id and places
+  /// the replacement in the same instruction. The pair counts as one reprice
+  /// and leaves the number of resting orders unchanged. Over an epoch the
+  /// reprices can't walk further than `max_reprice_walk_bps` from the first
+  /// order price of the market. A `dry_run` doesn't cancel the resting bid.
+  pub fn cancel_replace(
+    &mut self,
+    client_order_id: u64,
+    price: u64,
+    size: u64,
+    tracker_bump: u8,
+    dry_run: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_venue(Venue::Zeta)?;
+    self.vault.check_group_margin()?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    self.validate_market()?;
+    self.check_market_allowed()?;
+    // ids are handed out sequentially, anything above the counter was never placed
+    if client_order_id == 0 || client_order_id > self.vault.order_count {
+      return err!(VaultError::UnknownClientOrderId);
+    }
+
+    let snapshot = if dry_run { Some(self.snapshot()) } else { None };
+    self.vault.consume_reprice(clock.unix_timestamp)?;
+    let epoch = self.vault.epoch_schedule.current_epoch(clock.unix_timestamp);
+    // fail before cancelling, `track_order` checks the replacement again
+    self.order_tracker.check_reprice(price, epoch, self.vault.max_reprice_walk_bps)?;
+    if !dry_run {
+      let before = self.margin_snapshot()?;
+      self.cancel_order(client_order_id)?;
+      let cancelled = before.check_cancel(&self.margin_snapshot()?)?;
+      self.order_tracker.after_bid_cancelled(cancelled)?;
+    }
+    let cosigned = self.vault.keeper_set.is_cosigned(remaining_accounts);
+    self.vault.consume_order_budget(size, price, cosigned, clock.slot, clock.unix_timestamp)?;
+    self.track_order(price, size, clock.unix_timestamp, tracker_bump)?;
+    let new_client_order_id = self.vault.next_client_order_id();
+    if let Some(snapshot) = snapshot {
+      self.check_order(price)?;
+      self.check_greeks_limits()?;
+      return self.end_dry_run(snapshot, price, size, new_client_order_id);
+    }
+    self.place_order(price, size, Side::Bid, new_client_order_id)?;
+    self.check_greeks_limits()?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  /// Buys back `size` lots of a short to close it early, once most of the
+  /// premium has decayed. Closing is allowed outside the market registry.
+  /// With the closing bids already resting they can't exceed the short, so
+  /// they never open a long, and can't pay more than `max_close_price_bps`
+  /// of the mark. The order budget and the greeks limits apply as to any
+  /// other keeper bid.
+  pub fn buy_to_close(
+    &mut self,
+    price: u64,
+    size: u64,
+    tracker_bump: u8,
+    dry_run: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_venue(Venue::Zeta)?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    self.validate_market()?;
+
+    let resting_orders = count_resting_orders(&self.market, &self.open_orders, &self.dex_program.key())?;
+    self.vault.check_resting_orders(resting_orders)?;
+    self.check_short_position(size)?;
+    self.check_close_price(price)?;
+    let snapshot = if dry_run { Some(self.snapshot()) } else { None };
+    let cosigned = self.vault.keeper_set.is_cosigned(remaining_accounts);
+    self.vault.consume_order_budget(size, price, cosigned, clock.slot, clock.unix_timestamp)?;
+    self.track_order(price, size, clock.unix_timestamp, tracker_bump)?;
+    let client_order_id = self.vault.next_client_order_id();
+    if let Some(snapshot) = snapshot {
+      self.check_price_band(price)?;
+      self.check_greeks_limits()?;
+      return self.end_dry_run(snapshot, price, size, client_order_id);
+    }
+    self.place_closing_bid(price, size, client_order_id)?;
+    self.check_greeks_limits()?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn snapshot(&self) -> (Vault, OrderTracker) {
+    ((**self.vault).clone(), (**self.order_tracker).clone())
+  }
+
+  // puts the vault and the tracker back, a dry run leaves no state behind
+  fn end_dry_run(
+    &mut self,
+    snapshot: (Vault, OrderTracker),
+    price: u64,
+    size: u64,
+    client_order_id: u64,
+  ) -> Result<()> {
+    let (vault, order_tracker) = snapshot;
+    **self.vault = vault;
+    **self.order_tracker = order_tracker;
+    finish_dry_run(self.vault.key(), vec![DryRunOrder {
+      target: self.market.key(),
+      price,
+      size: size as i64,
+      client_order_id,
+    }])
+  }
+
+  fn check_short_position(&self, size: u64) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    let ledger = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      margin_account.product_ledgers[index]
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      margin_account.product_ledgers[index]
+    };
+    // packed struct, copy the fields out before reading them
+    let position = ledger.position.size;
+    let closing_orders = ledger.order_state.closing_orders;
+    let closing_size = closing_orders.checked_add(size).unwrap();
+    if size == 0 || position >= 0 || closing_size > position.unsigned_abs() {
+      msg!("position: {}, closing bids: {}, size: {}", position, closing_orders, size);
+      return err!(VaultError::NoShortToClose);
+    }
     Ok(())
   }
 
-  fn calculate_order(&self, max_amount: u64) -> Result<(u64, u64)> {
-    let market_info = self.market.to_account_info();
-    let bids = self.bids.to_account_info();
-    let dex_program = self.dex_program.key();
+  fn check_close_price(&self, price: u64) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    self.vault.check_close_price(price, greeks.mark_prices[index])
+  }
 
-    let market = Market::load(
-      &market_info,
-      &dex_program,
-      false
-    ).unwrap();
+  fn track_order(&mut self, price: u64, size: u64, now: i64, tracker_bump: u8) -> Result<()> {
+    if !self.order_tracker.is_initialized() {
+      self.order_tracker.initialize(
+        tracker_bump,
+        self.vault.key(),
+        self.market.key(),
+      )?;
+    }
+    let epoch = self.vault.epoch_schedule.current_epoch(now);
+    self.order_tracker.check_reprice(price, epoch, self.vault.max_reprice_walk_bps)?;
+    self.order_tracker.after_order(price, epoch)?;
+    self.order_tracker.after_bid_placed(size)
+  }
 
-    let bids = market.load_bids_mut(&bids).unwrap();
-    let best_price = bids.find_max().unwrap();
-    let best_price_node = bids
-      .get(best_price as u32).unwrap()
-      .as_leaf().unwrap();
+  fn cancel_order(&self, client_order_id: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::cancel_order_by_client_order_id(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::CancelOrder {
+        authority: self.executor.to_account_info(),
+        cancel_accounts: cpi::zeta::CancelAccounts {
+          zeta_group: self.zeta_group.to_account_info(),
+          state: self.state.to_account_info(),
+          margin_account: self.margin_account.to_account_info(),
+          dex_program: self.dex_program.to_account_info(),
+          serum_authority: self.serum_authority.to_account_info(),
+          open_orders: self.open_orders.to_account_info(),
+          market: self.market.to_account_info(),
+          bids: self.bids.to_account_info(),
+          asks: self.asks.to_account_info(),
+          event_queue: self.event_queue.to_account_info(),
+        },
+      },
+      client_order_id,
+      seeds,
+    )
+  }
+
+  // open orders count as filled, so the check covers the order just placed
+  fn check_greeks_limits(&self) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let exposure = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      calculate_cross_margin_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      calculate_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    };
+    self.vault.check_greeks(&exposure)
+  }
 
-    let price = best_price_node.price().get();
-    let size = max_amount
-      .checked_div(price).unwrap();
-    Ok((price, size))
+  // don't trust the keeper to pick a tradeable series
+  fn validate_market(&self) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    validate_market_tradeable(&zeta_group, &self.market.key())?;
+    Ok(())
+  }
+
+  // the executor only opens orders on the vault's registered markets
+  fn check_market_allowed(&self) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    let spot = if self.vault.market_registry.needs_spot() {
+      get_native_oracle_price(&self.price_update, &self.vault.oracle)?
+    } else {
+      0
+    };
+    self.vault.check_market_allowed(&zeta_group, index, spot)
   }
 
   fn init_open_orders(
@@ -159,9 +430,37 @@ impl<'info> BidOrder<'info> {
     Ok(())
   }
 
-  fn place_order(&self, price: u64, size: u64, kind: Side) -> Result<()> {
-    let seeds = executor_seeds!(self.vault);
-    let accounts = cpi::zeta::PlaceOrder {
+  // keepers have sold calls for dust before
+  fn check_premium_floor(&self, price: u64) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    let kind = zeta_group.products[index].kind;
+    let strike = match kind {
+      Kind::Call | Kind::Put => zeta_group.get_strike(index)?,
+      _ => 0,
+    };
+    let expiry_ts = zeta_group.get_expiry_series_by_product_index(index)?.expiry_ts;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    self.vault.check_premium_floor(
+      price,
+      kind,
+      spot,
+      strike,
+      expiry_ts.saturating_sub(now),
+    )
+  }
+
+  // guards against keeper bugs and oracle desync alike
+  fn check_price_band(&self, price: u64) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    self.vault.check_price_band(price, greeks.mark_prices[index])
+  }
+
+  fn place_order_accounts(&self) -> cpi::zeta::PlaceOrder<'info> {
+    cpi::zeta::PlaceOrder {
       zeta_group: self.zeta_group.to_account_info(),
       margin_account: self.margin_account.to_account_info(),
       authority: self.executor.to_account_info(),
@@ -188,18 +487,103 @@ impl<'info> BidOrder<'info> {
       mint_authority: self.mint_authority.to_account_info(),
       serum_authority: self.serum_authority.to_account_info(),
       dex_program: self.dex_program.to_account_info(),
-    };
+    }
+  }
+
+  fn check_order(&self, price: u64) -> Result<()> {
+    self.check_price_band(price)?;
+    self.check_premium_floor(price)
+  }
+
+  // the margin account has to move by the order only, see `MarginSnapshot::check_order`
+  fn margin_snapshot(&self) -> Result<MarginSnapshot> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))
+  }
+
+  fn place_order(&self, price: u64, size: u64, kind: Side, client_order_id: u64) -> Result<()> {
+    self.check_order(price)?;
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    let before = self.margin_snapshot()?;
+    self.send_order(price, size, kind, client_order_id)?;
+    before.check_order(&self.margin_snapshot()?, kind, price, size, spot)
+  }
+
+  fn send_order(&self, price: u64, size: u64, kind: Side, client_order_id: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let accounts = self.place_order_accounts();
     msg!("price: {}", price);
     msg!("size: {}", size);
-    cpi::zeta::zeta_client::place_order(
+    msg!("client_order_id: {}", client_order_id);
+    if self.vault.cross_margin {
+      return cpi::zeta::zeta_client::place_cross_margin_order(
+        self.zeta_program.to_account_info(),
+        accounts,
+        price,
+        size,
+        kind,
+        self.vault.order_type(),
+        Some(client_order_id),
+        keeper_order_tag(None)?,
+        self.vault.order_tif_offset(),
+        seeds
+      );
+    }
+    cpi::zeta::zeta_client::place_order_v4(
       self.zeta_program.to_account_info(),
       accounts,
       price,
       size,
       kind,
-      None,
+      self.vault.order_type(),
+      Some(client_order_id),
+      keeper_order_tag(None)?,
+      self.vault.order_tif_offset(),
       seeds
     )?;
     Ok(())
   }
+
+  // no premium floor, the bid buys back a short
+  fn place_closing_bid(&self, price: u64, size: u64, client_order_id: u64) -> Result<()> {
+    self.check_price_band(price)?;
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    let before = self.margin_snapshot()?;
+    self.send_closing_bid(price, size, client_order_id)?;
+    before.check_order(&self.margin_snapshot()?, Side::Bid, price, size, spot)
+  }
+
+  fn send_closing_bid(&self, price: u64, size: u64, client_order_id: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let accounts = self.place_order_accounts();
+    msg!("closing price: {}", price);
+    msg!("closing size: {}", size);
+    msg!("client_order_id: {}", client_order_id);
+    if self.vault.cross_margin {
+      return cpi::zeta::zeta_client::place_cross_margin_order(
+        self.zeta_program.to_account_info(),
+        accounts,
+        price,
+        size,
+        Side::Bid,
+        self.vault.order_type(),
+        Some(client_order_id),
+        keeper_order_tag(None)?,
+        self.vault.order_tif_offset(),
+        seeds
+      );
+    }
+    cpi::zeta::zeta_client::place_order_v3(
+      self.zeta_program.to_account_info(),
+      accounts,
+      price,
+      size,
+      Side::Bid,
+      self.vault.order_type(),
+      Some(client_order_id),
+      keeper_order_tag(None)?,
+      seeds
+    )
+  }
 }
@@ -0,0 +1,252 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use crate::VaultError;
+use crate::structs::{Bond, ProtocolConfig, Vault};
+use crate::pda::{BOND_ESCROW_SEED, BOND_SEED, PROTOCOL_CONFIG_SEED, VAULT_SEED};
+
+
+/// Fails unless a vault created with an operator bond holds a bond covering
+/// the protocol `min_bond` right now, read from the protocol config and bond
+/// passed as the last two remaining accounts, see `BOND_ACCOUNTS`.
+pub fn check_bonded<'info>(vault: &Account<'info, Vault>, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+  if !vault.bond_required {
+    return Ok(());
+  }
+  if remaining_accounts.len() < 2 {
+    return err!(VaultError::BondRequired);
+  }
+  let accounts = &remaining_accounts[remaining_accounts.len() - 2..];
+  let (protocol_config, _) = Pubkey::find_program_address(&[PROTOCOL_CONFIG_SEED], &crate::ID);
+  let (bond, _) = Pubkey::find_program_address(&[BOND_SEED, vault.key().as_ref()], &crate::ID);
+  if accounts[0].key() != protocol_config || accounts[1].key() != bond || accounts[1].data_is_empty() {
+    return err!(VaultError::BondRequired);
+  }
+  let protocol_config = Account::<ProtocolConfig>::try_from(&accounts[0])?;
+  let bond = Account::<Bond>::try_from(&accounts[1])?;
+  if !bond.covers(protocol_config.min_bond) {
+    msg!("bond: {}, min bond: {}", bond.amount, protocol_config.min_bond);
+    return err!(VaultError::BondRequired);
+  }
+  Ok(())
+}
+
+
+#[derive(Accounts)]
+pub struct PostBond<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  #[account(
+  seeds = [PROTOCOL_CONFIG_SEED],
+  bump = protocol_config.bump
+  )]
+  pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+  #[account(
+  init_if_needed,
+  space = 8 + Bond::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [BOND_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub bond: Box<Account<'info, Bond>>,
+  pub bond_mint: Box<Account<'info, Mint>>,
+  // owned by the bond PDA, the executor can't move it
+  #[account(
+  init_if_needed,
+  payer = authority,
+  token::mint = bond_mint,
+  token::authority = bond,
+  seeds = [BOND_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub bond_escrow: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = authority,
+  token::mint = bond_mint
+  )]
+  pub operator_tokens: Box<Account<'info, TokenAccount>>,
+  #[account(address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> PostBond<'info> {
+  /// Locks `amount` more of the operator's tokens in the vault bond. The
+  /// vault takes deposits once the bond covers the protocol `min_bond`.
+  pub fn post_bond(&mut self, amount: u64, bond_bump: u8) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let bond_mint = self.protocol_config.bond_mint_for(self.underlying_vault.mint);
+    if self.bond_mint.key() != bond_mint {
+      return err!(VaultError::InvalidBondMint);
+    }
+    if !self.bond.is_initialized() {
+      self.bond.initialize(bond_bump, self.vault.key(), bond_mint)?;
+    }
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.operator_tokens.to_account_info(),
+        to: self.bond_escrow.to_account_info(),
+        authority: self.authority.to_account_info(),
+      });
+    self.bond.after_post(amount)?;
+    transfer(ctx, amount)?;
+    msg!("bond: {}, min bond: {}", self.bond.amount, self.protocol_config.min_bond);
+    Ok(())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct SlashBond<'info> {
+  pub guardian: Signer<'info>,
+  #[account(
+  has_one = guardian,
+  seeds = [PROTOCOL_CONFIG_SEED],
+  bump = protocol_config.bump
+  )]
+  pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [BOND_SEED, vault.key().as_ref()],
+  bump = bond.bump
+  )]
+  pub bond: Box<Account<'info, Bond>>,
+  #[account(
+  mut,
+  seeds = [BOND_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub bond_escrow: Box<Account<'info, TokenAccount>>,
+  // slashed tokens go to the treasury
+  #[account(
+  mut,
+  token::authority = protocol_config.treasury,
+  token::mint = bond.mint
+  )]
+  pub treasury_tokens: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SlashBond<'info> {
+  /// Guardian penalty for a rule violation of the operator, at most the
+  /// whole bond including what is unbonding. The vault stops taking deposits
+  /// until the bond is topped back up to `min_bond`.
+  pub fn slash_bond(&mut self, amount: u64) -> Result<()> {
+    let slashed = self.bond.slash(amount)?;
+    msg!("slashed: {}", slashed);
+    if slashed > 0 {
+      let vault = self.vault.key();
+      let seeds = &[BOND_SEED, vault.as_ref(), &[self.bond.bump]];
+      let signer: &[&[&[u8]]] = &[&seeds[..]];
+      let ctx = CpiContext::new_with_signer(
+        self.token_program.to_account_info(),
+        Transfer {
+          from: self.bond_escrow.to_account_info(),
+          to: self.treasury_tokens.to_account_info(),
+          authority: self.bond.to_account_info(),
+        }, signer);
+      transfer(ctx, slashed)?;
+    }
+    Ok(())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct RequestUnbond<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [BOND_SEED, vault.key().as_ref()],
+  bump = bond.bump
+  )]
+  pub bond: Box<Account<'info, Bond>>,
+}
+
+impl<'info> RequestUnbond<'info> {
+  /// Starts unbonding `amount` of the bond. It stops counting towards
+  /// `min_bond` at once and stays slashable for `BOND_UNBOND_DELAY`.
+  pub fn request_unbond(&mut self, amount: u64) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let clock = Clock::get()?;
+    self.bond.request_unbond(amount, clock.unix_timestamp)?;
+    msg!("bond: {}, unbonding: {}, unbond ts: {}", self.bond.amount, self.bond.unbonding, self.bond.unbond_ts);
+    Ok(())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct WithdrawBond<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [BOND_SEED, vault.key().as_ref()],
+  bump = bond.bump
+  )]
+  pub bond: Box<Account<'info, Bond>>,
+  #[account(
+  mut,
+  seeds = [BOND_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub bond_escrow: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = authority,
+  token::mint = bond.mint
+  )]
+  pub operator_tokens: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawBond<'info> {
+  /// Returns the unbonding tokens to the operator once the unbond delay
+  /// has passed.
+  pub fn withdraw_bond(&mut self) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let clock = Clock::get()?;
+    let unbonded = self.bond.take_unbonded(clock.unix_timestamp)?;
+    msg!("unbonded: {}", unbonded);
+    let vault = self.vault.key();
+    let seeds = &[BOND_SEED, vault.as_ref(), &[self.bond.bump]];
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.bond_escrow.to_account_info(),
+        to: self.operator_tokens.to_account_info(),
+        authority: self.bond.to_account_info(),
+      }, signer);
+    transfer(ctx, unbonded)
+  }
+}
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{cpi_calls as cpi, executor_seeds, VaultError};
+use crate::instructions::{book_obligation, refresh_obligation, SolvencyAccounts};
+use crate::structs::{Feature, FeatureFlags, Vault};
+use crate::pda::{EXECUTOR_SEED, FEATURE_FLAGS_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct BorrowObligationLiquidity<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = obligation,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  // collateral reserve, used to value the obligation
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  #[account(
+  mut,
+  constraint = borrow_reserve.lending_market == reserve.lending_market,
+  constraint = borrow_reserve.liquidity.mint_pubkey == usdc_vault.mint
+  )]
+  pub borrow_reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK:
+  #[account(mut, address = borrow_reserve.liquidity.supply_pubkey)]
+  pub borrow_reserve_liquidity_supply: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, address = borrow_reserve.config.fee_receiver)]
+  pub borrow_reserve_fee_receiver: AccountInfo<'info>,
+  /// CHECK: validated in `check_obligation`
+  #[account(mut, owner = cpi::solend::solend_program::ID)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub zeta_vault: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  pub greeks: AccountInfo<'info>,
+  #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+  pub feature_flags: Box<Account<'info, FeatureFlags>>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> BorrowObligationLiquidity<'info> {
+  // reserves must be refreshed earlier in the same transaction, the
+  // obligation is refreshed here so the LTV check sees the accrued interest
+  pub fn borrow_obligation_liquidity(&mut self, amount: u64) -> Result<()> {
+    self.feature_flags.check_enabled(Feature::Leverage)?;
+    if self.vault.max_ltv_bps == 0 {
+      return err!(VaultError::LeverageDisabled);
+    }
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    refresh_obligation(
+      &self.lending_program.to_account_info(),
+      &self.obligation,
+      &[self.reserve.to_account_info(), self.borrow_reserve.to_account_info()],
+    )?;
+    {
+      let obligation = Account::<cpi::solend::Obligation>::try_from(&self.obligation)?;
+      self.vault.check_obligation(&obligation, &self.executor.key(), &self.reserve.lending_market, clock.slot)?;
+      self.vault.check_ltv(&obligation, &self.reserve, &self.borrow_reserve, amount, 0)?;
+    }
+
+    let before = self.usdc_vault.amount;
+    self.borrow_liquidity(amount)?;
+    self.usdc_vault.reload()?;
+    // fees are taken out of the borrowed amount
+    let received = self.usdc_vault.amount.checked_sub(before).unwrap();
+
+    self.deposit_zeta(received)?;
+    self.vault.margin_basis.after_margin_deposit(received)?;
+    self.borrow_reserve.reload()?;
+    book_obligation(
+      &mut self.vault,
+      &self.obligation,
+      &self.reserve,
+      &self.borrow_reserve,
+      received,
+      0,
+      clock.slot,
+    )?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn borrow_liquidity(&self, amount: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::BorrowObligationLiquidity {
+        source_liquidity: self.borrow_reserve_liquidity_supply.to_account_info(),
+        destination_liquidity: self.usdc_vault.to_account_info(),
+        borrow_reserve: self.borrow_reserve.to_account_info(),
+        borrow_reserve_liquidity_fee_receiver: self.borrow_reserve_fee_receiver.to_account_info(),
+        obligation: self.obligation.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        lending_market_authority: self.lending_market_authority.to_account_info(),
+        obligation_owner: self.executor.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    cpi::solend::borrow_obligation_liquidity(cpi, amount)
+  }
+
+  fn deposit_zeta(&self, amount_in: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      return cpi::zeta::zeta_client::deposit_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::DepositV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+        },
+        amount_in,
+        seeds
+      );
+    }
+    cpi::zeta::zeta_client::deposit(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::Deposit {
+        zeta_group: self.zeta_group.to_account_info(),
+        margin_account: self.margin_account.to_account_info(),
+        vault: self.zeta_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        socialized_loss_account: self.socialized_loss_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        token_program: self.token_program.clone(),
+        state: self.state.to_account_info(),
+        greeks: self.greeks.to_account_info(),
+      },
+      amount_in,
+      seeds
+    )?;
+    Ok(())
+  }
+}
@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use crate::{cpi_calls as cpi, executor_seeds, VaultError};
+use crate::cpi_calls::zeta::{load_zeta_group};
+use crate::instructions::MarginSnapshot;
+use crate::structs::{OrderTracker, Vault};
+use crate::pda::{EXECUTOR_SEED, ORDER_TRACKER_SEED, VAULT_SEED};
+
+#[derive(Accounts)]
+pub struct CancelClientOrder<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  pub authority: Signer<'info>,
+  /// CHECK: checked via external program
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub state: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub serum_authority: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub open_orders: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub market: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub bids: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub asks: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub event_queue: AccountInfo<'info>,
+  #[account(
+  mut,
+  seeds = [ORDER_TRACKER_SEED, vault.key().as_ref(), market.key().as_ref()],
+  bump = order_tracker.bump
+  )]
+  pub order_tracker: Box<Account<'info, OrderTracker>>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+}
+
+impl<'info> CancelClientOrder<'info> {
+  /// Cancels an order by the client order id assigned in `bid_order` and
+  /// takes the cancelled bid lots off the market's order tracker.
+  pub fn cancel_client_order(&mut self, client_order_id: u64) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    // ids are handed out sequentially, anything above the counter was never placed
+    if client_order_id == 0 || client_order_id > self.vault.order_count {
+      return err!(VaultError::UnknownClientOrderId);
+    }
+    // the market has to be one of the vault's zeta group
+    let index = {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      zeta_group.get_product_index_by_key(&self.market.key())?
+    };
+
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))?;
+    self.cancel_order(client_order_id)?;
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))?;
+    let cancelled = before.check_cancel(&after)?;
+    self.order_tracker.after_bid_cancelled(cancelled)
+  }
+
+  fn cancel_order(&self, client_order_id: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::cancel_order_by_client_order_id(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::CancelOrder {
+        authority: self.executor.to_account_info(),
+        cancel_accounts: cpi::zeta::CancelAccounts {
+          zeta_group: self.zeta_group.to_account_info(),
+          state: self.state.to_account_info(),
+          margin_account: self.margin_account.to_account_info(),
+          dex_program: self.dex_program.to_account_info(),
+          serum_authority: self.serum_authority.to_account_info(),
+          open_orders: self.open_orders.to_account_info(),
+          market: self.market.to_account_info(),
+          bids: self.bids.to_account_info(),
+          asks: self.asks.to_account_info(),
+          event_queue: self.event_queue.to_account_info(),
+        },
+      },
+      client_order_id,
+      seeds,
+    )
+  }
+}
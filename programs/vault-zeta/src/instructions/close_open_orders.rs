@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use crate::{cpi_calls as cpi, executor_seeds, VaultError};
+use crate::cpi_calls::zeta::{load_cross_margin_account, load_margin_account, load_zeta_group, ExpirySeriesStatus};
+use crate::instructions::SolvencyAccounts;
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+#[derive(Accounts)]
+pub struct CloseOpenOrders<'info> {
+  #[account(
+  has_one = authority,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  mut,
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  pub authority: Signer<'info>,
+  /// CHECK: checked via external program
+  pub state: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub open_orders: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub market: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub serum_authority: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub open_orders_map: AccountInfo<'info>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> CloseOpenOrders<'info> {
+  pub fn close_open_orders(&self) -> Result<()> {
+    self.validate_expired()?;
+    let (open_orders_map, map_nonce) = Pubkey::find_program_address(
+      &[self.open_orders.key().as_ref()],
+      &cpi::zeta::zeta_program::ID,
+    );
+    if open_orders_map != self.open_orders_map.key() {
+      return err!(VaultError::OpenOrdersNotClosable);
+    }
+
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::close_open_orders(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::CloseOpenOrders {
+        state: self.state.to_account_info(),
+        zeta_group: self.zeta_group.to_account_info(),
+        dex_program: self.dex_program.to_account_info(),
+        open_orders: self.open_orders.to_account_info(),
+        margin_account: self.margin_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        market: self.market.to_account_info(),
+        serum_authority: self.serum_authority.to_account_info(),
+        open_orders_map: self.open_orders_map.to_account_info(),
+      },
+      map_nonce,
+      seeds,
+    )?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  // Open orders can only be closed once its series is settled
+  // and the vault holds neither orders nor position in the market.
+  fn validate_expired(&self) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let (product_index, expiry_index) = zeta_group
+      .get_product_and_expiry_index_by_key(&self.market.key())?;
+
+    let series = &zeta_group.expiry_series[expiry_index];
+    let ledger = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      if series.status()? != ExpirySeriesStatus::Expired {
+        return err!(VaultError::OpenOrdersNotClosable);
+      }
+      margin_account.product_ledgers[product_index]
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      let series_expiry = margin_account.series_expiry[expiry_index];
+      let rolled_over = series_expiry < series.expiry_ts;
+      if series.status()? != ExpirySeriesStatus::Expired && !rolled_over {
+        return err!(VaultError::OpenOrdersNotClosable);
+      }
+      margin_account.product_ledgers[product_index]
+    };
+    if !ledger.position.empty() || ledger.order_state.has_active_orders() {
+      return err!(VaultError::OpenOrdersNotClosable);
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::instructions::refund_keeper_if_passed;
+use crate::structs::{NavSnapshot, Vault};
+use crate::pda::{SHARES_SEED, SNAPSHOT_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct CommitNavSnapshot<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  init_if_needed,
+  space = 8 + NavSnapshot::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [SNAPSHOT_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub nav_snapshot: Box<Account<'info, NavSnapshot>>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> CommitNavSnapshot<'info> {
+  /// Refunded from the keeper pool when it is passed.
+  pub fn commit_nav_snapshot(&mut self, bump: u8, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    let total_assets = self.vault.free_funds(clock.unix_timestamp).unwrap();
+    let share_price = self.vault.current_share_price(
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    msg!("snapshot share_price: {}, slot: {}", share_price, clock.slot);
+    self.nav_snapshot.commit(
+      bump,
+      self.vault.key(),
+      share_price,
+      total_assets,
+      self.shares_mint.supply,
+      clock.slot,
+    )?;
+    refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.authority.to_account_info(), clock.slot)
+  }
+}
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  calculate_settlement_pnl, calculate_short_settlement, load_margin_account, load_settlement_account,
+  load_zeta_group, ExpirySeriesStatus, SettlementAccount,
+};
+use crate::instructions::{pnl_to_underlying, SolvencyAccounts};
+use crate::structs::Vault;
+use crate::pda::{SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct ComputeSettlement<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = vault.margin_account)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: checked against the series expiry in `compute_settlement`
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub settlement_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::memo::memo_program::ID)]
+  pub memo_program: AccountInfo<'info>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> ComputeSettlement<'info> {
+  /// Books the payoff of an expired series from zeta's settlement price
+  /// without waiting for zeta to settle the margin account positions, then
+  /// reconciles the booked NAV with the assets held, see
+  /// `Vault::after_settlement_reconciled`.
+  pub fn compute_settlement(&mut self, expiry_index: u8) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.check_group_margin()?;
+    let underlying_price = self.solvency.platform_price(&self.vault)?;
+    let settled = book_settlement(
+      &mut self.vault,
+      &self.zeta_group,
+      &self.margin_account,
+      &self.settlement_account,
+      self.shares_mint.supply,
+      expiry_index,
+      underlying_price,
+      clock.unix_timestamp,
+    )?;
+    let nav = self.solvency.record_valuation(&mut self.vault, clock.slot)?;
+    let surplus = self.vault.after_settlement_reconciled(nav.total_assets(), nav.idle_underlying)?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+
+    let memo = cpi::memo::MemoTrailer::new(
+      "settle",
+      &self.vault.key(),
+      self.vault.epoch_schedule.current_epoch(clock.unix_timestamp),
+    )
+      .field("expiry_ts", settled.expiry_ts)
+      .field("pnl", settled.pnl)
+      .field("surplus", surplus)
+      .field("premium", settled.premium)
+      .field("assignment_loss", settled.assignment_loss)
+      .field("start_share_price", settled.start_share_price)
+      .finish();
+    cpi::memo::log_memo(self.memo_program.to_account_info(), memo)
+  }
+}
+
+/// Outcome of an expired series booked by `book_settlement`.
+pub struct SettledExpiry {
+  pub expiry_ts: u64,
+  // in underlying
+  pub pnl: i64,
+  pub premium: u64,
+  pub assignment_loss: u64,
+  pub start_share_price: u64,
+}
+
+/// Books the series at `expiry_index` into the vault, shared by
+/// `compute_settlement` and `fast_rollover`. The platform pnl converts to
+/// underlying at `underlying_price`, see `Vault::platform_to_underlying`.
+#[allow(clippy::too_many_arguments)]
+pub fn book_settlement(
+  vault: &mut Vault,
+  zeta_group: &AccountInfo,
+  margin_account: &AccountInfo,
+  settlement_account: &AccountInfo,
+  total_share_supply: u64,
+  expiry_index: u8,
+  underlying_price: Option<u64>,
+  now: i64,
+) -> Result<SettledExpiry> {
+  let expiry_index = expiry_index as usize;
+  let zeta_group = load_zeta_group(zeta_group)?;
+  let margin_account = load_margin_account(margin_account)?;
+  if expiry_index >= zeta_group.expiry_series.len() {
+    return err!(VaultError::SeriesNotExpired);
+  }
+
+  let series = zeta_group.expiry_series[expiry_index];
+  let status = series.status()?;
+  if status != ExpirySeriesStatus::Expired && status != ExpirySeriesStatus::ExpiredDirty {
+    msg!("Series status = {:?}", status);
+    return err!(VaultError::SeriesNotExpired);
+  }
+  // positions still belong to the series, zeta hasn't settled them yet
+  let series_expiry = margin_account.series_expiry;
+  if series_expiry[expiry_index] != series.expiry_ts {
+    return err!(VaultError::ExpiryAlreadySettled);
+  }
+  let settlement_address = SettlementAccount::get_settlement_address(
+    &zeta_group.underlying_mint,
+    series.expiry_ts,
+  );
+  if settlement_account.key() != settlement_address {
+    return err!(VaultError::InvalidSettlementAccount);
+  }
+
+  let settlement = load_settlement_account(settlement_account)?;
+  let platform_pnl = calculate_settlement_pnl(&zeta_group, &margin_account, &settlement, expiry_index)?;
+  // zeta can't take more than the margin balance, it socializes the rest
+  let balance = margin_account.balance;
+  let platform_pnl = platform_pnl.max(-(balance.min(i64::MAX as u64) as i64));
+  let pnl = pnl_to_underlying(vault, platform_pnl, underlying_price)
+    .ok_or_else(|| error!(VaultError::MathOverflow))?;
+  msg!("settlement_price: {}", settlement.settlement_price);
+  msg!("platform_pnl: {}, pnl: {}", platform_pnl, pnl);
+  let short = calculate_short_settlement(&zeta_group, &margin_account, &settlement, expiry_index)?;
+  msg!("premium: {}, assignment_loss: {}", short.premium, short.assignment_loss);
+
+  let start_share_price = vault.nav_share_price(total_share_supply)
+    .ok_or_else(|| error!(VaultError::MathOverflow))?;
+  vault.after_settlement(pnl, platform_pnl, expiry_index, series.expiry_ts, now)?;
+  vault.after_epoch_settled(
+    start_share_price,
+    total_share_supply,
+    short.premium,
+    short.assignment_loss,
+  )?;
+  vault.snapshot_entry_price(total_share_supply, now)?;
+  Ok(SettledExpiry {
+    expiry_ts: series.expiry_ts,
+    pnl,
+    premium: short.premium,
+    assignment_loss: short.assignment_loss,
+    start_share_price,
+  })
+}
@@ -1,82 +1,160 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Approve, approve, Mint, mint_to, MintTo, Token, TokenAccount};
-use num_traits::ToPrimitive;
-use crate::{cpi_calls as cpi, executor_seeds, ratio, vault_seeds, VaultError};
-use crate::structs::Vault;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Approve, approve, Mint, mint_to, MintTo, Token, TokenAccount, Transfer, transfer};
+use crate::{cpi_calls as cpi, executor_seeds, vault_seeds, VaultError};
+use crate::instructions::{check_bonded, DepositEscrow, SolvencyAccounts};
+use crate::structs::{NavSnapshot, UserPosition, UserState, Vault};
+use crate::pda::{EXECUTOR_SEED, SHARES_SEED, SNAPSHOT_SEED, USER_POSITION_SEED, USER_SEED, VAULT_SEED};
 
 
 #[derive(Accounts)]
 pub struct DepositToVault<'info> {
-  #[account(
-  mut,
-  token::authority = user_account,
-  token::mint = shares_mint
-  )]
-  pub user_shares: Box<Account<'info, TokenAccount>>,
   #[account(
   mut,
   token::authority = user_account,
   token::mint = reserve.liquidity.mint_pubkey
   )]
   pub user_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
   pub user_account: Signer<'info>,
   #[account(
+  init_if_needed,
+  space = 8 + UserState::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
   mut,
   has_one = reserve,
-  seeds = [b"vault", reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   #[account(mut, address = vault.collateral_vault)]
   pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  // holds the deposit when the reserve can't take it, see `reserve_fallback_idle`
+  #[account(mut, address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
   #[account(
   mut,
-  mint::decimals = 9,
+  mint::decimals = vault.share_mint_decimals(),
   mint::authority = vault.key(),
-  seeds = [b"shares", vault.key().as_ref()],
+  seeds = [SHARES_SEED, vault.key().as_ref()],
   bump = vault.mint_bump
   )]
   pub shares_mint: Box<Account<'info, Mint>>,
-  /// CHECK:
-  #[account(mut)]
+  // created on the first deposit, paid by the user
+  #[account(
+  init_if_needed,
+  payer = user_account,
+  associated_token::mint = shares_mint,
+  associated_token::authority = user_account
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  /// CHECK: NAV snapshot PDA, stays uninitialized until the keeper commits one
+  #[account(
+  seeds = [SNAPSHOT_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub nav_snapshot: AccountInfo<'info>,
+  #[account(mut, address = reserve.liquidity.supply_pubkey)]
   pub reserve_liquidity_supply: Box<Account<'info, TokenAccount>>,
-  /// CHECK:
-  #[account(mut)]
+  #[account(mut, address = reserve.collateral.mint_pubkey)]
   pub reserve_collateral_mint: Box<Account<'info, Mint>>,
   /// CHECK:
+  #[account(address = reserve.lending_market)]
   pub lending_market: AccountInfo<'info>,
   /// CHECK:
+  #[account(address = cpi::solend::get_lending_market_authority(&reserve.lending_market))]
   pub lending_market_authority: AccountInfo<'info>,
   #[account(mut)]
   pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
   pub token_program: Program<'info, Token>,
   pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub associated_token_program: Program<'info, AssociatedToken>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> DepositToVault<'info> {
-  pub fn deposit(&mut self, max_amount_in: u64) -> Result<()> {
-    if self.vault.statistic.total_deposit + (max_amount_in as u64) > self.vault.deposit_limit {
-      return err!(VaultError::VaultIsFull);
+  /// Shares go to the user, or to their deposit receipt while
+  /// `Vault::escrows_deposits`, see `DEPOSIT_RECEIPT_ACCOUNTS`.
+  pub fn deposit(
+    &mut self,
+    max_amount_in: u64,
+    user_bump: u8,
+    position_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    if !self.vault.setup_complete {
+      return err!(VaultError::SetupIncomplete);
     }
+    check_bonded(&self.vault, remaining_accounts)?;
+    self.vault.check_deposit_limit(max_amount_in)?;
 
     // if !self.vault.is_live {
     //   return err!(VaultError::DepositDisabled);
     // }
     // self.approve(max_amount_in)?;
     let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.vault.epoch_schedule.is_deposit_open(clock.unix_timestamp) {
+      return err!(VaultError::DepositWindowClosed);
+    }
+    self.solvency.check_not_under_maintenance(&self.vault)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
     let collateral = self.deposit_liquidity(max_amount_in)?;
     let shares = self.get_shares(
-      max_amount_in, clock.unix_timestamp
+      max_amount_in, clock.unix_timestamp, clock.slot
     )?;
-    self.mint(shares)?;
+    let escrowed = self.vault.escrows_deposits();
+    if escrowed {
+      let mut escrow = DepositEscrow::load(&self.vault.key(), &self.user_account.key(), remaining_accounts)?;
+      self.mint(escrow.escrow.clone(), shares)?;
+      escrow.after_deposit(shares, clock.slot, clock.unix_timestamp)?;
+    } else {
+      self.mint(self.user_shares.to_account_info(), shares)?;
+    }
     self.vault.after_deposit(max_amount_in)?;
-    Ok(())
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.user_state.is_initialized() {
+      self.user_state.initialize(
+        user_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    if !escrowed {
+      self.user_state.after_deposit(clock.slot, clock.unix_timestamp)?;
+      self.vault.after_wallet_deposit(clock.slot)?;
+    }
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    self.user_position.after_deposit(max_amount_in, shares)?;
+    self.solvency.record_and_assert_solvent(&mut self.vault, clock.slot)
   }
 
   // fn approve(&self, amount_in: u64) -> Result<()> {
@@ -91,32 +169,60 @@ impl<'info> DepositToVault<'info> {
   //   Ok(())
   // }
 
-  fn mint(&self, shares_amount: u64) -> Result<()> {
+  fn mint(&self, to: AccountInfo<'info>, shares_amount: u64) -> Result<()> {
     let seeds = vault_seeds!(self.vault);
     let seeds: &[&[&[u8]]] = &[&seeds[..]];
     let ctx = CpiContext::new_with_signer(
       self.token_program.to_account_info(),
       MintTo {
         mint: self.shares_mint.to_account_info(),
-        to: self.user_shares.to_account_info(),
+        to,
         authority: self.vault.to_account_info(),
       }, seeds);
     mint_to(ctx, shares_amount)
   }
 
-  fn get_shares(&self, underlying_amount: u64, now: i64) -> Result<u64> {
-    let total_supply = self.shares_mint.supply;
-    let total_assets = self.vault.free_funds(now).unwrap();
-    let shares = if total_supply > 0 {
-      ratio!(underlying_amount, total_supply, total_assets).unwrap()
-    } else {
-      // 1 share = 1 liquidity
-      underlying_amount as u64
-    };
+  fn get_shares(&self, underlying_amount: u64, now: i64, slot: u64) -> Result<u64> {
+    let snapshot_price = self.snapshot_price(slot)?;
+    if let Some(share_price) = snapshot_price {
+      msg!("priced off snapshot: {}", share_price);
+    }
+    let shares = self.vault.preview_deposit(
+      underlying_amount,
+      snapshot_price,
+      self.shares_mint.supply,
+      now,
+    ).unwrap();
+    self.vault.check_deposit_shares(underlying_amount, shares)?;
     Ok(shares)
   }
 
+  fn snapshot_price(&self, slot: u64) -> Result<Option<u64>> {
+    if self.nav_snapshot.data_is_empty() {
+      return Ok(None);
+    }
+    let snapshot: Account<NavSnapshot> = Account::try_from(&self.nav_snapshot)?;
+    if snapshot.is_active(slot, self.vault.snapshot_slots) {
+      Ok(Some(snapshot.share_price))
+    } else {
+      Ok(None)
+    }
+  }
+
   fn deposit_liquidity(&mut self, max_amount_in: u64) -> Result<u64> {
+    let slot = Clock::get()?.slot;
+    if !self.vault.check_reserve_deposit(&self.reserve, max_amount_in, slot)? {
+      let ctx = CpiContext::new(
+        self.token_program.to_account_info(),
+        Transfer {
+          from: self.user_token_account.to_account_info(),
+          to: self.underlying_vault.to_account_info(),
+          authority: self.user_account.to_account_info(),
+        });
+      transfer(ctx, max_amount_in)?;
+      self.vault.after_idle_deposit(max_amount_in)?;
+      return Ok(0);
+    }
     let cpi = CpiContext::new(
       self.lending_program.to_account_info(),
       cpi::solend::DepositReserveLiquidity {
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{cpi_calls as cpi, executor_seeds};
+use crate::instructions::SolvencyAccounts;
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct DepositObligationCollateral<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = obligation,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(mut, address = reserve.collateral.supply_pubkey)]
+  pub reserve_collateral_supply: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK:
+  #[account(mut)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> DepositObligationCollateral<'info> {
+  pub fn deposit_obligation_collateral(&mut self, collateral_amount: u64) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::DepositObligationCollateral {
+        source_collateral: self.collateral_vault.to_account_info(),
+        destination_collateral: self.reserve_collateral_supply.to_account_info(),
+        deposit_reserve: self.reserve.to_account_info(),
+        obligation: self.obligation.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        obligation_owner: self.executor.to_account_info(),
+        user_transfer_authority: self.executor.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    cpi::solend::deposit_obligation_collateral(cpi, collateral_amount)?;
+
+    self.vault.after_obligation_deposit(collateral_amount)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+}
@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{vault_seeds, VaultError};
+use crate::instructions::ProtocolFee;
+use crate::structs::{DepositReceipt, Vault};
+use crate::pda::{DEPOSIT_ESCROW_SEED, DEPOSIT_RECEIPT_SEED, PROTOCOL_CONFIG_SEED, SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct OpenDepositReceipt<'info> {
+  #[account(mut)]
+  pub payer: Signer<'info>,
+  /// CHECK: wallet the escrowed shares are released to
+  pub owner: AccountInfo<'info>,
+  #[account(
+  init_if_needed,
+  space = 8 + DepositReceipt::MAXIMUM_SIZE,
+  payer = payer,
+  seeds = [DEPOSIT_RECEIPT_SEED, vault.key().as_ref(), owner.key().as_ref()],
+  bump
+  )]
+  pub deposit_receipt: Box<Account<'info, DepositReceipt>>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  // shares of all deposit receipts
+  #[account(
+  init_if_needed,
+  payer = payer,
+  token::mint = shares_mint,
+  token::authority = vault,
+  seeds = [DEPOSIT_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub deposit_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> OpenDepositReceipt<'info> {
+  /// Creates the receipt the deposits of `owner` mint into while the vault
+  /// charges an exit fee or locks deposits, see `DEPOSIT_RECEIPT_ACCOUNTS`.
+  pub fn open_deposit_receipt(&mut self, receipt_bump: u8) -> Result<()> {
+    if !self.deposit_receipt.is_initialized() {
+      self.deposit_receipt.initialize(receipt_bump, self.vault.key(), self.owner.key())?;
+    }
+    Ok(())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct ReleaseDepositShares<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  pub user_account: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [DEPOSIT_RECEIPT_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump = deposit_receipt.bump
+  )]
+  pub deposit_receipt: Box<Account<'info, DepositReceipt>>,
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(
+  mut,
+  seeds = [DEPOSIT_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub deposit_escrow: Box<Account<'info, TokenAccount>>,
+  /// CHECK: the protocol config, empty until `init_protocol_config`
+  #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+  pub protocol_config: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ReleaseDepositShares<'info> {
+  /// Moves the escrowed shares to the owner once the deposit lock has
+  /// passed. The exit fee still due on them is burned, its value stays with
+  /// the vault like the fee withdrawals keep, but for the protocol part that
+  /// goes to the treasury share account passed with the protocol config.
+  pub fn release_deposit_shares(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let protocol_fee = ProtocolFee::load(&self.shares_mint.key(), &self.protocol_config, remaining_accounts)?;
+    let clock = Clock::get()?;
+    self.deposit_receipt.check_unlocked(clock.slot, self.vault.deposit_lock_slots)?;
+    let held_secs = self.deposit_receipt.held_secs(clock.unix_timestamp);
+    let shares = self.deposit_receipt.take();
+    if shares == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    let fee_shares = self.vault.exit_fee.fee(shares, held_secs).unwrap();
+    let protocol_shares = ProtocolFee::split(&protocol_fee, fee_shares);
+    msg!("shares: {}, held secs: {}, fee shares: {}, protocol shares: {}",
+      shares, held_secs, fee_shares, protocol_shares);
+
+    let seeds = vault_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    if let Some(protocol_fee) = protocol_fee.as_ref().filter(|_| protocol_shares > 0) {
+      let ctx = CpiContext::new_with_signer(
+        self.token_program.to_account_info(),
+        Transfer {
+          from: self.deposit_escrow.to_account_info(),
+          to: protocol_fee.treasury.clone(),
+          authority: self.vault.to_account_info(),
+        }, signer);
+      transfer(ctx, protocol_shares)?;
+    }
+    let burned_shares = fee_shares - protocol_shares;
+    if burned_shares > 0 {
+      self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+      let ctx = CpiContext::new_with_signer(
+        self.token_program.to_account_info(),
+        Burn {
+          mint: self.shares_mint.to_account_info(),
+          from: self.deposit_escrow.to_account_info(),
+          authority: self.vault.to_account_info(),
+        }, signer);
+      burn(ctx, burned_shares)?;
+      self.shares_mint.reload()?;
+      self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    }
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.deposit_escrow.to_account_info(),
+        to: self.user_shares.to_account_info(),
+        authority: self.vault.to_account_info(),
+      }, signer);
+    transfer(ctx, shares - fee_shares)
+  }
+}
+
+/// Receipt and escrow a deposit mints into while `Vault::escrows_deposits`,
+/// the first two remaining accounts of the deposit instructions.
+pub struct DepositEscrow<'info> {
+  pub receipt: Account<'info, DepositReceipt>,
+  pub escrow: AccountInfo<'info>,
+}
+
+impl<'info> DepositEscrow<'info> {
+  pub fn load(
+    vault: &Pubkey,
+    owner: &Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<Self> {
+    if remaining_accounts.len() < 2 {
+      return err!(VaultError::InvalidDepositReceipt);
+    }
+    let receipt = Account::<DepositReceipt>::try_from(&remaining_accounts[0])?;
+    let (escrow, _) = Pubkey::find_program_address(
+      &[DEPOSIT_ESCROW_SEED, vault.as_ref()],
+      &crate::ID,
+    );
+    if receipt.vault != *vault
+      || receipt.owner != *owner
+      || remaining_accounts[1].key() != escrow {
+      return err!(VaultError::InvalidDepositReceipt);
+    }
+    Ok(Self { receipt, escrow: remaining_accounts[1].clone() })
+  }
+
+  pub fn after_deposit(&mut self, shares: u64, slot: u64, now: i64) -> Result<()> {
+    self.receipt.after_deposit(shares, slot, now)?;
+    self.receipt.exit(&crate::ID)
+  }
+}
@@ -0,0 +1,252 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, mint_to, MintTo, Token, TokenAccount, Transfer, transfer};
+use crate::{cpi_calls as cpi, vault_seeds, VaultError};
+use crate::instructions::{check_bonded, DepositEscrow, SolvencyAccounts};
+use crate::structs::{DepositIntent, NavSnapshot, UserPosition, UserState, Vault};
+use crate::pda::{SHARES_SEED, SNAPSHOT_SEED, USER_POSITION_SEED, USER_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct DepositWithSignature<'info> {
+  // executes the intent and pays for the user accounts
+  #[account(mut)]
+  pub relayer: Signer<'info>,
+  /// CHECK: signer of the intent, checked against the ed25519 instruction
+  pub user_account: AccountInfo<'info>,
+  // the vault must be approved as delegate for at least the intent amount
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = reserve.liquidity.mint_pubkey
+  )]
+  pub user_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserState::MAXIMUM_SIZE,
+  payer = relayer,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = relayer,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  // holds the deposit when the reserve can't take it, see `reserve_fallback_idle`
+  #[account(mut, address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault.key(),
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(
+  init_if_needed,
+  payer = relayer,
+  associated_token::mint = shares_mint,
+  associated_token::authority = user_account
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  /// CHECK: NAV snapshot PDA, stays uninitialized until the keeper commits one
+  #[account(
+  seeds = [SNAPSHOT_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub nav_snapshot: AccountInfo<'info>,
+  #[account(mut, address = reserve.liquidity.supply_pubkey)]
+  pub reserve_liquidity_supply: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = reserve.collateral.mint_pubkey)]
+  pub reserve_collateral_mint: Box<Account<'info, Mint>>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::solend::get_lending_market_authority(&reserve.lending_market))]
+  pub lending_market_authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: instructions sysvar, holds the ed25519 verification
+  #[account(address = sysvar::instructions::ID)]
+  pub instructions: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub associated_token_program: Program<'info, AssociatedToken>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> DepositWithSignature<'info> {
+  /// Deposits `intent.amount` from the user's token account on their signed
+  /// intent. The instruction right before this one has to be the ed25519
+  /// program verifying the user's signature over `intent.message()`. The
+  /// vault moves the funds as the token account delegate, the relayer only
+  /// pays fees and rent.
+  pub fn deposit_with_signature(
+    &mut self,
+    intent: DepositIntent,
+    user_bump: u8,
+    position_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    if !self.vault.setup_complete {
+      return err!(VaultError::SetupIncomplete);
+    }
+    check_bonded(&self.vault, remaining_accounts)?;
+    if !self.user_state.is_initialized() {
+      self.user_state.initialize(
+        user_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    intent.check(&self.vault.key(), self.user_state.permit_nonce, clock.unix_timestamp)?;
+    self.check_signature(&intent)?;
+    self.user_state.after_permit()?;
+
+    let amount_in = intent.amount;
+    self.vault.check_deposit_limit(amount_in)?;
+    if !self.vault.epoch_schedule.is_deposit_open(clock.unix_timestamp) {
+      return err!(VaultError::DepositWindowClosed);
+    }
+    self.solvency.check_not_under_maintenance(&self.vault)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
+    self.deposit_liquidity(amount_in)?;
+    let shares = self.get_shares(
+      amount_in, clock.unix_timestamp, clock.slot
+    )?;
+    let escrowed = self.vault.escrows_deposits();
+    if escrowed {
+      let mut escrow = DepositEscrow::load(&self.vault.key(), &self.user_account.key(), remaining_accounts)?;
+      self.mint(escrow.escrow.clone(), shares)?;
+      escrow.after_deposit(shares, clock.slot, clock.unix_timestamp)?;
+    } else {
+      self.mint(self.user_shares.to_account_info(), shares)?;
+    }
+    self.vault.after_deposit(amount_in)?;
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !escrowed {
+      self.user_state.after_deposit(clock.slot, clock.unix_timestamp)?;
+      self.vault.after_wallet_deposit(clock.slot)?;
+    }
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    self.user_position.after_deposit(amount_in, shares)?;
+    self.solvency.record_and_assert_solvent(&mut self.vault, clock.slot)
+  }
+
+  fn check_signature(&self, intent: &DepositIntent) -> Result<()> {
+    let current = load_current_index_checked(&self.instructions)?;
+    if current == 0 {
+      return err!(VaultError::InvalidDepositSignature);
+    }
+    let ix = load_instruction_at_checked(current as usize - 1, &self.instructions)?;
+    intent.check_signature(&ix, &self.user_account.key())
+  }
+
+  fn mint(&self, to: AccountInfo<'info>, shares_amount: u64) -> Result<()> {
+    let seeds = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      MintTo {
+        mint: self.shares_mint.to_account_info(),
+        to,
+        authority: self.vault.to_account_info(),
+      }, seeds);
+    mint_to(ctx, shares_amount)
+  }
+
+  fn get_shares(&self, underlying_amount: u64, now: i64, slot: u64) -> Result<u64> {
+    let snapshot_price = self.snapshot_price(slot)?;
+    if let Some(share_price) = snapshot_price {
+      msg!("priced off snapshot: {}", share_price);
+    }
+    let shares = self.vault.preview_deposit(
+      underlying_amount,
+      snapshot_price,
+      self.shares_mint.supply,
+      now,
+    ).unwrap();
+    self.vault.check_deposit_shares(underlying_amount, shares)?;
+    Ok(shares)
+  }
+
+  fn snapshot_price(&self, slot: u64) -> Result<Option<u64>> {
+    if self.nav_snapshot.data_is_empty() {
+      return Ok(None);
+    }
+    let snapshot: Account<NavSnapshot> = Account::try_from(&self.nav_snapshot)?;
+    if snapshot.is_active(slot, self.vault.snapshot_slots) {
+      Ok(Some(snapshot.share_price))
+    } else {
+      Ok(None)
+    }
+  }
+
+  // the vault signs as the delegate of the user's token account
+  fn deposit_liquidity(&mut self, amount_in: u64) -> Result<u64> {
+    let slot = Clock::get()?.slot;
+    let seeds = vault_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    if !self.vault.check_reserve_deposit(&self.reserve, amount_in, slot)? {
+      let ctx = CpiContext::new_with_signer(
+        self.token_program.to_account_info(),
+        Transfer {
+          from: self.user_token_account.to_account_info(),
+          to: self.underlying_vault.to_account_info(),
+          authority: self.vault.to_account_info(),
+        }, signer);
+      transfer(ctx, amount_in)?;
+      self.vault.after_idle_deposit(amount_in)?;
+      return Ok(0);
+    }
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::DepositReserveLiquidity {
+        source_liquidity: self.user_token_account.to_account_info(),
+        destination_collateral: *self.collateral_vault.clone(),
+        reserve: self.reserve.to_account_info(),
+        reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+        reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        lending_market_authority: self.lending_market_authority.to_account_info(),
+        user_transfer_authority: self.vault.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    let collateral_before = self.collateral_vault.amount;
+    cpi::solend::deposit_liquidity(cpi, amount_in)?;
+    self.collateral_vault.reload()?;
+    let collateral_after = self.collateral_vault.amount
+      .checked_sub(collateral_before).unwrap();
+    Ok(collateral_after)
+  }
+}
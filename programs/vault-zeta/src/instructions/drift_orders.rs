@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{executor_seeds, cpi_calls as cpi};
+use crate::cpi_calls::drift::{OrderParams, PositionDirection, QUOTE_SPOT_MARKET_INDEX};
+use crate::cpi_calls::zeta::get_native_oracle_price;
+use crate::instructions::{finish_dry_run, SolvencyAccounts};
+use crate::structs::{DryRunOrder, Vault, Venue, DRIFT_BASE_PER_ZETA_POSITION};
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct DriftCollateral<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = drift_user,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(owner = cpi::drift::drift_program::ID)]
+  pub drift_state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, owner = cpi::drift::drift_program::ID)]
+  pub drift_user: AccountInfo<'info>,
+  /// CHECK: drift PDA of the executor
+  #[account(
+  mut,
+  address = cpi::drift::get_user_stats_address(&executor.key())
+  )]
+  pub drift_user_stats: AccountInfo<'info>,
+  /// CHECK: drift USDC spot market vault, checked by drift
+  #[account(mut)]
+  pub spot_market_vault: AccountInfo<'info>,
+  /// CHECK: checked by drift
+  pub drift_signer: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::drift::drift_program::ID)]
+  pub drift_program: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> DriftCollateral<'info> {
+  /// Moves up to `amount` of the free USDC vault into the drift user, the
+  /// collateral of the drift vault orders. Remaining accounts are the drift
+  /// oracle and markets of the deposit.
+  pub fn deposit_drift_collateral(&mut self, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.check_venue(Venue::Drift)?;
+    let amount = amount.min(self.vault.free_usdc(self.usdc_vault.amount));
+    if amount == 0 {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.drift_program.to_account_info(),
+      cpi::drift::Deposit {
+        state: self.drift_state.to_account_info(),
+        user: self.drift_user.to_account_info(),
+        user_stats: self.drift_user_stats.to_account_info(),
+        authority: self.executor.to_account_info(),
+        spot_market_vault: self.spot_market_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+      }, signer)
+      .with_remaining_accounts(remaining_accounts.to_vec());
+    cpi::drift::deposit(cpi, QUOTE_SPOT_MARKET_INDEX, amount, false)?;
+    self.vault.drift_collateral = self.vault.drift_collateral
+      .checked_add(amount).unwrap();
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  /// Moves `amount` USDC from the drift user back to the USDC vault. Drift
+  /// pnl is only valued once withdrawn, the booked collateral goes down by
+  /// the amount and any profit above it lands in the NAV. Open to any venue
+  /// so a vault switched back to Zeta can unwind.
+  pub fn withdraw_drift_collateral(&mut self, amount: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.drift_program.to_account_info(),
+      cpi::drift::Withdraw {
+        state: self.drift_state.to_account_info(),
+        user: self.drift_user.to_account_info(),
+        user_stats: self.drift_user_stats.to_account_info(),
+        authority: self.executor.to_account_info(),
+        spot_market_vault: self.spot_market_vault.to_account_info(),
+        drift_signer: self.drift_signer.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+      }, signer)
+      .with_remaining_accounts(remaining_accounts.to_vec());
+    cpi::drift::withdraw(cpi, QUOTE_SPOT_MARKET_INDEX, amount, true)?;
+    self.vault.drift_collateral = self.vault.drift_collateral.saturating_sub(amount);
+    self.solvency.assert_solvent(&self.vault)
+  }
+}
+
+#[derive(Accounts)]
+pub struct PlaceDriftOrder<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = drift_user,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::drift::drift_program::ID)]
+  pub drift_state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, owner = cpi::drift::drift_program::ID)]
+  pub drift_user: AccountInfo<'info>,
+  /// CHECK: drift USDC spot market vault, checked by drift
+  pub spot_market_vault: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::drift::drift_program::ID)]
+  pub drift_program: AccountInfo<'info>,
+}
+
+impl<'info> PlaceDriftOrder<'info> {
+  /// Keeper order of a drift vault on its perp market,
+  /// `hedge_perp_market_index`. `amount` is the base amount, 9.dp, positive
+  /// for longs, `price` the limit price, 6.dp, held to the mark band around
+  /// the vault oracle. The order counts against the keeper throttle like the
+  /// zeta orders. Remaining accounts are the drift oracle and markets of the
+  /// order, then the keeper cosigners, see `KeeperSet`. A `dry_run` reports
+  /// the order, `target` is the drift user, without placing it.
+  pub fn place_drift_order(
+    &mut self,
+    amount: i64,
+    price: u64,
+    reduce_only: bool,
+    dry_run: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_venue(Venue::Drift)?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    self.vault.check_price_band(price, spot)?;
+
+    // the throttle counts zeta positions, 3.dp
+    let size = amount.unsigned_abs() / DRIFT_BASE_PER_ZETA_POSITION as u64;
+    let cosigned = self.vault.keeper_set.is_cosigned(remaining_accounts);
+    self.vault.consume_order_budget(size, price, cosigned, clock.slot, clock.unix_timestamp)?;
+    if dry_run {
+      return finish_dry_run(self.vault.key(), vec![DryRunOrder {
+        target: self.drift_user.key(),
+        price,
+        size: amount,
+        client_order_id: 0,
+      }]);
+    }
+    let direction = if amount > 0 { PositionDirection::Long } else { PositionDirection::Short };
+    let params = OrderParams::perp_limit(
+      self.vault.delta_hedge.perp_market_index,
+      direction,
+      amount.unsigned_abs(),
+      price,
+      reduce_only,
+      self.vault.post_only,
+    );
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.drift_program.to_account_info(),
+      cpi::drift::PlacePerpOrder {
+        state: self.drift_state.to_account_info(),
+        user: self.drift_user.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer)
+      .with_remaining_accounts(remaining_accounts.to_vec());
+    cpi::drift::place_perp_order(cpi, params)
+  }
+
+  /// Settles the pnl of the vault's perp market into the drift user's USDC
+  /// balance. Remaining accounts are the drift oracle and markets.
+  pub fn settle_drift_pnl(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    self.vault.check_venue(Venue::Drift)?;
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.drift_program.to_account_info(),
+      cpi::drift::SettlePnl {
+        state: self.drift_state.to_account_info(),
+        user: self.drift_user.to_account_info(),
+        authority: self.executor.to_account_info(),
+        spot_market_vault: self.spot_market_vault.to_account_info(),
+      }, signer)
+      .with_remaining_accounts(remaining_accounts.to_vec());
+    cpi::drift::settle_pnl(cpi, self.vault.delta_hedge.perp_market_index)
+  }
+}
@@ -0,0 +1,20 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use crate::structs::{DryRunOrder, DryRunReturn};
+
+
+/// Orders a `dry_run` validated without placing them.
+#[event]
+pub struct DryRun {
+  pub vault: Pubkey,
+  pub orders: Vec<DryRunOrder>,
+}
+
+/// Reports the would-be orders as an event and as return data, meant for
+/// `simulateTransaction` against live state.
+pub fn finish_dry_run(vault: Pubkey, orders: Vec<DryRunOrder>) -> Result<()> {
+  msg!("dry run, {} orders skipped", orders.len());
+  set_return_data(&DryRunReturn { orders: orders.clone() }.try_to_vec()?);
+  emit!(DryRun { vault, orders });
+  Ok(())
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::instructions::refund_keeper_if_passed;
+use crate::structs::Vault;
+use crate::pda::VAULT_SEED;
+
+
+#[derive(Accounts)]
+pub struct EmergencyOpenWithdrawals<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut)]
+  pub keeper: Signer<'info>,
+}
+
+impl<'info> EmergencyOpenWithdrawals<'info> {
+  /// Permissionless, fires the dead-man switch once the keeper has been
+  /// inactive for `keeper_timeout_slots`. The first successful call is
+  /// refunded from the keeper pool when it is passed.
+  pub fn emergency_open_withdrawals(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let clock = Clock::get()?;
+    msg!("last_keeper_slot: {}", self.vault.last_keeper_slot);
+    let already_open = self.vault.aborted_until == i64::MAX;
+    self.vault.after_keeper_timeout(clock.slot)?;
+    if already_open {
+      return Ok(());
+    }
+    refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.keeper.to_account_info(), clock.slot)
+  }
+}
@@ -0,0 +1,25 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use crate::structs::{EpochHistoryReturn, Vault};
+
+
+#[derive(Accounts)]
+pub struct GetEpochHistory<'info> {
+  pub vault: Box<Account<'info, Vault>>,
+}
+
+impl<'info> GetEpochHistory<'info> {
+  /// Pages through the settled expiries newest first, `offset` skips the
+  /// newest ones. The page is bounded by the history length, which keeps it
+  /// within the return data limit.
+  pub fn get_epoch_history(&self, offset: u64, limit: u64) -> Result<EpochHistoryReturn> {
+    let history = &self.vault.epoch_history;
+    let page = EpochHistoryReturn {
+      total: history.count,
+      records: history.page(offset, limit),
+    };
+    msg!("epoch records: {} of {}", page.records.len(), page.total);
+    set_return_data(&page.try_to_vec()?);
+    Ok(page)
+  }
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::structs::{ErrorStats, RejectionKind, Vault};
+use crate::pda::{ERROR_STATS_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct RecordRejection<'info> {
+  #[account(
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  init_if_needed,
+  space = 8 + ErrorStats::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [ERROR_STATS_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub error_stats: Box<Account<'info, ErrorStats>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> RecordRejection<'info> {
+  pub fn record_rejection(&mut self, kind: RejectionKind, bump: u8) -> Result<()> {
+    if !self.error_stats.is_initialized() {
+      self.error_stats.initialize(bump, self.vault.key())?;
+    }
+    let slot = Clock::get()?.slot;
+    self.error_stats.record(kind, slot)
+  }
+}
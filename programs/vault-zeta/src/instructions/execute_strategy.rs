@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{get_return_data, invoke};
+use anchor_spl::token::Token;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::instructions::{
+  count_resting_orders, load_leg_tracker, KeeperOrders, MarginSnapshot, SolvencyAccounts, LADDER_LEG_ACCOUNTS,
+};
+use crate::structs::{
+  StrategyIntent, StrategyReturn, Vault, Venue, MAX_LADDER_LEGS, MAX_STRATEGY_INTENTS,
+};
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct ExecuteStrategy<'info> {
+  #[account(
+  mut,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  /// CHECK: allowlisted in the vault config
+  #[account(executable, address = vault.strategy_program)]
+  pub strategy_program: AccountInfo<'info>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: cross-margin account when the vault uses that model
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, owner = cpi::zeta::zeta_program::ID)]
+  pub greeks: AccountInfo<'info>,
+  /// CHECK:
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK:
+  pub serum_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub mint_authority: AccountInfo<'info>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> ExecuteStrategy<'info> {
+  /// Calls the strategy program with `data` and read-only views of the vault
+  /// margin account and the ladder legs in the remaining accounts, a keeper
+  /// cosigner may follow the legs. The strategy never gets the executor
+  /// signature, it returns `StrategyIntent`s and the vault places and
+  /// cancels the orders itself with the checks of `bid_order`.
+  pub fn execute_strategy(
+    &mut self,
+    data: Vec<u8>,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_venue(Venue::Zeta)?;
+    self.vault.check_group_margin()?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    self.vault.check_strategy_instruction(&self.strategy_program.key(), &data)?;
+    let legs_count = remaining_accounts.len() / LADDER_LEG_ACCOUNTS;
+    let legs_len = legs_count * LADDER_LEG_ACCOUNTS;
+    if legs_count > MAX_LADDER_LEGS || remaining_accounts.len() > legs_len + 1 {
+      return err!(VaultError::InvalidLadder);
+    }
+    let (legs, cosigner) = remaining_accounts.split_at(legs_len);
+    let cosigned = self.vault.keeper_set.is_cosigned(cosigner);
+
+    let intents = self.call_strategy(data, legs)?;
+    let keeper = KeeperOrders {
+      executor: &self.executor,
+      oracle: &self.oracle,
+      price_update: &self.price_update,
+      zeta_group: &self.zeta_group,
+      margin_account: &self.margin_account,
+      state: &self.state,
+      greeks: &self.greeks,
+      dex_program: &self.dex_program,
+      serum_authority: &self.serum_authority,
+      mint_authority: &self.mint_authority,
+      rent: &self.rent,
+      token_program: &self.token_program,
+      zeta_program: &self.zeta_program,
+    };
+    let epoch = self.vault.epoch_schedule.current_epoch(clock.unix_timestamp);
+    for intent in intents {
+      match intent {
+        StrategyIntent::Bid { leg, price, size } => {
+          let leg = strategy_leg(legs, leg)?;
+          let index = keeper.validate_market(&self.vault, &leg[0])?;
+          if price == 0 || size == 0 {
+            return err!(VaultError::InvalidStrategyIntents);
+          }
+          let resting_orders = count_resting_orders(&leg[0], &leg[9], &self.dex_program.key())?;
+          self.vault.check_resting_orders(resting_orders)?;
+          self.vault.consume_order_budget(size, price, cosigned, clock.slot, clock.unix_timestamp)?;
+          let mut order_tracker = load_leg_tracker(&self.vault.key(), leg)?;
+          order_tracker.check_reprice(price, epoch, self.vault.max_reprice_walk_bps)?;
+          order_tracker.after_order(price, epoch)?;
+          order_tracker.after_bid_placed(size)?;
+          order_tracker.exit(&crate::ID)?;
+          let client_order_id = self.vault.next_client_order_id();
+          keeper.place_bid(&self.vault, leg, index, price, size, client_order_id)?;
+        }
+        StrategyIntent::Cancel { leg, client_order_id } => {
+          let leg = strategy_leg(legs, leg)?;
+          // ids are handed out sequentially, anything above the counter was never placed
+          if client_order_id == 0 || client_order_id > self.vault.order_count {
+            return err!(VaultError::UnknownClientOrderId);
+          }
+          let index = keeper.product_index(&leg[0])?;
+          let mut order_tracker = load_leg_tracker(&self.vault.key(), leg)?;
+          let cancelled = keeper.cancel_bid(&self.vault, leg, index, client_order_id)?;
+          order_tracker.after_bid_cancelled(cancelled)?;
+          order_tracker.exit(&crate::ID)?;
+        }
+      }
+    }
+    keeper.check_greeks_limits(&self.vault)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  // the strategy only sees read-only accounts, the vault is flagged busy in
+  // case it calls back into the vault program anyway
+  fn call_strategy(&mut self, data: Vec<u8>, legs: &[AccountInfo<'info>]) -> Result<Vec<StrategyIntent>> {
+    let mut account_infos = vec![
+      self.zeta_group.clone(),
+      self.margin_account.clone(),
+      self.greeks.clone(),
+      self.price_update.clone(),
+    ];
+    account_infos.extend_from_slice(legs);
+    let instruction = Instruction {
+      program_id: self.strategy_program.key(),
+      accounts: account_infos
+        .iter()
+        .map(|account| AccountMeta::new_readonly(account.key(), false))
+        .collect(),
+      data,
+    };
+    account_infos.push(self.strategy_program.clone());
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    self.vault.before_external_call()?;
+    self.vault.exit(&crate::ID)?;
+    invoke(&instruction, &account_infos)?;
+    self.vault.reload()?;
+    self.vault.after_external_call();
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_unchanged(&after)?;
+
+    let intents = match get_return_data() {
+      Some((program, data)) if program == self.strategy_program.key() => {
+        StrategyReturn::try_from_slice(&data)
+          .map_err(|_| error!(VaultError::InvalidStrategyIntents))?
+          .intents
+      }
+      _ => vec![],
+    };
+    if intents.len() > MAX_STRATEGY_INTENTS {
+      return err!(VaultError::InvalidStrategyIntents);
+    }
+    Ok(intents)
+  }
+}
+
+fn strategy_leg<'a, 'info>(legs: &'a [AccountInfo<'info>], leg: u8) -> Result<&'a [AccountInfo<'info>]> {
+  legs.chunks(LADDER_LEG_ACCOUNTS)
+    .nth(leg as usize)
+    .ok_or_else(|| error!(VaultError::InvalidStrategyIntents))
+}
@@ -0,0 +1,311 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::log::sol_log_compute_units;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::{executor_seeds, cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  calculate_margin_account_state_with_parameters, deserialize_greeks, is_cross_margin_account_flat,
+  load_cross_margin_account, load_margin_account, load_zeta_group,
+};
+use crate::instructions::{
+  book_settlement, load_leg_tracker, resting_orders, MarginSnapshot, SolvencyAccounts, LADDER_LEG_ACCOUNTS,
+};
+use crate::lending::{LendingAdapter, SolendAdapter};
+use crate::structs::{Feature, FeatureFlags, Vault};
+use crate::pda::{EXECUTOR_SEED, FEATURE_FLAGS_SEED, SHARES_SEED, VAULT_SEED};
+
+// markets cancelled per rollover. A market holds the ladder bid and the
+// closing ask at most, each cancel is a zeta and a serum CPI of about 60k
+// compute units with the book walk, so 4 markets take under 500k next to
+// about 350k for the settlement, the margin withdrawal and the Solend
+// deposit, well within a 1.4M budget
+pub const MAX_ROLLOVER_MARKETS: usize = 4;
+
+#[derive(Accounts)]
+pub struct FastRollover<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  // the freed margin goes straight back to Solend, the reserve has to lend USDC
+  #[account(
+  mut,
+  address = vault.usdc_vault,
+  constraint = usdc_vault.mint == reserve.liquidity.mint_pubkey
+  )]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_liquidity_supply: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_collateral_mint: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub zeta_vault: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  /// CHECK:
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK:
+  pub serum_authority: AccountInfo<'info>,
+  #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+  pub feature_flags: Box<Account<'info, FeatureFlags>>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  /// CHECK:
+  #[account(address = cpi::memo::memo_program::ID)]
+  pub memo_program: AccountInfo<'info>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> FastRollover<'info> {
+  /// Runs the rollover steps in one instruction so the keeper can't get
+  /// stuck halfway: books the expired series at `expiry_index` when given,
+  /// cancels every order left on the markets passed (same account layout as
+  /// the `place_ladder` legs), withdraws the free margin and deposits it into
+  /// Solend. The zeta settlement account of the series follows the markets
+  /// when `expiry_index` is given. Static accounts are meant to come from the
+  /// vault lookup table.
+  pub fn fast_rollover(&mut self, expiry_index: Option<u8>, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    self.feature_flags.check_enabled(Feature::FastRollover)?;
+    self.vault.check_not_parked()?;
+    let clock = Clock::get()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    let settlement_len = expiry_index.map_or(0, |_| 1);
+    let markets_len = remaining_accounts.len().saturating_sub(settlement_len);
+    if remaining_accounts.len() < settlement_len
+      || markets_len % LADDER_LEG_ACCOUNTS != 0
+      || markets_len > MAX_ROLLOVER_MARKETS * LADDER_LEG_ACCOUNTS {
+      return err!(VaultError::InvalidRolloverAccounts);
+    }
+    let (markets, settlement) = remaining_accounts.split_at(markets_len);
+
+    if let Some(expiry_index) = expiry_index {
+      // series expiries are only tracked by the per-group margin account
+      self.vault.check_group_margin()?;
+      // checked against the series expiry in `book_settlement`
+      let settlement_account = &settlement[0];
+      if *settlement_account.owner != cpi::zeta::zeta_program::ID {
+        return err!(VaultError::InvalidRolloverAccounts);
+      }
+      let underlying_price = self.solvency.platform_price(&self.vault)?;
+      book_settlement(
+        &mut self.vault,
+        &self.zeta_group,
+        &self.margin_account,
+        settlement_account,
+        self.shares_mint.supply,
+        expiry_index,
+        underlying_price,
+        clock.unix_timestamp,
+      )?;
+      sol_log_compute_units();
+    }
+    for market in markets.chunks(LADDER_LEG_ACCOUNTS) {
+      self.cancel_all(market)?;
+    }
+    sol_log_compute_units();
+
+    let before = self.usdc_vault.amount;
+    self.withdraw_free_margin()?;
+    self.usdc_vault.reload()?;
+    let received = self.usdc_vault.amount
+      .checked_sub(before).unwrap();
+    self.deposit_liquidity(received)?;
+    sol_log_compute_units();
+
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)?;
+
+    let memo = cpi::memo::MemoTrailer::new(
+      "rollover",
+      &self.vault.key(),
+      self.vault.epoch_schedule.current_epoch(clock.unix_timestamp),
+    )
+      .field("settled", expiry_index.is_some())
+      .field("markets", markets.len() / LADDER_LEG_ACCOUNTS)
+      .field("redeposited", received)
+      .finish();
+    cpi::memo::log_memo(self.memo_program.to_account_info(), memo)
+  }
+
+  // the bid lots cancelled come off the market order tracker
+  fn cancel_all(&self, market: &[AccountInfo<'info>]) -> Result<()> {
+    if market[9].data_is_empty() {
+      return Ok(());
+    }
+    let mut order_tracker = load_leg_tracker(&self.vault.key(), market)?;
+    let index = load_zeta_group(&self.zeta_group)?
+      .get_product_index_by_key(&market[0].key())?;
+    let orders = resting_orders(&market[0], &market[9], &self.dex_program.key())?;
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))?;
+    let seeds = executor_seeds!(self.vault);
+    for (side, order_id) in orders {
+      cpi::zeta::zeta_client::cancel_order(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::CancelOrder {
+          authority: self.executor.to_account_info(),
+          cancel_accounts: cpi::zeta::CancelAccounts {
+            zeta_group: self.zeta_group.to_account_info(),
+            state: self.state.to_account_info(),
+            margin_account: self.margin_account.to_account_info(),
+            dex_program: self.dex_program.to_account_info(),
+            serum_authority: self.serum_authority.to_account_info(),
+            open_orders: market[9].clone(),
+            market: market[0].clone(),
+            bids: market[3].clone(),
+            asks: market[4].clone(),
+            event_queue: market[2].clone(),
+          },
+        },
+        side,
+        order_id,
+        seeds,
+      )?;
+    }
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))?;
+    order_tracker.after_bid_cancelled(before.check_cancel(&after)?)?;
+    order_tracker.exit(&crate::ID)
+  }
+
+  // a cross-margin account only frees its balance once it is flat
+  fn withdraw_free_margin(&mut self) -> Result<()> {
+    let amount_out = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      if is_cross_margin_account_flat(&margin_account) { margin_account.balance } else { 0 }
+    } else {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      let margin_account = load_margin_account(&self.margin_account)?;
+      let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+      let state = calculate_margin_account_state_with_parameters(
+        &zeta_group,
+        &self.vault.margin_parameters(&zeta_group),
+        &margin_account,
+        &greeks,
+        &self.price_update,
+        &self.vault.oracle,
+      )?;
+      (state.available_balance_initial.max(0) as u64).min(state.balance)
+    };
+    msg!("withdraw margin: {}", amount_out);
+    if amount_out == 0 {
+      return Ok(());
+    }
+
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      cpi::zeta::zeta_client::withdraw_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::WithdrawV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::withdraw(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::Withdraw {
+          zeta_group: self.zeta_group.to_account_info(),
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)?;
+    self.vault.margin_basis.after_margin_withdraw(amount_out)
+  }
+
+  fn lending(&self) -> SolendAdapter<'_, 'info> {
+    SolendAdapter {
+      reserve: &self.reserve,
+      reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+      reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+      lending_market: self.lending_market.to_account_info(),
+      lending_market_authority: self.lending_market_authority.to_account_info(),
+      liquidity: *self.usdc_vault.clone(),
+      collateral: *self.collateral_vault.clone(),
+      authority: self.executor.to_account_info(),
+      token_program: self.token_program.to_account_info(),
+      lending_program: self.lending_program.to_account_info(),
+    }
+  }
+
+  // funds the reserve can't take stay idle in the usdc vault
+  fn deposit_liquidity(&self, amount_in: u64) -> Result<()> {
+    if amount_in == 0 || !self.vault.check_reserve_deposit(&self.reserve, amount_in, Clock::get()?.slot)? {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    self.lending().deposit(amount_in, signer)
+  }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::structs::{FeatureFlags, ProtocolConfig};
+use crate::pda::{FEATURE_FLAGS_SEED, PROTOCOL_CONFIG_SEED};
+
+
+#[derive(Accounts)]
+pub struct SetFeatureFlags<'info> {
+  #[account(mut)]
+  pub admin: Signer<'info>,
+  #[account(
+  has_one = admin,
+  seeds = [PROTOCOL_CONFIG_SEED],
+  bump = protocol_config.bump
+  )]
+  pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+  #[account(
+  init_if_needed,
+  space = 8 + FeatureFlags::MAXIMUM_SIZE,
+  payer = admin,
+  seeds = [FEATURE_FLAGS_SEED],
+  bump
+  )]
+  pub feature_flags: Box<Account<'info, FeatureFlags>>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetFeatureFlags<'info> {
+  /// Replaces the whole bitmask, bits of `Feature` only.
+  pub fn set_feature_flags(&mut self, flags: u64, bump: u8) -> Result<()> {
+    msg!("feature flags: {:#b} -> {:#b}", self.feature_flags.flags, flags);
+    self.feature_flags.set(bump, flags)
+  }
+}
@@ -1,7 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
-use crate::{executor_seeds, cpi_calls as cpi};
+use anchor_spl::token::{Mint, mint_to, MintTo, Token, TokenAccount};
+use crate::{executor_seeds, vault_seeds, cpi_calls as cpi};
+use crate::cpi_calls::zeta::{load_cross_margin_account, load_margin_account};
+use crate::instructions::{ProtocolFee, SolvencyAccounts};
 use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, PROTOCOL_CONFIG_SEED, SHARES_SEED, VAULT_SEED};
 
 
 #[derive(Accounts)]
@@ -10,18 +13,31 @@ pub struct HarvestYield<'info> {
   mut,
   has_one = authority,
   has_one = reserve,
-  seeds = [b"vault", reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
   pub authority: Signer<'info>,
   #[account(
+  mut,
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  // management fee shares go to the vault authority
+  #[account(
+  mut,
+  token::authority = vault.authority,
+  token::mint = shares_mint
+  )]
+  pub fee_shares: Box<Account<'info, TokenAccount>>,
+  #[account(
   mut, address = vault.collateral_vault
   )]
   pub collateral_vault: Box<Account<'info, TokenAccount>>,
@@ -41,32 +57,107 @@ pub struct HarvestYield<'info> {
   pub lending_market_authority: AccountInfo<'info>,
   #[account(mut)]
   pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: read for parked liquidity
+  #[account(address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: the protocol config, empty until `init_protocol_config`
+  #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+  pub protocol_config: AccountInfo<'info>,
   pub token_program: Program<'info, Token>,
   pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  /// CHECK:
+  #[account(address = cpi::memo::memo_program::ID)]
+  pub memo_program: AccountInfo<'info>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> HarvestYield<'info> {
-  pub fn harvest_yield(&mut self) -> Result<()> {
+  /// The protocol part of the fee shares goes to the treasury share account
+  /// passed with the protocol config, see `ProtocolFee`.
+  pub fn harvest_yield(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let protocol_fee = ProtocolFee::load(&self.shares_mint.key(), &self.protocol_config, remaining_accounts)?;
     let clock = Clock::get()?;
-    let real_underlying = self.vault.for_underlying(
-      self.collateral_vault.amount,
-      &self.reserve
-    ).unwrap();
-    let total_assets = self.vault.free_funds(clock.unix_timestamp).unwrap();
+    self.vault.refresh_exchange_rate(&self.reserve, clock.slot)?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    let real_underlying = real_assets(
+      &self.vault,
+      &self.reserve,
+      &self.collateral_vault,
+      &self.underlying_vault,
+      parked_margin_balance(&self.vault, &self.margin_account)?,
+    );
+    let yield_base = self.vault.yield_base().unwrap();
     msg!("real underlying: {}", real_underlying);
-    msg!("total_assets: {}", total_assets);
-    if real_underlying > total_assets {
-      let accrued_yield = real_underlying
-        .checked_sub(total_assets).unwrap();
+    msg!("yield base: {}", yield_base);
+    let (accrued_yield, surplus) = self.vault.split_accrued_yield(
+      real_underlying.saturating_sub(yield_base),
+      self.vault.total_assets().unwrap(),
+      clock.unix_timestamp,
+    );
+    msg!("surplus: {}", surplus);
+    self.vault.after_surplus(surplus)?;
+    self.vault.after_harvest_skipped(accrued_yield)?;
+    let pending_yield = self.vault.pending_yield;
+    let mut harvested = 0;
+    if pending_yield == 0
+      || pending_yield < self.vault.min_harvest_amount
+      || !self.vault.is_harvest_due(clock.unix_timestamp) {
+      msg!("skipped harvest of {}", pending_yield);
+    } else {
+      // yield of parked funds stays in the margin account until swept back
       let collateral_amount = self.vault.for_collateral(
-        accrued_yield,
+        pending_yield,
         &self.reserve
-      ).unwrap();
+      ).unwrap()
+        .min(self.collateral_vault.amount);
+      harvested = self.vault.for_underlying(collateral_amount, &self.reserve).unwrap();
 
-      self.redeem_collateral(collateral_amount)?;
-      self.vault.after_harvest(accrued_yield, clock.unix_timestamp)?;
+      if collateral_amount > 0 {
+        self.redeem_collateral(collateral_amount)?;
+      }
+      self.vault.after_harvest(harvested, clock.unix_timestamp)?;
+    }
+    self.vault.after_yield_booked(clock.unix_timestamp);
+    let fee_shares = self.vault.accrued_fee_shares;
+    msg!("fee_shares: {}", fee_shares);
+    if fee_shares > 0 {
+      let protocol_shares = ProtocolFee::split(&protocol_fee, fee_shares);
+      msg!("protocol_shares: {}", protocol_shares);
+      self.mint_fee_shares(self.fee_shares.to_account_info(), fee_shares - protocol_shares)?;
+      if let Some(protocol_fee) = protocol_fee.as_ref().filter(|_| protocol_shares > 0) {
+        self.mint_fee_shares(protocol_fee.treasury.clone(), protocol_shares)?;
+      }
+      self.vault.after_fee_shares_minted(fee_shares)?;
+      self.shares_mint.reload()?;
     }
-    Ok(())
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.vault.record_floor_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.solvency.record_and_assert_solvent(&mut self.vault, clock.slot)?;
+
+    let memo = cpi::memo::MemoTrailer::new(
+      "harvest",
+      &self.vault.key(),
+      self.vault.epoch_schedule.current_epoch(clock.unix_timestamp),
+    )
+      .field("harvested", harvested)
+      .field("fee_shares", fee_shares)
+      .field("share_price", self.vault.nav_share_price(self.shares_mint.supply).unwrap())
+      .finish();
+    cpi::memo::log_memo(self.memo_program.to_account_info(), memo)
+  }
+
+  fn mint_fee_shares(&self, to: AccountInfo<'info>, fee_shares: u64) -> Result<()> {
+    let seeds = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      MintTo {
+        mint: self.shares_mint.to_account_info(),
+        to,
+        authority: self.vault.to_account_info(),
+      }, seeds);
+    mint_to(ctx, fee_shares)
   }
 
   fn redeem_collateral(&self, amount_in: u64) -> Result<u64>  {
@@ -90,3 +181,31 @@ impl<'info> HarvestYield<'info> {
     Ok(0)
   }
 }
+
+/// Underlying the vault holds against NAV: the Solend collateral, the
+/// parked margin and the idle deposits. Harvested yield waiting in the
+/// underlying vault is left out, it's booked by `reinvest_solend`.
+pub fn real_assets(
+  vault: &Vault,
+  reserve: &cpi::solend::Reserve,
+  collateral_vault: &TokenAccount,
+  underlying_vault: &TokenAccount,
+  margin_balance: u64,
+) -> u64 {
+  vault.for_underlying(collateral_vault.amount, reserve).unwrap()
+    .checked_add(vault.parked_value(margin_balance)).unwrap()
+    .checked_add(vault.idle_deposits.min(underlying_vault.amount)).unwrap()
+}
+
+/// Balance of the margin account while liquidity is parked in it.
+pub fn parked_margin_balance(vault: &Vault, margin_account: &AccountInfo) -> Result<u64> {
+  if vault.parked_margin == 0 {
+    return Ok(0);
+  }
+  if vault.cross_margin {
+    let margin_account = load_cross_margin_account(margin_account)?;
+    return Ok(margin_account.balance);
+  }
+  let margin_account = load_margin_account(margin_account)?;
+  Ok(margin_account.balance)
+}
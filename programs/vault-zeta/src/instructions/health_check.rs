@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use crate::cpi_calls as cpi;
+use crate::cpi_calls::zeta::{
+  deserialize_greeks, get_oracle_age, load_margin_account_state, load_zeta_group,
+};
+use crate::structs::{HealthReport, Vault};
+
+
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+  #[account(
+  has_one = reserve,
+  has_one = zeta_group,
+  has_one = margin_account
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub greeks: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+}
+
+impl<'info> HealthCheck<'info> {
+  pub fn health_check(&self) -> Result<HealthReport> {
+    let clock = Clock::get()?;
+    let now = clock.unix_timestamp;
+    let schedule = self.vault.epoch_schedule;
+
+    let oracle_age = get_oracle_age(&self.price_update, &self.vault.oracle, &clock)?;
+    let mut report = HealthReport {
+      reserve_utilization_bps: self.reserve.liquidity.utilization_bps()?,
+      oracle_age,
+      oracle_stale: oracle_age > self.vault.oracle.max_age,
+      epoch: schedule.current_epoch(now),
+      deposit_open: schedule.is_deposit_open(now),
+      withdraw_open: self.vault.is_withdraw_open(now),
+      receipt_epoch_live: self.vault.receipt_epoch_live,
+      ..Default::default()
+    };
+
+    // a stale pull price can't be used, the margin fields stay empty
+    if !report.oracle_stale {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+      let state = load_margin_account_state(
+        &zeta_group,
+        &self.vault.margin_parameters(&zeta_group),
+        &self.margin_account,
+        self.vault.cross_margin,
+        &greeks,
+        &self.price_update,
+        &self.vault.oracle,
+      )?;
+      report.margin_balance = state.balance;
+      report.initial_margin = state.initial_margin;
+      report.maintenance_margin = state.maintenance_margin;
+      report.account_equity = state.account_equity;
+      report.margin_ratio_bps = HealthReport::margin_ratio_bps(
+        state.maintenance_margin,
+        state.account_equity,
+      );
+    }
+    report.compute_risk_score();
+
+    msg!("risk score: {}", report.risk_score);
+    set_return_data(&report.try_to_vec()?);
+    Ok(report)
+  }
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{create_account, CreateAccount};
+use anchor_spl::token::Token;
+use crate::{cpi_calls as cpi, executor_seeds};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, OBLIGATION_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct InitObligation<'info> {
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK: created and owned by the lending program
+  #[account(
+  mut,
+  seeds = [OBLIGATION_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub obligation: AccountInfo<'info>,
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+}
+
+impl<'info> InitObligation<'info> {
+  pub fn init_obligation(&mut self, obligation_bump: u8) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.create_obligation_account(obligation_bump)?;
+
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::InitObligation {
+        obligation: self.obligation.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        obligation_owner: self.executor.to_account_info(),
+        rent: self.rent.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    cpi::solend::init_obligation(cpi)?;
+
+    self.vault.set_obligation(self.obligation.key())
+  }
+
+  fn create_obligation_account(&self, obligation_bump: u8) -> Result<()> {
+    let vault_key = self.vault.key();
+    let seeds = &[OBLIGATION_SEED, vault_key.as_ref(), &[obligation_bump]];
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let space = cpi::solend::OBLIGATION_LEN;
+    create_account(
+      CpiContext::new_with_signer(
+        self.system_program.to_account_info(),
+        CreateAccount {
+          from: self.authority.to_account_info(),
+          to: self.obligation.to_account_info(),
+        },
+        signer,
+      ),
+      self.rent.minimum_balance(space),
+      space as u64,
+      &self.lending_program.key(),
+    )
+  }
+}
@@ -2,19 +2,20 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::{cpi_calls as cpi, executor_seeds};
 use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
 
 #[derive(Accounts)]
 pub struct InitOpenOrders<'info> {
   #[account(
   mut,
   has_one = authority,
-  seeds = [b"vault", vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::cpi_calls as cpi;
+use crate::cpi_calls::zeta::{load_zeta_group, validate_market_tradeable};
+use crate::structs::{OrderTracker, Vault};
+use crate::pda::{ORDER_TRACKER_SEED, VAULT_SEED};
+
+#[derive(Accounts)]
+pub struct InitOrderTracker<'info> {
+  #[account(
+  has_one = authority,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: validated against the zeta group
+  pub market: AccountInfo<'info>,
+  #[account(
+  init,
+  space = 8 + OrderTracker::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [ORDER_TRACKER_SEED, vault.key().as_ref(), market.key().as_ref()],
+  bump
+  )]
+  pub order_tracker: Box<Account<'info, OrderTracker>>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitOrderTracker<'info> {
+  /// Creates the tracker of a market ahead of the instructions that take it
+  /// with the ladder legs, `bid_order` creates its own on first use.
+  pub fn init_order_tracker(&mut self, bump: u8) -> Result<()> {
+    {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      validate_market_tradeable(&zeta_group, &self.market.key())?;
+    }
+    self.order_tracker.initialize(bump, self.vault.key(), self.market.key())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct ResizeOrderTracker<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut)]
+  pub payer: Signer<'info>,
+  /// CHECK: seeds only
+  pub market: AccountInfo<'info>,
+  /// CHECK: may be too short to deserialize, that's what the resize is for
+  #[account(
+  mut,
+  owner = crate::ID,
+  seeds = [ORDER_TRACKER_SEED, vault.key().as_ref(), market.key().as_ref()],
+  bump
+  )]
+  pub order_tracker: AccountInfo<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> ResizeOrderTracker<'info> {
+  /// Permissionless, grows a tracker created before it recorded the market
+  /// position to the current size. The new fields start at 0 until the next
+  /// `reconcile_fills`.
+  pub fn resize_order_tracker(&mut self) -> Result<()> {
+    let space = 8 + OrderTracker::MAXIMUM_SIZE;
+    if self.order_tracker.data_len() >= space {
+      return Ok(());
+    }
+    let lamports = Rent::get()?.minimum_balance(space)
+      .saturating_sub(self.order_tracker.lamports());
+    if lamports > 0 {
+      let ctx = CpiContext::new(
+        self.system_program.to_account_info(),
+        Transfer {
+          from: self.payer.to_account_info(),
+          to: self.order_tracker.to_account_info(),
+        },
+      );
+      transfer(ctx, lamports)?;
+    }
+    self.order_tracker.realloc(space, true)?;
+    Ok(())
+  }
+}
@@ -2,30 +2,33 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use anchor_spl::mint::USDC;
 use crate::{cpi_calls as cpi, executor_seeds};
-use crate::structs::Vault;
+use crate::cpi_calls::zeta::{load_zeta_group, validate_margin_account};
+use crate::structs::{Vault, VaultPreset};
+use crate::pda::{EXECUTOR_SEED, SHARES_SEED, VAULT_SEED};
 
 #[derive(Accounts)]
+#[instruction(share_decimals: u8)]
 pub struct InitializeVault<'info> {
   #[account(
   init,
   space = 8 + Vault::MAXIMUM_SIZE,
   payer = authority,
-  seeds = [b"vault", reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
   bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   #[account(
   init,
   payer = authority,
-  mint::decimals = 9,
+  mint::decimals = share_decimals,
   mint::authority = vault,
-  seeds = [b"shares", vault.key().as_ref()],
+  seeds = [SHARES_SEED, vault.key().as_ref()],
   bump
   )]
   pub shares_mint: Box<Account<'info, Mint>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump
   )]
   pub executor: AccountInfo<'info>,
@@ -51,7 +54,11 @@ pub struct InitializeVault<'info> {
   #[account(mut)]
   pub margin_account: AccountInfo<'info>,
   /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
   pub zeta_group: AccountInfo<'info>,
+  /// CHECK: Pyth feed of the underlying, checked against the zeta group oracle
+  #[account(owner = cpi::zeta::pyth_client::pyth_program::ID)]
+  pub price_feed: AccountInfo<'info>,
   pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
   pub rent: Sysvar<'info, Rent>,
   pub token_program: Program<'info, Token>,
@@ -64,11 +71,13 @@ impl<'info> InitializeVault<'info> {
     bump: u8,
     mint_bump: u8,
     executor_bump: u8,
+    share_decimals: u8,
     deposit_limit: u64,
     management_fee_bps: u64,
   ) -> Result<()> {
     let clock = Clock::get()?;
     let now = clock.unix_timestamp;
+    let zeta_underlying_mint = self.validate_zeta_group()?;
 
     self.vault.initialize(
       now,
@@ -82,11 +91,29 @@ impl<'info> InitializeVault<'info> {
       self.underlying_vault.key(),
       self.usdc_vault.key(),
       self.margin_account.key(),
+      self.price_feed.key(),
+      zeta_underlying_mint,
+      self.reserve.liquidity.mint_decimals,
+      share_decimals,
       deposit_limit,
       management_fee_bps,
     )
   }
 
+  // the margin account and oracle must be the zeta group's, returns its underlying
+  fn validate_zeta_group(&self) -> Result<Pubkey> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    validate_margin_account(
+      &self.zeta_group.key(),
+      &self.executor.key(),
+      &self.margin_account.key(),
+    )?;
+    if zeta_group.oracle != self.price_feed.key() {
+      return err!(cpi::zeta::FuzeErrorCode::InvalidOracle);
+    }
+    Ok(zeta_group.underlying_mint)
+  }
+
   fn create_margin_account(
     &self,
   ) -> Result<()> {
@@ -110,6 +137,7 @@ impl<'info> InitializeVault<'info> {
 
 pub fn initialize_vault(
   ctx: Context<InitializeVault>,
+  share_decimals: u8,
   deposit_limit: u64,
   management_fee_bps: u64,
 ) -> Result<()> {
@@ -117,6 +145,7 @@ pub fn initialize_vault(
     *ctx.bumps.get("vault").unwrap(),
     *ctx.bumps.get("shares_mint").unwrap(),
     *ctx.bumps.get("executor").unwrap(),
+    share_decimals,
     deposit_limit,
     management_fee_bps,
   )?;
@@ -124,4 +153,21 @@ pub fn initialize_vault(
   Ok(())
 }
 
-
+pub fn initialize_vault_from_preset(
+  ctx: Context<InitializeVault>,
+  share_decimals: u8,
+  deposit_limit: u64,
+  preset: VaultPreset,
+) -> Result<()> {
+  ctx.accounts.init_vault(
+    *ctx.bumps.get("vault").unwrap(),
+    *ctx.bumps.get("shares_mint").unwrap(),
+    *ctx.bumps.get("executor").unwrap(),
+    share_decimals,
+    deposit_limit,
+    preset.management_fee_bps(),
+  )?;
+  ctx.accounts.vault.update_config(&preset.config())?;
+  ctx.accounts.create_margin_account()?;
+  Ok(())
+}
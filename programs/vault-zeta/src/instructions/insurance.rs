@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{transfer, Mint, Token, TokenAccount, Transfer};
+use crate::{executor_seeds, vault_seeds, cpi_calls as cpi, VaultError};
+use crate::instructions::SolvencyAccounts;
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, INSURANCE_SEED, SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct InitInsuranceVault<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  // owned by the vault, the executor can't trade or lend it
+  #[account(
+  init,
+  payer = authority,
+  token::mint = usdc_mint,
+  token::authority = vault,
+  seeds = [INSURANCE_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub insurance_vault: Box<Account<'info, TokenAccount>>,
+  #[account(address = cpi::zeta::USDC)]
+  pub usdc_mint: Box<Account<'info, Mint>>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitInsuranceVault<'info> {
+  pub fn init_insurance_vault(&mut self) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.set_insurance_account(self.insurance_vault.key())
+  }
+}
+
+#[derive(Accounts)]
+pub struct FundInsurance<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.insurance.token_account)]
+  pub insurance_vault: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> FundInsurance<'info> {
+  /// Moves the carve-out booked at settlement from the usdc vault into the
+  /// insurance account, as much as the usdc vault holds outside the in-kind
+  /// claims.
+  pub fn fund_insurance(&mut self) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let amount = self.vault.insurance.due.min(self.vault.free_usdc(self.usdc_vault.amount));
+    if amount == 0 {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.usdc_vault.to_account_info(),
+        to: self.insurance_vault.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer,
+    );
+    transfer(cpi, amount)?;
+    self.vault.insurance.after_funded(amount);
+    msg!("insurance funded: {}, balance: {}", amount, self.vault.insurance.balance);
+    self.solvency.assert_solvent(&self.vault)
+  }
+}
+
+#[derive(Accounts)]
+pub struct CoverLoss<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.insurance.token_account)]
+  pub insurance_vault: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> CoverLoss<'info> {
+  /// Pays insurance funds back into the usdc vault after a drawdown, at most
+  /// what brings the NAV share price back to the floor price.
+  pub fn cover_loss(&mut self, amount: u64) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let shortfall = self.vault.drawdown_shortfall(self.shares_mint.supply).unwrap();
+    if shortfall == 0 {
+      return err!(VaultError::NoDrawdown);
+    }
+    let amount = amount
+      .min(shortfall)
+      .min(self.insurance_vault.amount);
+    if amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    let seeds = vault_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.insurance_vault.to_account_info(),
+        to: self.usdc_vault.to_account_info(),
+        authority: self.vault.to_account_info(),
+      }, signer,
+    );
+    transfer(cpi, amount)?;
+    self.vault.after_insurance_cover(amount)?;
+    msg!("covered: {}, shortfall: {}", amount, shortfall);
+    self.solvency.assert_solvent(&self.vault)
+  }
+}
@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use serum_dex::critbit::SlabView;
+use serum_dex::state::Market;
+use crate::{executor_seeds, cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  calculate_cross_margin_greeks_exposure, calculate_greeks_exposure, deserialize_greeks,
+  get_native_oracle_price, load_cross_margin_account, load_margin_account, load_zeta_group,
+  validate_market_tradeable, Kind, MarketAccounts, Side,
+};
+use crate::instructions::MarginSnapshot;
+use crate::structs::{keeper_order_tag, OrderTracker, Vault};
+
+// position of the market's `OrderTracker` in a ladder leg, see
+// `client_helpers::LADDER_LEG_ACCOUNTS`
+pub const LEG_ORDER_TRACKER: usize = 12;
+
+/// Best bid resting on a serum book, an empty book has none to join.
+pub fn best_bid_price<'a, 'b>(
+  market: &'a AccountInfo<'b>,
+  bids: &'a AccountInfo<'b>,
+  dex_program: &Pubkey,
+) -> Result<u64> {
+  let market = Market::load(market, dex_program, false)
+    .map_err(|_| error!(VaultError::InvalidMarketAccounts))?;
+  let bids = market.load_bids_mut(bids)
+    .map_err(|_| error!(VaultError::InvalidMarketAccounts))?;
+  let best_price_node = bids.find_max()
+    .and_then(|handle| bids.get(handle))
+    .and_then(|node| node.as_leaf());
+  match best_price_node {
+    Some(node) => Ok(node.price().get()),
+    None => err!(VaultError::EmptyOrderBook),
+  }
+}
+
+/// Price and size of a bid joining the best bid with up to `max_amount`.
+pub fn order_at_best_bid<'a, 'b>(
+  market: &'a AccountInfo<'b>,
+  bids: &'a AccountInfo<'b>,
+  dex_program: &Pubkey,
+  max_amount: u64,
+) -> Result<(u64, u64)> {
+  let price = best_bid_price(market, bids, dex_program)?;
+  let size = max_amount
+    .checked_div(price).ok_or_else(|| error!(VaultError::MathOverflow))?;
+  Ok((price, size))
+}
+
+/// The `OrderTracker` of a ladder leg, created by `init_order_tracker`.
+pub fn load_leg_tracker<'info>(
+  vault: &Pubkey,
+  leg: &[AccountInfo<'info>],
+) -> Result<Account<'info, OrderTracker>> {
+  let order_tracker = Account::<OrderTracker>::try_from(&leg[LEG_ORDER_TRACKER])?;
+  if order_tracker.vault != *vault || order_tracker.market != leg[0].key() {
+    return err!(VaultError::InvalidOrderTracker);
+  }
+  Ok(order_tracker)
+}
+
+/// Zeta accounts of the instructions placing keeper orders on ladder legs.
+/// Every order goes through the same market, price and margin checks as
+/// `bid_order`, whichever instruction asked for it.
+pub struct KeeperOrders<'a, 'info> {
+  pub executor: &'a AccountInfo<'info>,
+  pub oracle: &'a AccountInfo<'info>,
+  pub price_update: &'a AccountInfo<'info>,
+  pub zeta_group: &'a AccountInfo<'info>,
+  pub margin_account: &'a AccountInfo<'info>,
+  pub state: &'a AccountInfo<'info>,
+  pub greeks: &'a AccountInfo<'info>,
+  pub dex_program: &'a AccountInfo<'info>,
+  pub serum_authority: &'a AccountInfo<'info>,
+  pub mint_authority: &'a AccountInfo<'info>,
+  pub rent: &'a Sysvar<'info, Rent>,
+  pub token_program: &'a Program<'info, Token>,
+  pub zeta_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> KeeperOrders<'a, 'info> {
+  /// Product index of a tradeable series in the vault market registry.
+  pub fn validate_market(&self, vault: &Account<'info, Vault>, market: &AccountInfo<'info>) -> Result<usize> {
+    let zeta_group = load_zeta_group(self.zeta_group)?;
+    let index = validate_market_tradeable(&zeta_group, &market.key())?;
+    let spot = if vault.market_registry.needs_spot() {
+      get_native_oracle_price(self.price_update, &vault.oracle)?
+    } else {
+      0
+    };
+    vault.check_market_allowed(&zeta_group, index, spot)?;
+    Ok(index)
+  }
+
+  pub fn product_index(&self, market: &AccountInfo<'info>) -> Result<usize> {
+    let zeta_group = load_zeta_group(self.zeta_group)?;
+    zeta_group.get_product_index_by_key(&market.key())
+  }
+
+  pub fn best_bid(&self, leg: &[AccountInfo<'info>]) -> Result<u64> {
+    best_bid_price(&leg[0], &leg[3], &self.dex_program.key())
+  }
+
+  /// Mark price band and premium floor of an order at `price`.
+  pub fn check_price(&self, vault: &Account<'info, Vault>, index: usize, price: u64) -> Result<()> {
+    let zeta_group = load_zeta_group(self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, self.greeks)?;
+    vault.check_price_band(price, greeks.mark_prices[index])?;
+    let kind = zeta_group.products[index].kind;
+    let strike = match kind {
+      Kind::Call | Kind::Put => zeta_group.get_strike(index)?,
+      _ => 0,
+    };
+    let expiry_ts = zeta_group.get_expiry_series_by_product_index(index)?.expiry_ts;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let spot = get_native_oracle_price(self.price_update, &vault.oracle)?;
+    vault.check_premium_floor(price, kind, spot, strike, expiry_ts.saturating_sub(now))
+  }
+
+  pub fn check_greeks_limits(&self, vault: &Account<'info, Vault>) -> Result<()> {
+    let zeta_group = load_zeta_group(self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, self.greeks)?;
+    let exposure = if vault.cross_margin {
+      let margin_account = load_cross_margin_account(self.margin_account)?;
+      calculate_cross_margin_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    } else {
+      let margin_account = load_margin_account(self.margin_account)?;
+      calculate_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    };
+    vault.check_greeks(&exposure)
+  }
+
+  /// Places a keeper bid on `leg`, guarded by `MarginSnapshot::check_order`.
+  pub fn place_bid(
+    &self,
+    vault: &Account<'info, Vault>,
+    leg: &[AccountInfo<'info>],
+    index: usize,
+    price: u64,
+    size: u64,
+    client_order_id: u64,
+  ) -> Result<()> {
+    self.check_price(vault, index, price)?;
+    let spot = get_native_oracle_price(self.price_update, &vault.oracle)?;
+    let before = MarginSnapshot::load(self.margin_account, vault.cross_margin, Some(index))?;
+    let seeds = executor_seeds!(vault);
+    let accounts = cpi::zeta::PlaceOrder {
+      zeta_group: self.zeta_group.clone(),
+      margin_account: self.margin_account.clone(),
+      authority: self.executor.clone(),
+      token_program: self.token_program.clone(),
+      state: self.state.clone(),
+      greeks: self.greeks.clone(),
+      market_accounts: MarketAccounts {
+        market: leg[0].clone(),
+        request_queue: leg[1].clone(),
+        event_queue: leg[2].clone(),
+        bids: leg[3].clone(),
+        asks: leg[4].clone(),
+        order_payer_token_account: leg[8].clone(),
+        coin_vault: leg[5].clone(),
+        pc_vault: leg[6].clone(),
+        coin_wallet: leg[7].clone(),
+        pc_wallet: leg[8].clone(),
+      },
+      oracle: self.oracle.clone(),
+      open_orders: leg[9].clone(),
+      rent: self.rent.clone(),
+      market_node: leg[10].clone(),
+      market_mint: leg[11].clone(),
+      mint_authority: self.mint_authority.clone(),
+      serum_authority: self.serum_authority.clone(),
+      dex_program: self.dex_program.clone(),
+    };
+    msg!("market: {}", leg[0].key());
+    msg!("price: {}", price);
+    msg!("size: {}", size);
+    msg!("client_order_id: {}", client_order_id);
+    if vault.cross_margin {
+      cpi::zeta::zeta_client::place_cross_margin_order(
+        self.zeta_program.clone(),
+        accounts,
+        price,
+        size,
+        Side::Bid,
+        vault.order_type(),
+        Some(client_order_id),
+        keeper_order_tag(None)?,
+        vault.order_tif_offset(),
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::place_order_v4(
+        self.zeta_program.clone(),
+        accounts,
+        price,
+        size,
+        Side::Bid,
+        vault.order_type(),
+        Some(client_order_id),
+        keeper_order_tag(None)?,
+        vault.order_tif_offset(),
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(self.margin_account, vault.cross_margin, Some(index))?;
+    before.check_order(&after, Side::Bid, price, size, spot)
+  }
+
+  /// Cancels a keeper order by client order id, returns the bid lots it
+  /// took off the book.
+  pub fn cancel_bid(
+    &self,
+    vault: &Account<'info, Vault>,
+    leg: &[AccountInfo<'info>],
+    index: usize,
+    client_order_id: u64,
+  ) -> Result<u64> {
+    let before = MarginSnapshot::load(self.margin_account, vault.cross_margin, Some(index))?;
+    let seeds = executor_seeds!(vault);
+    cpi::zeta::zeta_client::cancel_order_by_client_order_id(
+      self.zeta_program.clone(),
+      cpi::zeta::CancelOrder {
+        authority: self.executor.clone(),
+        cancel_accounts: cpi::zeta::CancelAccounts {
+          zeta_group: self.zeta_group.clone(),
+          state: self.state.clone(),
+          margin_account: self.margin_account.clone(),
+          dex_program: self.dex_program.clone(),
+          serum_authority: self.serum_authority.clone(),
+          open_orders: leg[9].clone(),
+          market: leg[0].clone(),
+          bids: leg[3].clone(),
+          asks: leg[4].clone(),
+          event_queue: leg[2].clone(),
+        },
+      },
+      client_order_id,
+      seeds,
+    )?;
+    let after = MarginSnapshot::load(self.margin_account, vault.cross_margin, Some(index))?;
+    before.check_cancel(&after)
+  }
+}
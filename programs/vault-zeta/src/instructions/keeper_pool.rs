@@ -0,0 +1,144 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::VaultError;
+use crate::structs::{KeeperPool, Vault};
+use crate::pda::{KEEPER_POOL_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct InitKeeperPool<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  #[account(
+  init,
+  space = 8 + KeeperPool::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [KEEPER_POOL_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub keeper_pool: Box<Account<'info, KeeperPool>>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitKeeperPool<'info> {
+  pub fn init_keeper_pool(&mut self, pool_bump: u8) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.keeper_pool.initialize(pool_bump, self.vault.key())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct FundKeeperPool<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [KEEPER_POOL_SEED, vault.key().as_ref()],
+  bump = keeper_pool.bump
+  )]
+  pub keeper_pool: Box<Account<'info, KeeperPool>>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> FundKeeperPool<'info> {
+  pub fn fund_keeper_pool(&mut self, lamports: u64) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let ctx = CpiContext::new(
+      self.system_program.to_account_info(),
+      Transfer {
+        from: self.authority.to_account_info(),
+        to: self.keeper_pool.to_account_info(),
+      },
+    );
+    transfer(ctx, lamports)
+  }
+}
+
+#[derive(Accounts)]
+pub struct DefundKeeperPool<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [KEEPER_POOL_SEED, vault.key().as_ref()],
+  bump = keeper_pool.bump
+  )]
+  pub keeper_pool: Box<Account<'info, KeeperPool>>,
+}
+
+impl<'info> DefundKeeperPool<'info> {
+  /// Takes `lamports` of the refunds back, the pool keeps its rent exemption.
+  pub fn defund_keeper_pool(&mut self, lamports: u64) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let keeper_pool = self.keeper_pool.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(keeper_pool.data_len());
+    if lamports > keeper_pool.lamports().saturating_sub(rent_exempt) {
+      return err!(VaultError::InsufficientKeeperPool);
+    }
+    **keeper_pool.try_borrow_mut_lamports()? -= lamports;
+    **self.authority.to_account_info().try_borrow_mut_lamports()? += lamports;
+    Ok(())
+  }
+}
+
+/// Refunds the crank fee to `keeper` when the vault keeper pool is passed as
+/// the last remaining account, the crank runs the same without it.
+pub fn refund_keeper_if_passed<'info>(
+  vault: &Pubkey,
+  remaining_accounts: &[AccountInfo<'info>],
+  keeper: &AccountInfo<'info>,
+  slot: u64,
+) -> Result<()> {
+  let (address, _) = Pubkey::find_program_address(&[KEEPER_POOL_SEED, vault.as_ref()], &crate::ID);
+  match remaining_accounts.last() {
+    Some(keeper_pool) if keeper_pool.key() == address => refund_keeper(keeper_pool, keeper, slot),
+    _ => Ok(()),
+  }
+}
+
+/// Refunds the crank fee to `keeper` out of the vault keeper pool. Vaults
+/// without a pool, or with a drained one, just don't refund: the crank
+/// itself must never fail on it. `keeper_pool` is the seeds-checked PDA.
+pub fn refund_keeper<'info>(
+  keeper_pool: &AccountInfo<'info>,
+  keeper: &AccountInfo<'info>,
+  slot: u64,
+) -> Result<()> {
+  if keeper_pool.owner != &crate::ID || keeper_pool.data_is_empty() {
+    return Ok(());
+  }
+  let mut pool = Account::<KeeperPool>::try_from(keeper_pool)?;
+  let rent_exempt = Rent::get()?.minimum_balance(keeper_pool.data_len());
+  let available = keeper_pool.lamports().saturating_sub(rent_exempt);
+  let amount = pool.refund_amount(available, slot);
+  if amount == 0 {
+    return Ok(());
+  }
+  pool.after_refund(amount, slot)?;
+  pool.exit(&crate::ID)?;
+  **keeper_pool.try_borrow_mut_lamports()? -= amount;
+  **keeper.try_borrow_mut_lamports()? += amount;
+  msg!("refunded {} lamports to {}", amount, keeper.key());
+  Ok(())
+}
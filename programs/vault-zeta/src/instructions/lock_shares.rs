@@ -0,0 +1,137 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{executor_seeds, VaultError};
+use crate::structs::{ShareLock, Vault};
+use crate::pda::{EXECUTOR_SEED, SHARES_SEED, SHARE_LOCK_ESCROW_SEED, SHARE_LOCK_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct LockShares<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub user_account: Signer<'info>,
+  #[account(
+  init_if_needed,
+  space = 8 + ShareLock::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [SHARE_LOCK_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub share_lock: Box<Account<'info, ShareLock>>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  // shares of all share locks
+  #[account(
+  init_if_needed,
+  payer = user_account,
+  token::mint = shares_mint,
+  token::authority = executor,
+  seeds = [SHARE_LOCK_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub lock_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> LockShares<'info> {
+  pub fn lock_shares(&mut self, shares_amount: u64, lock_epochs: u64, lock_bump: u8) -> Result<()> {
+    if shares_amount == 0 || lock_epochs == 0 {
+      return err!(VaultError::InvalidLock);
+    }
+    if !self.share_lock.is_initialized() {
+      self.share_lock.initialize(lock_bump, self.vault.key(), self.user_account.key())?;
+    }
+    let epoch = self.vault.lock_epoch();
+
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.user_shares.to_account_info(),
+        to: self.lock_escrow.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      });
+    transfer(ctx, shares_amount)?;
+    self.share_lock.lock(shares_amount, epoch, lock_epochs)
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct UnlockShares<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = lock_escrow.mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  pub user_account: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [SHARE_LOCK_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump = share_lock.bump
+  )]
+  pub share_lock: Box<Account<'info, ShareLock>>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  seeds = [SHARE_LOCK_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub lock_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> UnlockShares<'info> {
+  pub fn unlock_shares(&mut self) -> Result<()> {
+    if !self.share_lock.is_unlocked(self.vault.lock_epoch()) {
+      return err!(VaultError::SharesLocked);
+    }
+    let shares_amount = self.share_lock.take();
+    if shares_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.lock_escrow.to_account_info(),
+        to: self.user_shares.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer);
+    transfer(ctx, shares_amount)
+  }
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::cpi_calls::address_lookup_table::{address_lookup_table_program, get_lookup_table_address};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, SHARES_SEED, VAULT_SEED};
+
+// addresses per extend, keeps the instruction inside the legacy tx size
+pub const MAX_LOOKUP_TABLE_EXTEND: usize = 20;
+
+
+#[derive(Accounts)]
+pub struct CreateVaultLookupTable<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: AccountInfo<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK: derived from the authority and `recent_slot`, created here
+  #[account(mut)]
+  pub lookup_table: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = address_lookup_table_program::ID)]
+  pub address_lookup_table_program: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateVaultLookupTable<'info> {
+  /// Creates an address lookup table owned by the vault authority, fills it
+  /// with the vault's static accounts and stores it on the vault.
+  /// `recent_slot` has to be a recent slot, it seeds the table address.
+  pub fn create_lookup_table(&mut self, recent_slot: u64) -> Result<()> {
+    let (lookup_table, bump_seed) = get_lookup_table_address(&self.authority.key(), recent_slot);
+    if lookup_table != self.lookup_table.key() {
+      return err!(VaultError::InvalidLookupTable);
+    }
+    let cpi = CpiContext::new(
+      self.address_lookup_table_program.to_account_info(),
+      cpi::address_lookup_table::CreateLookupTable {
+        lookup_table: self.lookup_table.to_account_info(),
+        authority: self.authority.to_account_info(),
+        payer: self.authority.to_account_info(),
+        system_program: self.system_program.to_account_info(),
+      });
+    cpi::address_lookup_table::create_lookup_table(cpi, recent_slot, bump_seed)?;
+
+    let mut addresses = vec![
+      self.vault.key(),
+      self.executor.key(),
+      self.shares_mint.key(),
+      self.token_program.key(),
+      cpi::zeta::zeta_program::ID,
+      cpi::solend::SolendProgram::id(),
+    ];
+    addresses.extend(self.vault.static_accounts());
+    let cpi = CpiContext::new(
+      self.address_lookup_table_program.to_account_info(),
+      cpi::address_lookup_table::ExtendLookupTable {
+        lookup_table: self.lookup_table.to_account_info(),
+        authority: self.authority.to_account_info(),
+        payer: self.authority.to_account_info(),
+        system_program: self.system_program.to_account_info(),
+      });
+    cpi::address_lookup_table::extend_lookup_table(cpi, addresses)?;
+
+    self.vault.set_lookup_table(lookup_table)
+  }
+}
+
+#[derive(Accounts)]
+pub struct ExtendVaultLookupTable<'info> {
+  #[account(
+  has_one = authority,
+  has_one = lookup_table,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(mut, owner = address_lookup_table_program::ID)]
+  pub lookup_table: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = address_lookup_table_program::ID)]
+  pub address_lookup_table_program: AccountInfo<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExtendVaultLookupTable<'info> {
+  /// Adds the remaining accounts to the vault lookup table, for the Zeta and
+  /// Solend accounts the vault doesn't store (markets, state, lending market).
+  pub fn extend_lookup_table(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    if remaining_accounts.is_empty() || remaining_accounts.len() > MAX_LOOKUP_TABLE_EXTEND {
+      return err!(VaultError::InvalidLookupTable);
+    }
+    let cpi = CpiContext::new(
+      self.address_lookup_table_program.to_account_info(),
+      cpi::address_lookup_table::ExtendLookupTable {
+        lookup_table: self.lookup_table.to_account_info(),
+        authority: self.authority.to_account_info(),
+        payer: self.authority.to_account_info(),
+        system_program: self.system_program.to_account_info(),
+      });
+    cpi::address_lookup_table::extend_lookup_table(
+      cpi,
+      remaining_accounts.iter().map(|account| account.key()).collect(),
+    )
+  }
+}
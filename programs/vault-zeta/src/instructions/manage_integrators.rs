@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::structs::{Integrator, Vault};
+use crate::pda::{INTEGRATOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct AddIntegrator<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  init,
+  space = 8 + Integrator::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [INTEGRATOR_SEED, vault.key().as_ref(), integrator_authority.key().as_ref()],
+  bump
+  )]
+  pub integrator: Box<Account<'info, Integrator>>,
+  /// CHECK: signer of future `deposit_for` calls
+  pub integrator_authority: AccountInfo<'info>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddIntegrator<'info> {
+  pub fn add_integrator(&mut self, bump: u8) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.integrator.initialize(
+      bump,
+      self.vault.key(),
+      self.integrator_authority.key(),
+    )
+  }
+}
+
+#[derive(Accounts)]
+pub struct RemoveIntegrator<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  close = authority,
+  seeds = [INTEGRATOR_SEED, vault.key().as_ref(), integrator.authority.as_ref()],
+  bump = integrator.bump
+  )]
+  pub integrator: Box<Account<'info, Integrator>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+}
+
+impl<'info> RemoveIntegrator<'info> {
+  pub fn remove_integrator(&self) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())
+  }
+}
@@ -8,6 +8,71 @@ mod reinvest_solend;
 mod swap;
 mod redeem_zeta;
 mod init_open_orders;
+mod share_price_twap;
+mod open_receipts;
+mod split_shares;
+mod settle_receipts;
+mod redeem_receipts;
+mod set_oracle;
+mod update_vault_config;
+mod close_open_orders;
+mod setup_vault;
+mod commit_nav_snapshot;
+mod init_obligation;
+mod deposit_obligation_collateral;
+mod borrow_obligation_liquidity;
+mod health_check;
+mod withdraw_instant;
+mod rebalance_margin;
+mod solvency;
+mod manage_integrators;
+mod deposit_for;
+mod sweep_idle;
+mod cancel_client_order;
+mod lock_shares;
+mod execute_strategy;
+mod rent_reserve;
+mod place_ladder;
+mod compute_settlement;
+mod abort_epoch;
+mod preview;
+mod set_share_metadata;
+mod emergency_open_withdrawals;
+mod setup_cross_margin_account;
+mod rotate_executor;
+mod withdraw_as_collateral;
+mod setup_drift_user;
+mod rebalance_hedge;
+mod drift_orders;
+mod insurance;
+mod deposit_with_signature;
+mod lookup_table;
+mod rewards;
+mod socialize_loss;
+mod withdraw_in_kind;
+mod keeper_pool;
+mod reconcile_fills;
+mod protocol_config;
+mod bond;
+mod dry_run;
+mod pending_deposit;
+mod refresh_valuation;
+mod set_margin_overrides;
+mod zeta_guard;
+mod validate_zeta_constants;
+mod beneficial_owner;
+mod fast_rollover;
+mod error_stats;
+mod set_keeper_set;
+mod epoch_history;
+mod skim_surplus;
+mod feature_flags;
+mod keeper_orders;
+mod init_order_tracker;
+mod obligation;
+mod repay_obligation_liquidity;
+mod withdraw_obligation_collateral;
+mod deposit_receipt;
 
 pub use initialize::*;
 pub use deposit::*;
@@ -19,3 +84,68 @@ pub use bid_order::*;
 pub use swap::*;
 pub use redeem_zeta::*;
 pub use init_open_orders::*;
+pub use share_price_twap::*;
+pub use open_receipts::*;
+pub use split_shares::*;
+pub use settle_receipts::*;
+pub use redeem_receipts::*;
+pub use set_oracle::*;
+pub use update_vault_config::*;
+pub use close_open_orders::*;
+pub use setup_vault::*;
+pub use commit_nav_snapshot::*;
+pub use init_obligation::*;
+pub use deposit_obligation_collateral::*;
+pub use borrow_obligation_liquidity::*;
+pub use health_check::*;
+pub use withdraw_instant::*;
+pub use rebalance_margin::*;
+pub use solvency::*;
+pub use manage_integrators::*;
+pub use deposit_for::*;
+pub use sweep_idle::*;
+pub use cancel_client_order::*;
+pub use lock_shares::*;
+pub use execute_strategy::*;
+pub use rent_reserve::*;
+pub use place_ladder::*;
+pub use compute_settlement::*;
+pub use abort_epoch::*;
+pub use preview::*;
+pub use set_share_metadata::*;
+pub use emergency_open_withdrawals::*;
+pub use setup_cross_margin_account::*;
+pub use rotate_executor::*;
+pub use withdraw_as_collateral::*;
+pub use setup_drift_user::*;
+pub use rebalance_hedge::*;
+pub use drift_orders::*;
+pub use insurance::*;
+pub use deposit_with_signature::*;
+pub use lookup_table::*;
+pub use rewards::*;
+pub use socialize_loss::*;
+pub use withdraw_in_kind::*;
+pub use keeper_pool::*;
+pub use reconcile_fills::*;
+pub use protocol_config::*;
+pub use bond::*;
+pub use dry_run::*;
+pub use pending_deposit::*;
+pub use refresh_valuation::*;
+pub use set_margin_overrides::*;
+pub use zeta_guard::*;
+pub use validate_zeta_constants::*;
+pub use beneficial_owner::*;
+pub use fast_rollover::*;
+pub use error_stats::*;
+pub use set_keeper_set::*;
+pub use epoch_history::*;
+pub use skim_surplus::*;
+pub use feature_flags::*;
+pub use keeper_orders::*;
+pub use init_order_tracker::*;
+pub use obligation::*;
+pub use repay_obligation_liquidity::*;
+pub use withdraw_obligation_collateral::*;
+pub use deposit_receipt::*;
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::structs::Vault;
+
+
+/// Refreshes the vault obligation so its debt includes the interest accrued
+/// up to now. `reserves` are the refreshed reserves the vault lends and
+/// borrows on, an obligation holding any other reserve fails.
+pub fn refresh_obligation<'info>(
+  lending_program: &AccountInfo<'info>,
+  obligation: &AccountInfo<'info>,
+  reserves: &[AccountInfo<'info>],
+) -> Result<()> {
+  let state = Account::<cpi::solend::Obligation>::try_from(obligation)?;
+  let obligation_reserves = state.reserves()
+    .iter()
+    .map(|key| {
+      reserves.iter()
+        .find(|reserve| reserve.key == key)
+        .cloned()
+        .ok_or_else(|| error!(VaultError::InvalidObligation))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  let cpi = CpiContext::new(
+    lending_program.clone(),
+    cpi::solend::RefreshObligation {
+      obligation: obligation.clone(),
+      lending_program: lending_program.clone(),
+    })
+    .with_remaining_accounts(obligation_reserves);
+  cpi::solend::refresh_obligation(cpi)
+}
+
+/// Reads the collateral and the debt of the obligation onto the vault after
+/// a lending CPI changed them, `borrowed` and `repaid` are the USDC the
+/// vault received and paid back in it.
+pub fn book_obligation<'info>(
+  vault: &mut Account<'info, Vault>,
+  obligation: &AccountInfo<'info>,
+  collateral_reserve: &Account<'info, cpi::solend::Reserve>,
+  borrow_reserve: &Account<'info, cpi::solend::Reserve>,
+  borrowed: u64,
+  repaid: u64,
+  slot: u64,
+) -> Result<()> {
+  let state = Account::<cpi::solend::Obligation>::try_from(obligation)?;
+  let debt = vault.obligation_debt(&state, borrow_reserve)?;
+  let collateral = state.deposited_amount(&collateral_reserve.key());
+  msg!("obligation collateral: {}, debt: {}", collateral, debt);
+  vault.after_obligation_refreshed(collateral, debt, borrowed, repaid, slot)
+}
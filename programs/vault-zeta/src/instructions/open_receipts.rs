@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::VaultError;
+use crate::structs::{EpochReceipts, Vault};
+use crate::pda::{EXECUTOR_SEED, PRINCIPAL_SEED, RECEIPTS_ESCROW_SEED, RECEIPTS_SEED, SHARES_SEED, VAULT_SEED, YIELD_SEED};
+
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct OpenReceipts<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump,
+  constraint = epoch == vault.receipt_epoch + 1
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  init,
+  space = 8 + EpochReceipts::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [RECEIPTS_SEED, vault.key().as_ref(), epoch.to_le_bytes().as_ref()],
+  bump
+  )]
+  pub receipts: Box<Account<'info, EpochReceipts>>,
+  #[account(
+  init,
+  payer = authority,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault,
+  seeds = [PRINCIPAL_SEED, receipts.key().as_ref()],
+  bump
+  )]
+  pub principal_mint: Box<Account<'info, Mint>>,
+  #[account(
+  init,
+  payer = authority,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault,
+  seeds = [YIELD_SEED, receipts.key().as_ref()],
+  bump
+  )]
+  pub yield_mint: Box<Account<'info, Mint>>,
+  #[account(
+  init,
+  payer = authority,
+  token::mint = shares_mint,
+  token::authority = executor,
+  seeds = [RECEIPTS_ESCROW_SEED, receipts.key().as_ref()],
+  bump
+  )]
+  pub shares_escrow: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> OpenReceipts<'info> {
+  pub fn open_receipts(
+    &mut self,
+    epoch: u64,
+    bump: u8,
+    principal_mint_bump: u8,
+    yield_mint_bump: u8,
+    escrow_bump: u8,
+  ) -> Result<()> {
+    if self.vault.receipt_epoch_live {
+      return err!(VaultError::ReceiptsEpochLive);
+    }
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    let start_price = self.vault.current_share_price(
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    msg!("start_price: {}", start_price);
+    self.receipts.initialize(
+      clock.unix_timestamp,
+      bump,
+      principal_mint_bump,
+      yield_mint_bump,
+      escrow_bump,
+      self.vault.key(),
+      self.principal_mint.key(),
+      self.yield_mint.key(),
+      self.shares_escrow.key(),
+      epoch,
+      start_price,
+    )?;
+    self.vault.receipt_epoch = epoch;
+    self.vault.receipt_epoch_live = true;
+    Ok(())
+  }
+}
+
+
+pub fn open_receipts_epoch(ctx: Context<OpenReceipts>, epoch: u64) -> Result<()> {
+  ctx.accounts.open_receipts(
+    epoch,
+    *ctx.bumps.get("receipts").unwrap(),
+    *ctx.bumps.get("principal_mint").unwrap(),
+    *ctx.bumps.get("yield_mint").unwrap(),
+    *ctx.bumps.get("shares_escrow").unwrap(),
+  )
+}
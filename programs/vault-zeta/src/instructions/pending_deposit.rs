@@ -0,0 +1,379 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, mint_to, MintTo, Token, TokenAccount, Transfer, transfer};
+use crate::{cpi_calls as cpi, executor_seeds, vault_seeds, VaultError};
+use crate::instructions::{check_bonded, refund_keeper_if_passed, DepositEscrow, SolvencyAccounts};
+use crate::structs::{PendingDeposit, UserPosition, Vault};
+use crate::pda::{
+  EXECUTOR_SEED, PENDING_DEPOSIT_ESCROW_SEED, PENDING_DEPOSIT_SEED, SHARES_SEED, USER_POSITION_SEED, VAULT_SEED,
+};
+
+
+#[derive(Accounts)]
+pub struct DepositPending<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = underlying_mint
+  )]
+  pub user_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub user_account: Signer<'info>,
+  #[account(
+  init_if_needed,
+  space = 8 + PendingDeposit::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [PENDING_DEPOSIT_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub pending_deposit: Box<Account<'info, PendingDeposit>>,
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  #[account(address = underlying_vault.mint)]
+  pub underlying_mint: Box<Account<'info, Mint>>,
+  // underlying of all pending deposits, kept out of the vault NAV
+  #[account(
+  init_if_needed,
+  payer = user_account,
+  token::mint = underlying_mint,
+  token::authority = executor,
+  seeds = [PENDING_DEPOSIT_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub pending_deposit_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> DepositPending<'info> {
+  /// Escrows `amount` for the next epoch without minting shares, see
+  /// `convert_pending_deposit`. Open outside the deposit window.
+  pub fn deposit_pending(
+    &mut self,
+    amount: u64,
+    pending_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    if !self.vault.setup_complete {
+      return err!(VaultError::SetupIncomplete);
+    }
+    check_bonded(&self.vault, remaining_accounts)?;
+    self.vault.check_pending_deposit(amount)?;
+    if !self.pending_deposit.is_initialized() {
+      self.pending_deposit.initialize(
+        pending_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    let clock = Clock::get()?;
+    let epoch = self.vault.epoch_schedule.current_epoch(clock.unix_timestamp);
+    self.pending_deposit.queue(amount, epoch)?;
+
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.user_token_account.to_account_info(),
+        to: self.pending_deposit_escrow.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      });
+    transfer(ctx, amount)?;
+    self.vault.after_pending_deposit(amount)?;
+    msg!("pending: {}, epoch: {}", self.pending_deposit.amount, epoch);
+    Ok(())
+  }
+}
+
+#[derive(Accounts)]
+pub struct ConvertPendingDeposit<'info> {
+  #[account(mut)]
+  pub payer: Signer<'info>,
+  /// CHECK: owner of the pending deposit, receives the shares
+  pub owner: AccountInfo<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  has_one = owner,
+  seeds = [PENDING_DEPOSIT_SEED, vault.key().as_ref(), owner.key().as_ref()],
+  bump = pending_deposit.bump
+  )]
+  pub pending_deposit: Box<Account<'info, PendingDeposit>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = payer,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), owner.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  seeds = [PENDING_DEPOSIT_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub pending_deposit_escrow: Box<Account<'info, TokenAccount>>,
+  // refund of a deposit that missed its conversion epoch
+  #[account(
+  mut,
+  token::authority = owner,
+  token::mint = pending_deposit_escrow.mint
+  )]
+  pub owner_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  // holds the deposit when the reserve can't take it, see `reserve_fallback_idle`
+  #[account(mut, address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault.key(),
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(
+  init_if_needed,
+  payer = payer,
+  associated_token::mint = shares_mint,
+  associated_token::authority = owner
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = reserve.liquidity.supply_pubkey)]
+  pub reserve_liquidity_supply: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = reserve.collateral.mint_pubkey)]
+  pub reserve_collateral_mint: Box<Account<'info, Mint>>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::solend::get_lending_market_authority(&reserve.lending_market))]
+  pub lending_market_authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub associated_token_program: Program<'info, AssociatedToken>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> ConvertPendingDeposit<'info> {
+  /// Permissionless crank, during the epoch after the one the deposit was
+  /// queued in it moves the escrowed funds into the vault and mints the owner
+  /// shares at the entry share price recorded when that epoch rolled over.
+  /// A deposit that missed its epoch is refunded to the owner instead. The
+  /// shares wait in the owner's deposit receipt like any deposit while
+  /// `Vault::escrows_deposits`. The keeper pool comes last when the crank is
+  /// refunded to the payer.
+  pub fn convert_pending_deposit(
+    &mut self,
+    position_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    let epoch = self.vault.epoch_schedule.current_epoch(clock.unix_timestamp);
+    if self.pending_deposit.is_expired(epoch) {
+      let amount = self.pending_deposit.take();
+      msg!("refunded: {}, queued epoch: {}", amount, self.pending_deposit.epoch);
+      refund_pending_deposit(
+        &mut self.vault,
+        &self.executor,
+        &self.pending_deposit_escrow,
+        &self.owner_token_account,
+        &self.token_program,
+        amount,
+      )?;
+      return refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.payer.to_account_info(), clock.slot);
+    }
+    if !self.pending_deposit.is_due(epoch) {
+      return err!(VaultError::PendingDepositNotDue);
+    }
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.check_not_under_maintenance(&self.vault)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
+    let share_price = self.vault.entry_share_price(clock.unix_timestamp)?;
+    let amount = self.pending_deposit.take();
+    let shares = self.vault.preview_deposit(
+      amount,
+      Some(share_price),
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    msg!("amount: {}, entry price: {}, shares: {}", amount, share_price, shares);
+    self.vault.check_deposit_shares(amount, shares)?;
+
+    self.deposit_liquidity(amount, clock.slot)?;
+    let escrowed = self.vault.escrows_deposits();
+    if escrowed {
+      let mut escrow = DepositEscrow::load(&self.vault.key(), &self.owner.key(), remaining_accounts)?;
+      self.mint(escrow.escrow.clone(), shares)?;
+      escrow.after_deposit(shares, clock.slot, clock.unix_timestamp)?;
+    } else {
+      self.mint(self.user_shares.to_account_info(), shares)?;
+    }
+    self.vault.after_pending_deposit_converted(amount)?;
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.owner.key(),
+      )?;
+    }
+    self.user_position.after_deposit(amount, shares)?;
+    self.solvency.record_and_assert_solvent(&mut self.vault, clock.slot)?;
+    refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.payer.to_account_info(), clock.slot)
+  }
+
+  fn mint(&self, to: AccountInfo<'info>, shares_amount: u64) -> Result<()> {
+    let seeds = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      MintTo {
+        mint: self.shares_mint.to_account_info(),
+        to,
+        authority: self.vault.to_account_info(),
+      }, seeds);
+    mint_to(ctx, shares_amount)
+  }
+
+  fn deposit_liquidity(&mut self, amount: u64, slot: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    if !self.vault.check_reserve_deposit(&self.reserve, amount, slot)? {
+      let ctx = CpiContext::new_with_signer(
+        self.token_program.to_account_info(),
+        Transfer {
+          from: self.pending_deposit_escrow.to_account_info(),
+          to: self.underlying_vault.to_account_info(),
+          authority: self.executor.to_account_info(),
+        }, signer);
+      transfer(ctx, amount)?;
+      return self.vault.after_idle_deposit(amount);
+    }
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::DepositReserveLiquidity {
+        source_liquidity: self.pending_deposit_escrow.to_account_info(),
+        destination_collateral: *self.collateral_vault.clone(),
+        reserve: self.reserve.to_account_info(),
+        reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+        reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        lending_market_authority: self.lending_market_authority.to_account_info(),
+        user_transfer_authority: self.executor.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    cpi::solend::deposit_liquidity(cpi, amount)?;
+    Ok(())
+  }
+}
+
+#[derive(Accounts)]
+pub struct CancelPendingDeposit<'info> {
+  pub user_account: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  constraint = pending_deposit.owner == user_account.key(),
+  seeds = [PENDING_DEPOSIT_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump = pending_deposit.bump
+  )]
+  pub pending_deposit: Box<Account<'info, PendingDeposit>>,
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  seeds = [PENDING_DEPOSIT_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub pending_deposit_escrow: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = pending_deposit_escrow.mint
+  )]
+  pub user_token_account: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelPendingDeposit<'info> {
+  /// Takes a queued deposit back before it converts.
+  pub fn cancel_pending_deposit(&mut self) -> Result<()> {
+    let amount = self.pending_deposit.take();
+    if amount == 0 {
+      return err!(VaultError::NoPendingDeposit);
+    }
+    msg!("cancelled: {}", amount);
+    refund_pending_deposit(
+      &mut self.vault,
+      &self.executor,
+      &self.pending_deposit_escrow,
+      &self.user_token_account,
+      &self.token_program,
+      amount,
+    )
+  }
+}
+
+fn refund_pending_deposit<'info>(
+  vault: &mut Account<'info, Vault>,
+  executor: &AccountInfo<'info>,
+  escrow: &Account<'info, TokenAccount>,
+  to: &Account<'info, TokenAccount>,
+  token_program: &Program<'info, Token>,
+  amount: u64,
+) -> Result<()> {
+  let seeds = executor_seeds!(vault);
+  let signer: &[&[&[u8]]] = &[&seeds[..]];
+  let ctx = CpiContext::new_with_signer(
+    token_program.to_account_info(),
+    Transfer {
+      from: escrow.to_account_info(),
+      to: to.to_account_info(),
+      authority: executor.to_account_info(),
+    }, signer);
+  transfer(ctx, amount)?;
+  vault.after_pending_deposit_refunded(amount)
+}
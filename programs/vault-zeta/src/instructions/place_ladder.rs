@@ -0,0 +1,257 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use crate::{executor_seeds, cpi_calls as cpi, ratio_floor, VaultError};
+use crate::cpi_calls::zeta::{
+  calculate_cross_margin_greeks_exposure, calculate_greeks_exposure, deserialize_greeks,
+  get_native_oracle_price, load_cross_margin_account, load_margin_account, load_margin_account_state,
+  load_zeta_group, validate_market_tradeable, Kind, MarketAccounts, Side,
+};
+use crate::instructions::{
+  count_resting_orders, finish_dry_run, load_leg_tracker, order_at_best_bid, MarginSnapshot,
+  SolvencyAccounts,
+};
+use crate::structs::{keeper_order_tag, DryRunOrder, Vault, Venue};
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+use crate::client_helpers;
+
+// accounts per leg passed as remaining accounts, ordered as in
+// `client_helpers::LADDER_LEG_ACCOUNTS`
+pub const LADDER_LEG_ACCOUNTS: usize = client_helpers::LADDER_LEG_ACCOUNTS.len();
+
+#[derive(Accounts)]
+pub struct PlaceLadder<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  pub authority: Signer<'info>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, address = vault.margin_account)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  /// CHECK:
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK:
+  pub serum_authority: AccountInfo<'info>,
+  /// CHECK:
+  pub mint_authority: AccountInfo<'info>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> PlaceLadder<'info> {
+  /// Splits `max_amount` across the legs by `vault.ladder_weights_bps` and bids
+  /// each leg at its best bid. Every leg must be on a tradeable series in the
+  /// market registry and fit in the initial margin left after the previous legs.
+  /// A `dry_run` reports the legs without placing them, the margin of the
+  /// legs is not checked then. A keeper cosigner goes after the legs. The
+  /// bids placed are recorded on the order tracker of each leg.
+  pub fn place_ladder(
+    &mut self,
+    max_amount: u64,
+    dry_run: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_venue(Venue::Zeta)?;
+    self.vault.check_group_margin()?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    let weights = self.vault.ladder_weights();
+    let legs_len = weights.len() * LADDER_LEG_ACCOUNTS;
+    if weights.is_empty()
+      || remaining_accounts.len() < legs_len
+      || remaining_accounts.len() > legs_len + 1 {
+      return err!(VaultError::InvalidLadder);
+    }
+    let (legs, cosigner) = remaining_accounts.split_at(legs_len);
+    let cosigned = self.vault.keeper_set.is_cosigned(cosigner);
+
+    let snapshot = if dry_run { Some((**self.vault).clone()) } else { None };
+    let mut orders = vec![];
+    for (leg, weight_bps) in legs.chunks(LADDER_LEG_ACCOUNTS).zip(weights) {
+      let leg_amount = ratio_floor!(max_amount, weight_bps, 10_000_u64).unwrap();
+      self.validate_market(&leg[0])?;
+      let (price, size) = order_at_best_bid(&leg[0], &leg[3], &self.dex_program.key(), leg_amount)?;
+      if size == 0 {
+        continue;
+      }
+      let resting_orders = count_resting_orders(&leg[0], &leg[9], &self.dex_program.key())?;
+      self.vault.check_resting_orders(resting_orders)?;
+      self.vault.consume_order_budget(size, price, cosigned, clock.slot, clock.unix_timestamp)?;
+      let mut order_tracker = load_leg_tracker(&self.vault.key(), leg)?;
+      let epoch = self.vault.epoch_schedule.current_epoch(clock.unix_timestamp);
+      order_tracker.check_reprice(price, epoch, self.vault.max_reprice_walk_bps)?;
+      let client_order_id = self.vault.next_client_order_id();
+      if dry_run {
+        self.check_price(&leg[0], price)?;
+        orders.push(DryRunOrder { target: leg[0].key(), price, size: size as i64, client_order_id });
+        continue;
+      }
+      self.place_order(leg, price, size, client_order_id)?;
+      self.check_margin()?;
+      order_tracker.after_order(price, epoch)?;
+      order_tracker.after_bid_placed(size)?;
+      order_tracker.exit(&crate::ID)?;
+    }
+    self.check_greeks_limits()?;
+    if let Some(vault) = snapshot {
+      **self.vault = vault;
+      return finish_dry_run(self.vault.key(), orders);
+    }
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn validate_market(&self, market: &AccountInfo<'info>) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let index = validate_market_tradeable(&zeta_group, &market.key())?;
+    let spot = if self.vault.market_registry.needs_spot() {
+      get_native_oracle_price(&self.price_update, &self.vault.oracle)?
+    } else {
+      0
+    };
+    self.vault.check_market_allowed(&zeta_group, index, spot)
+  }
+
+  fn check_margin(&self) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let state = load_margin_account_state(
+      &zeta_group,
+      &self.vault.margin_parameters(&zeta_group),
+      &self.margin_account,
+      self.vault.cross_margin,
+      &greeks,
+      &self.price_update,
+      &self.vault.oracle,
+    )?;
+    if state.available_balance_initial < 0 {
+      msg!("available_balance_initial: {}", state.available_balance_initial);
+      return err!(VaultError::LadderMarginExceeded);
+    }
+    Ok(())
+  }
+
+  fn check_greeks_limits(&self) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let exposure = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      calculate_cross_margin_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      calculate_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    };
+    self.vault.check_greeks(&exposure)
+  }
+
+  // mark price band and premium floor
+  fn check_price(&self, market: &AccountInfo<'info>, price: u64) -> Result<()> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let index = zeta_group.get_product_index_by_key(&market.key())?;
+    self.vault.check_price_band(price, greeks.mark_prices[index])?;
+    let kind = zeta_group.products[index].kind;
+    let strike = match kind {
+      Kind::Call | Kind::Put => zeta_group.get_strike(index)?,
+      _ => 0,
+    };
+    let expiry_ts = zeta_group.get_expiry_series_by_product_index(index)?.expiry_ts;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    self.vault.check_premium_floor(price, kind, spot, strike, expiry_ts.saturating_sub(now))
+  }
+
+  fn place_order(
+    &self,
+    leg: &[AccountInfo<'info>],
+    price: u64,
+    size: u64,
+    client_order_id: u64,
+  ) -> Result<()> {
+    self.check_price(&leg[0], price)?;
+    let index = {
+      let zeta_group = load_zeta_group(&self.zeta_group)?;
+      zeta_group.get_product_index_by_key(&leg[0].key())?
+    };
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))?;
+    let seeds = executor_seeds!(self.vault);
+    let accounts = cpi::zeta::PlaceOrder {
+      zeta_group: self.zeta_group.to_account_info(),
+      margin_account: self.margin_account.to_account_info(),
+      authority: self.executor.to_account_info(),
+      token_program: self.token_program.clone(),
+      state: self.state.to_account_info(),
+      greeks: self.greeks.to_account_info(),
+      market_accounts: MarketAccounts {
+        market: leg[0].clone(),
+        request_queue: leg[1].clone(),
+        event_queue: leg[2].clone(),
+        bids: leg[3].clone(),
+        asks: leg[4].clone(),
+        order_payer_token_account: leg[8].clone(),
+        coin_vault: leg[5].clone(),
+        pc_vault: leg[6].clone(),
+        coin_wallet: leg[7].clone(),
+        pc_wallet: leg[8].clone(),
+      },
+      oracle: self.oracle.to_account_info(),
+      open_orders: leg[9].clone(),
+      rent: self.rent.clone(),
+      market_node: leg[10].clone(),
+      market_mint: leg[11].clone(),
+      mint_authority: self.mint_authority.to_account_info(),
+      serum_authority: self.serum_authority.to_account_info(),
+      dex_program: self.dex_program.to_account_info(),
+    };
+    msg!("market: {}", leg[0].key());
+    msg!("price: {}", price);
+    msg!("size: {}", size);
+    msg!("client_order_id: {}", client_order_id);
+    cpi::zeta::zeta_client::place_order_v4(
+      self.zeta_program.to_account_info(),
+      accounts,
+      price,
+      size,
+      Side::Bid,
+      self.vault.order_type(),
+      Some(client_order_id),
+      keeper_order_tag(None)?,
+      self.vault.order_tif_offset(),
+      seeds
+    )?;
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, Some(index))?;
+    before.check_order(&after, Side::Bid, price, size, spot)
+  }
+}
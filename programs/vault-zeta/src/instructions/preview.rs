@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token::Mint;
+use crate::structs::{NavSnapshot, PreviewDepositReturn, PreviewWithdrawReturn, Vault};
+use crate::pda::{SHARES_SEED, SNAPSHOT_SEED};
+
+
+#[derive(Accounts)]
+pub struct Preview<'info> {
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  /// CHECK: NAV snapshot PDA, stays uninitialized until the keeper commits one
+  #[account(
+  seeds = [SNAPSHOT_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub nav_snapshot: AccountInfo<'info>,
+}
+
+impl<'info> Preview<'info> {
+  /// Shares a deposit of `assets` would mint right now.
+  pub fn preview_deposit(&self, assets: u64) -> Result<u64> {
+    let clock = Clock::get()?;
+    let snapshot_price = self.snapshot_price(clock.slot)?;
+    let shares = self.vault.preview_deposit(
+      assets,
+      snapshot_price,
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    msg!("preview deposit shares: {}", shares);
+    set_return_data(&PreviewDepositReturn { shares }.try_to_vec()?);
+    Ok(shares)
+  }
+
+  /// Underlying a withdrawal of `shares` would pay out right now.
+  pub fn preview_withdraw(&self, shares: u64) -> Result<u64> {
+    let clock = Clock::get()?;
+    let assets = self.vault.preview_withdraw(
+      shares,
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    msg!("preview withdraw assets: {}", assets);
+    set_return_data(&PreviewWithdrawReturn { assets }.try_to_vec()?);
+    Ok(assets)
+  }
+
+  fn snapshot_price(&self, slot: u64) -> Result<Option<u64>> {
+    if self.nav_snapshot.data_is_empty() {
+      return Ok(None);
+    }
+    let snapshot: Account<NavSnapshot> = Account::try_from(&self.nav_snapshot)?;
+    if snapshot.is_active(slot, self.vault.snapshot_slots) {
+      Ok(Some(snapshot.share_price))
+    } else {
+      Ok(None)
+    }
+  }
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::VaultError;
+use crate::structs::{ProtocolConfig, ProtocolConfigParams};
+use crate::pda::{ProgramDataPda, PROTOCOL_CONFIG_SEED};
+
+
+#[derive(Accounts)]
+pub struct InitProtocolConfig<'info> {
+  #[account(mut)]
+  pub upgrade_authority: Signer<'info>,
+  #[account(
+  address = ProgramDataPda::find().0,
+  constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key())
+  )]
+  pub program_data: Account<'info, ProgramData>,
+  #[account(
+  init,
+  space = 8 + ProtocolConfig::MAXIMUM_SIZE,
+  payer = upgrade_authority,
+  seeds = [PROTOCOL_CONFIG_SEED],
+  bump
+  )]
+  pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitProtocolConfig<'info> {
+  /// Created once by the upgrade authority, which hands control to `admin`.
+  pub fn init_protocol_config(
+    &mut self,
+    bump: u8,
+    admin: Pubkey,
+    treasury: Pubkey,
+    protocol_fee_bps: u64,
+  ) -> Result<()> {
+    self.protocol_config.initialize(bump, admin, treasury, protocol_fee_bps)
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+  pub admin: Signer<'info>,
+  #[account(
+  mut,
+  has_one = admin,
+  seeds = [PROTOCOL_CONFIG_SEED],
+  bump = protocol_config.bump
+  )]
+  pub protocol_config: Box<Account<'info, ProtocolConfig>>,
+}
+
+impl<'info> UpdateProtocolConfig<'info> {
+  pub fn update_protocol_config(&mut self, params: &ProtocolConfigParams) -> Result<()> {
+    self.protocol_config.update(params)
+  }
+}
+
+/// Protocol config and treasury token account of the instructions charging
+/// a vault fee. The config is a required account of them, once it exists
+/// the treasury has to follow as the first remaining account, see
+/// `PROTOCOL_FEE_ACCOUNTS`. Only a deployment without a protocol config lets
+/// the vault keep the whole fee.
+pub struct ProtocolFee<'info> {
+  pub protocol_config: Account<'info, ProtocolConfig>,
+  pub treasury: AccountInfo<'info>,
+}
+
+impl<'info> ProtocolFee<'info> {
+  /// The treasury account has to be owned by the config treasury and hold
+  /// `mint`, the shares mint or the underlying depending on the fee.
+  pub fn load(
+    mint: &Pubkey,
+    protocol_config: &AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<Option<Self>> {
+    if protocol_config.data_is_empty() {
+      return Ok(None);
+    }
+    let treasury = match remaining_accounts.first() {
+      Some(treasury) => treasury,
+      None => return err!(VaultError::InvalidProtocolFeeAccounts),
+    };
+    let protocol_config = Account::<ProtocolConfig>::try_from(protocol_config)?;
+    let treasury_account = Account::<TokenAccount>::try_from(treasury)?;
+    if treasury_account.owner != protocol_config.treasury || treasury_account.mint != *mint {
+      return err!(VaultError::InvalidProtocolFeeAccounts);
+    }
+    Ok(Some(Self { protocol_config, treasury: treasury.clone() }))
+  }
+
+  /// Protocol part of `fee`, 0 without a protocol config.
+  pub fn split(protocol_fee: &Option<Self>, fee: u64) -> u64 {
+    protocol_fee.as_ref()
+      .map_or(0, |protocol_fee| protocol_fee.protocol_config.protocol_fee(fee).unwrap())
+  }
+}
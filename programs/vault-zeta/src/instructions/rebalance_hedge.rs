@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use crate::{executor_seeds, cpi_calls as cpi, VaultError};
+use crate::cpi_calls::drift::{OrderParams, PerpPositionView, PositionDirection};
+use crate::cpi_calls::zeta::{
+  calculate_cross_margin_greeks_exposure, calculate_greeks_exposure, deserialize_greeks,
+  get_native_oracle_price, load_cross_margin_account, load_margin_account, load_zeta_group,
+};
+use crate::instructions::finish_dry_run;
+use crate::structs::{DryRunOrder, Vault};
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct RebalanceHedge<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = zeta_group,
+  has_one = drift_user,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: cross-margin account when the vault uses that model
+  #[account(address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  pub greeks: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::drift::drift_program::ID)]
+  pub drift_state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, owner = cpi::drift::drift_program::ID)]
+  pub drift_user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::drift::drift_program::ID)]
+  pub drift_program: AccountInfo<'info>,
+}
+
+impl<'info> RebalanceHedge<'info> {
+  /// Trades the Drift perp so the net delta (filled option positions plus the
+  /// perp, its resting orders included) goes back to zero once it leaves
+  /// `delta_band`. `price` is the limit price, 6.dp, held to the mark band
+  /// around the vault oracle. Remaining accounts are the drift oracle and
+  /// markets of the order. A `dry_run` reports the perp order, `target` is the
+  /// drift user, without placing it.
+  pub fn rebalance_hedge(
+    &mut self,
+    price: u64,
+    dry_run: bool,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    if !self.vault.delta_hedge.is_enabled() {
+      return err!(VaultError::HedgeDisabled);
+    }
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    let spot = get_native_oracle_price(&self.price_update, &self.vault.oracle)?;
+    self.vault.check_price_band(price, spot)?;
+
+    let option_delta = self.option_delta()?;
+    let market_index = self.vault.delta_hedge.perp_market_index;
+    let perp = PerpPositionView::load(&self.drift_user, market_index)?;
+    let perp_base_asset_amount = perp.expected_base_asset_amount()
+      .ok_or(ProgramError::ArithmeticOverflow)?;
+    msg!("option delta: {}, perp base: {}", option_delta, perp_base_asset_amount);
+
+    let amount = match self.vault.delta_hedge.rebalance_amount(option_delta, perp_base_asset_amount) {
+      Some(amount) if amount != 0 => amount,
+      _ => return Ok(()),
+    };
+    if dry_run {
+      return finish_dry_run(self.vault.key(), vec![DryRunOrder {
+        target: self.drift_user.key(),
+        price,
+        size: amount,
+        client_order_id: 0,
+      }]);
+    }
+    let direction = if amount > 0 { PositionDirection::Long } else { PositionDirection::Short };
+    self.place_perp_order(
+      OrderParams::perp_limit(market_index, direction, amount.unsigned_abs(), price, false, false),
+      remaining_accounts,
+    )
+  }
+
+  fn option_delta(&self) -> Result<i64> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let exposure = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      calculate_cross_margin_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      calculate_greeks_exposure(&zeta_group, &margin_account, &greeks)?
+    };
+    Ok(exposure.net_delta)
+  }
+
+  fn place_perp_order(&self, params: OrderParams, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.drift_program.to_account_info(),
+      cpi::drift::PlacePerpOrder {
+        state: self.drift_state.to_account_info(),
+        user: self.drift_user.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer)
+      .with_remaining_accounts(remaining_accounts.to_vec());
+    cpi::drift::place_perp_order(cpi, params)
+  }
+}
@@ -0,0 +1,228 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{executor_seeds, cpi_calls as cpi, ratio_floor, VaultError};
+use crate::cpi_calls::zeta::{
+  deserialize_greeks, load_margin_account_state, load_zeta_group,
+};
+use crate::instructions::{finish_dry_run, MarginSnapshot, SolvencyAccounts};
+use crate::lending::{LendingAdapter, SolendAdapter};
+use crate::structs::{DryRunOrder, Vault};
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct RebalanceMargin<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  // the Solend reserve has to lend USDC for both legs to run in one call
+  #[account(
+  mut,
+  address = vault.usdc_vault,
+  constraint = usdc_vault.mint == reserve.liquidity.mint_pubkey
+  )]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_liquidity_supply: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_collateral_mint: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK: the push feed or a pull PriceUpdateV2, checked against the
+  /// vault oracle config when the price is read
+  #[account(owner = vault.oracle.price_account_owner())]
+  pub price_update: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub zeta_vault: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, address = vault.margin_account)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> RebalanceMargin<'info> {
+  /// Moves USDC between Solend and the Zeta margin account so that the
+  /// initial margin uses `target_utilization_bps` of the margin balance.
+  /// A `dry_run` reports the transfer, in margin precision, without moving funds.
+  pub fn rebalance_margin(&mut self, target_utilization_bps: u64, dry_run: bool) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    if target_utilization_bps == 0 || target_utilization_bps > 10_000 {
+      return err!(VaultError::InvalidMarginTarget);
+    }
+    self.vault.check_group_margin()?;
+    self.vault.check_not_parked()?;
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    let (balance, initial_margin) = self.margin_state()?;
+    let target_balance = ratio_floor!(initial_margin, 10_000_u64, target_utilization_bps)
+      .unwrap();
+    msg!("balance: {}, target_balance: {}", balance, target_balance);
+    if dry_run {
+      return finish_dry_run(self.vault.key(), vec![DryRunOrder {
+        target: self.margin_account.key(),
+        price: 0,
+        size: target_balance as i64 - balance as i64,
+        client_order_id: 0,
+      }]);
+    }
+
+    if balance > target_balance {
+      let excess = balance - target_balance;
+      let before = self.usdc_vault.amount;
+      self.withdraw_zeta(excess)?;
+      self.vault.margin_basis.after_margin_withdraw(excess)?;
+      self.usdc_vault.reload()?;
+      let received = self.usdc_vault.amount
+        .checked_sub(before).unwrap();
+      self.deposit_liquidity(received)?;
+    } else if balance < target_balance {
+      let needed = self.vault.decimals
+        .to_underlying(target_balance - balance).unwrap();
+      let collateral_amount = self.vault
+        .for_collateral(needed, &self.reserve).unwrap()
+        .min(self.collateral_vault.amount);
+      let received = self.redeem_collateral(collateral_amount)?;
+      self.deposit_zeta(received)?;
+      self.vault.margin_basis.after_margin_deposit(received)?;
+    }
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn margin_state(&self) -> Result<(u64, u64)> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let state = load_margin_account_state(
+      &zeta_group,
+      &self.vault.margin_parameters(&zeta_group),
+      &self.margin_account,
+      self.vault.cross_margin,
+      &greeks,
+      &self.price_update,
+      &self.vault.oracle,
+    )?;
+    Ok((state.balance, state.initial_margin))
+  }
+
+  fn withdraw_zeta(&self, amount_out: u64) -> Result<()> {
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::withdraw(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::Withdraw {
+        zeta_group: self.zeta_group.to_account_info(),
+        margin_account: self.margin_account.to_account_info(),
+        vault: self.zeta_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        socialized_loss_account: self.socialized_loss_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        token_program: self.token_program.clone(),
+        state: self.state.to_account_info(),
+        greeks: self.greeks.to_account_info(),
+        oracle: self.oracle.to_account_info(),
+      },
+      amount_out,
+      seeds
+    )?;
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)
+  }
+
+  fn deposit_zeta(&self, amount_in: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    cpi::zeta::zeta_client::deposit(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::Deposit {
+        zeta_group: self.zeta_group.to_account_info(),
+        margin_account: self.margin_account.to_account_info(),
+        vault: self.zeta_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        socialized_loss_account: self.socialized_loss_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        token_program: self.token_program.clone(),
+        state: self.state.to_account_info(),
+        greeks: self.greeks.to_account_info(),
+      },
+      amount_in,
+      seeds
+    )?;
+    Ok(())
+  }
+
+  fn lending(&self) -> SolendAdapter<'_, 'info> {
+    SolendAdapter {
+      reserve: &self.reserve,
+      reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+      reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+      lending_market: self.lending_market.to_account_info(),
+      lending_market_authority: self.lending_market_authority.to_account_info(),
+      liquidity: *self.usdc_vault.clone(),
+      collateral: *self.collateral_vault.clone(),
+      authority: self.executor.to_account_info(),
+      token_program: self.token_program.to_account_info(),
+      lending_program: self.lending_program.to_account_info(),
+    }
+  }
+
+  // funds the reserve can't take stay idle in the usdc vault
+  fn deposit_liquidity(&self, amount_in: u64) -> Result<()> {
+    if !self.vault.check_reserve_deposit(&self.reserve, amount_in, Clock::get()?.slot)? {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    self.lending().deposit(amount_in, signer)
+  }
+
+  fn redeem_collateral(&mut self, amount_in: u64) -> Result<u64> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let liquidity_before = self.usdc_vault.amount;
+    self.lending().redeem(amount_in, signer)?;
+    self.usdc_vault.reload()?;
+    let liquidity_after = self.usdc_vault.amount
+      .checked_sub(liquidity_before).unwrap();
+    Ok(liquidity_after)
+  }
+}
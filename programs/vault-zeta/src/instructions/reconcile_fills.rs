@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use crate::cpi_calls::zeta::{
+  load_cross_margin_account, load_margin_account, load_zeta_group, ProductLedger, BID_ORDERS_INDEX,
+};
+use crate::instructions::refund_keeper_if_passed;
+use crate::structs::{OrderTracker, Vault};
+use crate::pda::{ORDER_TRACKER_SEED, VAULT_SEED};
+
+
+/// The vault's record of a market disagreed with its margin account ledger.
+#[event]
+pub struct FillDiscrepancy {
+  pub vault: Pubkey,
+  pub market: Pubkey,
+  pub recorded_position: i64,
+  pub recorded_open_bids: u64,
+  pub position: i64,
+  pub open_bids: u64,
+  pub slot: u64,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileFills<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(address = vault.zeta_group)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: cross-margin account when the vault uses that model
+  #[account(address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: looked up in the zeta group products
+  pub market: AccountInfo<'info>,
+  #[account(
+  mut,
+  seeds = [ORDER_TRACKER_SEED, vault.key().as_ref(), market.key().as_ref()],
+  bump = order_tracker.bump
+  )]
+  pub order_tracker: Box<Account<'info, OrderTracker>>,
+  #[account(mut)]
+  pub keeper: Signer<'info>,
+}
+
+impl<'info> ReconcileFills<'info> {
+  /// Permissionless, resets the market record to the margin account ledger.
+  /// Fills alone leave the recorded exposure unchanged, any other difference
+  /// (cancels, expiries, liquidations, orders placed outside `bid_order`)
+  /// is emitted as a `FillDiscrepancy`. Refunded from the keeper pool when
+  /// it is passed.
+  pub fn reconcile_fills(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let clock = Clock::get()?;
+    let (position, open_bids) = self.market_ledger()?;
+    let recorded_position = self.order_tracker.position;
+    let recorded_open_bids = self.order_tracker.open_bids;
+    let drift = self.order_tracker.reconcile(position, open_bids, clock.slot)?;
+    msg!("position: {}, open bids: {}, drift: {}", position, open_bids, drift);
+    if drift != 0 {
+      emit!(FillDiscrepancy {
+        vault: self.vault.key(),
+        market: self.market.key(),
+        recorded_position,
+        recorded_open_bids,
+        position,
+        open_bids,
+        slot: clock.slot,
+      });
+    }
+    refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.keeper.to_account_info(), clock.slot)
+  }
+
+  fn market_ledger(&self) -> Result<(i64, u64)> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let index = zeta_group.get_product_index_by_key(&self.market.key())?;
+    if self.margin_account.data_is_empty() {
+      return Ok((0, 0));
+    }
+    if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      return Ok(ledger_state(&margin_account.product_ledgers[index]));
+    }
+    let margin_account = load_margin_account(&self.margin_account)?;
+    Ok(ledger_state(&margin_account.product_ledgers[index]))
+  }
+}
+
+// packed struct, copy the fields out before reading them. Bids closing a
+// short rest as closing orders, they count as open bids all the same.
+fn ledger_state(ledger: &ProductLedger) -> (i64, u64) {
+  let position = ledger.position.size;
+  let opening_orders = ledger.order_state.opening_orders;
+  let closing_orders = ledger.order_state.closing_orders;
+  (position, opening_orders[BID_ORDERS_INDEX].checked_add(closing_orders).unwrap())
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{executor_seeds, VaultError};
+use crate::instructions::{ProtocolFee, SolvencyAccounts};
+use crate::structs::{EpochReceipts, ShareLock, Vault};
+use crate::pda::{EXECUTOR_SEED, PROTOCOL_CONFIG_SEED, RECEIPTS_SEED, SHARE_LOCK_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct RedeemReceipts<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_escrow.mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = principal_mint
+  )]
+  pub user_principal: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = yield_mint
+  )]
+  pub user_yield: Box<Account<'info, TokenAccount>>,
+  pub user_account: Signer<'info>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  has_one = vault,
+  has_one = principal_mint,
+  has_one = yield_mint,
+  has_one = shares_escrow,
+  seeds = [RECEIPTS_SEED, vault.key().as_ref(), receipts.epoch.to_le_bytes().as_ref()],
+  bump = receipts.bump
+  )]
+  pub receipts: Box<Account<'info, EpochReceipts>>,
+  #[account(mut)]
+  pub principal_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub yield_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub shares_escrow: Box<Account<'info, TokenAccount>>,
+  /// CHECK: share lock of the user, may be uninitialized
+  #[account(
+  mut,
+  seeds = [SHARE_LOCK_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub share_lock: AccountInfo<'info>,
+  // performance fee shares go to the vault authority
+  #[account(
+  mut,
+  token::authority = vault.authority,
+  token::mint = shares_escrow.mint
+  )]
+  pub fee_shares: Box<Account<'info, TokenAccount>>,
+  /// CHECK: the protocol config, empty until `init_protocol_config`
+  #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+  pub protocol_config: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> RedeemReceipts<'info> {
+  /// The protocol part of the performance fee goes to the treasury share
+  /// account passed with the protocol config, see `ProtocolFee`.
+  pub fn redeem_receipts(
+    &self,
+    principal_amount: u64,
+    yield_amount: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let protocol_fee = ProtocolFee::load(&self.shares_escrow.mint, &self.protocol_config, remaining_accounts)?;
+    if !self.receipts.is_settled() {
+      return err!(VaultError::ReceiptsNotSettled);
+    }
+    if principal_amount == 0 && yield_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+
+    let yield_shares = self.receipts.yield_shares(yield_amount).unwrap();
+    let (locked_amount, lock_epochs) = self.credit_lock(yield_amount)?;
+    let locked_yield_shares = self.receipts.yield_shares(locked_amount).unwrap();
+    let fee_shares = self.vault
+      .performance_fee(yield_shares, locked_yield_shares, lock_epochs).unwrap();
+    let shares_amount = self.receipts.principal_shares(principal_amount).unwrap()
+      .checked_add(yield_shares).unwrap()
+      .checked_sub(fee_shares).unwrap();
+    msg!("shares_amount: {}", shares_amount);
+    msg!("fee_shares: {}", fee_shares);
+    if principal_amount > 0 {
+      self.burn(self.principal_mint.to_account_info(), self.user_principal.to_account_info(), principal_amount)?;
+    }
+    if yield_amount > 0 {
+      self.burn(self.yield_mint.to_account_info(), self.user_yield.to_account_info(), yield_amount)?;
+    }
+    let protocol_shares = ProtocolFee::split(&protocol_fee, fee_shares);
+    msg!("protocol_shares: {}", protocol_shares);
+    if fee_shares > protocol_shares {
+      self.release_shares(self.fee_shares.to_account_info(), fee_shares - protocol_shares)?;
+    }
+    if let Some(protocol_fee) = protocol_fee.as_ref().filter(|_| protocol_shares > 0) {
+      self.release_shares(protocol_fee.treasury.clone(), protocol_shares)?;
+    }
+    self.release_shares(self.user_shares.to_account_info(), shares_amount)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  // yield receipts discounted by the user lock and its length during the
+  // receipts epoch
+  fn credit_lock(&self, yield_amount: u64) -> Result<(u64, u64)> {
+    if self.share_lock.data_is_empty() {
+      return Ok((0, 0));
+    }
+    let mut share_lock = Account::<ShareLock>::try_from(&self.share_lock)?;
+    let lock_epochs = share_lock.lock_epochs_at(self.receipts.epoch);
+    let locked_amount = share_lock.credit_receipts(self.receipts.epoch, yield_amount);
+    share_lock.exit(&crate::ID)?;
+    Ok((locked_amount, lock_epochs))
+  }
+
+  fn burn(&self, mint: AccountInfo<'info>, from: AccountInfo<'info>, amount: u64) -> Result<()> {
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Burn {
+        mint,
+        from,
+        authority: self.user_account.to_account_info(),
+      });
+    burn(ctx, amount)
+  }
+
+  fn release_shares(&self, to: AccountInfo<'info>, shares_amount: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.shares_escrow.to_account_info(),
+        to,
+        authority: self.executor.to_account_info(),
+      }, signer);
+    transfer(ctx, shares_amount)
+  }
+}
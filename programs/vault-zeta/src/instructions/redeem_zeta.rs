@@ -4,20 +4,22 @@ use anchor_spl::token::{Token, TokenAccount};
 use anchor_spl::mint::USDC;
 use crate::{executor_seeds, cpi_calls as cpi};
 use crate::cpi_calls::zeta::MarginAccount;
+use crate::instructions::{MarginSnapshot, SolvencyAccounts};
 use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
 
 #[derive(Accounts)]
 pub struct RedeemZeta<'info> {
   #[account(
   mut,
   has_one = authority,
-  seeds = [b"vault", vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
@@ -27,7 +29,11 @@ pub struct RedeemZeta<'info> {
   token::authority = executor
   )]
   pub usdc_vault: Box<Account<'info, TokenAccount>>,
-  /// CHECK: Oracle is currently unused and will be enabled on subsequent updates
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
   pub oracle: UncheckedAccount<'info>,
   /// CHECK:
   pub zeta_group: AccountInfo<'info>,
@@ -35,7 +41,7 @@ pub struct RedeemZeta<'info> {
   #[account(mut)]
   pub zeta_vault: AccountInfo<'info>,
   /// CHECK:
-  #[account(mut, address = vault.margin_account)]
+  #[account(mut, address = vault.active_margin_account())]
   pub margin_account: AccountInfo<'info>,
   /// CHECK:
   #[account(mut)]
@@ -46,33 +52,57 @@ pub struct RedeemZeta<'info> {
   pub greeks: AccountInfo<'info>,
   pub token_program: Program<'info, Token>,
   pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> RedeemZeta<'info> {
   pub fn redeem_zeta(&mut self, amount_out: u64) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
     self.withdraw_zeta(amount_out)?;
-    Ok(())
+    self.vault.margin_basis.after_margin_withdraw(amount_out)?;
+    self.solvency.assert_solvent(&self.vault)
   }
 
   fn withdraw_zeta(&self, amount_out: u64) -> Result<()> {
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
     let seeds = executor_seeds!(self.vault);
-    cpi::zeta::zeta_client::withdraw(
-      self.zeta_program.to_account_info(),
-      cpi::zeta::Withdraw {
-        zeta_group: self.zeta_group.to_account_info(),
-        margin_account: self.margin_account.to_account_info(),
-        vault: self.zeta_vault.to_account_info(),
-        user_token_account: self.usdc_vault.to_account_info(),
-        socialized_loss_account:  self.socialized_loss_account.to_account_info(),
-        authority: self.executor.to_account_info(),
-        token_program: self.token_program.clone(),
-        state: self.state.to_account_info(),
-        greeks: self.greeks.to_account_info(),
-        oracle: self.oracle.to_account_info(),
-      },
-      amount_out,
-      seeds
-    )?;
-    Ok(())
+    if self.vault.cross_margin {
+      cpi::zeta::zeta_client::withdraw_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::WithdrawV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::withdraw(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::Withdraw {
+          zeta_group: self.zeta_group.to_account_info(),
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account:  self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)
   }
 }
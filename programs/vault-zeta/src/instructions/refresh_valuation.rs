@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::instructions::{book_obligation, refund_keeper_if_passed, SolvencyAccounts};
+use crate::structs::Vault;
+use crate::pda::{ExecutorPda, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct RefreshValuation<'info> {
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // refreshed by a Solend `refresh_reserve` earlier in the transaction
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  #[account(mut)]
+  pub keeper: Signer<'info>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> RefreshValuation<'info> {
+  /// Keeper crank, caches the reserve exchange rate and the NAV breakdown
+  /// so pricing paths pass `check_valuation_fresh` between harvests. A vault
+  /// with a Solend obligation passes it and the borrow reserve in the
+  /// remaining accounts, refreshed earlier in the transaction, so the debt
+  /// it subtracts from NAV includes the accrued interest. The keeper pool
+  /// comes last when the crank is refunded.
+  pub fn refresh_valuation(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.refresh_exchange_rate(&self.reserve, clock.slot)?;
+    msg!("exchange rate: {}", self.vault.reserve_exchange_rate);
+    if self.vault.obligation != Pubkey::default() {
+      self.refresh_debt(remaining_accounts, clock.slot)?;
+    }
+    self.solvency.record_and_assert_solvent(&mut self.vault, clock.slot)?;
+    refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.keeper.to_account_info(), clock.slot)
+  }
+
+  fn refresh_debt(&mut self, remaining_accounts: &[AccountInfo<'info>], slot: u64) -> Result<()> {
+    let (obligation, borrow_reserve) = match remaining_accounts {
+      [obligation, borrow_reserve, ..] if obligation.key() == self.vault.obligation => (obligation, borrow_reserve),
+      _ => return err!(VaultError::InvalidObligation),
+    };
+    let borrow_reserve = Account::<cpi::solend::Reserve>::try_from(borrow_reserve)?;
+    if borrow_reserve.lending_market != self.reserve.lending_market {
+      return err!(VaultError::InvalidObligation);
+    }
+    {
+      let state = Account::<cpi::solend::Obligation>::try_from(obligation)?;
+      let executor = ExecutorPda::create(
+        &self.vault.key(),
+        self.vault.executor_version,
+        self.vault.executor_bump,
+      )?;
+      self.vault.check_obligation(&state, &executor, &self.reserve.lending_market, slot)?;
+    }
+    book_obligation(&mut self.vault, obligation, &self.reserve, &borrow_reserve, 0, 0, slot)
+  }
+}
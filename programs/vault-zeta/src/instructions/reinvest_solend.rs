@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Approve, Mint, mint_to, MintTo, Token, TokenAccount};
 use crate::{cpi_calls as cpi, executor_seeds, VaultError};
+use crate::instructions::SolvencyAccounts;
 use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
 
 
 #[derive(Accounts)]
@@ -10,14 +12,14 @@ pub struct ReinvestSolend<'info> {
   mut,
   has_one = authority,
   has_one = reserve,
-  seeds = [b"vault", reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   pub authority: Signer<'info>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
@@ -39,15 +41,25 @@ pub struct ReinvestSolend<'info> {
   pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
   pub token_program: Program<'info, Token>,
   pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> ReinvestSolend<'info> {
+  /// Deposits the underlying vault into Solend. Deposits held idle while the
+  /// reserve was unavailable go in with it but aren't booked as gain, the
+  /// harvested yield is booked as locked profit and tokens sent to the
+  /// underlying vault directly join the surplus.
   pub fn reinvest_solend(&mut self) -> Result<()> {
-    let gain_amount = self.underlying_vault.amount;
+    let amount_in = self.underlying_vault.amount;
     let clock = Clock::get()?;
-    self.deposit_liquidity(gain_amount)?;
+    self.vault.record_keeper_activity(clock.slot);
+    if !self.vault.check_reserve_deposit(&self.reserve, amount_in, clock.slot)? {
+      return Ok(());
+    }
+    self.deposit_liquidity(amount_in)?;
+    let gain_amount = self.vault.after_idle_reinvested(amount_in)?;
     self.vault.after_gain(gain_amount, clock.unix_timestamp)?;
-    Ok(())
+    self.solvency.assert_solvent(&self.vault)
   }
 
   fn deposit_liquidity(&mut self, max_amount_in: u64) -> Result<u64> {
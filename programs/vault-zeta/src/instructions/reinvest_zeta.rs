@@ -3,20 +3,22 @@ use anchor_spl::token::{Token, TokenAccount};
 use anchor_spl::mint::USDC;
 use crate::{executor_seeds, cpi_calls as cpi};
 use crate::cpi_calls::whirlpool::sqrt_price_from_tick_index;
+use crate::instructions::SolvencyAccounts;
 use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
 
 #[derive(Accounts)]
 pub struct ReinvestZeta<'info> {
   #[account(
   mut,
   has_one = authority,
-  seeds = [b"vault", vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
@@ -29,7 +31,7 @@ pub struct ReinvestZeta<'info> {
   #[account(mut)]
   pub zeta_vault: AccountInfo<'info>,
   /// CHECK:
-  #[account(mut, address = vault.margin_account)]
+  #[account(mut, address = vault.active_margin_account())]
   pub margin_account: AccountInfo<'info>,
   /// CHECK:
   #[account(mut)]
@@ -40,16 +42,42 @@ pub struct ReinvestZeta<'info> {
   pub greeks: AccountInfo<'info>,
   pub token_program: Program<'info, Token>,
   pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> ReinvestZeta<'info> {
-  pub fn reinvest_zeta(&self) -> Result<()> {
-    self.deposit_zeta(self.usdc_vault.amount)?;
-    Ok(())
+  /// Deposits the USDC vault into the margin account, but for the USDC
+  /// owed to the in-kind claims.
+  pub fn reinvest_zeta(&mut self) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    let amount = self.vault.free_usdc(self.usdc_vault.amount);
+    if amount == 0 {
+      return Ok(());
+    }
+    self.deposit_zeta(amount)?;
+    self.vault.margin_basis.after_margin_deposit(amount)?;
+    self.solvency.assert_solvent(&self.vault)
   }
 
   fn deposit_zeta(&self, amount_in: u64) -> Result<()> {
     let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      return cpi::zeta::zeta_client::deposit_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::DepositV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+        },
+        amount_in,
+        seeds
+      );
+    }
     cpi::zeta::zeta_client::deposit(
       self.zeta_program.to_account_info(),
       cpi::zeta::Deposit {
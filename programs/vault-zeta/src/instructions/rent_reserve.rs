@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::{executor_seeds, VaultError};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+/// Lamports of the vault PDAs are not part of NAV. The executor keeps
+/// `Vault::executor_rent_reserve` lamports, anything on top of it (e.g. rent refunded by
+/// closing Zeta open orders) or above the vault account rent exemption can be
+/// reclaimed by the authority.
+#[derive(Accounts)]
+pub struct ManageRent<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  mut,
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> ManageRent<'info> {
+  pub fn top_up_rent(&mut self, lamports: u64) -> Result<()> {
+    if lamports == 0 {
+      return err!(VaultError::ZeroDeposit);
+    }
+    let ctx = CpiContext::new(
+      self.system_program.to_account_info(),
+      Transfer {
+        from: self.authority.to_account_info(),
+        to: self.executor.to_account_info(),
+      });
+    transfer(ctx, lamports)?;
+    self.vault.after_rent_top_up(lamports)
+  }
+
+  pub fn reclaim_rent(&mut self) -> Result<()> {
+    let rent = Rent::get()?;
+    let executor_excess = self.executor.lamports()
+      .saturating_sub(self.vault.executor_rent_reserve(&rent));
+    if executor_excess > 0 {
+      let seeds = executor_seeds!(self.vault);
+      let signer: &[&[&[u8]]] = &[&seeds[..]];
+      let ctx = CpiContext::new_with_signer(
+        self.system_program.to_account_info(),
+        Transfer {
+          from: self.executor.to_account_info(),
+          to: self.authority.to_account_info(),
+        }, signer);
+      transfer(ctx, executor_excess)?;
+    }
+
+    let vault_info = self.vault.to_account_info();
+    let rent_exempt = rent.minimum_balance(vault_info.data_len());
+    let vault_excess = vault_info.lamports().saturating_sub(rent_exempt);
+    if vault_excess > 0 {
+      **vault_info.try_borrow_mut_lamports()? -= vault_excess;
+      **self.authority.to_account_info().try_borrow_mut_lamports()? += vault_excess;
+    }
+    msg!("executor_excess: {}, vault_excess: {}", executor_excess, vault_excess);
+    Ok(())
+  }
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{cpi_calls as cpi, executor_seeds};
+use crate::instructions::{book_obligation, refresh_obligation, SolvencyAccounts};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct RepayObligationLiquidity<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = obligation,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  // collateral reserve, used to value the obligation
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  #[account(
+  mut,
+  constraint = borrow_reserve.lending_market == reserve.lending_market,
+  constraint = borrow_reserve.liquidity.mint_pubkey == usdc_vault.mint
+  )]
+  pub borrow_reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK:
+  #[account(mut, address = borrow_reserve.liquidity.supply_pubkey)]
+  pub borrow_reserve_liquidity_supply: AccountInfo<'info>,
+  /// CHECK: validated in `check_obligation`
+  #[account(mut, owner = cpi::solend::solend_program::ID)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> RepayObligationLiquidity<'info> {
+  /// Repays up to `amount` of the debt, interest included, out of the USDC
+  /// vault. Margin has to be withdrawn from Zeta into it first.
+  pub fn repay_obligation_liquidity(&mut self, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    refresh_obligation(
+      &self.lending_program.to_account_info(),
+      &self.obligation,
+      &[self.reserve.to_account_info(), self.borrow_reserve.to_account_info()],
+    )?;
+    let debt = {
+      let obligation = Account::<cpi::solend::Obligation>::try_from(&self.obligation)?;
+      self.vault.check_obligation(&obligation, &self.executor.key(), &self.reserve.lending_market, clock.slot)?;
+      self.vault.obligation_debt(&obligation, &self.borrow_reserve)?
+    };
+    // USDC owed to in-kind claims stays in the vault
+    let amount = amount
+      .min(debt)
+      .min(self.vault.free_usdc(self.usdc_vault.amount));
+    msg!("repay: {}, debt: {}", amount, debt);
+    if amount > 0 {
+      self.repay_liquidity(amount)?;
+    }
+    self.borrow_reserve.reload()?;
+    book_obligation(
+      &mut self.vault,
+      &self.obligation,
+      &self.reserve,
+      &self.borrow_reserve,
+      0,
+      amount,
+      clock.slot,
+    )?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn repay_liquidity(&self, amount: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::RepayObligationLiquidity {
+        source_liquidity: self.usdc_vault.to_account_info(),
+        destination_liquidity: self.borrow_reserve_liquidity_supply.to_account_info(),
+        repay_reserve: self.borrow_reserve.to_account_info(),
+        obligation: self.obligation.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        user_transfer_authority: self.executor.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    cpi::solend::repay_obligation_liquidity(cpi, amount)
+  }
+}
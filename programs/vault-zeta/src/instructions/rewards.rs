@@ -0,0 +1,324 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{executor_seeds, VaultError};
+use crate::instructions::beneficial_owner;
+use crate::structs::{RewardPool, StakeAccount, Vault};
+use crate::pda::{
+  EXECUTOR_SEED, REWARD_POOL_SEED, REWARD_VAULT_SEED, SHARES_SEED, STAKE_ESCROW_SEED, STAKE_SEED, VAULT_SEED,
+};
+
+
+#[derive(Accounts)]
+pub struct InitRewardPool<'info> {
+  #[account(
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  #[account(
+  init,
+  space = 8 + RewardPool::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [REWARD_POOL_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub reward_pool: Box<Account<'info, RewardPool>>,
+  pub reward_mint: Box<Account<'info, Mint>>,
+  #[account(
+  init,
+  payer = authority,
+  token::mint = reward_mint,
+  token::authority = executor,
+  seeds = [REWARD_VAULT_SEED, reward_pool.key().as_ref()],
+  bump
+  )]
+  pub reward_vault: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> InitRewardPool<'info> {
+  pub fn init_reward_pool(&mut self, emission_rate: u64, pool_bump: u8) -> Result<()> {
+    let clock = Clock::get()?;
+    self.reward_pool.initialize(
+      pool_bump,
+      self.vault.key(),
+      self.reward_mint.key(),
+      self.reward_vault.key(),
+      emission_rate,
+      clock.unix_timestamp,
+    )
+  }
+}
+
+#[derive(Accounts)]
+pub struct FundRewardPool<'info> {
+  #[account(
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  has_one = reward_vault,
+  seeds = [REWARD_POOL_SEED, vault.key().as_ref()],
+  bump = reward_pool.bump
+  )]
+  pub reward_pool: Box<Account<'info, RewardPool>>,
+  #[account(mut)]
+  pub reward_vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = authority,
+  token::mint = reward_pool.reward_mint
+  )]
+  pub authority_token_account: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundRewardPool<'info> {
+  /// Adds `amount` reward tokens to the pool and sets the emission rate from
+  /// now on, rewards up to now are emitted at the previous rate.
+  pub fn fund_reward_pool(&mut self, amount: u64, emission_rate: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    self.reward_pool.update(clock.unix_timestamp)?;
+    if amount > 0 {
+      let ctx = CpiContext::new(
+        self.token_program.to_account_info(),
+        Transfer {
+          from: self.authority_token_account.to_account_info(),
+          to: self.reward_vault.to_account_info(),
+          authority: self.authority.to_account_info(),
+        });
+      transfer(ctx, amount)?;
+    }
+    self.reward_pool.after_funded(amount, emission_rate)
+  }
+}
+
+#[derive(Accounts)]
+pub struct StakeShares<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub user_account: Signer<'info>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [REWARD_POOL_SEED, vault.key().as_ref()],
+  bump = reward_pool.bump
+  )]
+  pub reward_pool: Box<Account<'info, RewardPool>>,
+  #[account(
+  init_if_needed,
+  space = 8 + StakeAccount::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [STAKE_SEED, reward_pool.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub stake_account: Box<Account<'info, StakeAccount>>,
+  // shares of all stake accounts
+  #[account(
+  init_if_needed,
+  payer = user_account,
+  token::mint = shares_mint,
+  token::authority = executor,
+  seeds = [STAKE_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub stake_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> StakeShares<'info> {
+  pub fn stake_shares(&mut self, shares_amount: u64, stake_bump: u8) -> Result<()> {
+    if shares_amount == 0 {
+      return err!(VaultError::InvalidStakeAmount);
+    }
+    if !self.stake_account.is_initialized() {
+      self.stake_account.initialize(stake_bump, self.reward_pool.key(), self.user_account.key())?;
+    }
+    let clock = Clock::get()?;
+    self.reward_pool.update(clock.unix_timestamp)?;
+
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.user_shares.to_account_info(),
+        to: self.stake_escrow.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      });
+    transfer(ctx, shares_amount)?;
+    self.stake_account.stake(shares_amount, self.reward_pool.reward_per_share)?;
+    self.reward_pool.after_stake(shares_amount)
+  }
+}
+
+#[derive(Accounts)]
+pub struct UnstakeShares<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = stake_escrow.mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  pub user_account: Signer<'info>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [REWARD_POOL_SEED, vault.key().as_ref()],
+  bump = reward_pool.bump
+  )]
+  pub reward_pool: Box<Account<'info, RewardPool>>,
+  #[account(
+  mut,
+  constraint = stake_account.pool == reward_pool.key(),
+  seeds = [STAKE_SEED, reward_pool.key().as_ref(), user_account.key().as_ref()],
+  bump = stake_account.bump
+  )]
+  pub stake_account: Box<Account<'info, StakeAccount>>,
+  #[account(
+  mut,
+  seeds = [STAKE_ESCROW_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub stake_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> UnstakeShares<'info> {
+  /// Returns staked shares, their rewards stay claimable.
+  pub fn unstake_shares(&mut self, shares_amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    self.reward_pool.update(clock.unix_timestamp)?;
+    self.stake_account.unstake(shares_amount, self.reward_pool.reward_per_share)?;
+    self.reward_pool.after_unstake(shares_amount)?;
+
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.stake_escrow.to_account_info(),
+        to: self.user_shares.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer);
+    transfer(ctx, shares_amount)
+  }
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+  pub user_account: Signer<'info>,
+  // owned by the beneficial owner, see `beneficial_owner`
+  #[account(
+  mut,
+  token::mint = reward_pool.reward_mint
+  )]
+  pub user_reward_account: Box<Account<'info, TokenAccount>>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  has_one = vault,
+  has_one = reward_vault,
+  seeds = [REWARD_POOL_SEED, vault.key().as_ref()],
+  bump = reward_pool.bump
+  )]
+  pub reward_pool: Box<Account<'info, RewardPool>>,
+  #[account(mut)]
+  pub reward_vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  constraint = stake_account.pool == reward_pool.key(),
+  seeds = [STAKE_SEED, reward_pool.key().as_ref(), user_account.key().as_ref()],
+  bump = stake_account.bump
+  )]
+  pub stake_account: Box<Account<'info, StakeAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimRewards<'info> {
+  /// The user state is an optional remaining account, the rewards go to the
+  /// beneficial owner set on it.
+  pub fn claim_rewards(&mut self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let beneficial_owner = beneficial_owner(&self.vault.key(), &self.user_account.key(), remaining_accounts)?;
+    if self.user_reward_account.owner != beneficial_owner {
+      return err!(VaultError::InvalidBeneficialOwner);
+    }
+    let clock = Clock::get()?;
+    self.reward_pool.update(clock.unix_timestamp)?;
+    self.stake_account.settle(self.reward_pool.reward_per_share)?;
+    let rewards = self.stake_account.take_rewards();
+    if rewards == 0 {
+      return err!(VaultError::NoRewards);
+    }
+    msg!("rewards: {}", rewards);
+
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.reward_vault.to_account_info(),
+        to: self.user_reward_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer);
+    transfer(ctx, rewards)
+  }
+}
@@ -0,0 +1,352 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_spl::token::{set_authority, SetAuthority, Token, TokenAccount};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use crate::{executor_seeds, cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  is_cross_margin_account_flat, is_margin_account_flat, load_cross_margin_account, load_margin_account,
+  validate_margin_account,
+};
+use crate::instructions::{MarginSnapshot, SolvencyAccounts};
+use crate::structs::{Vault, Venue};
+use crate::pda::{ExecutorPda, EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct ProposeExecutorRotation<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+}
+
+impl<'info> ProposeExecutorRotation<'info> {
+  pub fn propose_executor_rotation(&mut self) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.propose_executor_rotation(Clock::get()?.unix_timestamp)?;
+    msg!("pending version: {}, rotation at: {}",
+      self.vault.pending_executor_version, self.vault.executor_rotation_at);
+    Ok(())
+  }
+}
+
+#[derive(Accounts)]
+pub struct RotateExecutor<'info> {
+  #[account(
+  mut,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  mut,
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK:
+  #[account(
+  mut,
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), &[vault.pending_executor_version]],
+  bump
+  )]
+  pub new_executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub zeta_vault: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: zeta PDA of the new executor, checked in `rotate_executor`
+  #[account(mut)]
+  pub new_margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub system_program: Program<'info, System>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> RotateExecutor<'info> {
+  /// Moves everything the executor owns to the pending executor derivation
+  /// once the rotation timelock has passed: the balance of the active Zeta
+  /// margin account goes to the new executor's per-group margin account, the token vaults (Solend collateral
+  /// included) change authority and the lamports follow.
+  ///
+  /// Zeta open orders and the Solend obligation can't change owner. The margin
+  /// account has to be flat and the obligation empty, old open orders are
+  /// closed with `close_open_orders` and the new executor creates its own on
+  /// the next order.
+  ///
+  /// The escrows of the executor (share locks, receipts, pending deposits,
+  /// reward staking and reward vaults) are passed as remaining accounts and
+  /// change authority too, any left behind can follow with `migrate_escrow`.
+  pub fn rotate_executor(
+    &mut self,
+    new_executor_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.check_executor_rotation(Clock::get()?.unix_timestamp)?;
+    self.vault.check_venue(Venue::Zeta)?;
+    self.check_migratable()?;
+    validate_margin_account(
+      &self.zeta_group.key(),
+      &self.new_executor.key(),
+      &self.new_margin_account.key(),
+    )?;
+
+    let balance = if self.vault.cross_margin {
+      load_cross_margin_account(&self.margin_account)?.balance
+    } else {
+      load_margin_account(&self.margin_account)?.balance
+    };
+    let before = self.usdc_vault.amount;
+    if balance > 0 {
+      self.withdraw_zeta(balance)?;
+      self.vault.margin_basis.after_margin_withdraw(balance)?;
+    }
+    self.usdc_vault.reload()?;
+    let received = self.usdc_vault.amount
+      .checked_sub(before).unwrap();
+
+    self.transfer_authority(self.usdc_vault.to_account_info())?;
+    self.transfer_authority(self.collateral_vault.to_account_info())?;
+    self.transfer_authority(self.underlying_vault.to_account_info())?;
+    for escrow in remaining_accounts {
+      let escrow = Account::<TokenAccount>::try_from(escrow)?;
+      if escrow.owner != self.executor.key() {
+        return err!(VaultError::InvalidEscrow);
+      }
+      self.transfer_authority(escrow.to_account_info())?;
+    }
+    self.transfer_lamports()?;
+
+    let vault_key = self.vault.key();
+    let version = [self.vault.pending_executor_version];
+    let bump = [new_executor_bump];
+    let new_seeds: &[&[u8]] = &[EXECUTOR_SEED, vault_key.as_ref(), &version, &bump];
+    if self.new_margin_account.data_is_empty() {
+      cpi::zeta::zeta_client::initialize_margin_account(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::InitializeMarginAccount {
+          margin_account: self.new_margin_account.to_account_info(),
+          authority: self.new_executor.to_account_info(),
+          payer: self.authority.clone(),
+          zeta_group: self.zeta_group.to_account_info(),
+          system_program: self.system_program.clone(),
+          zeta_program: self.zeta_program.to_account_info(),
+        },
+        new_seeds,
+      )?;
+    }
+    if received > 0 {
+      cpi::zeta::zeta_client::deposit(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::Deposit {
+          zeta_group: self.zeta_group.to_account_info(),
+          margin_account: self.new_margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.new_executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+        },
+        received,
+        new_seeds,
+      )?;
+      self.vault.margin_basis.after_margin_deposit(received)?;
+    }
+
+    self.vault.after_executor_rotation(new_executor_bump, self.new_margin_account.key())?;
+    msg!("executor: {}, version: {}, margin moved: {}",
+      self.new_executor.key(), self.vault.executor_version, received);
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  // zeta zeroes the nonce of a market once its open orders are closed
+  fn check_migratable(&self) -> Result<()> {
+    let (flat, open_orders) = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      let open_orders_nonce = margin_account.open_orders_nonce;
+      (is_cross_margin_account_flat(&margin_account), open_orders_nonce.iter().any(|nonce| *nonce != 0))
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      let open_orders_nonce = margin_account.open_orders_nonce;
+      (is_margin_account_flat(&margin_account), open_orders_nonce.iter().any(|nonce| *nonce != 0))
+    };
+    if !flat
+      || open_orders
+      || self.vault.obligation_collateral > 0
+      || self.vault.total_borrowed > 0
+      || self.vault.drift_collateral > 0 {
+      return err!(VaultError::ExecutorNotMigratable);
+    }
+    Ok(())
+  }
+
+  fn withdraw_zeta(&self, amount_out: u64) -> Result<()> {
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      cpi::zeta::zeta_client::withdraw_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::WithdrawV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::withdraw(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::Withdraw {
+          zeta_group: self.zeta_group.to_account_info(),
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)
+  }
+
+  fn transfer_authority(&self, token_account: AccountInfo<'info>) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      SetAuthority {
+        current_authority: self.executor.to_account_info(),
+        account_or_mint: token_account,
+      }, signer);
+    set_authority(ctx, AuthorityType::AccountOwner, Some(self.new_executor.key()))
+  }
+
+  // the rent reserve follows the executor, lamports above it are returned to
+  // the authority as `reclaim_rent` would
+  fn transfer_lamports(&self) -> Result<()> {
+    let lamports = self.executor.lamports();
+    let reserve = lamports.min(self.vault.executor_rent_reserve(&Rent::get()?));
+    self.transfer_executor_lamports(self.new_executor.to_account_info(), reserve)?;
+    self.transfer_executor_lamports(self.authority.to_account_info(), lamports - reserve)
+  }
+
+  fn transfer_executor_lamports(&self, to: AccountInfo<'info>, lamports: u64) -> Result<()> {
+    if lamports == 0 {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.system_program.to_account_info(),
+      Transfer {
+        from: self.executor.to_account_info(),
+        to,
+      }, signer);
+    transfer(ctx, lamports)
+  }
+}
+
+#[derive(Accounts)]
+pub struct MigrateEscrow<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK: executor of `old_version`, checked in `migrate_escrow`
+  pub old_executor: AccountInfo<'info>,
+  #[account(mut)]
+  pub escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> MigrateEscrow<'info> {
+  /// Hands an escrow left with a rotated executor to the current one, e.g.
+  /// the shares escrow of an older receipts epoch.
+  pub fn migrate_escrow(&self, old_version: u8) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    if old_version >= self.vault.executor_version {
+      return err!(VaultError::InvalidEscrow);
+    }
+    let (old_executor, old_bump) = ExecutorPda::find(&self.vault.key(), old_version);
+    if old_executor != self.old_executor.key() || self.escrow.owner != old_executor {
+      return err!(VaultError::InvalidEscrow);
+    }
+    let vault_key = self.vault.key();
+    let bump = [old_bump];
+    let old_seeds: &[&[u8]] = &[
+      EXECUTOR_SEED,
+      vault_key.as_ref(),
+      ExecutorPda::version_seed(&old_version),
+      &bump,
+    ];
+    let signer: &[&[&[u8]]] = &[old_seeds];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      SetAuthority {
+        current_authority: self.old_executor.to_account_info(),
+        account_or_mint: self.escrow.to_account_info(),
+      }, signer);
+    set_authority(ctx, AuthorityType::AccountOwner, Some(self.executor.key()))
+  }
+}
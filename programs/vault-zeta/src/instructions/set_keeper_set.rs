@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+use crate::structs::{KeeperSet, Vault};
+use crate::pda::VAULT_SEED;
+
+
+#[derive(Accounts)]
+pub struct SetKeeperSet<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+}
+
+impl<'info> SetKeeperSet<'info> {
+  pub fn set_keeper_set(&mut self, keeper_set: KeeperSet) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.set_keeper_set(keeper_set)
+  }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::cpi_calls as cpi;
+use crate::cpi_calls::zeta::{load_zeta_group};
+use crate::structs::{MarginOverrides, Vault};
+use crate::pda::VAULT_SEED;
+
+
+#[derive(Accounts)]
+pub struct SetMarginOverrides<'info> {
+  #[account(
+  mut,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  /// CHECK: overrides are bounded by its margin parameters
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+}
+
+impl<'info> SetMarginOverrides<'info> {
+  pub fn set_margin_overrides(&mut self, overrides: MarginOverrides) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    self.vault.set_margin_overrides(overrides, &zeta_group)
+  }
+}
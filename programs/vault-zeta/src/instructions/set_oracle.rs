@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::cpi_calls as cpi;
+use crate::cpi_calls::zeta::pyth_client::OracleSource;
+use crate::structs::Vault;
+use crate::pda::VAULT_SEED;
+
+
+#[derive(Accounts)]
+pub struct SetOracle<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  /// CHECK: Pyth push feed of the underlying, Zeta prices against it
+  #[account(owner = cpi::zeta::pyth_client::pyth_program::ID)]
+  pub price_feed: AccountInfo<'info>,
+}
+
+impl<'info> SetOracle<'info> {
+  pub fn set_oracle(
+    &mut self,
+    source: OracleSource,
+    feed_id: [u8; 32],
+    max_age: u64,
+  ) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.set_oracle(
+      source,
+      self.price_feed.key(),
+      feed_id,
+      max_age,
+    )
+  }
+}
+
+#[derive(Accounts)]
+pub struct SetReserveOracle<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  /// CHECK: Pyth feed of the Solend reserve liquidity
+  #[account(owner = cpi::zeta::pyth_client::pyth_program::ID)]
+  pub price_feed: AccountInfo<'info>,
+}
+
+impl<'info> SetReserveOracle<'info> {
+  pub fn set_reserve_oracle(
+    &mut self,
+    source: OracleSource,
+    feed_id: [u8; 32],
+    max_age: u64,
+  ) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    self.vault.set_reserve_oracle(
+      source,
+      self.price_feed.key(),
+      feed_id,
+      max_age,
+    )
+  }
+}
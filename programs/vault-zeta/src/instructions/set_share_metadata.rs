@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::{cpi_calls as cpi, vault_seeds};
+use crate::cpi_calls::metaplex::DataV2;
+use crate::structs::Vault;
+use crate::pda::{SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SetShareMetadata<'info> {
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  /// CHECK: metadata PDA of the shares mint, created on first call
+  #[account(
+  mut,
+  address = cpi::metaplex::get_metadata_address(&shares_mint.key())
+  )]
+  pub metadata: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::metaplex::token_metadata_program::ID)]
+  pub token_metadata_program: AccountInfo<'info>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SetShareMetadata<'info> {
+  /// Creates or updates the Metaplex metadata of the shares mint, the vault
+  /// PDA signs both as mint authority and as update authority.
+  pub fn set_share_metadata(&self, name: String, symbol: String, uri: String) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    let data = DataV2 {
+      name,
+      symbol,
+      uri,
+      seller_fee_basis_points: 0,
+      creators: None,
+      collection: None,
+      uses: None,
+    };
+    let seeds = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&seeds[..]];
+
+    if self.metadata.data_is_empty() {
+      let cpi = CpiContext::new_with_signer(
+        self.token_metadata_program.to_account_info(),
+        cpi::metaplex::CreateMetadataAccount {
+          metadata: self.metadata.to_account_info(),
+          mint: self.shares_mint.to_account_info(),
+          mint_authority: self.vault.to_account_info(),
+          payer: self.authority.to_account_info(),
+          update_authority: self.vault.to_account_info(),
+          system_program: self.system_program.to_account_info(),
+          rent: self.rent.to_account_info(),
+        },
+        seeds,
+      );
+      cpi::metaplex::create_metadata_account(cpi, data)
+    } else {
+      let cpi = CpiContext::new_with_signer(
+        self.token_metadata_program.to_account_info(),
+        cpi::metaplex::UpdateMetadataAccount {
+          metadata: self.metadata.to_account_info(),
+          update_authority: self.vault.to_account_info(),
+        },
+        seeds,
+      );
+      cpi::metaplex::update_metadata_account(cpi, data)
+    }
+  }
+}
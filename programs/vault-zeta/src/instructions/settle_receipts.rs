@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::structs::{EpochReceipts, Vault};
+use crate::pda::{RECEIPTS_SEED, SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SettleReceipts<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [RECEIPTS_SEED, vault.key().as_ref(), receipts.epoch.to_le_bytes().as_ref()],
+  bump = receipts.bump
+  )]
+  pub receipts: Box<Account<'info, EpochReceipts>>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(address = cpi::memo::memo_program::ID)]
+  pub memo_program: AccountInfo<'info>,
+}
+
+impl<'info> SettleReceipts<'info> {
+  pub fn settle_receipts(&mut self) -> Result<()> {
+    if !self.vault.receipt_epoch_live
+      || self.vault.receipt_epoch != self.receipts.epoch
+      || self.receipts.is_settled() {
+      return err!(VaultError::ReceiptsEpochNotLive);
+    }
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    let end_price = self.vault.current_share_price(
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    self.receipts.settle(end_price, clock.unix_timestamp)?;
+    msg!("start_price: {}", self.receipts.start_price);
+    msg!("end_price: {}", end_price);
+    msg!("realized_premium: {}", self.receipts.realized_premium);
+    self.vault.receipt_epoch_live = false;
+
+    let memo = cpi::memo::MemoTrailer::new("settle_receipts", &self.vault.key(), self.receipts.epoch)
+      .field("start_price", self.receipts.start_price)
+      .field("end_price", end_price)
+      .field("realized_premium", self.receipts.realized_premium)
+      .finish();
+    cpi::memo::log_memo(self.memo_program.to_account_info(), memo)
+  }
+}
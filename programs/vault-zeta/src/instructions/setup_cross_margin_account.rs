@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use crate::{executor_seeds, cpi_calls as cpi};
+use crate::cpi_calls::zeta::CrossMarginAccount;
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+// the vault trades from a single cross-margin subaccount
+const SUBACCOUNT_INDEX: u8 = 0;
+
+#[derive(Accounts)]
+pub struct SetupCrossMarginAccount<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK: zeta PDA of the executor
+  #[account(
+  mut,
+  address = CrossMarginAccount::get_manager_address(&executor.key())
+  )]
+  pub cross_margin_account_manager: AccountInfo<'info>,
+  /// CHECK: zeta PDA of the executor
+  #[account(
+  mut,
+  address = CrossMarginAccount::get_address(&executor.key(), SUBACCOUNT_INDEX)
+  )]
+  pub cross_margin_account: AccountInfo<'info>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetupCrossMarginAccount<'info> {
+  /// Creates the executor's cross-margin account, the vault switches to it
+  /// with the `cross_margin` config flag.
+  pub fn setup_cross_margin_account(&mut self) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    if self.cross_margin_account_manager.data_is_empty() {
+      cpi::zeta::zeta_client::initialize_cross_margin_account_manager(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::InitializeCrossMarginAccountManager {
+          cross_margin_account_manager: self.cross_margin_account_manager.to_account_info(),
+          authority: self.executor.to_account_info(),
+          payer: self.authority.clone(),
+          zeta_program: self.zeta_program.to_account_info(),
+          system_program: self.system_program.clone(),
+        },
+        seeds,
+      )?;
+    }
+    if self.cross_margin_account.data_is_empty() {
+      cpi::zeta::zeta_client::initialize_cross_margin_account(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::InitializeCrossMarginAccount {
+          cross_margin_account: self.cross_margin_account.to_account_info(),
+          cross_margin_account_manager: self.cross_margin_account_manager.to_account_info(),
+          authority: self.executor.to_account_info(),
+          payer: self.authority.clone(),
+          zeta_program: self.zeta_program.to_account_info(),
+          system_program: self.system_program.clone(),
+        },
+        SUBACCOUNT_INDEX,
+        seeds,
+      )?;
+    }
+    self.vault.set_cross_margin_account(self.cross_margin_account.key())
+  }
+}
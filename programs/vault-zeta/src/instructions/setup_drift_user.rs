@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use crate::{executor_seeds, cpi_calls as cpi};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+// the vault trades from a single drift sub account
+const SUB_ACCOUNT_ID: u16 = 0;
+
+#[derive(Accounts)]
+pub struct SetupDriftUser<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  /// CHECK: drift PDA of the executor
+  #[account(
+  mut,
+  address = cpi::drift::get_user_stats_address(&executor.key())
+  )]
+  pub drift_user_stats: AccountInfo<'info>,
+  /// CHECK: drift PDA of the executor
+  #[account(
+  mut,
+  address = cpi::drift::get_user_address(&executor.key(), SUB_ACCOUNT_ID)
+  )]
+  pub drift_user: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut, owner = cpi::drift::drift_program::ID)]
+  pub drift_state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::drift::drift_program::ID)]
+  pub drift_program: AccountInfo<'info>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SetupDriftUser<'info> {
+  /// Creates the executor's Drift user the perp hedge trades from, see
+  /// `rebalance_hedge`, and drift vaults place their orders from, see the
+  /// `venue` config option.
+  pub fn setup_drift_user(&mut self) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    if self.drift_user_stats.data_is_empty() {
+      let cpi = CpiContext::new_with_signer(
+        self.drift_program.to_account_info(),
+        cpi::drift::InitializeUserStats {
+          user_stats: self.drift_user_stats.to_account_info(),
+          state: self.drift_state.to_account_info(),
+          authority: self.executor.to_account_info(),
+          payer: self.authority.to_account_info(),
+          rent: self.rent.to_account_info(),
+          system_program: self.system_program.to_account_info(),
+        },
+        signer,
+      );
+      cpi::drift::initialize_user_stats(cpi)?;
+    }
+    if self.drift_user.data_is_empty() {
+      let mut name = [b' '; 32];
+      name[..5].copy_from_slice(b"vault");
+      let cpi = CpiContext::new_with_signer(
+        self.drift_program.to_account_info(),
+        cpi::drift::InitializeUser {
+          user: self.drift_user.to_account_info(),
+          user_stats: self.drift_user_stats.to_account_info(),
+          state: self.drift_state.to_account_info(),
+          authority: self.executor.to_account_info(),
+          payer: self.authority.to_account_info(),
+          rent: self.rent.to_account_info(),
+          system_program: self.system_program.to_account_info(),
+        },
+        signer,
+      );
+      cpi::drift::initialize_user(cpi, SUB_ACCOUNT_ID, name)?;
+    }
+    self.vault.set_drift_user(self.drift_user.key())
+  }
+}
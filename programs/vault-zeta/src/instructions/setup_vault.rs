@@ -0,0 +1,301 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::{cpi_calls as cpi, executor_seeds, VaultError};
+use crate::cpi_calls::zeta::{load_zeta_group, validate_margin_account};
+use crate::structs::{SetupSession, Vault};
+use crate::pda::{EXECUTOR_SEED, SETUP_SEED, SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct BeginSetup<'info> {
+  #[account(
+  init,
+  space = 8 + Vault::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  init,
+  space = 8 + SetupSession::MAXIMUM_SIZE,
+  payer = authority,
+  seeds = [SETUP_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub setup_session: Box<Account<'info, SetupSession>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump
+  )]
+  pub executor: AccountInfo<'info>,
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK:
+  pub zeta_group: AccountInfo<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> BeginSetup<'info> {
+  pub fn begin_setup(
+    &mut self,
+    bump: u8,
+    session_bump: u8,
+    executor_bump: u8,
+    deposit_limit: u64,
+    management_fee_bps: u64,
+  ) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.begin_setup(
+      clock.unix_timestamp,
+      bump,
+      executor_bump,
+      self.authority.key(),
+      self.reserve.key(),
+      self.zeta_group.key(),
+      deposit_limit,
+      management_fee_bps,
+    )?;
+    self.setup_session.initialize(
+      session_bump,
+      self.vault.key(),
+      self.authority.key(),
+    )
+  }
+}
+
+
+#[derive(Accounts)]
+#[instruction(share_decimals: u8)]
+pub struct SetupSharesMint<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [SETUP_SEED, vault.key().as_ref()],
+  bump = setup_session.bump
+  )]
+  pub setup_session: Box<Account<'info, SetupSession>>,
+  #[account(
+  init,
+  payer = authority,
+  mint::decimals = share_decimals,
+  mint::authority = vault,
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  pub rent: Sysvar<'info, Rent>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetupSharesMint<'info> {
+  /// Creates the shares mint with `share_decimals`, the first deposit mints
+  /// shares at the same bootstrap price as a vault from `initialize_vault`.
+  pub fn setup_shares_mint(&mut self, share_decimals: u8, mint_bump: u8) -> Result<()> {
+    self.vault.mint_bump = mint_bump;
+    self.vault.set_share_decimals(share_decimals)?;
+    self.setup_session.shares_mint_ready = true;
+    Ok(())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct SetupTokenAccounts<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [SETUP_SEED, vault.key().as_ref()],
+  bump = setup_session.bump
+  )]
+  pub setup_session: Box<Account<'info, SetupSession>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  token::mint = reserve.collateral.mint_pubkey,
+  token::authority = executor.key()
+  )]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+  token::mint = reserve.liquidity.mint_pubkey,
+  token::authority = executor.key()
+  )]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  #[account(
+  token::mint = cpi::zeta::USDC,
+  token::authority = executor.key()
+  )]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  pub authority: Signer<'info>,
+}
+
+impl<'info> SetupTokenAccounts<'info> {
+  pub fn setup_token_accounts(&mut self) -> Result<()> {
+    self.vault.set_token_accounts(
+      self.collateral_vault.key(),
+      self.underlying_vault.key(),
+      self.usdc_vault.key(),
+      self.reserve.liquidity.mint_decimals,
+    )?;
+    // the shares can't be coarser than the underlying whichever step ran first
+    if self.setup_session.shares_mint_ready {
+      let share_decimals = self.vault.share_decimals;
+      self.vault.set_share_decimals(share_decimals)?;
+    }
+    self.setup_session.token_accounts_ready = true;
+    Ok(())
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct SetupMarginAccount<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  seeds = [SETUP_SEED, vault.key().as_ref()],
+  bump = setup_session.bump
+  )]
+  pub setup_session: Box<Account<'info, SetupSession>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: Pyth feed of the underlying, checked against the zeta group oracle
+  #[account(owner = cpi::zeta::pyth_client::pyth_program::ID)]
+  pub price_feed: AccountInfo<'info>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetupMarginAccount<'info> {
+  pub fn setup_margin_account(&mut self) -> Result<()> {
+    let zeta_underlying_mint = self.validate_zeta_group()?;
+    if self.margin_account.data_is_empty() {
+      let seeds = executor_seeds!(self.vault);
+      cpi::zeta::zeta_client::initialize_margin_account(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::InitializeMarginAccount {
+          margin_account: self.margin_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          payer: self.authority.clone(),
+          zeta_group: self.zeta_group.to_account_info(),
+          system_program: self.system_program.clone(),
+          zeta_program: self.zeta_program.to_account_info(),
+        },
+        seeds,
+      )?;
+    }
+    self.vault.set_margin_account(
+      self.margin_account.key(),
+      self.price_feed.key(),
+      zeta_underlying_mint,
+    )?;
+    self.setup_session.margin_account_ready = true;
+    Ok(())
+  }
+
+  // the margin account and oracle must be the zeta group's, returns its underlying
+  fn validate_zeta_group(&self) -> Result<Pubkey> {
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    validate_margin_account(
+      &self.zeta_group.key(),
+      &self.executor.key(),
+      &self.margin_account.key(),
+    )?;
+    if zeta_group.oracle != self.price_feed.key() {
+      return err!(cpi::zeta::FuzeErrorCode::InvalidOracle);
+    }
+    Ok(zeta_group.underlying_mint)
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct CompleteSetup<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  close = authority,
+  seeds = [SETUP_SEED, vault.key().as_ref()],
+  bump = setup_session.bump
+  )]
+  pub setup_session: Box<Account<'info, SetupSession>>,
+  #[account(mut)]
+  pub authority: Signer<'info>,
+}
+
+impl<'info> CompleteSetup<'info> {
+  pub fn complete_setup(&mut self) -> Result<()> {
+    if !self.setup_session.is_complete() {
+      return err!(VaultError::SetupIncomplete);
+    }
+    self.vault.setup_complete = true;
+    Ok(())
+  }
+}
+
+
+pub fn begin_vault_setup(
+  ctx: Context<BeginSetup>,
+  deposit_limit: u64,
+  management_fee_bps: u64,
+) -> Result<()> {
+  ctx.accounts.begin_setup(
+    *ctx.bumps.get("vault").unwrap(),
+    *ctx.bumps.get("setup_session").unwrap(),
+    *ctx.bumps.get("executor").unwrap(),
+    deposit_limit,
+    management_fee_bps,
+  )
+}
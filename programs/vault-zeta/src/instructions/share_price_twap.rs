@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use crate::structs::{SharePriceTwapReturn, Vault};
+
+
+#[derive(Accounts)]
+pub struct SharePriceTwap<'info> {
+  pub vault: Box<Account<'info, Vault>>,
+}
+
+impl<'info> SharePriceTwap<'info> {
+  pub fn share_price_twap(&self, window: i64) -> Result<u64> {
+    let clock = Clock::get()?;
+    let twap = self.vault.share_price.twap(window, clock.unix_timestamp)?;
+    msg!("share price twap: {}", twap);
+    set_return_data(&SharePriceTwapReturn { twap }.try_to_vec()?);
+    Ok(twap)
+  }
+}
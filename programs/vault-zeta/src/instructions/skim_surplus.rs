@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+use crate::cpi_calls as cpi;
+use crate::instructions::{parked_margin_balance, real_assets};
+use crate::structs::Vault;
+use crate::pda::{SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SkimSurplus<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  #[account(address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  #[account(address = vault.reserve)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: read for parked liquidity
+  #[account(address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::memo::memo_program::ID)]
+  pub memo_program: AccountInfo<'info>,
+}
+
+impl<'info> SkimSurplus<'info> {
+  /// Releases the donation surplus found by the last `harvest_yield` into the
+  /// share price gradually, as locked profit. The funds never left the
+  /// collateral vault, only the booking changes, so the balances are read
+  /// again and only the surplus they still back is skimmed.
+  pub fn skim_surplus(&mut self) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.refresh_exchange_rate(&self.reserve, clock.slot)?;
+    let real_underlying = real_assets(
+      &self.vault,
+      &self.reserve,
+      &self.collateral_vault,
+      &self.underlying_vault,
+      parked_margin_balance(&self.vault, &self.margin_account)?,
+    );
+    msg!("real underlying: {}", real_underlying);
+    let skimmed = self.vault.skim_surplus(real_underlying, clock.unix_timestamp)?;
+    msg!("skimmed surplus: {}", skimmed);
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+
+    let memo = cpi::memo::MemoTrailer::new(
+      "skim",
+      &self.vault.key(),
+      self.vault.epoch_schedule.current_epoch(clock.unix_timestamp),
+    )
+      .field("skimmed", skimmed)
+      .field("total_skimmed", self.vault.total_surplus_skimmed)
+      .finish();
+    cpi::memo::log_memo(self.memo_program.to_account_info(), memo)
+  }
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::instructions::SolvencyAccounts;
+use crate::structs::Vault;
+use crate::pda::{SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SocializeLoss<'info> {
+  #[account(
+  mut,
+  has_one = guardian,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub guardian: Signer<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> SocializeLoss<'info> {
+  /// Marks the vault down to what it holds after the margin account was
+  /// liquidated. Withdraw requests are queued as shares, so the haircut
+  /// scales them down pro-rata with every other holder, and the USDC claims
+  /// of in-kind withdrawals take the same haircut through the claims index.
+  pub fn socialize_loss(&mut self) -> Result<()> {
+    let clock = Clock::get()?;
+    // the mark the shortfall is measured against has to be the current one
+    let nav = self.solvency.record_valuation(&mut self.vault, clock.slot)?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    let loss = self.vault.after_socialized_loss(
+      nav.total_assets(),
+      nav.usdc_claims_value,
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    )?;
+    msg!("socialized loss: {}, haircut bps: {}", loss, self.vault.last_haircut_bps);
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+}
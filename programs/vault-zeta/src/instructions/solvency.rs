@@ -0,0 +1,303 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+use crate::{cpi_calls as cpi, VaultError};
+use crate::constants::ACTIVE_EXPIRIES;
+use crate::cpi_calls::zeta::{
+  calculate_cross_margin_account_equity, calculate_margin_account_equity, calculate_open_orders_value,
+  calculate_series_unrealized_pnl, deserialize_greeks, get_native_oracle_price, get_series_exposure,
+  load_cross_margin_account, load_margin_account, load_zeta_group, ExpirySeriesStatus, Greeks, MarginAccount,
+};
+use crate::structs::Vault;
+use crate::pda::SharesMintPda;
+
+
+/// Components of the vault assets, in underlying.
+pub struct NavBreakdown {
+  pub idle_underlying: u64,
+  pub lending_value: u64,
+  pub zeta_margin_value: u64,
+  pub locked_in_orders: u64,
+  // unrealized pnl of the zeta positions at mark, part of `zeta_margin_value`
+  pub marked_pnl: i64,
+  // Solend debt, interest included
+  pub borrowed_value: u64,
+  // `MarginBasis::usdc_held` at the reserve oracle
+  pub usdc_held_value: u64,
+  // in-kind USDC claims, already netted out of `zeta_margin_value`
+  pub usdc_claims_value: u64,
+  pub share_supply: u64,
+}
+
+impl NavBreakdown {
+  pub fn total_assets(&self) -> u64 {
+    self.lending_value
+      .checked_add(self.idle_underlying).unwrap()
+      .checked_add(self.zeta_margin_value).unwrap()
+      .saturating_sub(self.borrowed_value)
+  }
+
+  /// Whether the vault holds nothing but the cTokens of its collateral vault
+  /// and the idle underlying: no margin equity or USDC, no obligation.
+  pub fn is_lending_only(&self, vault: &Vault) -> bool {
+    self.zeta_margin_value == 0
+      && self.borrowed_value == 0
+      && vault.obligation_collateral == 0
+  }
+}
+
+/// Read-only view of everything the vault holds, appended to state-mutating
+/// instructions so they can end with `assert_solvent`. The accounts may
+/// repeat accounts of the parent instruction, they are read after its CPIs.
+#[derive(Accounts)]
+pub struct SolvencyAccounts<'info> {
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub shares_mint: AccountInfo<'info>,
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub reserve: AccountInfo<'info>,
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub collateral_vault: AccountInfo<'info>,
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub underlying_vault: AccountInfo<'info>,
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub usdc_vault: AccountInfo<'info>,
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: checked against the vault in `assert_solvent`
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: checked against the zeta group in `margin_value`
+  pub greeks: AccountInfo<'info>,
+  /// CHECK: checked against the vault reserve oracle, unused while the
+  /// divergence check is disabled
+  pub reserve_oracle: AccountInfo<'info>,
+}
+
+impl<'info> SolvencyAccounts<'info> {
+  /// Recomputes the vault assets (Solend value + Zeta margin equity + idle
+  /// tokens - Solend debt with its interest as of the last obligation read)
+  /// and fails if they don't cover what the vault owes its shares, see
+  /// `Vault::assert_solvent`. The USDC legs of a non-USDC underlying are
+  /// priced with the reserve oracle. Lamports of the vault PDAs are never
+  /// counted, see `ManageRent`.
+  pub fn assert_solvent(&self, vault: &Account<'info, Vault>) -> Result<()> {
+    let (total_assets, _) = self.total_assets(vault)?;
+    vault.assert_solvent(total_assets, Clock::get()?.unix_timestamp)
+  }
+
+  /// `assert_solvent` that also caches the NAV breakdown on the vault at `slot`.
+  pub fn record_and_assert_solvent(&self, vault: &mut Account<'info, Vault>, slot: u64) -> Result<()> {
+    let nav = self.record_valuation(vault, slot)?;
+    vault.assert_solvent(nav.total_assets(), Clock::get()?.unix_timestamp)
+  }
+
+  /// Caches the NAV breakdown on the vault at `slot` without asserting it,
+  /// and marks the USDC margin leg the booked NAV carries, see `MarginBasis`.
+  pub fn record_valuation(&self, vault: &mut Account<'info, Vault>, slot: u64) -> Result<NavBreakdown> {
+    let nav = self.nav_breakdown(vault)?;
+    vault.margin_basis.mark(nav.usdc_held_value);
+    vault.record_valuation(
+      nav.idle_underlying,
+      nav.lending_value,
+      nav.zeta_margin_value,
+      nav.locked_in_orders,
+      nav.marked_pnl,
+      slot,
+    );
+    Ok(nav)
+  }
+
+  /// Assets the vault actually holds, in underlying, and the share supply.
+  pub fn total_assets(&self, vault: &Account<'info, Vault>) -> Result<(u64, u64)> {
+    let nav = self.nav_breakdown(vault)?;
+    Ok((nav.total_assets(), nav.share_supply))
+  }
+
+  pub fn nav_breakdown(&self, vault: &Account<'info, Vault>) -> Result<NavBreakdown> {
+    let shares_mint = SharesMintPda::create(&vault.key(), vault.mint_bump)?;
+    if self.shares_mint.key() != shares_mint
+      || self.reserve.key() != vault.reserve
+      || self.collateral_vault.key() != vault.collateral_vault
+      || self.underlying_vault.key() != vault.underlying_vault
+      || self.usdc_vault.key() != vault.usdc_vault
+      || self.margin_account.key() != vault.active_margin_account()
+      || self.zeta_group.key() != vault.zeta_group {
+      return err!(VaultError::InvalidSolvencyAccounts);
+    }
+
+    let shares_mint = Account::<Mint>::try_from(&self.shares_mint)?;
+    let reserve = Account::<cpi::solend::Reserve>::try_from(&self.reserve)?;
+    let collateral_vault = Account::<TokenAccount>::try_from(&self.collateral_vault)?;
+    let underlying_vault = Account::<TokenAccount>::try_from(&self.underlying_vault)?;
+    let usdc_vault = Account::<TokenAccount>::try_from(&self.usdc_vault)?;
+    self.check_reserve_price(vault, &reserve)?;
+
+    let collateral = collateral_vault.amount
+      .checked_add(vault.obligation_collateral).unwrap();
+    let solend_value = vault.for_underlying(collateral, &reserve).unwrap();
+    let (margin_value, open_orders_value, unrealized_pnl) = self.margin_breakdown(vault)?;
+    // USDC claims of in-kind withdrawals are owed out of it
+    let platform_value = usdc_vault.amount
+      .checked_add(margin_value).unwrap()
+      .checked_add(vault.drift_collateral).unwrap()
+      .saturating_sub(vault.usdc_claims);
+    let underlying_price = self.underlying_price(vault, &underlying_vault.mint)?;
+
+    Ok(NavBreakdown {
+      idle_underlying: underlying_vault.amount,
+      lending_value: solend_value,
+      zeta_margin_value: vault.platform_to_underlying(platform_value, underlying_price).unwrap(),
+      locked_in_orders: vault.platform_to_underlying(open_orders_value, underlying_price).unwrap(),
+      marked_pnl: pnl_to_underlying(vault, unrealized_pnl, underlying_price).unwrap(),
+      // the vault borrows USDC, marked like the margin
+      borrowed_value: vault.platform_to_underlying(vault.total_borrowed, underlying_price).unwrap(),
+      usdc_held_value: vault.platform_to_underlying(vault.margin_basis.usdc_held, underlying_price).unwrap(),
+      usdc_claims_value: vault.platform_to_underlying(vault.usdc_claims, underlying_price).unwrap(),
+      share_supply: shares_mint.supply,
+    })
+  }
+
+  /// Fails while zeta is halted, a series the vault is exposed to has
+  /// expired and waits for settlement, or the Solend reserve price diverges
+  /// from pyth. The share price is unreliable then.
+  pub fn check_not_under_maintenance(&self, vault: &Account<'info, Vault>) -> Result<()> {
+    if self.margin_account.key() != vault.active_margin_account()
+      || self.zeta_group.key() != vault.zeta_group
+      || self.reserve.key() != vault.reserve {
+      return err!(VaultError::InvalidSolvencyAccounts);
+    }
+    vault.check_not_in_progress()?;
+    let reserve = Account::<cpi::solend::Reserve>::try_from(&self.reserve)?;
+    self.check_reserve_price(vault, &reserve)?;
+    if self.margin_account.data_is_empty() {
+      return Ok(());
+    }
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    if zeta_group.halt_state.is_halted() {
+      return err!(VaultError::MarketUnderMaintenance);
+    }
+    let exposure = if vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      get_series_exposure(&margin_account.product_ledgers)
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      get_series_exposure(&margin_account.product_ledgers)
+    };
+    for (expiry_index, exposed) in exposure.iter().enumerate() {
+      if !exposed {
+        continue;
+      }
+      let status = zeta_group.expiry_series[expiry_index].status()?;
+      if status == ExpirySeriesStatus::Expired || status == ExpirySeriesStatus::ExpiredDirty {
+        msg!("Series {} status = {:?}", expiry_index, status);
+        return err!(VaultError::MarketUnderMaintenance);
+      }
+    }
+    Ok(())
+  }
+
+  // Solend values the cTokens with its own market price, pyth has to agree
+  fn check_reserve_price(&self, vault: &Vault, reserve: &cpi::solend::Reserve) -> Result<()> {
+    if vault.max_reserve_price_divergence_bps == 0 {
+      return Ok(());
+    }
+    if *self.reserve_oracle.owner != vault.reserve_oracle.price_account_owner() {
+      return err!(VaultError::InvalidSolvencyAccounts);
+    }
+    let oracle_price = get_native_oracle_price(&self.reserve_oracle, &vault.reserve_oracle)?;
+    vault.check_reserve_price(reserve, oracle_price)
+  }
+
+  /// Reserve oracle price the platform amounts convert to underlying at,
+  /// `None` for a USDC underlying, see `Vault::platform_to_underlying`.
+  pub fn platform_price(&self, vault: &Vault) -> Result<Option<u64>> {
+    if self.underlying_vault.key() != vault.underlying_vault {
+      return err!(VaultError::InvalidSolvencyAccounts);
+    }
+    let underlying_vault = Account::<TokenAccount>::try_from(&self.underlying_vault)?;
+    self.underlying_price(vault, &underlying_vault.mint)
+  }
+
+  // the USDC legs of a non-USDC underlying are marked with the reserve oracle
+  fn underlying_price(&self, vault: &Vault, underlying_mint: &Pubkey) -> Result<Option<u64>> {
+    if *underlying_mint == cpi::zeta::USDC {
+      return Ok(None);
+    }
+    if *self.reserve_oracle.owner != vault.reserve_oracle.price_account_owner() {
+      return err!(VaultError::InvalidSolvencyAccounts);
+    }
+    Ok(Some(get_native_oracle_price(&self.reserve_oracle, &vault.reserve_oracle)?))
+  }
+
+  // open positions count at mark, so placing or cancelling orders leaves NAV flat
+  pub fn margin_value(&self, vault: &Vault) -> Result<u64> {
+    Ok(self.margin_breakdown(vault)?.0)
+  }
+
+  // margin equity, the mark value of the resting orders and the unrealized
+  // pnl of the positions, in platform precision
+  fn margin_breakdown(&self, vault: &Vault) -> Result<(u64, u64, i64)> {
+    if self.margin_account.data_is_empty() {
+      return Ok((0, 0, 0));
+    }
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let greeks = deserialize_greeks(&zeta_group, &self.greeks)?;
+    let (equity, open_orders_value, unrealized_pnl) = if vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      (
+        calculate_cross_margin_account_equity(&margin_account, &greeks)?,
+        calculate_open_orders_value(&margin_account.product_ledgers, &greeks)?,
+        margin_account.get_unrealized_pnl(&greeks)?,
+      )
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      let (equity, unrealized_pnl) = at_booked_settlement(
+        vault,
+        &margin_account,
+        &greeks,
+        calculate_margin_account_equity(&margin_account, &greeks)?,
+        margin_account.get_unrealized_pnl(&greeks)?,
+      )?;
+      (
+        equity,
+        calculate_open_orders_value(&margin_account.product_ledgers, &greeks)?,
+        unrealized_pnl,
+      )
+    };
+    Ok((equity.max(0) as u64, open_orders_value, unrealized_pnl))
+  }
+}
+
+// series booked by `compute_settlement` stay in the margin account until
+// zeta settles them into the balance, they count at the booked pnl instead
+// of the mark, which is also out of the unrealized pnl the shares are priced
+// with on top of the booked NAV
+fn at_booked_settlement(
+  vault: &Vault,
+  margin_account: &MarginAccount,
+  greeks: &Greeks,
+  mut equity: i64,
+  mut unrealized_pnl: i64,
+) -> Result<(i64, i64)> {
+  let series_expiry = margin_account.series_expiry;
+  for expiry_index in 0..ACTIVE_EXPIRIES {
+    let booked_pnl = match vault.booked_series_pnl(expiry_index, series_expiry[expiry_index]) {
+      Some(booked_pnl) => booked_pnl,
+      None => continue,
+    };
+    let series_pnl = calculate_series_unrealized_pnl(margin_account, greeks, expiry_index)?;
+    equity = equity
+      .checked_sub(series_pnl)
+      .and_then(|equity| equity.checked_add(booked_pnl))
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    unrealized_pnl = unrealized_pnl
+      .checked_sub(series_pnl)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+  }
+  Ok((equity, unrealized_pnl))
+}
+
+/// Signed `Vault::platform_to_underlying`.
+pub fn pnl_to_underlying(vault: &Vault, pnl: i64, underlying_price: Option<u64>) -> Option<i64> {
+  let value = vault.platform_to_underlying(pnl.unsigned_abs(), underlying_price)?;
+  let value = i64::try_from(value).ok()?;
+  Some(if pnl < 0 { -value } else { value })
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, mint_to, MintTo, Token, TokenAccount, Transfer, transfer};
+use crate::{vault_seeds, VaultError};
+use crate::instructions::SolvencyAccounts;
+use crate::structs::{EpochReceipts, Vault};
+use crate::pda::{RECEIPTS_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SplitShares<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_escrow.mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = principal_mint
+  )]
+  pub user_principal: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = yield_mint
+  )]
+  pub user_yield: Box<Account<'info, TokenAccount>>,
+  pub user_account: Signer<'info>,
+  #[account(
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  #[account(
+  mut,
+  has_one = vault,
+  has_one = principal_mint,
+  has_one = yield_mint,
+  has_one = shares_escrow,
+  seeds = [RECEIPTS_SEED, vault.key().as_ref(), receipts.epoch.to_le_bytes().as_ref()],
+  bump = receipts.bump
+  )]
+  pub receipts: Box<Account<'info, EpochReceipts>>,
+  #[account(mut)]
+  pub principal_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub yield_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub shares_escrow: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> SplitShares<'info> {
+  pub fn split_shares(&mut self, shares_amount: u64) -> Result<()> {
+    if shares_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    if shares_amount > self.user_shares.amount {
+      return err!(VaultError::SharesOverflow);
+    }
+    if !self.vault.receipt_epoch_live
+      || self.vault.receipt_epoch != self.receipts.epoch
+      || self.receipts.is_settled() {
+      return err!(VaultError::ReceiptsEpochNotLive);
+    }
+    self.lock_shares(shares_amount)?;
+    self.mint(self.principal_mint.to_account_info(), self.user_principal.to_account_info(), shares_amount)?;
+    self.mint(self.yield_mint.to_account_info(), self.user_yield.to_account_info(), shares_amount)?;
+    self.receipts.after_split(shares_amount)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn lock_shares(&self, shares_amount: u64) -> Result<()> {
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.user_shares.to_account_info(),
+        to: self.shares_escrow.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      });
+    transfer(ctx, shares_amount)
+  }
+
+  fn mint(&self, mint: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+    let seeds = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      MintTo {
+        mint,
+        to,
+        authority: self.vault.to_account_info(),
+      }, seeds);
+    mint_to(ctx, amount)
+  }
+}
@@ -2,20 +2,22 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount};
 use crate::{executor_seeds, cpi_calls as cpi};
 use crate::cpi_calls::whirlpool::sqrt_price_from_tick_index;
+use crate::instructions::SolvencyAccounts;
 use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
 
 #[derive(Accounts)]
 pub struct Swap<'info> {
   #[account(
   mut,
   has_one = authority,
-  seeds = [b"vault", vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
@@ -43,17 +45,36 @@ pub struct Swap<'info> {
   pub oracle: AccountInfo<'info>,
   pub token_program: Program<'info, Token>,
   pub pool_program_id: Program<'info, cpi::whirlpool::OrcaWhirlpool>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> Swap<'info> {
-  pub fn swap_underlying_to_usdc(&self) -> Result<()> {
+  pub fn swap_underlying_to_usdc(&mut self) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    let (underlying_before, usdc_before) = (self.underlying_vault.amount, self.usdc_vault.amount);
     self.swap(self.underlying_vault.amount, 0, true)?;
-    Ok(())
+    self.underlying_vault.reload()?;
+    self.vault.after_underlying_spent(self.underlying_vault.amount);
+    self.usdc_vault.reload()?;
+    if self.vault.margin_basis.enabled {
+      let underlying_spent = underlying_before.saturating_sub(self.underlying_vault.amount);
+      let usdc_received = self.usdc_vault.amount.saturating_sub(usdc_before);
+      self.vault.margin_basis.after_usdc_bought(underlying_spent, usdc_received)?;
+    }
+    self.solvency.assert_solvent(&self.vault)
   }
 
-  pub fn swap_usdc_to_underlying(&self) -> Result<()> {
-    self.swap(self.usdc_vault.amount, 0, false)?;
-    Ok(())
+  pub fn swap_usdc_to_underlying(&mut self) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    let usdc_before = self.usdc_vault.amount;
+    // USDC owed to in-kind claims stays in the vault
+    self.swap(self.vault.free_usdc(self.usdc_vault.amount), 0, false)?;
+    self.usdc_vault.reload()?;
+    if self.vault.margin_basis.enabled {
+      let usdc_spent = usdc_before.saturating_sub(self.usdc_vault.amount);
+      self.vault.margin_basis.after_usdc_sold(usdc_spent)?;
+    }
+    self.solvency.assert_solvent(&self.vault)
   }
 
   fn is_a_to_b(&self) -> bool {
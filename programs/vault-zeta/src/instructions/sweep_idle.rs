@@ -0,0 +1,254 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{executor_seeds, cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  is_cross_margin_account_flat, is_margin_account_flat, load_cross_margin_account, load_margin_account,
+};
+use crate::instructions::{MarginSnapshot, SolvencyAccounts};
+use crate::lending::{LendingAdapter, SolendAdapter};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct SweepIdle<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = zeta_group,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), zeta_group.key().as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  // parked funds go through the usdc vault, so the reserve has to lend USDC
+  #[account(
+  mut,
+  address = vault.usdc_vault,
+  constraint = usdc_vault.mint == reserve.liquidity.mint_pubkey
+  )]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_liquidity_supply: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_collateral_mint: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: validated against the vault price feed
+  #[account(
+  address = vault.oracle.price_feed,
+  owner = cpi::zeta::pyth_client::pyth_program::ID
+  )]
+  pub oracle: AccountInfo<'info>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub zeta_vault: AccountInfo<'info>,
+  /// CHECK: cross-margin account when the vault uses that model
+  #[account(mut, address = vault.active_margin_account())]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK:
+  pub state: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub zeta_program: Program<'info, cpi::zeta::ZetaProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> SweepIdle<'info> {
+  /// Redeems all Solend collateral and parks it in the Zeta margin account.
+  /// Only allowed while the margin account holds no options.
+  pub fn sweep_to_margin(&mut self) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    self.vault.check_not_aborted(clock.unix_timestamp)?;
+    self.check_flat()?;
+    let received = self.redeem_collateral(self.collateral_vault.amount)?;
+    self.deposit_zeta(received)?;
+    self.vault.margin_basis.after_margin_deposit(received)?;
+    self.vault.after_park(received)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  /// Moves the whole margin balance, parked principal and yield, back to Solend.
+  pub fn sweep_to_solend(&mut self) -> Result<()> {
+    self.vault.record_keeper_activity(Clock::get()?.slot);
+    if self.vault.parked_margin == 0 {
+      return Ok(());
+    }
+    self.check_flat()?;
+    let balance = self.margin_balance()?;
+    let before = self.usdc_vault.amount;
+    self.withdraw_zeta(balance)?;
+    self.vault.margin_basis.after_margin_withdraw(balance)?;
+    self.usdc_vault.reload()?;
+    let received = self.usdc_vault.amount
+      .checked_sub(before).unwrap();
+    self.deposit_liquidity(received)?;
+    self.vault.after_unpark()?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn check_flat(&self) -> Result<()> {
+    let flat = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      is_cross_margin_account_flat(&margin_account)
+    } else {
+      let margin_account = load_margin_account(&self.margin_account)?;
+      is_margin_account_flat(&margin_account)
+    };
+    if !flat {
+      return err!(VaultError::MarginNotFlat);
+    }
+    Ok(())
+  }
+
+  fn margin_balance(&self) -> Result<u64> {
+    if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(&self.margin_account)?;
+      return Ok(margin_account.balance);
+    }
+    let margin_account = load_margin_account(&self.margin_account)?;
+    Ok(margin_account.balance)
+  }
+
+  fn withdraw_zeta(&self, amount_out: u64) -> Result<()> {
+    let before = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      cpi::zeta::zeta_client::withdraw_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::WithdrawV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::withdraw(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::Withdraw {
+          zeta_group: self.zeta_group.to_account_info(),
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+          oracle: self.oracle.to_account_info(),
+        },
+        amount_out,
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(&self.margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)
+  }
+
+  fn deposit_zeta(&self, amount_in: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      return cpi::zeta::zeta_client::deposit_v2(
+        self.zeta_program.to_account_info(),
+        cpi::zeta::DepositV2 {
+          margin_account: self.margin_account.to_account_info(),
+          vault: self.zeta_vault.to_account_info(),
+          user_token_account: self.usdc_vault.to_account_info(),
+          socialized_loss_account: self.socialized_loss_account.to_account_info(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: self.state.to_account_info(),
+          greeks: self.greeks.to_account_info(),
+        },
+        amount_in,
+        seeds
+      );
+    }
+    cpi::zeta::zeta_client::deposit(
+      self.zeta_program.to_account_info(),
+      cpi::zeta::Deposit {
+        zeta_group: self.zeta_group.to_account_info(),
+        margin_account: self.margin_account.to_account_info(),
+        vault: self.zeta_vault.to_account_info(),
+        user_token_account: self.usdc_vault.to_account_info(),
+        socialized_loss_account: self.socialized_loss_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+        token_program: self.token_program.clone(),
+        state: self.state.to_account_info(),
+        greeks: self.greeks.to_account_info(),
+      },
+      amount_in,
+      seeds
+    )?;
+    Ok(())
+  }
+
+  fn lending(&self) -> SolendAdapter<'_, 'info> {
+    SolendAdapter {
+      reserve: &self.reserve,
+      reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+      reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+      lending_market: self.lending_market.to_account_info(),
+      lending_market_authority: self.lending_market_authority.to_account_info(),
+      liquidity: *self.usdc_vault.clone(),
+      collateral: *self.collateral_vault.clone(),
+      authority: self.executor.to_account_info(),
+      token_program: self.token_program.to_account_info(),
+      lending_program: self.lending_program.to_account_info(),
+    }
+  }
+
+  // funds the reserve can't take stay idle in the usdc vault
+  fn deposit_liquidity(&self, amount_in: u64) -> Result<()> {
+    if !self.vault.check_reserve_deposit(&self.reserve, amount_in, Clock::get()?.slot)? {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    self.lending().deposit(amount_in, signer)
+  }
+
+  fn redeem_collateral(&mut self, amount_in: u64) -> Result<u64> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let liquidity_before = self.usdc_vault.amount;
+    self.lending().redeem(amount_in, signer)?;
+    self.usdc_vault.reload()?;
+    let liquidity_after = self.usdc_vault.amount
+      .checked_sub(liquidity_before).unwrap();
+    Ok(liquidity_after)
+  }
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{
+  is_cross_margin_account_flat, is_margin_account_flat, load_cross_margin_account, load_margin_account,
+};
+use crate::structs::{Vault, VaultConfigParams};
+use crate::pda::{SHARES_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct UpdateVaultConfig<'info> {
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  // the governance treasury once `governance` is set, see `check_config_authority`
+  pub authority: Signer<'info>,
+  #[account(
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+}
+
+impl<'info> UpdateVaultConfig<'info> {
+  /// Switching `cross_margin` takes the margin account the vault trades from
+  /// until then as the first remaining account. The management fee accrued
+  /// so far is booked at the old rate before a new one applies.
+  pub fn update_vault_config(
+    &mut self,
+    params: VaultConfigParams,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    self.vault.check_config_authority(&self.authority.key())?;
+    if params.cross_margin.map_or(false, |cross_margin| cross_margin != self.vault.cross_margin) {
+      self.check_margin_account_empty(remaining_accounts)?;
+    }
+    let clock = Clock::get()?;
+    if params.management_fee_bps.is_some() {
+      self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    }
+    self.vault.update_config(&params)?;
+    // the authority is still around, restart the dead-man switch
+    if self.authority.key() == self.vault.authority {
+      self.vault.record_keeper_activity(clock.slot);
+    }
+    Ok(())
+  }
+
+  // the instructions only see the active margin account, whatever is left in
+  // the other one would drop out of the NAV
+  fn check_margin_account_empty(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let active_margin_account = self.vault.active_margin_account();
+    if active_margin_account == Pubkey::default() {
+      return Ok(());
+    }
+    let margin_account = match remaining_accounts.first() {
+      Some(margin_account) if margin_account.key() == active_margin_account
+        && *margin_account.owner == cpi::zeta::zeta_program::ID => margin_account,
+      _ => return err!(VaultError::MarginNotEmpty),
+    };
+    if margin_account.data_is_empty() {
+      return Ok(());
+    }
+    let (flat, balance) = if self.vault.cross_margin {
+      let margin_account = load_cross_margin_account(margin_account)?;
+      (is_cross_margin_account_flat(&margin_account), margin_account.balance)
+    } else {
+      let margin_account = load_margin_account(margin_account)?;
+      (is_margin_account_flat(&margin_account), margin_account.balance)
+    };
+    if !flat {
+      return err!(VaultError::MarginNotFlat);
+    }
+    if balance > 0 {
+      return err!(VaultError::MarginNotEmpty);
+    }
+    Ok(())
+  }
+}
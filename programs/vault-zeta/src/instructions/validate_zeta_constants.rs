@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_spl::mint::USDC;
+use anchor_spl::token::Mint;
+use crate::{cpi_calls as cpi, VaultError};
+use crate::cpi_calls::zeta::{load_zeta_group, Kind};
+use crate::constants::{
+  ACTIVE_EXPIRIES, NATIVE_PRECISION_DENOMINATOR, NUM_STRIKES, PLATFORM_PRECISION, SERIES_FUTURE_INDEX,
+};
+use crate::instructions::refund_keeper_if_passed;
+use crate::structs::Vault;
+
+
+#[derive(Accounts)]
+pub struct ValidateZetaConstants<'info> {
+  #[account(has_one = zeta_group)]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(owner = cpi::zeta::zeta_program::ID)]
+  pub zeta_group: AccountInfo<'info>,
+  // zeta margin and prices are amounts of this mint
+  #[account(address = USDC)]
+  pub quote_mint: Box<Account<'info, Mint>>,
+  #[account(mut)]
+  pub keeper: Signer<'info>,
+}
+
+impl<'info> ValidateZetaConstants<'info> {
+  /// Permissionless check that the live zeta group still matches the
+  /// constants in `crate::constants`. A failure means a Zeta parameter change
+  /// the vault math doesn't account for, keepers should stop trading.
+  /// Refunded from the keeper pool when it is passed.
+  pub fn validate_zeta_constants(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    if u32::from(self.quote_mint.decimals) != PLATFORM_PRECISION {
+      msg!("quote decimals: {}, platform precision: {}", self.quote_mint.decimals, PLATFORM_PRECISION);
+      return err!(VaultError::ZetaConstantsMismatch);
+    }
+
+    let zeta_group = load_zeta_group(&self.zeta_group)?;
+    let margin_parameters = zeta_group.margin_parameters;
+    let percentages = [
+      margin_parameters.future_margin_initial,
+      margin_parameters.future_margin_maintenance,
+      margin_parameters.option_mark_percentage_long_initial,
+      margin_parameters.option_spot_percentage_long_initial,
+      margin_parameters.option_spot_percentage_short_initial,
+      margin_parameters.option_dynamic_percentage_short_initial,
+      margin_parameters.option_mark_percentage_long_maintenance,
+      margin_parameters.option_spot_percentage_long_maintenance,
+      margin_parameters.option_spot_percentage_short_maintenance,
+      margin_parameters.option_dynamic_percentage_short_maintenance,
+      margin_parameters.option_short_put_cap_percentage,
+    ];
+    if let Some(percentage) = percentages.iter().find(|&&p| u128::from(p) > NATIVE_PRECISION_DENOMINATOR) {
+      msg!("margin percentage {} above {}", percentage, NATIVE_PRECISION_DENOMINATOR);
+      return err!(VaultError::ZetaConstantsMismatch);
+    }
+
+    // products of a series not listed yet are uninitialized
+    for expiry_index in 0..ACTIVE_EXPIRIES {
+      for (index, product) in zeta_group.get_products_slice(expiry_index).iter().enumerate() {
+        let expected = if index == SERIES_FUTURE_INDEX {
+          Kind::Future
+        } else if index < NUM_STRIKES {
+          Kind::Call
+        } else {
+          Kind::Put
+        };
+        if product.kind != Kind::Uninitialized && product.kind != expected {
+          msg!("expiry {} product {}: unexpected kind", expiry_index, index);
+          return err!(VaultError::ZetaConstantsMismatch);
+        }
+      }
+    }
+    refund_keeper_if_passed(&self.vault.key(), remaining_accounts, &self.keeper.to_account_info(), Clock::get()?.slot)
+  }
+}
@@ -1,9 +1,20 @@
 use std::cmp::min;
 use anchor_lang::prelude::*;
 use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer, transfer};
-use crate::{executor_seeds, vault_seeds, cpi_calls as cpi, VaultError};
-use crate::structs::Vault;
+use crate::{executor_seeds, vault_seeds, cpi_calls as cpi, ratio_ceil, VaultError};
+use crate::cpi_calls::zeta::{
+  deserialize_greeks, load_margin_account_state, load_zeta_group,
+};
+use crate::instructions::{MarginSnapshot, SolvencyAccounts};
+use crate::structs::{UserPosition, UserState, Vault};
+use crate::pda::{EXECUTOR_SEED, SHARES_SEED, USER_POSITION_SEED, USER_SEED, VAULT_SEED};
+use crate::client_helpers;
 
+// Zeta accounts passed as remaining accounts, ordered as in
+// `client_helpers::MARGIN_WITHDRAW_ACCOUNTS`
+pub const MARGIN_WITHDRAW_ACCOUNTS: usize = client_helpers::MARGIN_WITHDRAW_ACCOUNTS.len();
+// equity a withdrawal paid from margin has to leave, in bps of the maintenance margin
+pub const MARGIN_WITHDRAW_BUFFER_BPS: u64 = 15_000;
 
 #[derive(Accounts)]
 pub struct WithdrawFromVault<'info> {
@@ -19,25 +30,42 @@ pub struct WithdrawFromVault<'info> {
   token::mint = reserve.liquidity.mint_pubkey
   )]
   pub user_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
   pub user_account: Signer<'info>,
   #[account(
+  init_if_needed,
+  space = 8 + UserState::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
   mut,
   has_one = reserve,
-  seeds = [b"vault", reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
   bump = vault.bump
   )]
   pub vault: Box<Account<'info, Vault>>,
   /// CHECK:
   #[account(
-  seeds = [b"executor", vault.key().as_ref()],
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
   bump = vault.executor_bump
   )]
   pub executor: AccountInfo<'info>,
   #[account(
   mut,
-  mint::decimals = 9,
+  mint::decimals = vault.share_mint_decimals(),
   mint::authority = vault.key(),
-  seeds = [b"shares", vault.key().as_ref()],
+  seeds = [SHARES_SEED, vault.key().as_ref()],
   bump = vault.mint_bump
   )]
   pub shares_mint: Box<Account<'info, Mint>>,
@@ -59,10 +87,21 @@ pub struct WithdrawFromVault<'info> {
   pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
   pub token_program: Program<'info, Token>,
   pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub system_program: Program<'info, System>,
+  pub solvency: SolvencyAccounts<'info>,
 }
 
 impl<'info> WithdrawFromVault<'info> {
-  pub fn withdraw(&mut self, max_shares_amount: u64) -> Result<()> {
+  /// Pays out of Solend, and out of the free Zeta margin for the part the
+  /// reserve liquidity doesn't cover when the Zeta accounts are passed as
+  /// remaining accounts.
+  pub fn withdraw(
+    &mut self,
+    max_shares_amount: u64,
+    user_bump: u8,
+    position_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
     if max_shares_amount > self.user_shares.amount {
       return err!(VaultError::SharesOverflow);
     }
@@ -75,17 +114,26 @@ impl<'info> WithdrawFromVault<'info> {
     //   return err!(VaultError::UseEmergencyWithdraw)
     // }
     let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.vault.is_withdraw_open(clock.unix_timestamp) {
+      return err!(VaultError::WithdrawWindowClosed);
+    }
+    if !self.user_state.is_initialized() {
+      self.user_state.initialize(user_bump, self.vault.key(), self.user_account.key())?;
+    }
+    self.vault.check_withdraw_unlocked(&self.user_state, clock.slot)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
     let total_underlying = self.vault.free_funds(clock.unix_timestamp)
       .unwrap();
-    let underlying_value = self.vault.share_value(
+    let underlying_value = self.vault.preview_withdraw(
       max_shares_amount,
-      total_underlying,
       self.shares_mint.supply,
+      clock.unix_timestamp,
     ).unwrap();
     let shares_amount = self.vault.shares_for_amount(
       underlying_value,
       total_underlying,
-      self.shares_mint.supply,
+      self.vault.share_supply(self.shares_mint.supply, clock.unix_timestamp).unwrap(),
     ).unwrap();
     let collateral_amount = self.vault.for_collateral(
       underlying_value,
@@ -98,12 +146,93 @@ impl<'info> WithdrawFromVault<'info> {
     msg!("collateral_amount: {}", collateral_amount);
     msg!("collateral_balance: {}", self.collateral_vault.amount);
     self.burn_shares(shares_amount)?;
-    let actual = self.redeem_collateral(collateral_amount)?;
+    let actual = self.redeem_or_withdraw_margin(collateral_amount, underlying_value, remaining_accounts)?;
     msg!("actual: {}", actual);
     msg!("expected: {}", underlying_value);
     let withdraw_value = min(actual, underlying_value);
     self.transfer_underlying(withdraw_value)?;
-    self.vault.after_withdraw(withdraw_value)
+    self.vault.after_withdraw(withdraw_value)?;
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    self.user_position.after_withdraw(withdraw_value, shares_amount)?;
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  /// Burns the user's whole share balance. Rounding dust stays with the other
+  /// holders, except for the last holder who redeems all collateral and takes
+  /// everything left in the underlying vault, so the vault drains to 0. That
+  /// needs the zeta margin, the USDC vault and the obligation flat first,
+  /// nothing is left behind for the next depositor.
+  pub fn withdraw_all(
+    &mut self,
+    user_bump: u8,
+    position_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+    let shares_amount = self.user_shares.amount;
+    if shares_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.vault.is_withdraw_open(clock.unix_timestamp) {
+      return err!(VaultError::WithdrawWindowClosed);
+    }
+    if !self.user_state.is_initialized() {
+      self.user_state.initialize(user_bump, self.vault.key(), self.user_account.key())?;
+    }
+    self.vault.check_withdraw_unlocked(&self.user_state, clock.slot)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
+
+    // unminted fee shares keep the vault from draining
+    let last_holder = shares_amount == self.vault.share_supply(self.shares_mint.supply, clock.unix_timestamp).unwrap();
+    msg!("shares_amount: {}", shares_amount);
+    msg!("last_holder: {}", last_holder);
+    let withdraw_value = if last_holder {
+      if !self.solvency.nav_breakdown(&self.vault)?.is_lending_only(&self.vault) {
+        return err!(VaultError::VaultNotDrainable);
+      }
+      self.burn_shares(shares_amount)?;
+      self.redeem_collateral(self.collateral_vault.amount)?;
+      self.underlying_vault.amount
+    } else {
+      let underlying_value = self.vault.preview_withdraw(
+        shares_amount,
+        self.shares_mint.supply,
+        clock.unix_timestamp,
+      ).unwrap();
+      let collateral_amount = self.vault.for_collateral(
+        underlying_value,
+        &self.reserve,
+      ).unwrap();
+      self.burn_shares(shares_amount)?;
+      let actual = self.redeem_or_withdraw_margin(collateral_amount, underlying_value, remaining_accounts)?;
+      min(actual, underlying_value)
+    };
+    msg!("withdraw_value: {}", withdraw_value);
+    self.transfer_underlying(withdraw_value)?;
+    if last_holder {
+      self.vault.after_withdraw_all(withdraw_value)?;
+    } else {
+      self.vault.after_withdraw(withdraw_value)?;
+    }
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    self.user_position.after_withdraw(withdraw_value, shares_amount)?;
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)
   }
 
   fn burn_shares(&self, shares_amount: u64) -> Result<()> {
@@ -146,6 +275,127 @@ impl<'info> WithdrawFromVault<'info> {
     Ok(liquidity_after)
   }
 
+  // the reserve pays what its liquidity covers and the free margin the
+  // rest, the Zeta accounts are only needed when the reserve runs short
+  fn redeem_or_withdraw_margin(
+    &mut self,
+    collateral_amount: u64,
+    underlying_value: u64,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<u64> {
+    let available_amount = self.reserve.liquidity.available_amount;
+    if available_amount >= underlying_value || remaining_accounts.is_empty() {
+      return self.redeem_collateral(collateral_amount);
+    }
+    let redeemed = if available_amount > 0 {
+      let collateral_amount = self.vault.for_collateral(
+        available_amount,
+        &self.reserve,
+      ).unwrap();
+      self.redeem_collateral(collateral_amount)?
+    } else {
+      0
+    };
+    msg!("redeemed: {}, from margin: {}", redeemed, underlying_value.saturating_sub(redeemed));
+    let withdrawn = self.withdraw_margin(underlying_value.saturating_sub(redeemed), remaining_accounts)?;
+    Ok(redeemed.checked_add(withdrawn).unwrap())
+  }
+
+  /// Withdraws `underlying_value` from the Zeta margin account straight into
+  /// the underlying vault, USDC vaults only. The equity left has to stay
+  /// `MARGIN_WITHDRAW_BUFFER_BPS` of the maintenance margin, a withdrawal
+  /// that doesn't fit fails instead of paying out part of the shares. The
+  /// margin account is the cross-margin one when the vault uses that model.
+  fn withdraw_margin(&mut self, underlying_value: u64, remaining_accounts: &[AccountInfo<'info>]) -> Result<u64> {
+    if remaining_accounts.len() != MARGIN_WITHDRAW_ACCOUNTS
+      || remaining_accounts[0].key() != self.vault.zeta_group
+      || remaining_accounts[1].key() != self.vault.active_margin_account()
+      || remaining_accounts[6].key() != self.vault.oracle.price_feed
+      || remaining_accounts[7].key() != cpi::zeta::zeta_program::ID
+      || *remaining_accounts[8].owner != self.vault.oracle.price_account_owner()
+      || self.underlying_vault.mint != cpi::zeta::USDC {
+      return err!(VaultError::InvalidMarginWithdrawAccounts);
+    }
+    let zeta_group = &remaining_accounts[0];
+    let margin_account = &remaining_accounts[1];
+    let greeks = &remaining_accounts[5];
+    let oracle = &remaining_accounts[6];
+    let price_update = &remaining_accounts[8];
+
+    let amount_out = self.vault.decimals
+      .to_platform(underlying_value).unwrap();
+    let withdrawable = {
+      let zeta_group = load_zeta_group(zeta_group)?;
+      let greeks = deserialize_greeks(&zeta_group, greeks)?;
+      let state = load_margin_account_state(
+        &zeta_group,
+        &self.vault.margin_parameters(&zeta_group),
+        margin_account,
+        self.vault.cross_margin,
+        &greeks,
+        price_update,
+        &self.vault.oracle,
+      )?;
+      let kept = ratio_ceil!(state.maintenance_margin, MARGIN_WITHDRAW_BUFFER_BPS, 10_000_u64)
+        .unwrap();
+      (state.account_equity.max(0) as u64)
+        .saturating_sub(kept)
+        .min(state.balance)
+    };
+    msg!("margin amount_out: {}, withdrawable: {}", amount_out, withdrawable);
+    if amount_out > withdrawable {
+      return err!(VaultError::InsufficientFreeMargin);
+    }
+
+    let before = MarginSnapshot::load(margin_account, self.vault.cross_margin, None)?;
+    let seeds = executor_seeds!(self.vault);
+    if self.vault.cross_margin {
+      cpi::zeta::zeta_client::withdraw_v2(
+        remaining_accounts[7].clone(),
+        cpi::zeta::WithdrawV2 {
+          margin_account: margin_account.clone(),
+          vault: remaining_accounts[2].clone(),
+          user_token_account: self.underlying_vault.to_account_info(),
+          socialized_loss_account: remaining_accounts[3].clone(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: remaining_accounts[4].clone(),
+          greeks: greeks.clone(),
+          oracle: oracle.clone(),
+        },
+        amount_out,
+        seeds
+      )?;
+    } else {
+      cpi::zeta::zeta_client::withdraw(
+        remaining_accounts[7].clone(),
+        cpi::zeta::Withdraw {
+          zeta_group: zeta_group.clone(),
+          margin_account: margin_account.clone(),
+          vault: remaining_accounts[2].clone(),
+          user_token_account: self.underlying_vault.to_account_info(),
+          socialized_loss_account: remaining_accounts[3].clone(),
+          authority: self.executor.to_account_info(),
+          token_program: self.token_program.clone(),
+          state: remaining_accounts[4].clone(),
+          greeks: greeks.clone(),
+          oracle: oracle.clone(),
+        },
+        amount_out,
+        seeds
+      )?;
+    }
+    let after = MarginSnapshot::load(margin_account, self.vault.cross_margin, None)?;
+    before.check_withdraw(&after, amount_out)?;
+    self.vault.margin_basis.after_margin_withdraw(amount_out)?;
+
+    let liquidity_before = self.underlying_vault.amount;
+    self.underlying_vault.reload()?;
+    let liquidity_after = self.underlying_vault.amount
+      .checked_sub(liquidity_before).unwrap();
+    Ok(liquidity_after)
+  }
+
   fn transfer_underlying(&mut self, amount: u64) -> Result<()> {
     let seeds = executor_seeds!(self.vault);
     let signer: &[&[&[u8]]] = &[&seeds[..]];
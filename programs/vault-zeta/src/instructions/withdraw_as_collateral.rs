@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{executor_seeds, vault_seeds, cpi_calls as cpi, VaultError};
+use crate::instructions::SolvencyAccounts;
+use crate::structs::{UserPosition, UserState, Vault};
+use crate::pda::{EXECUTOR_SEED, SHARES_SEED, USER_POSITION_SEED, USER_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct WithdrawAsCollateral<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = reserve.collateral.mint_pubkey
+  )]
+  pub user_collateral_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub user_account: Signer<'info>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserState::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault.key(),
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  // refreshed in the same slot, it prices the collateral
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> WithdrawAsCollateral<'info> {
+  /// Same pricing as `withdraw`, but pays the share value out in Solend
+  /// cTokens from the collateral vault instead of redeeming them.
+  pub fn withdraw_as_collateral(&mut self, shares_amount: u64, user_bump: u8, position_bump: u8) -> Result<()> {
+    if shares_amount > self.user_shares.amount {
+      return err!(VaultError::SharesOverflow);
+    }
+    if shares_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.vault.is_withdraw_open(clock.unix_timestamp) {
+      return err!(VaultError::WithdrawWindowClosed);
+    }
+    if !self.user_state.is_initialized() {
+      self.user_state.initialize(user_bump, self.vault.key(), self.user_account.key())?;
+    }
+    self.vault.check_withdraw_unlocked(&self.user_state, clock.slot)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
+    if self.reserve.last_update.is_stale(clock.slot)? {
+      return err!(VaultError::ReservePaused);
+    }
+
+    let underlying_value = self.vault.preview_withdraw(
+      shares_amount,
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    let collateral_amount = self.vault.for_collateral(
+      underlying_value,
+      &self.reserve,
+    ).unwrap();
+    msg!("shares_amount: {}", shares_amount);
+    msg!("underlying_value: {}", underlying_value);
+    msg!("collateral_amount: {}", collateral_amount);
+    msg!("collateral_balance: {}", self.collateral_vault.amount);
+    if collateral_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    self.burn_shares(shares_amount)?;
+    self.transfer_collateral(collateral_amount)?;
+    self.vault.after_withdraw(underlying_value)?;
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    self.user_position.after_withdraw(underlying_value, shares_amount)?;
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn burn_shares(&self, shares_amount: u64) -> Result<()> {
+    let signer = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&signer[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Burn {
+        mint: self.shares_mint.to_account_info(),
+        from: self.user_shares.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      },
+      seeds,
+    );
+    burn(ctx, shares_amount)
+  }
+
+  fn transfer_collateral(&self, amount: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.collateral_vault.to_account_info(),
+        to: self.user_collateral_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer,
+    );
+    transfer(cpi, amount)
+  }
+}
@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{executor_seeds, ratio_floor, vault_seeds, cpi_calls as cpi, VaultError};
+use crate::instructions::SolvencyAccounts;
+use crate::structs::{Feature, FeatureFlags, UserPosition, UserState, Vault};
+use crate::pda::{EXECUTOR_SEED, FEATURE_FLAGS_SEED, SHARES_SEED, USER_POSITION_SEED, USER_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct WithdrawInKind<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = reserve.collateral.mint_pubkey
+  )]
+  pub user_collateral_account: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = reserve.liquidity.mint_pubkey
+  )]
+  pub user_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_state.beneficial_owner(),
+  token::mint = cpi::zeta::USDC
+  )]
+  pub user_usdc_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub user_account: Signer<'info>,
+  #[account(
+  mut,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump = user_state.bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault.key(),
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  #[account(seeds = [FEATURE_FLAGS_SEED], bump = feature_flags.bump)]
+  pub feature_flags: Box<Account<'info, FeatureFlags>>,
+  pub token_program: Program<'info, Token>,
+  pub system_program: Program<'info, System>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> WithdrawInKind<'info> {
+  /// Pays `shares_amount` out as the pro-rata slice of what the vault holds:
+  /// cTokens, idle underlying and USDC as they are, plus a USDC claim on the
+  /// margin account equity paid by `claim_usdc` once the authority redeemed
+  /// it. Nothing is sold, so it works while the markets can't absorb exits.
+  /// No exit fee is charged, shares minted under one paid it when
+  /// `release_deposit_shares` moved them out of their deposit receipt.
+  pub fn withdraw_in_kind(&mut self, shares_amount: u64, position_bump: u8) -> Result<()> {
+    self.feature_flags.check_enabled(Feature::InKindRedemption)?;
+    if !self.vault.in_kind_redemption || self.vault.total_borrowed > 0 {
+      return err!(VaultError::InKindRedemptionUnavailable);
+    }
+    if shares_amount > self.user_shares.amount {
+      return err!(VaultError::SharesOverflow);
+    }
+    if shares_amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.vault.check_withdraw_unlocked(&self.user_state, clock.slot)?;
+    // the permissionless `refresh_valuation` keeps it fresh, no harvest needed
+    self.vault.check_valuation_fresh(clock.slot)?;
+
+    let share_supply = self.vault.share_supply(self.shares_mint.supply, clock.unix_timestamp)
+      .unwrap();
+    let underlying_value = self.vault.preview_withdraw(
+      shares_amount,
+      self.shares_mint.supply,
+      clock.unix_timestamp,
+    ).unwrap();
+    // earlier claims are owed out of the USDC vault first, then the margin account
+    let free_usdc = self.vault.free_usdc(self.usdc_vault.amount);
+    let margin_equity = self.solvency.margin_value(&self.vault)?
+      .saturating_sub(self.vault.usdc_claims.saturating_sub(self.usdc_vault.amount));
+    let collateral_amount = ratio_floor!(self.collateral_vault.amount, shares_amount, share_supply).unwrap();
+    let underlying_amount = ratio_floor!(self.underlying_vault.amount, shares_amount, share_supply).unwrap();
+    let usdc_amount = ratio_floor!(free_usdc, shares_amount, share_supply).unwrap();
+    let usdc_claim = ratio_floor!(margin_equity, shares_amount, share_supply).unwrap();
+    msg!("shares_amount: {}", shares_amount);
+    msg!("collateral: {}, underlying: {}, usdc: {}, usdc_claim: {}",
+      collateral_amount, underlying_amount, usdc_amount, usdc_claim);
+
+    self.burn_shares(shares_amount)?;
+    self.transfer_out(&self.collateral_vault, &self.user_collateral_account, collateral_amount)?;
+    self.transfer_out(&self.underlying_vault, &self.user_token_account, underlying_amount)?;
+    self.underlying_vault.reload()?;
+    self.vault.after_underlying_spent(self.underlying_vault.amount);
+    self.transfer_out(&self.usdc_vault, &self.user_usdc_account, usdc_amount)?;
+    self.user_state.apply_claims_index(self.vault.current_claims_index())?;
+    self.user_state.after_usdc_claim(usdc_claim)?;
+    self.vault.after_usdc_claim(usdc_claim)?;
+    self.vault.after_withdraw(underlying_value)?;
+    if !self.user_position.is_initialized() {
+      self.user_position.initialize(
+        position_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    self.user_position.after_withdraw(underlying_value, shares_amount)?;
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn burn_shares(&self, shares_amount: u64) -> Result<()> {
+    let signer = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&signer[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Burn {
+        mint: self.shares_mint.to_account_info(),
+        from: self.user_shares.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      },
+      seeds,
+    );
+    burn(ctx, shares_amount)
+  }
+
+  fn transfer_out(
+    &self,
+    from: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    amount: u64,
+  ) -> Result<()> {
+    if amount == 0 {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer,
+    );
+    transfer(cpi, amount)
+  }
+}
+
+
+#[derive(Accounts)]
+pub struct ClaimUsdc<'info> {
+  #[account(
+  mut,
+  token::authority = user_state.beneficial_owner(),
+  token::mint = cpi::zeta::USDC
+  )]
+  pub user_usdc_account: Box<Account<'info, TokenAccount>>,
+  pub user_account: Signer<'info>,
+  #[account(
+  mut,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump = user_state.bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  mut,
+  seeds = [VAULT_SEED, vault.reserve.as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.usdc_vault)]
+  pub usdc_vault: Box<Account<'info, TokenAccount>>,
+  pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimUsdc<'info> {
+  /// Pays the user's in-kind USDC claim to the beneficial owner, as far as
+  /// the USDC ring-fenced for the claims covers it.
+  pub fn claim_usdc(&mut self) -> Result<()> {
+    self.user_state.apply_claims_index(self.vault.current_claims_index())?;
+    let amount = self.user_state.usdc_claim
+      .min(self.vault.claimable_usdc(self.usdc_vault.amount));
+    if amount == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.usdc_vault.to_account_info(),
+        to: self.user_usdc_account.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer,
+    );
+    transfer(cpi, amount)?;
+    self.user_state.after_usdc_claim_paid(amount)?;
+    self.vault.after_usdc_claim_paid(amount)
+  }
+}
@@ -0,0 +1,308 @@
+use std::cmp::min;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{burn, Burn, Mint, Token, TokenAccount, Transfer, transfer};
+use crate::{executor_seeds, vault_seeds, cpi_calls as cpi, VaultError};
+use crate::instructions::{ProtocolFee, SolvencyAccounts};
+use crate::structs::{UserPosition, UserState, Vault, WithdrawRequest};
+use crate::pda::{
+  EXECUTOR_SEED, PROTOCOL_CONFIG_SEED, SHARES_SEED, USER_POSITION_SEED, USER_SEED, VAULT_SEED,
+  WITHDRAW_QUEUE_SEED, WITHDRAW_REQUEST_SEED,
+};
+
+
+#[derive(Accounts)]
+pub struct WithdrawInstant<'info> {
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = shares_mint
+  )]
+  pub user_shares: Box<Account<'info, TokenAccount>>,
+  #[account(
+  mut,
+  token::authority = user_account,
+  token::mint = reserve.liquidity.mint_pubkey
+  )]
+  pub user_token_account: Box<Account<'info, TokenAccount>>,
+  #[account(mut)]
+  pub user_account: Signer<'info>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserState::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_state: Box<Account<'info, UserState>>,
+  #[account(
+  init_if_needed,
+  space = 8 + UserPosition::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [USER_POSITION_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub user_position: Box<Account<'info, UserPosition>>,
+  #[account(
+  init_if_needed,
+  space = 8 + WithdrawRequest::MAXIMUM_SIZE,
+  payer = user_account,
+  seeds = [WITHDRAW_REQUEST_SEED, vault.key().as_ref(), user_account.key().as_ref()],
+  bump
+  )]
+  pub withdraw_request: Box<Account<'info, WithdrawRequest>>,
+  #[account(
+  mut,
+  has_one = reserve,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), vault.authority.as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(
+  mut,
+  mint::decimals = vault.share_mint_decimals(),
+  mint::authority = vault.key(),
+  seeds = [SHARES_SEED, vault.key().as_ref()],
+  bump = vault.mint_bump
+  )]
+  pub shares_mint: Box<Account<'info, Mint>>,
+  // shares of all queued withdraw requests
+  #[account(
+  init_if_needed,
+  payer = user_account,
+  token::mint = shares_mint,
+  token::authority = executor,
+  seeds = [WITHDRAW_QUEUE_SEED, vault.key().as_ref()],
+  bump
+  )]
+  pub withdraw_queue: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  #[account(mut, address = vault.underlying_vault)]
+  pub underlying_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_liquidity_supply: AccountInfo<'info>,
+  /// CHECK:
+  #[account(mut)]
+  pub reserve_collateral_mint: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  pub lending_market_authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: the protocol config, empty until `init_protocol_config`
+  #[account(seeds = [PROTOCOL_CONFIG_SEED], bump)]
+  pub protocol_config: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub system_program: Program<'info, System>,
+  pub rent: Sysvar<'info, Rent>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> WithdrawInstant<'info> {
+  /// Withdraws `shares_amount` plus any previously queued shares.
+  /// The part covered by the idle cTokens is paid out now, the rest is queued.
+  /// The protocol part of the instant exit fee goes to the treasury
+  /// underlying account passed with the protocol config, see `ProtocolFee`.
+  pub fn withdraw_instant(
+    &mut self,
+    shares_amount: u64,
+    request_bump: u8,
+    user_bump: u8,
+    position_bump: u8,
+    remaining_accounts: &[AccountInfo<'info>],
+  ) -> Result<()> {
+    let protocol_fee = ProtocolFee::load(&self.underlying_vault.mint, &self.protocol_config, remaining_accounts)?;
+    if shares_amount > self.user_shares.amount {
+      return err!(VaultError::SharesOverflow);
+    }
+    if !self.withdraw_request.is_initialized() {
+      self.withdraw_request.initialize(
+        request_bump,
+        self.vault.key(),
+        self.user_account.key(),
+      )?;
+    }
+    let queued_shares = self.withdraw_request.take();
+    let total_shares = shares_amount
+      .checked_add(queued_shares).unwrap();
+    if total_shares == 0 {
+      return err!(VaultError::ZeroWithdraw);
+    }
+
+    let clock = Clock::get()?;
+    self.vault.accrue_fees(self.shares_mint.supply, clock.unix_timestamp)?;
+    if !self.vault.is_withdraw_open(clock.unix_timestamp) {
+      return err!(VaultError::WithdrawWindowClosed);
+    }
+    if !self.user_state.is_initialized() {
+      self.user_state.initialize(user_bump, self.vault.key(), self.user_account.key())?;
+    }
+    self.vault.check_withdraw_unlocked(&self.user_state, clock.slot)?;
+    self.vault.check_valuation_fresh(clock.slot)?;
+    self.solvency.check_not_under_maintenance(&self.vault)?;
+    self.release_queued(queued_shares)?;
+
+    let total_underlying = self.vault.free_funds(clock.unix_timestamp)
+      .unwrap();
+    let share_supply = self.vault.share_supply(self.shares_mint.supply, clock.unix_timestamp)
+      .unwrap();
+    let underlying_value = self.vault.share_value(
+      total_shares,
+      total_underlying,
+      share_supply,
+    ).unwrap();
+    let idle_value = self.vault.for_underlying(
+      self.collateral_vault.amount,
+      &self.reserve,
+    ).unwrap();
+    let instant_value = min(underlying_value, idle_value);
+    let instant_shares = min(
+      self.vault.shares_for_amount(
+        instant_value,
+        total_underlying,
+        share_supply,
+      ).unwrap(),
+      total_shares,
+    );
+    let excess_shares = total_shares
+      .checked_sub(instant_shares).unwrap();
+    msg!("instant_value: {}", instant_value);
+    msg!("instant_shares: {}", instant_shares);
+    msg!("excess_shares: {}", excess_shares);
+
+    if instant_shares > 0 {
+      let collateral_amount = self.vault.for_collateral(
+        instant_value,
+        &self.reserve,
+      ).unwrap();
+      self.burn_shares(instant_shares)?;
+      let actual = self.redeem_collateral(collateral_amount)?;
+      let withdraw_value = min(actual, instant_value);
+      // the fee is left in the vault for the remaining holders, but for the
+      // protocol part
+      let fee = self.vault.instant_exit_fee(withdraw_value).unwrap()
+        .min(withdraw_value);
+      let protocol_amount = ProtocolFee::split(&protocol_fee, fee);
+      let payout = withdraw_value
+        .checked_sub(fee).unwrap();
+      msg!("fee: {}, protocol_amount: {}", fee, protocol_amount);
+      self.transfer_underlying(self.user_token_account.to_account_info(), payout)?;
+      if let Some(protocol_fee) = protocol_fee.as_ref().filter(|_| protocol_amount > 0) {
+        self.transfer_underlying(protocol_fee.treasury.clone(), protocol_amount)?;
+      }
+      self.vault.after_withdraw(payout.checked_add(protocol_amount).unwrap())?;
+      if !self.user_position.is_initialized() {
+        self.user_position.initialize(
+          position_bump,
+          self.vault.key(),
+          self.user_account.key(),
+        )?;
+      }
+      self.user_position.after_withdraw(payout, instant_shares)?;
+    }
+
+    self.queue_shares(excess_shares)?;
+    self.withdraw_request.queue(excess_shares, clock.unix_timestamp)?;
+
+    self.shares_mint.reload()?;
+    self.vault.update_share_price(self.shares_mint.supply, clock.unix_timestamp)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+
+  fn release_queued(&self, shares_amount: u64) -> Result<()> {
+    if shares_amount == 0 {
+      return Ok(());
+    }
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.withdraw_queue.to_account_info(),
+        to: self.user_shares.to_account_info(),
+        authority: self.executor.to_account_info(),
+      }, signer,
+    );
+    transfer(ctx, shares_amount)
+  }
+
+  fn queue_shares(&self, shares_amount: u64) -> Result<()> {
+    if shares_amount == 0 {
+      return Ok(());
+    }
+    let ctx = CpiContext::new(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.user_shares.to_account_info(),
+        to: self.withdraw_queue.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      },
+    );
+    transfer(ctx, shares_amount)
+  }
+
+  fn burn_shares(&self, shares_amount: u64) -> Result<()> {
+    let signer = vault_seeds!(self.vault);
+    let seeds: &[&[&[u8]]] = &[&signer[..]];
+    let ctx = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Burn {
+        mint: self.shares_mint.to_account_info(),
+        from: self.user_shares.to_account_info(),
+        authority: self.user_account.to_account_info(),
+      },
+      seeds,
+    );
+    burn(ctx, shares_amount)
+  }
+
+  fn redeem_collateral(&mut self, amount_in: u64) -> Result<u64> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::RedeemReserveCollateral {
+        source_collateral: *self.collateral_vault.clone(),
+        destination_liquidity: *self.underlying_vault.clone(),
+        reserve: self.reserve.to_account_info(),
+        reserve_collateral_mint: self.reserve_collateral_mint.to_account_info(),
+        reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        lending_market_authority: self.lending_market_authority.to_account_info(),
+        user_transfer_authority: self.executor.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    let liquidity_before = self.underlying_vault.amount;
+    cpi::solend::redeem_collateral(cpi, amount_in)?;
+    self.underlying_vault.reload()?;
+    let liquidity_after = self.underlying_vault.amount
+      .checked_sub(liquidity_before).unwrap();
+    Ok(liquidity_after)
+  }
+
+  fn transfer_underlying(&mut self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+
+    let cpi = CpiContext::new_with_signer(
+      self.token_program.to_account_info(),
+      Transfer {
+        from: self.underlying_vault.to_account_info(),
+        to,
+        authority: self.executor.to_account_info(),
+      }, signer,
+    );
+    transfer(cpi, amount)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use crate::{cpi_calls as cpi, executor_seeds};
+use crate::instructions::{book_obligation, refresh_obligation, SolvencyAccounts};
+use crate::structs::Vault;
+use crate::pda::{EXECUTOR_SEED, VAULT_SEED};
+
+
+#[derive(Accounts)]
+pub struct WithdrawObligationCollateral<'info> {
+  #[account(
+  mut,
+  has_one = authority,
+  has_one = reserve,
+  has_one = obligation,
+  seeds = [VAULT_SEED, reserve.key().as_ref(), vault.zeta_group.as_ref(), authority.key().as_ref()],
+  bump = vault.bump
+  )]
+  pub vault: Box<Account<'info, Vault>>,
+  pub authority: Signer<'info>,
+  /// CHECK:
+  #[account(
+  seeds = [EXECUTOR_SEED, vault.key().as_ref(), vault.executor_version_seed()],
+  bump = vault.executor_bump
+  )]
+  pub executor: AccountInfo<'info>,
+  #[account(mut, address = vault.collateral_vault)]
+  pub collateral_vault: Box<Account<'info, TokenAccount>>,
+  /// CHECK:
+  #[account(mut, address = reserve.collateral.supply_pubkey)]
+  pub reserve_collateral_supply: AccountInfo<'info>,
+  pub reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  // reserve the vault borrows from, the remaining debt is checked against it
+  #[account(
+  constraint = borrow_reserve.lending_market == reserve.lending_market,
+  constraint = borrow_reserve.liquidity.mint_pubkey == cpi::zeta::USDC
+  )]
+  pub borrow_reserve: Box<Account<'info, cpi::solend::Reserve>>,
+  /// CHECK: validated in `check_obligation`
+  #[account(mut, owner = cpi::solend::solend_program::ID)]
+  pub obligation: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = reserve.lending_market)]
+  pub lending_market: AccountInfo<'info>,
+  /// CHECK:
+  #[account(address = cpi::solend::get_lending_market_authority(&reserve.lending_market))]
+  pub lending_market_authority: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  pub lending_program: Program<'info, cpi::solend::SolendProgram>,
+  pub solvency: SolvencyAccounts<'info>,
+}
+
+impl<'info> WithdrawObligationCollateral<'info> {
+  /// Moves `collateral_amount` cTokens from the obligation back to the
+  /// collateral vault, as long as the remaining debt stays under `max_ltv_bps`.
+  pub fn withdraw_obligation_collateral(&mut self, collateral_amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    self.vault.record_keeper_activity(clock.slot);
+    refresh_obligation(
+      &self.lending_program.to_account_info(),
+      &self.obligation,
+      &[self.reserve.to_account_info(), self.borrow_reserve.to_account_info()],
+    )?;
+    {
+      let obligation = Account::<cpi::solend::Obligation>::try_from(&self.obligation)?;
+      self.vault.check_obligation(&obligation, &self.executor.key(), &self.reserve.lending_market, clock.slot)?;
+      self.vault.check_ltv(&obligation, &self.reserve, &self.borrow_reserve, 0, collateral_amount)?;
+    }
+
+    let seeds = executor_seeds!(self.vault);
+    let signer: &[&[&[u8]]] = &[&seeds[..]];
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.to_account_info(),
+      cpi::solend::WithdrawObligationCollateral {
+        source_collateral: self.reserve_collateral_supply.to_account_info(),
+        destination_collateral: self.collateral_vault.to_account_info(),
+        withdraw_reserve: self.reserve.to_account_info(),
+        obligation: self.obligation.to_account_info(),
+        lending_market: self.lending_market.to_account_info(),
+        lending_market_authority: self.lending_market_authority.to_account_info(),
+        obligation_owner: self.executor.to_account_info(),
+        token_program: self.token_program.to_account_info(),
+        lending_program: self.lending_program.to_account_info(),
+      }, signer);
+    cpi::solend::withdraw_obligation_collateral(cpi, collateral_amount)?;
+
+    book_obligation(&mut self.vault, &self.obligation, &self.reserve, &self.borrow_reserve, 0, 0, clock.slot)?;
+    self.solvency.assert_solvent(&self.vault)
+  }
+}
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use crate::{ratio_ceil, VaultError};
+use crate::cpi_calls::zeta::{
+  load_cross_margin_account, load_margin_account, Side, ASK_ORDERS_INDEX, BID_ORDERS_INDEX,
+};
+use crate::constants::POSITION_PRECISION_DENOMINATOR;
+
+// taker fees zeta may charge on the filled lots, bps of the spot notional
+pub const ZETA_FEE_TOLERANCE_BPS: u64 = 100;
+
+
+/// Balance and one product ledger of the vault margin account, read around a
+/// Zeta CPI. The vault checks the exchange moved the account by what the
+/// instruction asked for, a compromised or upgraded Zeta program can't take
+/// more unnoticed.
+#[derive(Clone, Copy, Default)]
+pub struct MarginSnapshot {
+  pub balance: u64,
+  pub position: i64,
+  pub opening_orders: [u64; 2],
+  // bids of a short rest as closing orders
+  pub closing_orders: u64,
+}
+
+impl MarginSnapshot {
+  /// Reads the balance, and the ledger at `product_index` when given.
+  pub fn load(margin_account: &AccountInfo, cross_margin: bool, product_index: Option<usize>) -> Result<Self> {
+    let (balance, ledger) = if cross_margin {
+      let account = load_cross_margin_account(margin_account)?;
+      (account.balance, product_index.map(|index| account.product_ledgers[index]))
+    } else {
+      let account = load_margin_account(margin_account)?;
+      (account.balance, product_index.map(|index| account.product_ledgers[index]))
+    };
+    let mut snapshot = Self { balance, ..Default::default() };
+    if let Some(ledger) = ledger {
+      // packed struct, copy the fields out before reading them
+      snapshot.position = ledger.position.size;
+      snapshot.opening_orders = ledger.order_state.opening_orders;
+      snapshot.closing_orders = ledger.order_state.closing_orders;
+    }
+    Ok(snapshot)
+  }
+
+  /// A call that isn't meant to touch the account left it as it was.
+  pub fn check_unchanged(&self, after: &Self) -> Result<()> {
+    if after.balance != self.balance
+      || after.position != self.position
+      || after.opening_orders != self.opening_orders
+      || after.closing_orders != self.closing_orders {
+      msg!("margin balance before: {}, after: {}", self.balance, after.balance);
+      return err!(VaultError::UnexpectedZetaState);
+    }
+    Ok(())
+  }
+
+  /// A withdrawal takes exactly `amount` off the balance.
+  pub fn check_withdraw(&self, after: &Self, amount: u64) -> Result<()> {
+    if self.balance.checked_sub(amount) != Some(after.balance) {
+      msg!("margin balance before: {}, after: {}, withdrawn: {}", self.balance, after.balance, amount);
+      return err!(VaultError::UnexpectedZetaState);
+    }
+    Ok(())
+  }
+
+  /// A cancel only takes resting orders off the book, returns the bid lots
+  /// it removed, opening and closing.
+  pub fn check_cancel(&self, after: &Self) -> Result<u64> {
+    if after.balance != self.balance || after.position != self.position {
+      msg!("margin balance before: {}, after: {}", self.balance, after.balance);
+      return err!(VaultError::UnexpectedZetaState);
+    }
+    Ok(self.resting_bids().saturating_sub(after.resting_bids()))
+  }
+
+  pub fn resting_bids(&self) -> u64 {
+    self.opening_orders[BID_ORDERS_INDEX]
+      .checked_add(self.closing_orders).unwrap()
+  }
+
+  /// An order adds at most `size` lots on its side, resting and filled
+  /// together, and the balance only pays for the filled lots: the premium
+  /// at `price` for a bid plus the fee tolerance on the `spot` notional.
+  pub fn check_order(&self, after: &Self, side: Side, price: u64, size: u64, spot: u64) -> Result<()> {
+    let (rested, filled) = match side {
+      Side::Bid => (
+        i128::from(after.resting_bids()) - i128::from(self.resting_bids()),
+        i128::from(after.position) - i128::from(self.position),
+      ),
+      Side::Ask => (
+        i128::from(after.opening_orders[ASK_ORDERS_INDEX]) - i128::from(self.opening_orders[ASK_ORDERS_INDEX]),
+        i128::from(self.position) - i128::from(after.position),
+      ),
+      Side::Uninitialized => return err!(VaultError::UnexpectedZetaState),
+    };
+    if filled < 0 || rested < 0 || filled + rested > i128::from(size) {
+      msg!("size: {}, filled: {}, rested: {}", size, filled, rested);
+      return err!(VaultError::UnexpectedZetaState);
+    }
+
+    let filled = filled as u64;
+    let premium = match side {
+      Side::Bid => ratio_ceil!(filled, price, POSITION_PRECISION_DENOMINATOR as u64).unwrap(),
+      _ => 0,
+    };
+    let notional = ratio_ceil!(filled, spot, POSITION_PRECISION_DENOMINATOR as u64).unwrap();
+    let max_paid = premium
+      .checked_add(ratio_ceil!(notional, ZETA_FEE_TOLERANCE_BPS, 10_000_u64).unwrap())
+      .unwrap();
+    let paid = self.balance.saturating_sub(after.balance);
+    if paid > max_paid {
+      msg!("margin balance before: {}, after: {}, max paid: {}", self.balance, after.balance, max_paid);
+      return err!(VaultError::UnexpectedZetaState);
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,28 @@
+//! Lending leg of the idle capital. The instructions moving liquidity
+//! between the lending market and the margin account (`fast_rollover`,
+//! `rebalance_margin`, `sweep_idle`) go through `LendingAdapter`, the
+//! deposit, withdraw and harvest paths still call `cpi_calls::solend`.
+//! Vaults only run on Solend.
+use anchor_lang::prelude::*;
+
+mod solend;
+
+pub use self::solend::SolendAdapter;
+
+
+pub trait LendingAdapter<'info> {
+  /// Brings the venue's exchange rate up to date, or fails when it can't be
+  /// trusted for this slot.
+  fn refresh(&self) -> Result<()>;
+
+  /// Lends `amount` of liquidity from the vault's liquidity account.
+  fn deposit(&self, amount: u64, signer: &[&[&[u8]]]) -> Result<()>;
+
+  /// Turns `collateral_amount` of the venue's collateral (cTokens, bank
+  /// shares) back into liquidity.
+  fn redeem(&self, collateral_amount: u64, signer: &[&[&[u8]]]) -> Result<()>;
+
+  /// Liquidity `collateral_amount` is worth at the current exchange rate,
+  /// rounded down.
+  fn value_collateral(&self, collateral_amount: u64) -> Result<u64>;
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::{cpi_calls as cpi, VaultError};
+use super::LendingAdapter;
+
+
+/// Solend reserve, collateral is held as cTokens in the vault's collateral
+/// token account. Solend has no permissionless refresh the vault could sign,
+/// clients refresh the reserve earlier in the same transaction.
+pub struct SolendAdapter<'a, 'info> {
+  pub reserve: &'a Account<'info, cpi::solend::Reserve>,
+  pub reserve_liquidity_supply: AccountInfo<'info>,
+  pub reserve_collateral_mint: AccountInfo<'info>,
+  pub lending_market: AccountInfo<'info>,
+  pub lending_market_authority: AccountInfo<'info>,
+  pub liquidity: Account<'info, TokenAccount>,
+  pub collateral: Account<'info, TokenAccount>,
+  pub authority: AccountInfo<'info>,
+  pub token_program: AccountInfo<'info>,
+  pub lending_program: AccountInfo<'info>,
+}
+
+impl<'a, 'info> LendingAdapter<'info> for SolendAdapter<'a, 'info> {
+  fn refresh(&self) -> Result<()> {
+    if self.reserve.last_update.is_stale(Clock::get()?.slot)? {
+      return err!(VaultError::ReservePaused);
+    }
+    Ok(())
+  }
+
+  fn deposit(&self, amount: u64, signer: &[&[&[u8]]]) -> Result<()> {
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.clone(),
+      cpi::solend::DepositReserveLiquidity {
+        source_liquidity: self.liquidity.to_account_info(),
+        destination_collateral: self.collateral.clone(),
+        reserve: self.reserve.to_account_info(),
+        reserve_liquidity_supply: self.reserve_liquidity_supply.clone(),
+        reserve_collateral_mint: self.reserve_collateral_mint.clone(),
+        lending_market: self.lending_market.clone(),
+        lending_market_authority: self.lending_market_authority.clone(),
+        user_transfer_authority: self.authority.clone(),
+        token_program: self.token_program.clone(),
+        lending_program: self.lending_program.clone(),
+      }, signer);
+    cpi::solend::deposit_liquidity(cpi, amount)?;
+    Ok(())
+  }
+
+  fn redeem(&self, collateral_amount: u64, signer: &[&[&[u8]]]) -> Result<()> {
+    let cpi = CpiContext::new_with_signer(
+      self.lending_program.clone(),
+      cpi::solend::RedeemReserveCollateral {
+        source_collateral: self.collateral.clone(),
+        destination_liquidity: self.liquidity.clone(),
+        reserve: self.reserve.to_account_info(),
+        reserve_collateral_mint: self.reserve_collateral_mint.clone(),
+        reserve_liquidity_supply: self.reserve_liquidity_supply.clone(),
+        lending_market: self.lending_market.clone(),
+        lending_market_authority: self.lending_market_authority.clone(),
+        user_transfer_authority: self.authority.clone(),
+        token_program: self.token_program.clone(),
+        lending_program: self.lending_program.clone(),
+      }, signer);
+    cpi::solend::redeem_collateral(cpi, collateral_amount)?;
+    Ok(())
+  }
+
+  fn value_collateral(&self, collateral_amount: u64) -> Result<u64> {
+    let total_supply = self.reserve.liquidity.total_supply()?;
+    let rate = self.reserve.collateral.exchange_rate(total_supply)?;
+    rate.collateral_to_liquidity(collateral_amount)
+  }
+}
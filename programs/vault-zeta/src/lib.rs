@@ -1,14 +1,27 @@
 use anchor_lang::prelude::*;
 
+#[cfg(not(feature = "localnet-mocks"))]
 mod cpi_calls;
+// program-test suites build fixtures from the vault's view of Zeta and Solend
+#[cfg(feature = "localnet-mocks")]
+pub mod cpi_calls;
 mod instructions;
 mod structs;
 mod macros;
 mod errors;
+pub mod lending;
+pub mod client_helpers;
+pub mod pda;
+pub mod constants;
 
 pub use instructions::*;
 pub use macros::*;
 pub use errors::*;
+pub use cpi_calls::zeta::pyth_client::OracleSource;
+pub use structs::{
+  DepositIntent, EpochHistoryReturn, EpochRecord, Feature, HealthReport, KeeperSet, MarginOverrides, PreviewDepositReturn, PreviewWithdrawReturn,
+  ProtocolConfigParams, RejectionKind, SharePriceTwapReturn, VaultConfigParams, VaultPreset,
+};
 
 declare_id!("CXeQdAb6PZHSEwtHQNQafDSxpSfVhG9JWhebsrwzP1Q8");
 
@@ -18,57 +31,754 @@ pub mod vault_zeta {
 
   pub fn initialize(
     ctx: Context<InitializeVault>,
+    share_decimals: u8,
     deposit_limit: u64,
     management_fee_bps: u64
   ) -> Result<()> {
-    initialize_vault(ctx, deposit_limit, management_fee_bps)
+    initialize_vault(ctx, share_decimals, deposit_limit, management_fee_bps)
   }
 
-  pub fn deposit(ctx: Context<DepositToVault>, amount_in: u64) -> Result<()> {
-    ctx.accounts.deposit(amount_in)?;
+  pub fn initialize_vault_with_preset(
+    ctx: Context<InitializeVault>,
+    share_decimals: u8,
+    deposit_limit: u64,
+    preset: VaultPreset,
+  ) -> Result<()> {
+    initialize_vault_from_preset(ctx, share_decimals, deposit_limit, preset)
+  }
+
+  // Multi-step setup, same result as `initialize`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn begin_setup(
+    ctx: Context<BeginSetup>,
+    deposit_limit: u64,
+    management_fee_bps: u64,
+  ) -> Result<()> {
+    begin_vault_setup(ctx, deposit_limit, management_fee_bps)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn setup_shares_mint(ctx: Context<SetupSharesMint>, share_decimals: u8) -> Result<()> {
+    let mint_bump = *ctx.bumps.get("shares_mint").unwrap();
+    ctx.accounts.setup_shares_mint(share_decimals, mint_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn setup_token_accounts(ctx: Context<SetupTokenAccounts>) -> Result<()> {
+    ctx.accounts.setup_token_accounts()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn setup_margin_account(ctx: Context<SetupMarginAccount>) -> Result<()> {
+    ctx.accounts.setup_margin_account()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn complete_setup(ctx: Context<CompleteSetup>) -> Result<()> {
+    ctx.accounts.complete_setup()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn deposit<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositToVault<'info>>,
+    amount_in: u64,
+  ) -> Result<()> {
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.deposit(amount_in, user_bump, position_bump, ctx.remaining_accounts)?;
     Ok(())
   }
 
-  pub fn withdraw(ctx: Context<WithdrawFromVault>, amount_out: u64) -> Result<()> {
-    ctx.accounts.withdraw(amount_out)?;
+  // Deposit routed by a whitelisted integrator, shares go to `beneficiary`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn deposit_for<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositFor<'info>>,
+    amount_in: u64,
+  ) -> Result<()> {
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.deposit_for(amount_in, user_bump, position_bump, ctx.remaining_accounts)
+  }
+
+  // Relayed deposit of a user signed intent, see `DepositIntent`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn deposit_with_signature<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositWithSignature<'info>>,
+    intent: DepositIntent,
+  ) -> Result<()> {
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.deposit_with_signature(intent, user_bump, position_bump, ctx.remaining_accounts)
+  }
+
+  // Escrows a deposit that enters at the next epoch's share price
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn deposit_pending<'info>(
+    ctx: Context<'_, '_, '_, 'info, DepositPending<'info>>,
+    amount: u64,
+  ) -> Result<()> {
+    let pending_bump = *ctx.bumps.get("pending_deposit").unwrap();
+    ctx.accounts.deposit_pending(amount, pending_bump, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn convert_pending_deposit<'info>(
+    ctx: Context<'_, '_, '_, 'info, ConvertPendingDeposit<'info>>,
+  ) -> Result<()> {
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.convert_pending_deposit(position_bump, ctx.remaining_accounts)
+  }
+
+  // Receipt new shares wait in while the vault charges an exit fee or locks deposits
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn open_deposit_receipt(ctx: Context<OpenDepositReceipt>) -> Result<()> {
+    let receipt_bump = *ctx.bumps.get("deposit_receipt").unwrap();
+    ctx.accounts.open_deposit_receipt(receipt_bump)
+  }
+
+  // Treasury shares as a remaining account once the protocol config exists, see `ProtocolFee`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn release_deposit_shares<'info>(ctx: Context<'_, '_, '_, 'info, ReleaseDepositShares<'info>>) -> Result<()> {
+    ctx.accounts.release_deposit_shares(ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn cancel_pending_deposit(ctx: Context<CancelPendingDeposit>) -> Result<()> {
+    ctx.accounts.cancel_pending_deposit()
+  }
+
+  // Zeta accounts as remaining accounts let margin pay what the Solend reserve can't
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawFromVault<'info>>,
+    amount_out: u64,
+  ) -> Result<()> {
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.withdraw(amount_out, user_bump, position_bump, ctx.remaining_accounts)?;
     Ok(())
   }
 
+  // Burns all of the user's shares, the last holder drains the vault
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_all<'info>(ctx: Context<'_, '_, '_, 'info, WithdrawFromVault<'info>>) -> Result<()> {
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.withdraw_all(user_bump, position_bump, ctx.remaining_accounts)
+  }
+
+  // Treasury underlying account as a remaining account once the protocol config exists, see `ProtocolFee`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_instant<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawInstant<'info>>,
+    shares_amount: u64,
+  ) -> Result<()> {
+    let request_bump = *ctx.bumps.get("withdraw_request").unwrap();
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.withdraw_instant(shares_amount, request_bump, user_bump, position_bump, ctx.remaining_accounts)
+  }
+
+  // Pays the shares out in Solend cTokens, skipping the redeem
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_as_collateral(ctx: Context<WithdrawAsCollateral>, shares_amount: u64) -> Result<()> {
+    let user_bump = *ctx.bumps.get("user_state").unwrap();
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.withdraw_as_collateral(shares_amount, user_bump, position_bump)
+  }
+
+  // Crisis exit, pays the shares out pro-rata in kind
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_in_kind(ctx: Context<WithdrawInKind>, shares_amount: u64) -> Result<()> {
+    let position_bump = *ctx.bumps.get("user_position").unwrap();
+    ctx.accounts.withdraw_in_kind(shares_amount, position_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn claim_usdc(ctx: Context<ClaimUsdc>) -> Result<()> {
+    ctx.accounts.claim_usdc()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn set_beneficial_owner(ctx: Context<SetBeneficialOwner>, beneficial_owner: Pubkey) -> Result<()> {
+    ctx.accounts.set_beneficial_owner(beneficial_owner)
+  }
+
   // Manager tools
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
   pub fn init_open_orders(
     ctx: Context<InitOpenOrders>
   ) -> Result<()> {
     ctx.accounts.initialize_open_orders()
   }
 
-  pub fn harvest_yield(ctx: Context<HarvestYield>) -> Result<()> {
-    ctx.accounts.harvest_yield()
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn close_open_orders(ctx: Context<CloseOpenOrders>) -> Result<()> {
+    ctx.accounts.close_open_orders()
+  }
+
+  // Treasury shares as a remaining account once the protocol config exists, see `ProtocolFee`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn harvest_yield<'info>(ctx: Context<'_, '_, '_, 'info, HarvestYield<'info>>) -> Result<()> {
+    ctx.accounts.harvest_yield(ctx.remaining_accounts)
   }
 
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn refresh_valuation<'info>(ctx: Context<'_, '_, '_, 'info, RefreshValuation<'info>>) -> Result<()> {
+    ctx.accounts.refresh_valuation(ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
   pub fn swap_to_usdc(ctx: Context<Swap>) -> Result<()> {
     ctx.accounts.swap_underlying_to_usdc()
   }
 
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
   pub fn swap_to_underlying(ctx: Context<Swap>) -> Result<()> {
     ctx.accounts.swap_usdc_to_underlying()
   }
 
+  // Books a donation surplus held back by `harvest_yield` as locked profit
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn skim_surplus(ctx: Context<SkimSurplus>) -> Result<()> {
+    ctx.accounts.skim_surplus()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
   pub fn reinvest_zeta(ctx: Context<ReinvestZeta>) -> Result<()> {
     ctx.accounts.reinvest_zeta()
   }
 
-  pub fn bid_order(
-    ctx: Context<BidOrder>,
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn commit_nav_snapshot<'info>(ctx: Context<'_, '_, '_, 'info, CommitNavSnapshot<'info>>) -> Result<()> {
+    let bump = *ctx.bumps.get("nav_snapshot").unwrap();
+    ctx.accounts.commit_nav_snapshot(bump, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn bid_order<'info>(
+    ctx: Context<'_, '_, '_, 'info, BidOrder<'info>>,
+    dry_run: bool,
   ) -> Result<()> {
-    ctx.accounts.bid_order()?;
+    let tracker_bump = *ctx.bumps.get("order_tracker").unwrap();
+    ctx.accounts.bid_order(tracker_bump, dry_run, ctx.remaining_accounts)?;
     Ok(())
   }
 
+  // Reprices a keeper bid within the order budget
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn cancel_replace<'info>(
+    ctx: Context<'_, '_, '_, 'info, BidOrder<'info>>,
+    client_order_id: u64,
+    price: u64,
+    size: u64,
+    dry_run: bool,
+  ) -> Result<()> {
+    let tracker_bump = *ctx.bumps.get("order_tracker").unwrap();
+    ctx.accounts.cancel_replace(client_order_id, price, size, tracker_bump, dry_run, ctx.remaining_accounts)
+  }
+
+  // Buys back a short early once its premium has decayed
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn buy_to_close<'info>(
+    ctx: Context<'_, '_, '_, 'info, BidOrder<'info>>,
+    price: u64,
+    size: u64,
+    dry_run: bool,
+  ) -> Result<()> {
+    let tracker_bump = *ctx.bumps.get("order_tracker").unwrap();
+    ctx.accounts.buy_to_close(price, size, tracker_bump, dry_run, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn place_ladder<'info>(
+    ctx: Context<'_, '_, '_, 'info, PlaceLadder<'info>>,
+    max_amount: u64,
+    dry_run: bool,
+  ) -> Result<()> {
+    ctx.accounts.place_ladder(max_amount, dry_run, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn reconcile_fills<'info>(ctx: Context<'_, '_, '_, 'info, ReconcileFills<'info>>) -> Result<()> {
+    ctx.accounts.reconcile_fills(ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn execute_strategy<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExecuteStrategy<'info>>,
+    data: Vec<u8>,
+  ) -> Result<()> {
+    ctx.accounts.execute_strategy(data, ctx.remaining_accounts)
+  }
+
+  // Tracker of a market passed with ladder legs, see `LADDER_LEG_ACCOUNTS`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn init_order_tracker(ctx: Context<InitOrderTracker>) -> Result<()> {
+    let bump = *ctx.bumps.get("order_tracker").unwrap();
+    ctx.accounts.init_order_tracker(bump)
+  }
+
+  // Trackers created before the position record, see `OrderTracker`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn resize_order_tracker(ctx: Context<ResizeOrderTracker>) -> Result<()> {
+    ctx.accounts.resize_order_tracker()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn cancel_client_order(
+    ctx: Context<CancelClientOrder>,
+    client_order_id: u64,
+  ) -> Result<()> {
+    ctx.accounts.cancel_client_order(client_order_id)
+  }
+
+  // Books an expired series at zeta's settlement price
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn compute_settlement(ctx: Context<ComputeSettlement>, expiry_index: u8) -> Result<()> {
+    ctx.accounts.compute_settlement(expiry_index)
+  }
+
+  // Guardian break-glass exit, markets passed like `place_ladder` legs
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn abort_epoch<'info>(
+    ctx: Context<'_, '_, '_, 'info, AbortEpoch<'info>>,
+    close_positions: bool,
+    max_slippage_bps: u64,
+  ) -> Result<()> {
+    ctx.accounts.abort_epoch(close_positions, max_slippage_bps, ctx.remaining_accounts)
+  }
+
+  // Settle, cancel, withdraw and redeposit in one go, markets passed like
+  // `place_ladder` legs
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn fast_rollover<'info>(
+    ctx: Context<'_, '_, '_, 'info, FastRollover<'info>>,
+    expiry_index: Option<u8>,
+  ) -> Result<()> {
+    ctx.accounts.fast_rollover(expiry_index, ctx.remaining_accounts)
+  }
+
+  // Guardian marks the vault down to its real assets after a liquidation
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn socialize_loss(ctx: Context<SocializeLoss>) -> Result<()> {
+    ctx.accounts.socialize_loss()
+  }
+
+  // Dead-man switch, anyone can open withdrawals once the keeper went silent
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn emergency_open_withdrawals<'info>(
+    ctx: Context<'_, '_, '_, 'info, EmergencyOpenWithdrawals<'info>>,
+  ) -> Result<()> {
+    ctx.accounts.emergency_open_withdrawals(ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn init_keeper_pool(ctx: Context<InitKeeperPool>) -> Result<()> {
+    let pool_bump = *ctx.bumps.get("keeper_pool").unwrap();
+    ctx.accounts.init_keeper_pool(pool_bump)
+  }
+
+  // Lamports refunded to permissionless cranks
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn fund_keeper_pool(ctx: Context<FundKeeperPool>, lamports: u64) -> Result<()> {
+    ctx.accounts.fund_keeper_pool(lamports)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn defund_keeper_pool(ctx: Context<DefundKeeperPool>, lamports: u64) -> Result<()> {
+    ctx.accounts.defund_keeper_pool(lamports)
+  }
+
+  pub fn init_protocol_config(
+    ctx: Context<InitProtocolConfig>,
+    admin: Pubkey,
+    treasury: Pubkey,
+    protocol_fee_bps: u64,
+  ) -> Result<()> {
+    let bump = *ctx.bumps.get("protocol_config").unwrap();
+    ctx.accounts.init_protocol_config(bump, admin, treasury, protocol_fee_bps)
+  }
+
+  pub fn update_protocol_config(
+    ctx: Context<UpdateProtocolConfig>,
+    params: ProtocolConfigParams,
+  ) -> Result<()> {
+    ctx.accounts.update_protocol_config(&params)
+  }
+
+  // Bitmask of the instructions shipped dark, see `Feature`
+  pub fn set_feature_flags(ctx: Context<SetFeatureFlags>, flags: u64) -> Result<()> {
+    let bump = *ctx.bumps.get("feature_flags").unwrap();
+    ctx.accounts.set_feature_flags(flags, bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn post_bond(ctx: Context<PostBond>, amount: u64) -> Result<()> {
+    let bond_bump = *ctx.bumps.get("bond").unwrap();
+    ctx.accounts.post_bond(amount, bond_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn slash_bond(ctx: Context<SlashBond>, amount: u64) -> Result<()> {
+    ctx.accounts.slash_bond(amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn request_unbond(ctx: Context<RequestUnbond>, amount: u64) -> Result<()> {
+    ctx.accounts.request_unbond(amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_bond(ctx: Context<WithdrawBond>) -> Result<()> {
+    ctx.accounts.withdraw_bond()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
   pub fn redeem_zeta(ctx: Context<RedeemZeta>, amount_out: u64) -> Result<()> {
     ctx.accounts.redeem_zeta(amount_out)
   }
 
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
   pub fn reinvest_solend(ctx: Context<ReinvestSolend>) -> Result<()> {
     ctx.accounts.reinvest_solend()
   }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn rebalance_margin(
+    ctx: Context<RebalanceMargin>,
+    target_utilization_bps: u64,
+    dry_run: bool,
+  ) -> Result<()> {
+    ctx.accounts.rebalance_margin(target_utilization_bps, dry_run)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn sweep_to_margin(ctx: Context<SweepIdle>) -> Result<()> {
+    ctx.accounts.sweep_to_margin()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn sweep_to_solend(ctx: Context<SweepIdle>) -> Result<()> {
+    ctx.accounts.sweep_to_solend()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn add_integrator(ctx: Context<AddIntegrator>) -> Result<()> {
+    let bump = *ctx.bumps.get("integrator").unwrap();
+    ctx.accounts.add_integrator(bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn remove_integrator(ctx: Context<RemoveIntegrator>) -> Result<()> {
+    ctx.accounts.remove_integrator()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn top_up_rent(ctx: Context<ManageRent>, lamports: u64) -> Result<()> {
+    ctx.accounts.top_up_rent(lamports)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn reclaim_rent(ctx: Context<ManageRent>) -> Result<()> {
+    ctx.accounts.reclaim_rent()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn setup_cross_margin_account(ctx: Context<SetupCrossMarginAccount>) -> Result<()> {
+    ctx.accounts.setup_cross_margin_account()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn setup_drift_user(ctx: Context<SetupDriftUser>) -> Result<()> {
+    ctx.accounts.setup_drift_user()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn rebalance_hedge<'info>(
+    ctx: Context<'_, '_, '_, 'info, RebalanceHedge<'info>>,
+    price: u64,
+    dry_run: bool,
+  ) -> Result<()> {
+    ctx.accounts.rebalance_hedge(price, dry_run, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn deposit_drift_collateral<'info>(
+    ctx: Context<'_, '_, '_, 'info, DriftCollateral<'info>>,
+    amount: u64,
+  ) -> Result<()> {
+    ctx.accounts.deposit_drift_collateral(amount, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_drift_collateral<'info>(
+    ctx: Context<'_, '_, '_, 'info, DriftCollateral<'info>>,
+    amount: u64,
+  ) -> Result<()> {
+    ctx.accounts.withdraw_drift_collateral(amount, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn place_drift_order<'info>(
+    ctx: Context<'_, '_, '_, 'info, PlaceDriftOrder<'info>>,
+    amount: i64,
+    price: u64,
+    reduce_only: bool,
+    dry_run: bool,
+  ) -> Result<()> {
+    ctx.accounts.place_drift_order(amount, price, reduce_only, dry_run, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn settle_drift_pnl<'info>(ctx: Context<'_, '_, '_, 'info, PlaceDriftOrder<'info>>) -> Result<()> {
+    ctx.accounts.settle_drift_pnl(ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn init_insurance_vault(ctx: Context<InitInsuranceVault>) -> Result<()> {
+    ctx.accounts.init_insurance_vault()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn fund_insurance(ctx: Context<FundInsurance>) -> Result<()> {
+    ctx.accounts.fund_insurance()
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn cover_loss(ctx: Context<CoverLoss>, amount: u64) -> Result<()> {
+    ctx.accounts.cover_loss(amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn propose_executor_rotation(ctx: Context<ProposeExecutorRotation>) -> Result<()> {
+    ctx.accounts.propose_executor_rotation()
+  }
+
+  // Escrows of the old executor passed as remaining accounts, see `ROTATE_EXECUTOR_ACCOUNTS`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn rotate_executor<'info>(ctx: Context<'_, '_, '_, 'info, RotateExecutor<'info>>) -> Result<()> {
+    let bump = *ctx.bumps.get("new_executor").unwrap();
+    ctx.accounts.rotate_executor(bump, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn migrate_escrow(ctx: Context<MigrateEscrow>, old_version: u8) -> Result<()> {
+    ctx.accounts.migrate_escrow(old_version)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn update_vault_config<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateVaultConfig<'info>>,
+    params: VaultConfigParams,
+  ) -> Result<()> {
+    ctx.accounts.update_vault_config(params, ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn set_oracle(
+    ctx: Context<SetOracle>,
+    source: OracleSource,
+    feed_id: [u8; 32],
+    max_age: u64,
+  ) -> Result<()> {
+    ctx.accounts.set_oracle(source, feed_id, max_age)
+  }
+
+  // Pyth feed the Solend reserve price is cross-checked against
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn set_reserve_oracle(
+    ctx: Context<SetReserveOracle>,
+    source: OracleSource,
+    feed_id: [u8; 32],
+    max_age: u64,
+  ) -> Result<()> {
+    ctx.accounts.set_reserve_oracle(source, feed_id, max_age)
+  }
+
+  // stricter put cap and dynamic short margin than the zeta group ones
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn set_margin_overrides(
+    ctx: Context<SetMarginOverrides>,
+    overrides: MarginOverrides,
+  ) -> Result<()> {
+    ctx.accounts.set_margin_overrides(overrides)
+  }
+
+  // Second keeper signature for orders above a notional
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn set_keeper_set(ctx: Context<SetKeeperSet>, keeper_set: KeeperSet) -> Result<()> {
+    ctx.accounts.set_keeper_set(keeper_set)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn set_share_metadata(
+    ctx: Context<SetShareMetadata>,
+    name: String,
+    symbol: String,
+    uri: String,
+  ) -> Result<()> {
+    ctx.accounts.set_share_metadata(name, symbol, uri)
+  }
+
+  // Lookup table of the vault accounts for v0 transactions
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn create_lookup_table(ctx: Context<CreateVaultLookupTable>, recent_slot: u64) -> Result<()> {
+    ctx.accounts.create_lookup_table(recent_slot)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn extend_lookup_table<'info>(
+    ctx: Context<'_, '_, '_, 'info, ExtendVaultLookupTable<'info>>,
+  ) -> Result<()> {
+    ctx.accounts.extend_lookup_table(ctx.remaining_accounts)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn open_receipts(ctx: Context<OpenReceipts>, epoch: u64) -> Result<()> {
+    open_receipts_epoch(ctx, epoch)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn settle_receipts(ctx: Context<SettleReceipts>) -> Result<()> {
+    ctx.accounts.settle_receipts()
+  }
+
+  // Principal/yield receipts
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn split_shares(ctx: Context<SplitShares>, shares_amount: u64) -> Result<()> {
+    ctx.accounts.split_shares(shares_amount)
+  }
+
+  // Treasury shares as a remaining account once the protocol config exists, see `ProtocolFee`
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn redeem_receipts<'info>(
+    ctx: Context<'_, '_, '_, 'info, RedeemReceipts<'info>>,
+    principal_amount: u64,
+    yield_amount: u64,
+  ) -> Result<()> {
+    ctx.accounts.redeem_receipts(principal_amount, yield_amount, ctx.remaining_accounts)
+  }
+
+  // Share locks for performance fee tiers
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn lock_shares(
+    ctx: Context<LockShares>,
+    shares_amount: u64,
+    lock_epochs: u64,
+  ) -> Result<()> {
+    let lock_bump = *ctx.bumps.get("share_lock").unwrap();
+    ctx.accounts.lock_shares(shares_amount, lock_epochs, lock_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn unlock_shares(ctx: Context<UnlockShares>) -> Result<()> {
+    ctx.accounts.unlock_shares()
+  }
+
+  // Share staking rewards, funded by the authority
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn init_reward_pool(ctx: Context<InitRewardPool>, emission_rate: u64) -> Result<()> {
+    let pool_bump = *ctx.bumps.get("reward_pool").unwrap();
+    ctx.accounts.init_reward_pool(emission_rate, pool_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn fund_reward_pool(
+    ctx: Context<FundRewardPool>,
+    amount: u64,
+    emission_rate: u64,
+  ) -> Result<()> {
+    ctx.accounts.fund_reward_pool(amount, emission_rate)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn stake_shares(ctx: Context<StakeShares>, shares_amount: u64) -> Result<()> {
+    let stake_bump = *ctx.bumps.get("stake_account").unwrap();
+    ctx.accounts.stake_shares(shares_amount, stake_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn unstake_shares(ctx: Context<UnstakeShares>, shares_amount: u64) -> Result<()> {
+    ctx.accounts.unstake_shares(shares_amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn claim_rewards<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRewards<'info>>) -> Result<()> {
+    ctx.accounts.claim_rewards(ctx.remaining_accounts)
+  }
+
+  // Integrator tools
+  pub fn share_price_twap(ctx: Context<SharePriceTwap>, window: i64) -> Result<()> {
+    ctx.accounts.share_price_twap(window)?;
+    Ok(())
+  }
+
+  pub fn get_epoch_history(ctx: Context<GetEpochHistory>, offset: u64, limit: u64) -> Result<()> {
+    ctx.accounts.get_epoch_history(offset, limit)?;
+    Ok(())
+  }
+
+  pub fn preview_deposit(ctx: Context<Preview>, assets: u64) -> Result<()> {
+    ctx.accounts.preview_deposit(assets)?;
+    Ok(())
+  }
+
+  pub fn preview_withdraw(ctx: Context<Preview>, shares: u64) -> Result<()> {
+    ctx.accounts.preview_withdraw(shares)?;
+    Ok(())
+  }
+
+  pub fn health_check(ctx: Context<HealthCheck>) -> Result<()> {
+    ctx.accounts.health_check()?;
+    Ok(())
+  }
+
+  // Keeper counts a rejected transaction for ops monitoring
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn record_rejection(ctx: Context<RecordRejection>, kind: RejectionKind) -> Result<()> {
+    let bump = *ctx.bumps.get("error_stats").unwrap();
+    ctx.accounts.record_rejection(kind, bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn validate_zeta_constants<'info>(ctx: Context<'_, '_, '_, 'info, ValidateZetaConstants<'info>>) -> Result<()> {
+    ctx.accounts.validate_zeta_constants(ctx.remaining_accounts)
+  }
+
+  // Solend obligation leverage
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn init_obligation(ctx: Context<InitObligation>) -> Result<()> {
+    let obligation_bump = *ctx.bumps.get("obligation").unwrap();
+    ctx.accounts.init_obligation(obligation_bump)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn deposit_obligation_collateral(
+    ctx: Context<DepositObligationCollateral>,
+    collateral_amount: u64,
+  ) -> Result<()> {
+    ctx.accounts.deposit_obligation_collateral(collateral_amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn borrow_obligation_liquidity(ctx: Context<BorrowObligationLiquidity>, amount: u64) -> Result<()> {
+    ctx.accounts.borrow_obligation_liquidity(amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn repay_obligation_liquidity(
+    ctx: Context<RepayObligationLiquidity>,
+    amount: u64,
+  ) -> Result<()> {
+    ctx.accounts.repay_obligation_liquidity(amount)
+  }
+
+  #[access_control(ctx.accounts.vault.check_not_in_progress())]
+  pub fn withdraw_obligation_collateral(
+    ctx: Context<WithdrawObligationCollateral>,
+    collateral_amount: u64,
+  ) -> Result<()> {
+    ctx.accounts.withdraw_obligation_collateral(collateral_amount)
+  }
 }
@@ -1,4 +1,5 @@
 // TODO: https://github.com/solana-labs/solana/issues/19549
+// x * a / b rounded down, panics on overflow
 #[macro_export]
 macro_rules! ratio {
   ($x:expr, $a:expr, $b:expr) => {
@@ -9,11 +10,38 @@ macro_rules! ratio {
   };
 }
 
+// x * a / b rounded down, None on overflow or zero divisor.
+// Use it for anything paid out to the user (minted shares, withdrawn assets).
+#[macro_export]
+macro_rules! ratio_floor {
+  ($x:expr, $a:expr, $b:expr) => {
+    u128::from($x)
+      .checked_mul(u128::from($a))
+      .and_then(|value| value.checked_div(u128::from($b)))
+      .and_then(|value| u64::try_from(value).ok())
+  };
+}
+
+// x * a / b rounded up, None on overflow or zero divisor.
+// Use it for anything the user owes to the vault (burned shares).
+#[macro_export]
+macro_rules! ratio_ceil {
+  ($x:expr, $a:expr, $b:expr) => {
+    u128::from($x)
+      .checked_mul(u128::from($a))
+      .and_then(|value| {
+        let divisor = u128::from($b);
+        value.checked_add(divisor.checked_sub(1)?)?.checked_div(divisor)
+      })
+      .and_then(|value| u64::try_from(value).ok())
+  };
+}
+
 #[macro_export]
 macro_rules! vault_seeds {
   ($vault: expr) => {
     &[
-      b"vault" as &[u8],
+      $crate::pda::VAULT_SEED,
       &$vault.reserve.to_bytes(),
       &$vault.zeta_group.to_bytes(),
       &$vault.authority.to_bytes(),
@@ -26,8 +54,9 @@ macro_rules! vault_seeds {
 macro_rules! executor_seeds {
   ($vault: expr) => {
     &[
-      b"executor" as &[u8],
+      $crate::pda::EXECUTOR_SEED,
       &$vault.key().to_bytes(),
+      $vault.executor_version_seed(),
       &[$vault.executor_bump],
     ]
   };
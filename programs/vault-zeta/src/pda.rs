@@ -0,0 +1,115 @@
+//! Seeds of the program PDAs. Account constraints use the `*_SEED` constants,
+//! code and clients derive addresses through the typed helpers so the seed
+//! order and bumps live in one place.
+use anchor_lang::prelude::*;
+
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const EXECUTOR_SEED: &[u8] = b"executor";
+pub const SHARES_SEED: &[u8] = b"shares";
+pub const SETUP_SEED: &[u8] = b"setup";
+pub const USER_SEED: &[u8] = b"user";
+pub const INTEGRATOR_SEED: &[u8] = b"integrator";
+pub const OBLIGATION_SEED: &[u8] = b"obligation";
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+pub const SHARE_LOCK_SEED: &[u8] = b"share-lock";
+pub const SHARE_LOCK_ESCROW_SEED: &[u8] = b"share-lock-escrow";
+pub const RECEIPTS_SEED: &[u8] = b"receipts";
+pub const RECEIPTS_ESCROW_SEED: &[u8] = b"receipts-escrow";
+pub const PRINCIPAL_SEED: &[u8] = b"principal";
+pub const YIELD_SEED: &[u8] = b"yield";
+pub const WITHDRAW_REQUEST_SEED: &[u8] = b"withdraw-request";
+pub const WITHDRAW_QUEUE_SEED: &[u8] = b"withdraw-queue";
+pub const INSURANCE_SEED: &[u8] = b"insurance";
+pub const USER_POSITION_SEED: &[u8] = b"user-position";
+pub const ORDER_TRACKER_SEED: &[u8] = b"order-tracker";
+pub const REWARD_POOL_SEED: &[u8] = b"reward-pool";
+pub const REWARD_VAULT_SEED: &[u8] = b"reward-vault";
+pub const STAKE_SEED: &[u8] = b"stake";
+pub const STAKE_ESCROW_SEED: &[u8] = b"stake-escrow";
+pub const KEEPER_POOL_SEED: &[u8] = b"keeper-pool";
+pub const PROTOCOL_CONFIG_SEED: &[u8] = b"protocol-config";
+pub const BOND_SEED: &[u8] = b"bond";
+pub const BOND_ESCROW_SEED: &[u8] = b"bond-escrow";
+pub const PENDING_DEPOSIT_SEED: &[u8] = b"pending-deposit";
+pub const PENDING_DEPOSIT_ESCROW_SEED: &[u8] = b"pending-deposit-escrow";
+pub const ERROR_STATS_SEED: &[u8] = b"error-stats";
+pub const FEATURE_FLAGS_SEED: &[u8] = b"feature-flags";
+pub const DEPOSIT_RECEIPT_SEED: &[u8] = b"deposit-receipt";
+pub const DEPOSIT_ESCROW_SEED: &[u8] = b"deposit-escrow";
+
+
+/// `[b"vault", reserve, zeta_group, authority]`
+pub struct VaultPda;
+
+impl VaultPda {
+  pub fn find(reserve: &Pubkey, zeta_group: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+      &[VAULT_SEED, reserve.as_ref(), zeta_group.as_ref(), authority.as_ref()],
+      &crate::ID,
+    )
+  }
+}
+
+/// `[b"executor", vault, version]`, `rotate_executor` moves to the next version.
+pub struct ExecutorPda;
+
+impl ExecutorPda {
+  pub fn version_seed(version: &u8) -> &[u8] {
+    std::slice::from_ref(version)
+  }
+
+  pub fn find(vault: &Pubkey, version: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+      &[EXECUTOR_SEED, vault.as_ref(), Self::version_seed(&version)],
+      &crate::ID,
+    )
+  }
+
+  /// Address for a known bump, cheaper than `find`.
+  pub fn create(vault: &Pubkey, version: u8, bump: u8) -> Result<Pubkey> {
+    Ok(Pubkey::create_program_address(
+      &[EXECUTOR_SEED, vault.as_ref(), Self::version_seed(&version), &[bump]],
+      &crate::ID,
+    )
+      .map_err(|_| ProgramError::InvalidSeeds)?)
+  }
+}
+
+/// `[b"shares", vault]`
+pub struct SharesMintPda;
+
+impl SharesMintPda {
+  pub fn find(vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SHARES_SEED, vault.as_ref()], &crate::ID)
+  }
+
+  /// Address for a known bump, cheaper than `find`.
+  pub fn create(vault: &Pubkey, bump: u8) -> Result<Pubkey> {
+    Ok(Pubkey::create_program_address(&[SHARES_SEED, vault.as_ref(), &[bump]], &crate::ID)
+      .map_err(|_| ProgramError::InvalidSeeds)?)
+  }
+}
+
+/// `[b"receipts", vault, epoch]`
+pub struct ReceiptPda;
+
+impl ReceiptPda {
+  pub fn find(vault: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+      &[RECEIPTS_SEED, vault.as_ref(), epoch.to_le_bytes().as_ref()],
+      &crate::ID,
+    )
+  }
+}
+
+/// Program data account of the program, owned by the upgradeable loader.
+pub struct ProgramDataPda;
+
+impl ProgramDataPda {
+  pub fn find() -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+      &[crate::ID.as_ref()],
+      &anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+    )
+  }
+}
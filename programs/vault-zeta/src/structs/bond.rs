@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+
+// seconds unbonded tokens stay slashable before the operator gets them back
+pub const BOND_UNBOND_DELAY: i64 = 7 * 24 * 60 * 60;
+
+
+/// Tokens the vault operator locks as a guarantee of good conduct. They sit
+/// in an escrow owned by the bond PDA, out of reach of the executor, and
+/// only leave it when the protocol guardian slashes them or after the
+/// unbond delay.
+#[account]
+#[derive(Default)]
+pub struct Bond {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub mint: Pubkey,
+  pub amount: u64,
+  pub total_slashed: u64,
+  // requested out of `amount`, withdrawable from `unbond_ts` and slashable
+  // until then
+  pub unbonding: u64,
+  pub unbond_ts: i64,
+}
+
+impl Bond {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 4;
+
+  pub fn is_initialized(&self) -> bool {
+    self.vault != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, mint: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.mint = mint;
+    Ok(())
+  }
+
+  /// Whether the bond, unbonding tokens aside, covers `min_bond`.
+  pub fn covers(&self, min_bond: u64) -> bool {
+    self.amount >= min_bond
+  }
+
+  pub fn after_post(&mut self, amount: u64) -> Result<()> {
+    if amount == 0 {
+      return err!(VaultError::InvalidBondAmount);
+    }
+    self.amount = self.amount.checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// Moves `amount` to `unbonding`, a new request restarts the delay of
+  /// everything unbonding.
+  pub fn request_unbond(&mut self, amount: u64, now: i64) -> Result<()> {
+    if amount == 0 || amount > self.amount {
+      return err!(VaultError::InvalidBondAmount);
+    }
+    self.amount -= amount;
+    self.unbonding = self.unbonding.checked_add(amount).unwrap();
+    self.unbond_ts = now.checked_add(BOND_UNBOND_DELAY).unwrap();
+    Ok(())
+  }
+
+  /// Takes the unbonding tokens once the delay has passed.
+  pub fn take_unbonded(&mut self, now: i64) -> Result<u64> {
+    if self.unbonding == 0 {
+      return err!(VaultError::InvalidBondAmount);
+    }
+    if now < self.unbond_ts {
+      return err!(VaultError::BondLocked);
+    }
+    let unbonded = self.unbonding;
+    self.unbonding = 0;
+    Ok(unbonded)
+  }
+
+  /// Slashes up to `amount`, the bond first and then what is unbonding.
+  /// Returns what was actually taken.
+  pub fn slash(&mut self, amount: u64) -> Result<u64> {
+    let from_bond = amount.min(self.amount);
+    let from_unbonding = (amount - from_bond).min(self.unbonding);
+    self.amount -= from_bond;
+    self.unbonding -= from_unbonding;
+    let slashed = from_bond + from_unbonding;
+    self.total_slashed = self.total_slashed.checked_add(slashed).unwrap();
+    Ok(slashed)
+  }
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use crate::structs::{FeeTier, Venue, MAX_FEE_TIERS};
+
+pub const MAX_MANAGEMENT_FEE_BPS: u64 = 10_000;
+pub const MAX_LTV_BPS: u64 = 10_000;
+pub const MAX_INSTANT_EXIT_FEE_BPS: u64 = 1_000;
+pub const MAX_EXIT_FEE_BPS: u64 = 1_000;
+pub const MAX_STRATEGY_INSTRUCTIONS: usize = 4;
+pub const MAX_STRATEGY_INTENTS: usize = 8;
+pub const MAX_LADDER_LEGS: usize = 4;
+pub const MAX_CLOSE_PRICE_BPS: u64 = 20_000;
+
+
+/// Partial vault config update, `None` fields are left untouched.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Clone)]
+pub struct VaultConfigParams {
+  pub deposit_limit: Option<u64>,
+  pub management_fee_bps: Option<u64>,
+  pub harvest_interval: Option<i64>,
+  pub deposit_lock_slots: Option<u64>,
+  pub max_price_age: Option<u64>,
+  pub epoch_start: Option<i64>,
+  pub epoch_duration: Option<i64>,
+  pub deposit_window: Option<i64>,
+  pub withdraw_window: Option<i64>,
+  pub snapshot_slots: Option<u64>,
+  pub max_ltv_bps: Option<u64>,
+  pub max_abs_delta: Option<u64>,
+  pub max_abs_vega: Option<u64>,
+  pub instant_exit_fee_bps: Option<u64>,
+  pub idle_sweep_enabled: Option<bool>,
+  pub performance_fee_bps: Option<u64>,
+  pub fee_tiers: Option<[FeeTier; MAX_FEE_TIERS]>,
+  pub strategy_program: Option<Pubkey>,
+  pub strategy_instructions: Option<[[u8; 8]; MAX_STRATEGY_INSTRUCTIONS]>,
+  pub ladder_weights_bps: Option<[u64; MAX_LADDER_LEGS]>,
+  pub min_deposit: Option<u64>,
+  pub guardian: Option<Pubkey>,
+  // lifts an `abort_epoch` halt before it expires
+  pub clear_abort: Option<bool>,
+  pub max_lots_per_slot: Option<u64>,
+  pub max_notional_per_slot: Option<u64>,
+  pub max_notional_per_epoch: Option<u64>,
+  pub keeper_timeout_slots: Option<u64>,
+  // selects the zeta cross-margin account, needs `setup_cross_margin_account` first
+  pub cross_margin: Option<bool>,
+  pub post_only: Option<bool>,
+  pub order_tif_secs: Option<u64>,
+  pub min_iv_bps: Option<u64>,
+  pub max_mark_deviation_bps: Option<u64>,
+  pub reserve_fallback_idle: Option<bool>,
+  pub max_resting_orders: Option<u64>,
+  pub max_reprices_per_epoch: Option<u64>,
+  // switching to drift needs `setup_drift_user` first
+  pub venue: Option<Venue>,
+  pub hedge_perp_market_index: Option<u16>,
+  // enabling the hedge needs `setup_drift_user` first
+  pub hedge_delta_band: Option<u64>,
+  pub insurance_bps: Option<u64>,
+  pub max_order_notional: Option<u64>,
+  pub max_reprice_walk_bps: Option<u64>,
+  // enabling the check needs `set_reserve_oracle` first
+  pub max_reserve_price_divergence_bps: Option<u64>,
+  // marks the USDC margin with the reserve oracle, needs `set_reserve_oracle` first
+  pub priced_margin: Option<bool>,
+  pub min_harvest_amount: Option<u64>,
+  // crisis switch for `withdraw_in_kind`
+  pub in_kind_redemption: Option<bool>,
+  // slots, 0 disables the check
+  pub max_valuation_staleness: Option<u64>,
+  // `buy_to_close` price cap in bps of the mark, 0 caps at the mark
+  pub max_close_price_bps: Option<u64>,
+  // (expiry index, index in the series products) pairs, empty allows all
+  pub allowed_markets: Option<Vec<[u8; 2]>>,
+  pub max_expiries: Option<u8>,
+  pub max_strike_distance_bps: Option<u64>,
+  // slots, 0 values the cTokens at the live reserve rate
+  pub max_exchange_rate_staleness: Option<u64>,
+  // withdrawal fee decaying to 0 over `exit_fee_decay_secs` of holding
+  pub exit_fee_bps: Option<u64>,
+  pub exit_fee_decay_secs: Option<i64>,
+  // hands the config instructions to a governance treasury, default gives
+  // them back to the authority
+  pub governance: Option<Pubkey>,
+  // Solend yield above it is held back as a donation surplus, 0 disables it
+  pub max_yield_apr_bps: Option<u64>,
+}
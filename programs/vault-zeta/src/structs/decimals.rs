@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::constants::PLATFORM_PRECISION;
+
+pub const MAX_SHARE_DECIMALS: u8 = 9;
+
+
+/// Token decimals of the vault underlying and of Zeta amounts (USDC margin,
+/// prices). Amounts cross between the two only through `to_platform` and
+/// `to_underlying`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct Decimals {
+  pub underlying: u8,
+  pub platform: u8,
+}
+
+impl Decimals {
+  pub const MAXIMUM_SIZE: usize = 1 + 1;
+
+  pub fn new(underlying: u8) -> Self {
+    Self {
+      underlying,
+      platform: PLATFORM_PRECISION as u8,
+    }
+  }
+
+  /// Underlying amount in platform precision, rounded down.
+  pub fn to_platform(&self, amount: u64) -> Option<u64> {
+    rescale(amount, self.underlying, self.platform)
+  }
+
+  /// Platform amount in underlying precision, rounded down.
+  pub fn to_underlying(&self, amount: u64) -> Option<u64> {
+    rescale(amount, self.platform, self.underlying)
+  }
+
+  /// One whole underlying token in base units.
+  pub fn underlying_unit(&self) -> Option<u64> {
+    rescale(1, 0, self.underlying)
+  }
+
+  /// `whole` USDC in platform precision, for amounts fixed in whole USDC.
+  pub fn platform_amount(whole: u64) -> Option<u64> {
+    rescale(whole, 0, PLATFORM_PRECISION as u8)
+  }
+
+  /// Underlying amount in share mint precision, rounded down. Prices the
+  /// first deposit into an empty vault, 1 whole token for 1 whole share.
+  pub fn assets_to_shares(&self, assets: u64, share_decimals: u8) -> Option<u64> {
+    rescale(assets, self.underlying, share_decimals)
+  }
+
+  /// Share amount in underlying precision, rounded down.
+  pub fn shares_to_assets(&self, shares: u64, share_decimals: u8) -> Option<u64> {
+    rescale(shares, share_decimals, self.underlying)
+  }
+}
+
+fn rescale(amount: u64, from: u8, to: u8) -> Option<u64> {
+  if from > to {
+    amount.checked_div(10u64.checked_pow((from - to) as u32)?)
+  } else {
+    amount.checked_mul(10u64.checked_pow((to - from) as u32)?)
+  }
+}
@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+
+
+/// Shares minted by deposits while the vault charges an exit fee or locks
+/// deposits, held in the vault deposit escrow until `release_deposit_shares`.
+/// The fee decays from `deposit_ts` and the lock runs from `deposit_slot`,
+/// so both stay with the shares whichever wallet they go to afterwards.
+#[account]
+#[derive(Default)]
+pub struct DepositReceipt {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub owner: Pubkey,
+  pub shares: u64,
+  // share-weighted deposit time of `shares`
+  pub deposit_ts: i64,
+  pub deposit_slot: u64,
+}
+
+impl DepositReceipt {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 3;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.owner = owner;
+    Ok(())
+  }
+
+  /// Topping up moves `deposit_ts` by the weight of the new shares only, the
+  /// shares already held keep the decay they earned.
+  pub fn after_deposit(&mut self, shares: u64, slot: u64, now: i64) -> Result<()> {
+    let total_shares = self.shares
+      .checked_add(shares).unwrap();
+    if self.shares == 0 {
+      self.deposit_ts = now;
+    } else {
+      let weighted = (self.deposit_ts as i128)
+        .checked_mul(self.shares as i128).unwrap()
+        .checked_add((now as i128).checked_mul(shares as i128).unwrap()).unwrap();
+      self.deposit_ts = (weighted / total_shares as i128) as i64;
+    }
+    self.shares = total_shares;
+    self.deposit_slot = slot;
+    Ok(())
+  }
+
+  /// Seconds the escrowed shares have been held, the exit fee decays over it.
+  pub fn held_secs(&self, now: i64) -> i64 {
+    now.saturating_sub(self.deposit_ts)
+  }
+
+  /// Shares can't leave the escrow within `lock_slots` slots after the last
+  /// deposit, that kills flash-loan funded deposit/withdraw round trips.
+  pub fn check_unlocked(&self, slot: u64, lock_slots: u64) -> Result<()> {
+    let unlock_slot = self.deposit_slot
+      .checked_add(lock_slots).unwrap();
+    if slot <= unlock_slot {
+      return err!(VaultError::DepositLocked);
+    }
+    Ok(())
+  }
+
+  pub fn take(&mut self) -> u64 {
+    let shares = self.shares;
+    self.shares = 0;
+    self.deposit_ts = 0;
+    shares
+  }
+}
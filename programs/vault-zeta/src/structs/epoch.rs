@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+
+/// Fixed length epochs starting at `epoch_start`.
+/// Deposits and withdrawals are only allowed during the first
+/// `deposit_window`/`withdraw_window` seconds of an epoch, 0 means always.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct EpochSchedule {
+  pub epoch_start: i64,
+  pub epoch_duration: i64,
+  pub deposit_window: i64,
+  pub withdraw_window: i64,
+}
+
+impl EpochSchedule {
+  pub const MAXIMUM_SIZE: usize = 8 * 4;
+
+  pub fn is_enabled(&self) -> bool {
+    self.epoch_duration > 0
+  }
+
+  pub fn current_epoch(&self, now: i64) -> u64 {
+    if !self.is_enabled() || now < self.epoch_start {
+      return 0;
+    }
+    (now - self.epoch_start)
+      .checked_div(self.epoch_duration).unwrap() as u64
+  }
+
+  pub fn current_epoch_start(&self, now: i64) -> i64 {
+    if !self.is_enabled() {
+      return self.epoch_start;
+    }
+    self.epoch_start
+      .checked_add(
+        (self.current_epoch(now) as i64)
+          .checked_mul(self.epoch_duration).unwrap()
+      ).unwrap()
+  }
+
+  fn in_window(&self, window: i64, now: i64) -> bool {
+    if !self.is_enabled() || window == 0 {
+      return true;
+    }
+    if now < self.epoch_start {
+      return false;
+    }
+    now - self.current_epoch_start(now) < window
+  }
+
+  pub fn is_deposit_open(&self, now: i64) -> bool {
+    self.in_window(self.deposit_window, now)
+  }
+
+  pub fn is_withdraw_open(&self, now: i64) -> bool {
+    self.in_window(self.withdraw_window, now)
+  }
+
+  pub fn validate(&self) -> bool {
+    self.epoch_duration >= 0
+      && self.deposit_window >= 0
+      && self.withdraw_window >= 0
+      && (!self.is_enabled() || (
+        self.deposit_window <= self.epoch_duration
+          && self.withdraw_window <= self.epoch_duration
+      ))
+  }
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+
+/// Failure categories ops watch for systemic issues.
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, PartialEq, Eq)]
+pub enum RejectionKind {
+  // order priced outside the mark band or a swap below its minimum out
+  Slippage,
+  StaleOracle,
+  InsufficientMargin,
+}
+
+/// Rejection counters of a vault. A failed instruction rolls its own writes
+/// back, so the keeper records the rejection it got from the simulation or
+/// the failed transaction through `record_rejection`.
+#[account]
+#[derive(Default)]
+pub struct ErrorStats {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub slippage_rejections: u64,
+  pub stale_oracle_rejections: u64,
+  pub margin_rejections: u64,
+  pub last_rejection_slot: u64,
+}
+
+impl ErrorStats {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 + 8 * 4;
+
+  pub fn is_initialized(&self) -> bool {
+    self.vault != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    Ok(())
+  }
+
+  pub fn record(&mut self, kind: RejectionKind, slot: u64) -> Result<()> {
+    let counter = match kind {
+      RejectionKind::Slippage => &mut self.slippage_rejections,
+      RejectionKind::StaleOracle => &mut self.stale_oracle_rejections,
+      RejectionKind::InsufficientMargin => &mut self.margin_rejections,
+    };
+    *counter = counter.checked_add(1).unwrap();
+    self.last_rejection_slot = slot;
+    Ok(())
+  }
+}
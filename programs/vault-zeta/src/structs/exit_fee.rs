@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use crate::ratio_ceil;
+
+
+/// Fee kept by the vault on withdrawals, decaying linearly from `fee_bps`
+/// at the deposit to 0 once the shares are held `decay_secs`. A zero
+/// `fee_bps` disables it.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct ExitFee {
+  pub fee_bps: u64,
+  pub decay_secs: i64,
+}
+
+impl ExitFee {
+  pub const MAXIMUM_SIZE: usize = 8 + 8;
+
+  /// Fee on `amount` withdrawn `held_secs` after the last deposit.
+  pub fn fee(&self, amount: u64, held_secs: i64) -> Option<u64> {
+    if self.fee_bps == 0 || held_secs >= self.decay_secs {
+      return Some(0);
+    }
+    let remaining_secs = self.decay_secs.checked_sub(held_secs.max(0))? as u64;
+    let fee_bps = ratio_ceil!(self.fee_bps, remaining_secs, self.decay_secs as u64)?;
+    ratio_ceil!(amount, fee_bps, 10_000_u64)
+  }
+}
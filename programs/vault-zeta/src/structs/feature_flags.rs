@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+
+
+/// Instructions shipped dark, each one a bit of `FeatureFlags::flags`.
+/// New features go last, `Feature::ALL` counts up to the last variant.
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, PartialEq, Eq)]
+pub enum Feature {
+  // Solend obligation borrowing into the margin account
+  Leverage,
+  InKindRedemption,
+  FastRollover,
+}
+
+impl Feature {
+  const COUNT: u64 = Feature::FastRollover as u64 + 1;
+  pub const ALL: u64 = (1 << Feature::COUNT) - 1;
+
+  pub fn mask(&self) -> u64 {
+    1 << (*self as u8)
+  }
+}
+
+/// Program-wide switches of the features above, set by the protocol admin.
+/// Gated instructions fail until the account exists and their bit is set,
+/// so new code can be deployed disabled and turned on once reviewed.
+#[account]
+#[derive(Default)]
+pub struct FeatureFlags {
+  pub bump: u8,
+  pub flags: u64,
+}
+
+impl FeatureFlags {
+  pub const MAXIMUM_SIZE: usize = 1 + 8;
+
+  pub fn set(&mut self, bump: u8, flags: u64) -> Result<()> {
+    if flags & !Feature::ALL != 0 {
+      return err!(VaultError::InvalidConfig);
+    }
+    self.bump = bump;
+    self.flags = flags;
+    Ok(())
+  }
+
+  pub fn is_enabled(&self, feature: Feature) -> bool {
+    self.flags & feature.mask() != 0
+  }
+
+  pub fn check_enabled(&self, feature: Feature) -> Result<()> {
+    if !self.is_enabled(feature) {
+      return err!(VaultError::FeatureDisabled);
+    }
+    Ok(())
+  }
+}
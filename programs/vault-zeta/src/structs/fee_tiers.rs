@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_FEE_TIERS: usize = 4;
+pub const MAX_PERFORMANCE_FEE_BPS: u64 = 5_000;
+
+
+/// Performance fee for users locking shares for at least `min_lock_epochs`.
+/// Tiers with `min_lock_epochs == 0` are unused.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct FeeTier {
+  pub min_lock_epochs: u64,
+  pub performance_fee_bps: u64,
+}
+
+impl FeeTier {
+  pub const MAXIMUM_SIZE: usize = 8 * 2;
+
+  pub fn is_set(&self) -> bool {
+    self.min_lock_epochs > 0
+  }
+}
+
+/// Performance fee of a user locking for `lock_epochs`, `base_fee_bps` without a
+/// matching tier. The tier with the longest lock still satisfied wins.
+pub fn tiered_performance_fee_bps(
+  tiers: &[FeeTier; MAX_FEE_TIERS],
+  base_fee_bps: u64,
+  lock_epochs: u64,
+) -> u64 {
+  tiers
+    .iter()
+    .filter(|tier| tier.is_set() && tier.min_lock_epochs <= lock_epochs)
+    .max_by_key(|tier| tier.min_lock_epochs)
+    .map(|tier| tier.performance_fee_bps)
+    .unwrap_or(base_fee_bps)
+}
+
+/// Tiers are discounts, none may charge more than `base_fee_bps`.
+pub fn validate_fee_tiers(tiers: &[FeeTier; MAX_FEE_TIERS], base_fee_bps: u64) -> bool {
+  tiers
+    .iter()
+    .filter(|tier| tier.is_set())
+    .all(|tier| tier.performance_fee_bps <= base_fee_bps.min(MAX_PERFORMANCE_FEE_BPS))
+}
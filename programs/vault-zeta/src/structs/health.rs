@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_RISK_SCORE: u64 = 10_000;
+
+
+/// Snapshot of the vault risk inputs, returned by `health_check`.
+/// `risk_score` is the worst of the individual scores, in bps.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct HealthReport {
+  pub reserve_utilization_bps: u64,
+
+  pub margin_balance: u64,
+  pub initial_margin: u64,
+  pub maintenance_margin: u64,
+  pub account_equity: i64,
+  // maintenance margin over equity
+  pub margin_ratio_bps: u64,
+
+  // seconds since the last oracle update
+  pub oracle_age: u64,
+  pub oracle_stale: bool,
+
+  pub epoch: u64,
+  pub deposit_open: bool,
+  pub withdraw_open: bool,
+  pub receipt_epoch_live: bool,
+
+  pub risk_score: u64,
+}
+
+impl HealthReport {
+  pub fn margin_ratio_bps(maintenance_margin: u64, account_equity: i64) -> u64 {
+    if account_equity <= 0 {
+      return MAX_RISK_SCORE;
+    }
+    (maintenance_margin as u128)
+      .checked_mul(MAX_RISK_SCORE as u128).unwrap()
+      .checked_div(account_equity as u128).unwrap()
+      .min(MAX_RISK_SCORE as u128) as u64
+  }
+
+  pub fn compute_risk_score(&mut self) {
+    let oracle_score = if self.oracle_stale { MAX_RISK_SCORE } else { 0 };
+    self.risk_score = self.reserve_utilization_bps
+      .max(self.margin_ratio_bps)
+      .max(oracle_score)
+      .min(MAX_RISK_SCORE);
+  }
+}
@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+
+// drift base amounts are 9.dp, zeta positions 3.dp
+pub const DRIFT_BASE_PER_ZETA_POSITION: i64 = 1_000_000;
+
+
+/// Short perp hedge of the option delta on Drift. A zero `delta_band`
+/// disables it.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct DeltaHedge {
+  pub perp_market_index: u16,
+  // tolerated net delta, underlying units 3.dp
+  pub delta_band: u64,
+}
+
+impl DeltaHedge {
+  pub const MAXIMUM_SIZE: usize = 2 + 8;
+
+  pub fn is_enabled(&self) -> bool {
+    self.delta_band > 0
+  }
+
+  /// Perp base amount (9.dp) to trade so the net delta goes back to zero, none
+  /// while it is inside the band. Positive buys, negative sells.
+  pub fn rebalance_amount(&self, option_delta: i64, perp_base_asset_amount: i64) -> Option<i64> {
+    let perp_delta = perp_base_asset_amount / DRIFT_BASE_PER_ZETA_POSITION;
+    let net_delta = option_delta.checked_add(perp_delta)?;
+    if net_delta.unsigned_abs() <= self.delta_band {
+      return None;
+    }
+    net_delta.checked_neg()?.checked_mul(DRIFT_BASE_PER_ZETA_POSITION)
+  }
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+// at most half of the premium goes to the buffer
+pub const MAX_INSURANCE_BPS: u64 = 5_000;
+
+
+/// Buffer carved out of settled premium into the insurance token account.
+/// Amounts are USDC (platform precision). The carve-out is booked as a loss
+/// at settlement, `due` is what still waits in the usdc vault for
+/// `fund_insurance`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct InsuranceFund {
+  pub carve_out_bps: u64,
+  pub token_account: Pubkey,
+  pub due: u64,
+  pub balance: u64,
+  // paid back into the vault by `cover_loss`
+  pub covered: u64,
+}
+
+impl InsuranceFund {
+  pub const MAXIMUM_SIZE: usize = 8 + 32 + 8 * 3;
+
+  /// Carve-out of `premium`, rounded down. Nothing accrues before the token
+  /// account exists.
+  pub fn carve_out(&self, premium: u64) -> u64 {
+    if self.token_account == Pubkey::default() {
+      return 0;
+    }
+    (premium as u128 * self.carve_out_bps as u128 / 10_000) as u64
+  }
+
+  pub fn after_carve_out(&mut self, amount: u64) {
+    self.due = self.due
+      .checked_add(amount).unwrap();
+  }
+
+  pub fn after_funded(&mut self, amount: u64) {
+    self.due = self.due
+      .checked_sub(amount).unwrap();
+    self.balance = self.balance
+      .checked_add(amount).unwrap();
+  }
+
+  pub fn after_cover(&mut self, amount: u64) {
+    self.balance = self.balance
+      .checked_sub(amount).unwrap();
+    self.covered = self.covered
+      .checked_add(amount).unwrap();
+  }
+}
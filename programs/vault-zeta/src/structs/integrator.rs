@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+
+/// Whitelisted integrator allowed to deposit on behalf of other users.
+/// `authority` signs `deposit_for`, usually a PDA of the integrator program.
+#[account]
+#[derive(Default)]
+pub struct Integrator {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub authority: Pubkey,
+  pub total_deposit: u64,
+}
+
+impl Integrator {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8;
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, authority: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.authority = authority;
+    Ok(())
+  }
+
+  pub fn after_deposit(&mut self, amount: u64) -> Result<()> {
+    self.total_deposit = self.total_deposit
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+}
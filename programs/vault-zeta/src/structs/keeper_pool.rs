@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+
+// fee of a single signature transaction, what a crank is refunded
+pub const ESTIMATED_TX_FEE_LAMPORTS: u64 = 5_000;
+
+/// Lamports the authority sets aside to refund the fee of permissionless
+/// cranks. The pool account holds them itself, above its rent exemption.
+#[account]
+#[derive(Default)]
+pub struct KeeperPool {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub total_refunded: u64,
+  // one refund per slot, a crank repeated in a slot is paid once
+  pub last_refund_slot: u64,
+}
+
+impl KeeperPool {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 + 8 * 2;
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    Ok(())
+  }
+
+  /// Lamports refunded for a crank landing in `slot`, 0 once the pool ran
+  /// dry or already paid in this slot.
+  pub fn refund_amount(&self, available_lamports: u64, slot: u64) -> u64 {
+    if slot <= self.last_refund_slot || available_lamports < ESTIMATED_TX_FEE_LAMPORTS {
+      return 0;
+    }
+    ESTIMATED_TX_FEE_LAMPORTS
+  }
+
+  pub fn after_refund(&mut self, amount: u64, slot: u64) -> Result<()> {
+    self.total_refunded = self.total_refunded
+      .checked_add(amount).unwrap();
+    self.last_refund_slot = slot;
+    Ok(())
+  }
+}
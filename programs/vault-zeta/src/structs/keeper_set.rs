@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+
+pub const MAX_COSIGNERS: usize = 3;
+
+
+/// Second keeper keys for large orders. Once the orders of an epoch add up
+/// to more than `cosign_notional` (USDC, 6.dp) the next ones need one of
+/// `cosigners` to sign next to the authority, a single stolen key can only
+/// place that much. 0 disables it, unused slots are default.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct KeeperSet {
+  pub cosigners: [Pubkey; MAX_COSIGNERS],
+  pub cosign_notional: u64,
+}
+
+impl KeeperSet {
+  pub const MAXIMUM_SIZE: usize = 32 * MAX_COSIGNERS + 8;
+
+  /// A threshold needs at least one cosigner, and the authority can't
+  /// cosign its own orders.
+  pub fn is_valid(&self, authority: &Pubkey) -> bool {
+    let has_cosigner = self.cosigners.iter().any(|cosigner| *cosigner != Pubkey::default());
+    !self.cosigners.contains(authority) && (self.cosign_notional == 0 || has_cosigner)
+  }
+
+  /// Whether one of `accounts` is a cosigner that signed the transaction.
+  pub fn is_cosigned(&self, accounts: &[AccountInfo]) -> bool {
+    accounts.iter().any(|account| {
+      account.is_signer && *account.key != Pubkey::default() && self.cosigners.contains(account.key)
+    })
+  }
+
+  pub fn needs_cosigner(&self, notional: u64) -> bool {
+    self.cosign_notional > 0 && notional > self.cosign_notional
+  }
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use crate::ratio_floor;
+
+
+/// USDC leg of vaults whose underlying isn't Zeta's quote asset. The margin
+/// is bought with underlying through `swap`, so USDC held by the vault is
+/// marked to the reserve oracle in NAV instead of rescaled 1:1, and the
+/// underlying spent on it is kept as its cost basis. The difference between
+/// the last mark and the basis is the FX pnl the booked NAV, and so the share
+/// price, carries on top of the realized gains and losses.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct MarginBasis {
+  pub enabled: bool,
+  // USDC deposited to and withdrawn from the zeta margin account, platform units
+  pub usdc_deposited: u64,
+  pub usdc_withdrawn: u64,
+  // USDC bought with underlying and not sold back yet
+  pub usdc_held: u64,
+  // underlying spent on `usdc_held`
+  pub underlying_cost: u64,
+  // `usdc_held` in underlying at the reserve oracle, as of the last valuation
+  pub marked_value: u64,
+}
+
+impl MarginBasis {
+  pub const MAXIMUM_SIZE: usize = 1 + 8 * 5;
+
+  /// Bought USDC is marked at its cost until the next valuation.
+  pub fn after_usdc_bought(&mut self, underlying_spent: u64, usdc_received: u64) -> Result<()> {
+    self.usdc_held = self.usdc_held
+      .checked_add(usdc_received).unwrap();
+    self.underlying_cost = self.underlying_cost
+      .checked_add(underlying_spent).unwrap();
+    self.marked_value = self.marked_value
+      .checked_add(underlying_spent).unwrap();
+    Ok(())
+  }
+
+  /// Releases the cost basis of `usdc_spent` pro-rata, USDC beyond the
+  /// tracked amount (zeta pnl) carries no basis.
+  pub fn after_usdc_sold(&mut self, usdc_spent: u64) -> Result<()> {
+    if self.usdc_held == 0 {
+      return Ok(());
+    }
+    let tracked = usdc_spent.min(self.usdc_held);
+    let cost = ratio_floor!(self.underlying_cost, tracked, self.usdc_held).unwrap();
+    let marked = ratio_floor!(self.marked_value, tracked, self.usdc_held).unwrap();
+    self.underlying_cost = self.underlying_cost
+      .checked_sub(cost).unwrap();
+    self.marked_value = self.marked_value
+      .checked_sub(marked).unwrap();
+    self.usdc_held = self.usdc_held
+      .checked_sub(tracked).unwrap();
+    Ok(())
+  }
+
+  pub fn after_margin_deposit(&mut self, amount: u64) -> Result<()> {
+    self.usdc_deposited = self.usdc_deposited
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  pub fn after_margin_withdraw(&mut self, amount: u64) -> Result<()> {
+    self.usdc_withdrawn = self.usdc_withdrawn
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// USDC moved into the margin account and not taken out yet.
+  pub fn net_margin_deposits(&self) -> u64 {
+    self.usdc_deposited.saturating_sub(self.usdc_withdrawn)
+  }
+
+  /// Marks `usdc_held` at `marked_value` underlying, see `SolvencyAccounts::record_valuation`.
+  pub fn mark(&mut self, marked_value: u64) {
+    if self.enabled {
+      self.marked_value = marked_value;
+    }
+  }
+
+  /// FX gain of the USDC leg over its basis, counted in the booked NAV.
+  pub fn unrealized_gain(&self) -> u64 {
+    if !self.enabled {
+      return 0;
+    }
+    self.marked_value.saturating_sub(self.underlying_cost)
+  }
+
+  /// FX loss of the USDC leg below its basis, counted in the booked NAV.
+  pub fn unrealized_loss(&self) -> u64 {
+    if !self.enabled {
+      return 0;
+    }
+    self.underlying_cost.saturating_sub(self.marked_value)
+  }
+}
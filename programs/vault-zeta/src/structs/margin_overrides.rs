@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::cpi_calls::zeta::MarginParameters;
+use crate::constants::NATIVE_PRECISION_DENOMINATOR;
+
+
+/// Per-vault margin parameters the vault sizes its orders and margin with, in
+/// place of the zeta group ones. Zeta's 8.dp percentages, zero keeps the
+/// zeta group value. Overrides can only be more conservative than Zeta.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct MarginOverrides {
+  pub option_short_put_cap_percentage: u64,
+  pub option_dynamic_percentage_short_initial: u64,
+  pub option_dynamic_percentage_short_maintenance: u64,
+}
+
+impl MarginOverrides {
+  pub const MAXIMUM_SIZE: usize = 8 * 3;
+
+  /// Each override has to be at least the zeta group value and at most 100%.
+  pub fn is_valid(&self, margin_parameters: &MarginParameters) -> bool {
+    [
+      (self.option_short_put_cap_percentage, margin_parameters.option_short_put_cap_percentage),
+      (self.option_dynamic_percentage_short_initial, margin_parameters.option_dynamic_percentage_short_initial),
+      (self.option_dynamic_percentage_short_maintenance, margin_parameters.option_dynamic_percentage_short_maintenance),
+    ].iter().all(|&(value, zeta_value)| {
+      value == 0 || (value >= zeta_value && value as u128 <= NATIVE_PRECISION_DENOMINATOR)
+    })
+  }
+
+  /// The zeta group may raise its parameters after the overrides were set,
+  /// each field takes the larger of the override and the zeta group value.
+  pub fn apply(&self, margin_parameters: &MarginParameters) -> MarginParameters {
+    let mut parameters = *margin_parameters;
+    parameters.option_short_put_cap_percentage = parameters.option_short_put_cap_percentage
+      .max(self.option_short_put_cap_percentage);
+    parameters.option_dynamic_percentage_short_initial = parameters.option_dynamic_percentage_short_initial
+      .max(self.option_dynamic_percentage_short_initial);
+    parameters.option_dynamic_percentage_short_maintenance = parameters.option_dynamic_percentage_short_maintenance
+      .max(self.option_dynamic_percentage_short_maintenance);
+    parameters
+  }
+}
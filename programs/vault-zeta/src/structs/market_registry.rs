@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use crate::{ratio_floor, VaultError};
+use crate::cpi_calls::zeta::{get_products_slice_market_index, Kind, ZetaGroup};
+use crate::constants::{ACTIVE_EXPIRIES, NUM_PRODUCTS_PER_SERIES};
+
+
+/// Zeta markets the executor may open orders on. A market has to pass the
+/// allowlist and both rules, each of them is disabled at 0.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct MarketRegistry {
+  // one bit per zeta group market index
+  pub allowed_markets: u64,
+  // series counted from the front expiry
+  pub max_expiries: u8,
+  // option strikes within this distance of spot, futures are exempt
+  pub max_strike_distance_bps: u64,
+}
+
+impl MarketRegistry {
+  pub const MAXIMUM_SIZE: usize = 8 + 1 + 8;
+
+  /// Allowlist of (expiry index, index in the series products) pairs, none
+  /// when a pair is out of range. An empty list allows every market.
+  pub fn allowlist(markets: &[[u8; 2]]) -> Option<u64> {
+    let mut allowed_markets = 0u64;
+    for [expiry_index, product_index] in markets {
+      let (expiry_index, product_index) = (*expiry_index as usize, *product_index as usize);
+      if expiry_index >= ACTIVE_EXPIRIES || product_index >= NUM_PRODUCTS_PER_SERIES {
+        return None;
+      }
+      allowed_markets |= 1u64 << get_products_slice_market_index(expiry_index, product_index).ok()?;
+    }
+    Some(allowed_markets)
+  }
+
+  /// Whether the allowlist has the bit of `market_index`, indexes past the
+  /// bitmap are never listed.
+  fn is_listed(&self, market_index: usize) -> bool {
+    u32::try_from(market_index).ok()
+      .and_then(|index| 1u64.checked_shl(index))
+      .map_or(false, |bit| self.allowed_markets & bit != 0)
+  }
+
+  pub fn needs_spot(&self) -> bool {
+    self.max_strike_distance_bps > 0
+  }
+
+  /// Whether orders may be placed on the market at `market_index`, `spot`
+  /// is only read when `needs_spot`.
+  pub fn is_allowed(&self, zeta_group: &ZetaGroup, market_index: usize, spot: u64) -> Result<bool> {
+    if self.allowed_markets != 0 && !self.is_listed(market_index) {
+      return Ok(false);
+    }
+    if self.max_expiries > 0 {
+      // the series roll, the nearest one sits at `front_expiry_index`
+      let expiry_index = zeta_group.get_expiry_index_by_product_index(market_index)?;
+      let distance = (expiry_index + ACTIVE_EXPIRIES - zeta_group.front_expiry_index as usize) % ACTIVE_EXPIRIES;
+      if distance >= self.max_expiries as usize {
+        return Ok(false);
+      }
+    }
+    if self.needs_spot() && zeta_group.products[market_index].kind != Kind::Future {
+      let strike = zeta_group.get_strike(market_index)?;
+      let max_distance = ratio_floor!(spot, self.max_strike_distance_bps, 10_000_u64)
+        .ok_or_else(|| error!(VaultError::MathOverflow))?;
+      let distance = if strike > spot { strike - spot } else { spot - strike };
+      if distance > max_distance {
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn allowlist_sets_the_series_market_bits() {
+    let allowed_markets = MarketRegistry::allowlist(&[[0, 0], [1, 3]]).unwrap();
+    let registry = MarketRegistry { allowed_markets, ..Default::default() };
+    assert!(registry.is_listed(0));
+    assert!(registry.is_listed(NUM_PRODUCTS_PER_SERIES + 3));
+    assert!(!registry.is_listed(1));
+    assert!(!registry.is_listed(64));
+  }
+
+  #[test]
+  fn allowlist_rejects_out_of_range_pairs() {
+    assert!(MarketRegistry::allowlist(&[[ACTIVE_EXPIRIES as u8, 0]]).is_none());
+    assert!(MarketRegistry::allowlist(&[[0, NUM_PRODUCTS_PER_SERIES as u8]]).is_none());
+    assert_eq!(MarketRegistry::allowlist(&[]), Some(0));
+  }
+}
@@ -1,2 +1,78 @@
 mod vault;
+mod share_price;
+mod receipts;
+mod user;
+mod config;
+mod setup;
+mod epoch;
+mod snapshot;
+mod health;
+mod withdraw_request;
+mod integrator;
+mod fee_tiers;
+mod share_lock;
+mod decimals;
+mod throttle;
+mod premium_floor;
+mod track_record;
+mod venue;
+mod hedge;
+mod insurance;
+mod user_position;
+mod return_data;
+mod permit;
+mod order_tracker;
+mod rewards;
+mod margin_basis;
+mod preset;
+mod keeper_pool;
+mod market_registry;
+mod protocol_config;
+mod bond;
+mod pending_deposit;
+mod exit_fee;
+mod margin_overrides;
+mod error_stats;
+mod order_tag;
+mod keeper_set;
+mod feature_flags;
+mod deposit_receipt;
 pub use vault::*;
+pub use share_price::*;
+pub use receipts::*;
+pub use user::*;
+pub use config::*;
+pub use setup::*;
+pub use epoch::*;
+pub use snapshot::*;
+pub use health::*;
+pub use withdraw_request::*;
+pub use integrator::*;
+pub use fee_tiers::*;
+pub use share_lock::*;
+pub use decimals::*;
+pub use throttle::*;
+pub use premium_floor::*;
+pub use track_record::*;
+pub use venue::*;
+pub use hedge::*;
+pub use insurance::*;
+pub use user_position::*;
+pub use return_data::*;
+pub use permit::*;
+pub use order_tracker::*;
+pub use rewards::*;
+pub use margin_basis::*;
+pub use preset::*;
+pub use keeper_pool::*;
+pub use market_registry::*;
+pub use protocol_config::*;
+pub use bond::*;
+pub use pending_deposit::*;
+pub use exit_fee::*;
+pub use margin_overrides::*;
+pub use error_stats::*;
+pub use order_tag::*;
+pub use keeper_set::*;
+pub use feature_flags::*;
+pub use deposit_receipt::*;
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+
+// zeta doesn't store the tag, it only shows up in the transaction data
+pub const VAULT_ORDER_TAG: &str = "yasp-vault";
+pub const MAX_ORDER_TAG_LEN: usize = 16;
+
+
+/// Tag forwarded with the vault's zeta orders. Keeper tags are short
+/// lowercase ascii so they can't bloat the transaction or carry arbitrary
+/// data, no tag falls back to `VAULT_ORDER_TAG`.
+pub fn keeper_order_tag(tag: Option<String>) -> Result<Option<String>> {
+  let tag = match tag {
+    Some(tag) => tag,
+    None => return Ok(Some(VAULT_ORDER_TAG.to_string())),
+  };
+  let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-';
+  if tag.is_empty() || tag.len() > MAX_ORDER_TAG_LEN || !tag.chars().all(valid_char) {
+    msg!("order tag length: {}", tag.len());
+    return err!(VaultError::InvalidOrderTag);
+  }
+  Ok(Some(tag))
+}
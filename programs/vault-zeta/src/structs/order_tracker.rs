@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use crate::{ratio_floor, VaultError};
+
+
+/// Keeper order prices of one market in the current epoch. The first order
+/// of an epoch sets `reference_price`, reprices can't walk more than
+/// `max_reprice_walk_bps` away from it until the next epoch. Also the
+/// vault's own record of its position in the market, see `reconcile`.
+#[account]
+#[derive(Default)]
+pub struct OrderTracker {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub market: Pubkey,
+  pub epoch: u64,
+  pub reference_price: u64,
+  pub last_price: u64,
+  // lots, 3.dp, as of `last_reconcile_slot` plus the bids placed since
+  pub position: i64,
+  pub open_bids: u64,
+  pub last_reconcile_slot: u64,
+}
+
+impl OrderTracker {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 3 + 8 * 3;
+
+  pub fn is_initialized(&self) -> bool {
+    self.vault != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, market: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.market = market;
+    Ok(())
+  }
+
+  /// Records an order at `price`, a new epoch resets the reference.
+  pub fn after_order(&mut self, price: u64, epoch: u64) -> Result<()> {
+    if epoch != self.epoch || self.reference_price == 0 {
+      self.epoch = epoch;
+      self.reference_price = price;
+    }
+    self.last_price = price;
+    Ok(())
+  }
+
+  pub fn after_bid_placed(&mut self, size: u64) -> Result<()> {
+    self.open_bids = self.open_bids.checked_add(size).unwrap();
+    Ok(())
+  }
+
+  /// Takes cancelled lots off the record, bids that filled meanwhile are
+  /// already gone from `open_bids` after a reconcile.
+  pub fn after_bid_cancelled(&mut self, size: u64) -> Result<()> {
+    self.open_bids = self.open_bids.saturating_sub(size);
+    Ok(())
+  }
+
+  /// Lots the vault is exposed to if every recorded bid fills. Fills only
+  /// move lots from `open_bids` to `position`, so this stays put until an
+  /// order is cancelled, expires or the position is liquidated.
+  pub fn recorded_exposure(&self) -> i64 {
+    self.position.checked_add(self.open_bids as i64).unwrap()
+  }
+
+  /// Replaces the record with the margin account ledger, returns the
+  /// exposure drift between the two.
+  pub fn reconcile(&mut self, position: i64, open_bids: u64, slot: u64) -> Result<i64> {
+    let drift = position
+      .checked_add(open_bids as i64).unwrap()
+      .checked_sub(self.recorded_exposure()).unwrap();
+    self.position = position;
+    self.open_bids = open_bids;
+    self.last_reconcile_slot = slot;
+    Ok(drift)
+  }
+
+  /// Fails when a reprice to `price` is further than `max_walk_bps` from the
+  /// reference price of `epoch`, 0 disables the check.
+  pub fn check_reprice(&self, price: u64, epoch: u64, max_walk_bps: u64) -> Result<()> {
+    if max_walk_bps == 0 || epoch != self.epoch || self.reference_price == 0 {
+      return Ok(());
+    }
+    let max_walk = ratio_floor!(self.reference_price, max_walk_bps, 10_000_u64).unwrap();
+    let walk = if price > self.reference_price {
+      price - self.reference_price
+    } else {
+      self.reference_price - price
+    };
+    if walk > max_walk {
+      msg!("price: {}, reference price: {}", price, self.reference_price);
+      return err!(VaultError::RepriceWalkExceeded);
+    }
+    Ok(())
+  }
+}
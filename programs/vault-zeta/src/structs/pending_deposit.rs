@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+
+
+/// Underlying a user queued for the next epoch, held in the vault pending
+/// deposits escrow until `convert_pending_deposit`. It converts during the
+/// epoch after the one it was queued in only, later it is refunded.
+#[account]
+#[derive(Default)]
+pub struct PendingDeposit {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub owner: Pubkey,
+  pub amount: u64,
+  // epoch the amount was queued in, it converts in the next one
+  pub epoch: u64,
+}
+
+impl PendingDeposit {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 2;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.owner = owner;
+    Ok(())
+  }
+
+  pub fn is_due(&self, epoch: u64) -> bool {
+    self.amount > 0 && Some(epoch) == self.epoch.checked_add(1)
+  }
+
+  /// Missed its conversion epoch, the entry price of that epoch is gone.
+  pub fn is_expired(&self, epoch: u64) -> bool {
+    self.amount > 0 && epoch > self.epoch.saturating_add(1)
+  }
+
+  pub fn queue(&mut self, amount: u64, epoch: u64) -> Result<()> {
+    if self.amount > 0 && self.epoch != epoch {
+      return err!(VaultError::PendingDepositNotConverted);
+    }
+    self.amount = self.amount
+      .checked_add(amount).unwrap();
+    self.epoch = epoch;
+    Ok(())
+  }
+
+  pub fn take(&mut self) -> u64 {
+    let amount = self.amount;
+    self.amount = 0;
+    amount
+  }
+}
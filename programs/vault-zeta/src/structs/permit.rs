@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use crate::VaultError;
+
+pub mod ed25519_program {
+  anchor_lang::prelude::declare_id!("Ed25519SigVerify111111111111111111111111111");
+}
+
+// prefix of the signed message, keeps intents from passing as any other payload
+pub const DEPOSIT_INTENT_DOMAIN: &[u8] = b"vault-zeta:deposit-intent";
+
+// ed25519 program data: count, padding, then one offsets struct of 7 u16
+const ED25519_OFFSETS_START: usize = 2;
+const ED25519_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+const SIGNATURE_SIZE: usize = 64;
+
+
+/// Deposit a user signed off-chain, executed by a relayer through
+/// `deposit_with_signature`. `nonce` must match the user's `permit_nonce`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct DepositIntent {
+  pub vault: Pubkey,
+  pub amount: u64,
+  pub expiry: i64,
+  pub nonce: u64,
+}
+
+impl DepositIntent {
+  /// Bytes the user signs.
+  pub fn message(&self) -> Result<Vec<u8>> {
+    let mut message = DEPOSIT_INTENT_DOMAIN.to_vec();
+    message.extend_from_slice(&self.try_to_vec()?);
+    Ok(message)
+  }
+
+  pub fn check(&self, vault: &Pubkey, nonce: u64, now: i64) -> Result<()> {
+    if self.vault != *vault {
+      return err!(VaultError::InvalidDepositIntent);
+    }
+    if now > self.expiry {
+      return err!(VaultError::DepositIntentExpired);
+    }
+    if self.nonce != nonce {
+      return err!(VaultError::InvalidDepositIntent);
+    }
+    Ok(())
+  }
+
+  /// Checks that `ix` is an ed25519 program instruction verifying a single
+  /// signature of `signer` over this intent, with everything inlined.
+  pub fn check_signature(&self, ix: &Instruction, signer: &Pubkey) -> Result<()> {
+    if ix.program_id != ed25519_program::ID || !ix.accounts.is_empty() {
+      return err!(VaultError::InvalidDepositSignature);
+    }
+    let data = &ix.data;
+    if data.len() < ED25519_OFFSETS_START + ED25519_OFFSETS_SIZE || data[0] != 1 {
+      return err!(VaultError::InvalidDepositSignature);
+    }
+    let offsets: Vec<u16> = data[ED25519_OFFSETS_START..ED25519_OFFSETS_START + ED25519_OFFSETS_SIZE]
+      .chunks(2)
+      .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+      .collect();
+    let (signature_offset, signature_ix) = (offsets[0] as usize, offsets[1]);
+    let (pubkey_offset, pubkey_ix) = (offsets[2] as usize, offsets[3]);
+    let (message_offset, message_size, message_ix) = (offsets[4] as usize, offsets[5] as usize, offsets[6]);
+    // all data has to live in the ed25519 instruction itself
+    if signature_ix != u16::MAX || pubkey_ix != u16::MAX || message_ix != u16::MAX {
+      return err!(VaultError::InvalidDepositSignature);
+    }
+    if data.len() < signature_offset + SIGNATURE_SIZE {
+      return err!(VaultError::InvalidDepositSignature);
+    }
+
+    let pubkey = data.get(pubkey_offset..pubkey_offset + PUBKEY_SIZE);
+    let message = data.get(message_offset..message_offset + message_size);
+    if pubkey != Some(signer.as_ref()) || message != Some(self.message()?.as_slice()) {
+      return err!(VaultError::InvalidDepositSignature);
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::*;
+use crate::cpi_calls::zeta::{Kind, SECONDS_IN_A_YEAR};
+
+// 500 vol points, zeta's own volatility ceiling
+pub const MAX_IV_FLOOR_BPS: u64 = 50_000;
+
+
+/// Minimum premium for selling options, the Black-Scholes price (zero rates)
+/// at an admin-set implied volatility floor. 0 disables it.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct PremiumFloor {
+  pub min_iv_bps: u64,
+}
+
+impl PremiumFloor {
+  pub const MAXIMUM_SIZE: usize = 8;
+
+  pub fn is_enabled(&self) -> bool {
+    self.min_iv_bps > 0
+  }
+
+  /// Premium per contract in the precision of `spot` and `strike`, rounded
+  /// down. Expired options are worth their intrinsic value, futures have no
+  /// floor.
+  pub fn min_premium(&self, kind: Kind, spot: u64, strike: u64, seconds_to_expiry: u64) -> Option<u64> {
+    let intrinsic = match kind {
+      Kind::Call => spot.saturating_sub(strike),
+      Kind::Put => strike.saturating_sub(spot),
+      Kind::Future | Kind::Uninitialized => return Some(0),
+    };
+    if seconds_to_expiry == 0 || spot == 0 || strike == 0 {
+      return Some(intrinsic);
+    }
+
+    let spot = Decimal::from(spot);
+    let strike = Decimal::from(strike);
+    let sigma = Decimal::from(self.min_iv_bps).checked_div(Decimal::from(10_000))?;
+    let t = Decimal::from(seconds_to_expiry).checked_div(Decimal::from(SECONDS_IN_A_YEAR))?;
+    let sigma_sqrt_t = sigma.checked_mul(t.sqrt()?)?;
+    let d1 = spot.checked_div(strike)?.ln()
+      .checked_add(sigma.checked_mul(sigma)?.checked_mul(t)?.checked_div(Decimal::from(2))?)?
+      .checked_div(sigma_sqrt_t)?;
+    let d2 = d1.checked_sub(sigma_sqrt_t)?;
+
+    let premium = match kind {
+      Kind::Call => spot.checked_mul(d1.norm_cdf())?
+        .checked_sub(strike.checked_mul(d2.norm_cdf())?)?,
+      _ => strike.checked_mul((-d2).norm_cdf())?
+        .checked_sub(spot.checked_mul((-d1).norm_cdf())?)?,
+    };
+    // the approximation can dip below intrinsic deep in the money
+    Some(premium.max(Decimal::ZERO).floor().to_u64()?.max(intrinsic))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const SPOT: u64 = 100_000_000;
+  const MONTH: u64 = 30 * 86_400;
+
+  fn floor(min_iv_bps: u64) -> PremiumFloor {
+    PremiumFloor { min_iv_bps }
+  }
+
+  fn assert_close(value: u64, expected: u64, tolerance: u64) {
+    assert!(
+      value.abs_diff(expected) <= tolerance,
+      "value: {}, expected: {}", value, expected
+    );
+  }
+
+  #[test]
+  fn prices_at_the_money_call() {
+    let premium = floor(5_000).min_premium(Kind::Call, SPOT, SPOT, SECONDS_IN_A_YEAR).unwrap();
+    assert_close(premium, 19_741_265, 2_000);
+  }
+
+  #[test]
+  fn put_call_parity_holds() {
+    let strike = 110_000_000;
+    let call = floor(8_000).min_premium(Kind::Call, SPOT, strike, MONTH).unwrap();
+    let put = floor(8_000).min_premium(Kind::Put, SPOT, strike, MONTH).unwrap();
+    assert_close(call, 5_398_023, 2_000);
+    assert_close(put, 15_398_023, 2_000);
+    // zero rates: put - call = strike - spot
+    assert_close(put - call, strike - SPOT, 2);
+  }
+
+  #[test]
+  fn premium_grows_with_iv() {
+    let low = floor(3_000).min_premium(Kind::Call, SPOT, 120_000_000, MONTH).unwrap();
+    let high = floor(9_000).min_premium(Kind::Call, SPOT, 120_000_000, MONTH).unwrap();
+    assert!(low < high);
+  }
+
+  #[test]
+  fn never_below_intrinsic() {
+    let premium = floor(100).min_premium(Kind::Put, SPOT, 200_000_000, MONTH).unwrap();
+    assert!(premium >= 100_000_000);
+    let premium = floor(100).min_premium(Kind::Call, 200_000_000, SPOT, MONTH).unwrap();
+    assert!(premium >= 100_000_000);
+  }
+
+  #[test]
+  fn expired_options_and_futures() {
+    assert_eq!(floor(5_000).min_premium(Kind::Call, SPOT, 90_000_000, 0), Some(10_000_000));
+    assert_eq!(floor(5_000).min_premium(Kind::Put, SPOT, 90_000_000, 0), Some(0));
+    assert_eq!(floor(5_000).min_premium(Kind::Future, SPOT, 0, MONTH), Some(0));
+  }
+}
@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::structs::VaultConfigParams;
+
+
+/// Default strategy, risk and fee parameters for `initialize_with_preset`.
+/// Only size independent parameters are filled in, absolute limits (delta,
+/// vega, notional) and the Drift hedge are left to the authority as they
+/// depend on the vault size and `setup_drift_user`.
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, PartialEq, Eq)]
+pub enum VaultPreset {
+  ConservativeCoveredCall,
+  AggressivePutSelling,
+  DeltaNeutral,
+}
+
+impl VaultPreset {
+  pub fn management_fee_bps(&self) -> u64 {
+    match self {
+      VaultPreset::ConservativeCoveredCall => 100,
+      VaultPreset::AggressivePutSelling => 200,
+      VaultPreset::DeltaNeutral => 150,
+    }
+  }
+
+  /// Goes through `update_config`, so presets are validated like manual configs.
+  pub fn config(&self) -> VaultConfigParams {
+    let (performance_fee_bps, ladder_weights_bps, min_iv_bps, max_ltv_bps, insurance_bps) = match self {
+      // far OTM calls spread over the ladder, no leverage
+      VaultPreset::ConservativeCoveredCall => (1_000, [4_000, 3_000, 2_000, 1_000], 3_000, 0, 1_000),
+      // puts concentrated near the money, borrowing allowed up to half the collateral
+      VaultPreset::AggressivePutSelling => (2_000, [7_000, 3_000, 0, 0], 5_000, 5_000, 500),
+      // straddle legs, the hedge is enabled once the drift user is set up
+      VaultPreset::DeltaNeutral => (1_500, [5_000, 5_000, 0, 0], 4_000, 2_500, 1_000),
+    };
+    VaultConfigParams {
+      management_fee_bps: Some(self.management_fee_bps()),
+      performance_fee_bps: Some(performance_fee_bps),
+      ladder_weights_bps: Some(ladder_weights_bps),
+      min_iv_bps: Some(min_iv_bps),
+      max_ltv_bps: Some(max_ltv_bps),
+      insurance_bps: Some(insurance_bps),
+      instant_exit_fee_bps: Some(50),
+      max_mark_deviation_bps: Some(500),
+      max_reprice_walk_bps: Some(2_000),
+      post_only: Some(true),
+      ..VaultConfigParams::default()
+    }
+  }
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use crate::{ratio_floor, VaultError};
+
+
+pub const MAX_PROTOCOL_FEE_BPS: u64 = 5_000;
+
+/// Program-wide settings, one per deployment. The admin is set by the
+/// upgrade authority at creation and only rotated by itself afterwards.
+/// Operators bond `min_bond` of `bond_mint` (the vault underlying while it
+/// is unset) before their vault takes deposits, the guardian slashes it.
+#[account]
+#[derive(Default)]
+pub struct ProtocolConfig {
+  pub bump: u8,
+  pub admin: Pubkey,
+  // owner of the share accounts the protocol fee is paid to
+  pub treasury: Pubkey,
+  // part of every vault fee routed to the treasury
+  pub protocol_fee_bps: u64,
+  pub guardian: Pubkey,
+  pub bond_mint: Pubkey,
+  pub min_bond: u64,
+}
+
+/// Partial protocol config update, `None` fields are left untouched.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Clone)]
+pub struct ProtocolConfigParams {
+  pub admin: Option<Pubkey>,
+  pub treasury: Option<Pubkey>,
+  pub protocol_fee_bps: Option<u64>,
+  pub guardian: Option<Pubkey>,
+  pub bond_mint: Option<Pubkey>,
+  pub min_bond: Option<u64>,
+}
+
+impl ProtocolConfig {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 + 32 * 2 + 8;
+
+  pub fn initialize(&mut self, bump: u8, admin: Pubkey, treasury: Pubkey, protocol_fee_bps: u64) -> Result<()> {
+    self.bump = bump;
+    self.admin = admin;
+    self.update(&ProtocolConfigParams {
+      treasury: Some(treasury),
+      protocol_fee_bps: Some(protocol_fee_bps),
+      ..ProtocolConfigParams::default()
+    })
+  }
+
+  pub fn update(&mut self, params: &ProtocolConfigParams) -> Result<()> {
+    if let Some(protocol_fee_bps) = params.protocol_fee_bps {
+      if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if params.treasury == Some(Pubkey::default()) || params.admin == Some(Pubkey::default()) {
+      return err!(VaultError::InvalidConfig);
+    }
+    if let Some(admin) = params.admin {
+      self.admin = admin;
+    }
+    if let Some(treasury) = params.treasury {
+      self.treasury = treasury;
+    }
+    if let Some(protocol_fee_bps) = params.protocol_fee_bps {
+      self.protocol_fee_bps = protocol_fee_bps;
+    }
+    if let Some(guardian) = params.guardian {
+      self.guardian = guardian;
+    }
+    if let Some(bond_mint) = params.bond_mint {
+      self.bond_mint = bond_mint;
+    }
+    if let Some(min_bond) = params.min_bond {
+      self.min_bond = min_bond;
+    }
+    Ok(())
+  }
+
+  /// Mint bonds of a vault with `underlying_mint` are posted in.
+  pub fn bond_mint_for(&self, underlying_mint: Pubkey) -> Pubkey {
+    if self.bond_mint == Pubkey::default() {
+      underlying_mint
+    } else {
+      self.bond_mint
+    }
+  }
+
+  /// Protocol part of `fee_shares`, rounded down in favour of the operator.
+  pub fn protocol_fee(&self, fee_shares: u64) -> Option<u64> {
+    ratio_floor!(fee_shares, self.protocol_fee_bps, 10_000_u64)
+  }
+}
@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use num_traits::ToPrimitive;
+use std::cmp::min;
+use crate::{ratio, ratio_floor};
+use crate::structs::SHARE_PRICE_PRECISION;
+
+
+/// Principal/yield split of vault shares locked for a single epoch.
+/// Principal holders get back the value of the locked shares at the
+/// epoch start, yield holders get everything realized on top of it.
+#[account]
+#[derive(Default)]
+pub struct EpochReceipts {
+  pub bump: u8,
+  pub principal_mint_bump: u8,
+  pub yield_mint_bump: u8,
+  pub escrow_bump: u8,
+
+  pub vault: Pubkey,
+  pub principal_mint: Pubkey,
+  pub yield_mint: Pubkey,
+  pub shares_escrow: Pubkey,
+
+  pub epoch: u64,
+  pub locked_shares: u64,
+  // share price at the epoch start and at settlement (see SHARE_PRICE_PRECISION)
+  pub start_price: u64,
+  pub end_price: u64,
+  pub realized_premium: u64,
+
+  pub created_at: i64,
+  pub settled_at: i64,
+}
+
+impl EpochReceipts {
+  pub const MAXIMUM_SIZE: usize = 1 * 4 + 32 * 4 + 8 * 5 + 8 * 2;
+
+  pub fn is_settled(&self) -> bool {
+    self.settled_at != 0
+  }
+
+  pub fn initialize(
+    &mut self,
+    now: i64,
+    bump: u8,
+    principal_mint_bump: u8,
+    yield_mint_bump: u8,
+    escrow_bump: u8,
+    vault: Pubkey,
+    principal_mint: Pubkey,
+    yield_mint: Pubkey,
+    shares_escrow: Pubkey,
+    epoch: u64,
+    start_price: u64,
+  ) -> Result<()> {
+    self.bump = bump;
+    self.principal_mint_bump = principal_mint_bump;
+    self.yield_mint_bump = yield_mint_bump;
+    self.escrow_bump = escrow_bump;
+    self.vault = vault;
+    self.principal_mint = principal_mint;
+    self.yield_mint = yield_mint;
+    self.shares_escrow = shares_escrow;
+    self.epoch = epoch;
+    self.start_price = start_price;
+    self.created_at = now;
+    Ok(())
+  }
+
+  pub fn after_split(&mut self, shares_amount: u64) -> Result<()> {
+    self.locked_shares = self.locked_shares
+      .checked_add(shares_amount).unwrap();
+    Ok(())
+  }
+
+  pub fn settle(&mut self, end_price: u64, now: i64) -> Result<()> {
+    self.end_price = end_price;
+    let premium_per_share = end_price.saturating_sub(self.start_price);
+    self.realized_premium = ratio!(
+      self.locked_shares, premium_per_share, SHARE_PRICE_PRECISION
+    ).unwrap();
+    self.settled_at = now;
+    Ok(())
+  }
+
+  /// Shares owed for burning `amount` of principal receipts.
+  pub fn principal_shares(&self, amount: u64) -> Option<u64> {
+    if self.end_price == 0 {
+      return Some(0);
+    }
+    ratio_floor!(amount, min(self.start_price, self.end_price), self.end_price)
+  }
+
+  /// Shares owed for burning `amount` of yield receipts.
+  pub fn yield_shares(&self, amount: u64) -> Option<u64> {
+    if self.end_price == 0 {
+      return Some(0);
+    }
+    let premium_per_share = self.end_price.saturating_sub(self.start_price);
+    ratio_floor!(amount, premium_per_share, self.end_price)
+  }
+}
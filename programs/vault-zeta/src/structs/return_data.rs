@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use crate::structs::EpochRecord;
+
+
+/// Return data of `preview_deposit`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct PreviewDepositReturn {
+  pub shares: u64,
+}
+
+/// Return data of `preview_withdraw`, in underlying.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct PreviewWithdrawReturn {
+  pub assets: u64,
+}
+
+/// Return data of `share_price_twap`, 6.dp.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct SharePriceTwapReturn {
+  pub twap: u64,
+}
+
+/// Return data of `get_epoch_history`, newest records first. `total` counts
+/// every settled expiry, older ones than the history keeps are dropped.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Clone)]
+pub struct EpochHistoryReturn {
+  pub total: u64,
+  pub records: Vec<EpochRecord>,
+}
+
+/// An order or margin transfer skipped by `dry_run`. `target` is the market,
+/// or the margin account of a transfer. `size` is positive for bids, longs and
+/// deposits into the margin account.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct DryRunOrder {
+  pub target: Pubkey,
+  pub price: u64,
+  pub size: i64,
+  pub client_order_id: u64,
+}
+
+/// Return data of the trading instructions run with `dry_run`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Clone)]
+pub struct DryRunReturn {
+  pub orders: Vec<DryRunOrder>,
+}
+
+/// Order the strategy program asks `execute_strategy` for, `leg` indexes the
+/// ladder legs passed to the instruction.
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone)]
+pub enum StrategyIntent {
+  Bid { leg: u8, price: u64, size: u64 },
+  Cancel { leg: u8, client_order_id: u64 },
+}
+
+/// Return data the strategy program sets, at most `MAX_STRATEGY_INTENTS`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Clone)]
+pub struct StrategyReturn {
+  pub intents: Vec<StrategyIntent>,
+}
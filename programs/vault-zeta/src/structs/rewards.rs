@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+use crate::VaultError;
+
+// scale of `reward_per_share`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+
+/// Reward tokens emitted to staked vault shares at `emission_rate` a second,
+/// as long as funded rewards are left. `reward_per_share` accumulates the
+/// rewards of one staked share since the pool was created.
+#[account]
+#[derive(Default)]
+pub struct RewardPool {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub reward_mint: Pubkey,
+  pub reward_vault: Pubkey,
+  pub emission_rate: u64,
+  pub reward_per_share: u128,
+  pub total_staked: u64,
+  // funded rewards not emitted yet
+  pub undistributed: u64,
+  pub last_update_ts: i64,
+}
+
+impl RewardPool {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 3 + 8 + 16 + 8 * 3;
+
+  pub fn initialize(
+    &mut self,
+    bump: u8,
+    vault: Pubkey,
+    reward_mint: Pubkey,
+    reward_vault: Pubkey,
+    emission_rate: u64,
+    now: i64,
+  ) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.reward_mint = reward_mint;
+    self.reward_vault = reward_vault;
+    self.emission_rate = emission_rate;
+    self.last_update_ts = now;
+    Ok(())
+  }
+
+  /// Emits the rewards since `last_update_ts` to the staked shares. Nothing
+  /// is emitted while no shares are staked.
+  pub fn update(&mut self, now: i64) -> Result<()> {
+    if now <= self.last_update_ts {
+      return Ok(());
+    }
+    let elapsed = (now - self.last_update_ts) as u64;
+    self.last_update_ts = now;
+    if self.total_staked == 0 {
+      return Ok(());
+    }
+    let emitted = self.emission_rate
+      .saturating_mul(elapsed)
+      .min(self.undistributed);
+    self.reward_per_share = self.reward_per_share
+      .checked_add(
+        (emitted as u128)
+          .checked_mul(REWARD_PRECISION).unwrap()
+          .checked_div(self.total_staked as u128).unwrap()
+      ).unwrap();
+    self.undistributed = self.undistributed
+      .checked_sub(emitted).unwrap();
+    Ok(())
+  }
+
+  pub fn after_funded(&mut self, amount: u64, emission_rate: u64) -> Result<()> {
+    self.undistributed = self.undistributed
+      .checked_add(amount).unwrap();
+    self.emission_rate = emission_rate;
+    Ok(())
+  }
+
+  pub fn after_stake(&mut self, shares: u64) -> Result<()> {
+    self.total_staked = self.total_staked
+      .checked_add(shares).unwrap();
+    Ok(())
+  }
+
+  pub fn after_unstake(&mut self, shares: u64) -> Result<()> {
+    self.total_staked = self.total_staked
+      .checked_sub(shares).unwrap();
+    Ok(())
+  }
+}
+
+/// Vault shares a user staked in a reward pool. `reward_debt` is the part of
+/// `shares * reward_per_share` already credited to `pending_rewards`.
+#[account]
+#[derive(Default)]
+pub struct StakeAccount {
+  pub bump: u8,
+  pub pool: Pubkey,
+  pub owner: Pubkey,
+  pub shares: u64,
+  pub reward_debt: u128,
+  pub pending_rewards: u64,
+}
+
+impl StakeAccount {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 + 16 + 8;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, pool: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.pool = pool;
+    self.owner = owner;
+    Ok(())
+  }
+
+  /// Credits the rewards accrued up to `reward_per_share`, call it with the
+  /// updated pool before the staked shares change.
+  pub fn settle(&mut self, reward_per_share: u128) -> Result<()> {
+    let accrued = self.accumulated(reward_per_share)
+      .checked_sub(self.reward_debt).unwrap();
+    self.pending_rewards = self.pending_rewards
+      .checked_add(u64::try_from(accrued).unwrap()).unwrap();
+    self.reward_debt = self.accumulated(reward_per_share);
+    Ok(())
+  }
+
+  pub fn stake(&mut self, shares: u64, reward_per_share: u128) -> Result<()> {
+    self.settle(reward_per_share)?;
+    self.shares = self.shares
+      .checked_add(shares).unwrap();
+    self.reward_debt = self.accumulated(reward_per_share);
+    Ok(())
+  }
+
+  pub fn unstake(&mut self, shares: u64, reward_per_share: u128) -> Result<()> {
+    if shares == 0 || shares > self.shares {
+      return err!(VaultError::InvalidStakeAmount);
+    }
+    self.settle(reward_per_share)?;
+    self.shares = self.shares
+      .checked_sub(shares).unwrap();
+    self.reward_debt = self.accumulated(reward_per_share);
+    Ok(())
+  }
+
+  pub fn take_rewards(&mut self) -> u64 {
+    let rewards = self.pending_rewards;
+    self.pending_rewards = 0;
+    rewards
+  }
+
+  fn accumulated(&self, reward_per_share: u128) -> u128 {
+    (self.shares as u128)
+      .checked_mul(reward_per_share).unwrap()
+      .checked_div(REWARD_PRECISION).unwrap()
+  }
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+
+
+/// Tracks a vault setup split over several small transactions,
+/// so an authority with transaction size limits (multisig) can sign each step.
+#[account]
+#[derive(Default)]
+pub struct SetupSession {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub authority: Pubkey,
+  pub shares_mint_ready: bool,
+  pub token_accounts_ready: bool,
+  pub margin_account_ready: bool,
+}
+
+impl SetupSession {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 1 * 3;
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, authority: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.authority = authority;
+    Ok(())
+  }
+
+  pub fn is_complete(&self) -> bool {
+    self.shares_mint_ready && self.token_accounts_ready && self.margin_account_ready
+  }
+}
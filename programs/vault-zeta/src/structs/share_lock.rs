@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+
+/// Shares a user locked in the vault lock escrow until the receipts epoch
+/// `unlock_epoch`. Locking lowers the performance fee charged on the user's
+/// yield receipts of the epochs the lock spans, for as many receipts as
+/// shares are locked.
+#[account]
+#[derive(Default)]
+pub struct ShareLock {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub owner: Pubkey,
+  pub shares: u64,
+  pub locked_epoch: u64,
+  pub unlock_epoch: u64,
+  // lock length credited for fees, never more than the shares are committed
+  pub lock_epochs: u64,
+  // receipts of `credited_epoch` already redeemed at the discounted fee
+  pub credited_epoch: u64,
+  pub credited_receipts: u64,
+}
+
+impl ShareLock {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 6;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.owner = owner;
+    Ok(())
+  }
+
+  /// Adds shares to the lock, a lock can only be extended. The credited
+  /// length is what all the locked shares are committed for, so topping up
+  /// or extending late never credits more than the epochs left from `epoch`.
+  pub fn lock(&mut self, shares: u64, epoch: u64, lock_epochs: u64) -> Result<()> {
+    self.unlock_epoch = self.unlock_epoch
+      .max(epoch.checked_add(lock_epochs).unwrap());
+    let committed_epochs = self.unlock_epoch - epoch;
+    if self.shares == 0 {
+      self.locked_epoch = epoch;
+      self.lock_epochs = committed_epochs;
+    } else {
+      self.lock_epochs = self.lock_epochs.min(committed_epochs);
+    }
+    self.shares = self.shares
+      .checked_add(shares).unwrap();
+    Ok(())
+  }
+
+  pub fn is_unlocked(&self, epoch: u64) -> bool {
+    epoch >= self.unlock_epoch
+  }
+
+  pub fn take(&mut self) -> u64 {
+    let shares = self.shares;
+    self.shares = 0;
+    self.locked_epoch = 0;
+    self.unlock_epoch = 0;
+    self.lock_epochs = 0;
+    shares
+  }
+
+  /// Lock length credited for fees of `epoch`, 0 unless the lock spans it.
+  pub fn lock_epochs_at(&self, epoch: u64) -> u64 {
+    if self.shares == 0 || epoch < self.locked_epoch || epoch >= self.unlock_epoch {
+      return 0;
+    }
+    self.lock_epochs
+  }
+
+  /// Takes up to `receipts` yield receipts of `epoch` into the discounted
+  /// fee, at most `shares` per epoch however many redemptions they come in.
+  pub fn credit_receipts(&mut self, epoch: u64, receipts: u64) -> u64 {
+    if self.lock_epochs_at(epoch) == 0 {
+      return 0;
+    }
+    if self.credited_epoch != epoch {
+      self.credited_epoch = epoch;
+      self.credited_receipts = 0;
+    }
+    let credited = receipts.min(self.shares.saturating_sub(self.credited_receipts));
+    self.credited_receipts += credited;
+    credited
+  }
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use num_traits::ToPrimitive;
+use crate::{ratio, VaultError};
+
+// share price is stored as underlying per share scaled by this value
+pub const SHARE_PRICE_PRECISION: u64 = 1_000_000_000;
+pub const SHARE_PRICE_OBSERVATIONS: usize = 32;
+
+
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct SharePriceObservation {
+  pub timestamp: i64,
+  pub price: u64,
+  pub cumulative_price: u128,
+}
+
+
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct SharePriceAccumulator {
+  pub last_price: u64,
+  pub last_update: i64,
+  // sum of price * seconds since the first observation
+  pub cumulative_price: u128,
+  pub observation_index: u8,
+  pub observations: [SharePriceObservation; SHARE_PRICE_OBSERVATIONS],
+}
+
+impl SharePriceAccumulator {
+  pub const MAXIMUM_SIZE: usize = 8 * 2 + 16 + 1
+    + (8 * 2 + 16) * SHARE_PRICE_OBSERVATIONS;
+
+  pub fn cumulative_at(&self, now: i64) -> Option<u128> {
+    let elapsed = now.checked_sub(self.last_update)?.max(0) as u128;
+    self.cumulative_price
+      .checked_add((self.last_price as u128).checked_mul(elapsed)?)
+  }
+
+  pub fn update(&mut self, price: u64, now: i64) -> Result<()> {
+    if self.last_update != 0 {
+      self.cumulative_price = self.cumulative_at(now).unwrap();
+    }
+    self.last_price = price;
+    self.last_update = now;
+
+    let current = self.observations[self.observation_index as usize];
+    if current.timestamp != now {
+      self.observation_index = ((self.observation_index as usize + 1)
+        % SHARE_PRICE_OBSERVATIONS) as u8;
+    }
+    self.observations[self.observation_index as usize] = SharePriceObservation {
+      timestamp: now,
+      price,
+      cumulative_price: self.cumulative_price,
+    };
+    Ok(())
+  }
+
+  /// Time weighted share price over the last `window` seconds.
+  pub fn twap(&self, window: i64, now: i64) -> Result<u64> {
+    if window <= 0 {
+      return Ok(self.last_price);
+    }
+    let target = now.checked_sub(window).unwrap();
+    // newest observation at or before the start of the window
+    let start = (0..SHARE_PRICE_OBSERVATIONS)
+      .map(|i| {
+        let index = (self.observation_index as usize + SHARE_PRICE_OBSERVATIONS - i)
+          % SHARE_PRICE_OBSERVATIONS;
+        self.observations[index]
+      })
+      .filter(|observation| observation.timestamp != 0)
+      .find(|observation| observation.timestamp <= target);
+    let start = match start {
+      Some(observation) => observation,
+      None => return err!(VaultError::ShortSharePriceHistory),
+    };
+    let cumulative_at_target = start.cumulative_price
+      .checked_add(
+        (start.price as u128)
+          .checked_mul(target.checked_sub(start.timestamp).unwrap() as u128).unwrap()
+      ).unwrap();
+    let cumulative_now = self.cumulative_at(now).unwrap();
+    let twap = cumulative_now
+      .checked_sub(cumulative_at_target).unwrap()
+      .checked_div(window as u128).unwrap();
+    Ok(twap.to_u64().unwrap())
+  }
+}
+
+pub fn price_per_share(total_underlying: u64, total_share_supply: u64) -> Option<u64> {
+  if total_share_supply == 0 {
+    // 1 share = 1 liquidity
+    Some(SHARE_PRICE_PRECISION)
+  } else {
+    ratio!(total_underlying, SHARE_PRICE_PRECISION, total_share_supply)
+  }
+}
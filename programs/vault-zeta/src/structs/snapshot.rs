@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+
+/// Share price committed by the keeper right before it trades,
+/// deposits shortly after it are priced off this snapshot.
+#[account]
+#[derive(Default)]
+pub struct NavSnapshot {
+  pub bump: u8,
+  pub vault: Pubkey,
+  // see SHARE_PRICE_PRECISION
+  pub share_price: u64,
+  pub total_assets: u64,
+  pub total_share_supply: u64,
+  pub slot: u64,
+}
+
+impl NavSnapshot {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 + 8 * 4;
+
+  pub fn commit(
+    &mut self,
+    bump: u8,
+    vault: Pubkey,
+    share_price: u64,
+    total_assets: u64,
+    total_share_supply: u64,
+    slot: u64,
+  ) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.share_price = share_price;
+    self.total_assets = total_assets;
+    self.total_share_supply = total_share_supply;
+    self.slot = slot;
+    Ok(())
+  }
+
+  pub fn is_active(&self, slot: u64, snapshot_slots: u64) -> bool {
+    self.share_price > 0 && slot <= self.slot.saturating_add(snapshot_slots)
+  }
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use crate::{ratio_floor, VaultError};
+use crate::constants::POSITION_PRECISION_DENOMINATOR;
+
+
+/// Caps on keeper order flow, limits of 0 are disabled. Usage counters reset
+/// when the slot or the vault epoch changes.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct OrderThrottle {
+  pub max_lots_per_slot: u64,
+  pub max_notional_per_slot: u64,
+  pub max_notional_per_epoch: u64,
+  pub slot: u64,
+  pub slot_lots: u64,
+  pub slot_notional: u64,
+  pub epoch: u64,
+  pub epoch_notional: u64,
+}
+
+impl OrderThrottle {
+  pub const MAXIMUM_SIZE: usize = 8 * 8;
+
+  /// Books an order of `size` lots at `price` against the budgets of the
+  /// current slot and epoch, failing once any of them is exceeded.
+  pub fn consume(&mut self, size: u64, price: u64, slot: u64, epoch: u64) -> Result<()> {
+    if slot != self.slot {
+      self.slot = slot;
+      self.slot_lots = 0;
+      self.slot_notional = 0;
+    }
+    if epoch != self.epoch {
+      self.epoch = epoch;
+      self.epoch_notional = 0;
+    }
+
+    let notional = ratio_floor!(size, price, POSITION_PRECISION_DENOMINATOR as u64).unwrap();
+    self.slot_lots = self.slot_lots
+      .checked_add(size).unwrap();
+    self.slot_notional = self.slot_notional
+      .checked_add(notional).unwrap();
+    self.epoch_notional = self.epoch_notional
+      .checked_add(notional).unwrap();
+
+    if exceeds(self.slot_lots, self.max_lots_per_slot)
+      || exceeds(self.slot_notional, self.max_notional_per_slot)
+      || exceeds(self.epoch_notional, self.max_notional_per_epoch) {
+      msg!("slot lots: {}, slot notional: {}, epoch notional: {}",
+        self.slot_lots, self.slot_notional, self.epoch_notional);
+      return err!(VaultError::OrderThrottled);
+    }
+    Ok(())
+  }
+}
+
+/// Caps on the keeper's resting orders per market and on how often it
+/// reprices them with `cancel_replace` in an epoch, limits of 0 are disabled.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct OrderBudget {
+  pub max_resting_orders: u64,
+  pub max_reprices_per_epoch: u64,
+  pub epoch: u64,
+  pub reprices: u64,
+}
+
+impl OrderBudget {
+  pub const MAXIMUM_SIZE: usize = 8 * 4;
+
+  /// Fails when `resting_orders` already use the budget, before another rests.
+  pub fn check_resting(&self, resting_orders: u64) -> Result<()> {
+    if exceeds(resting_orders.checked_add(1).unwrap(), self.max_resting_orders) {
+      msg!("resting orders: {}", resting_orders);
+      return err!(VaultError::OrderBudgetExceeded);
+    }
+    Ok(())
+  }
+
+  pub fn consume_reprice(&mut self, epoch: u64) -> Result<()> {
+    if epoch != self.epoch {
+      self.epoch = epoch;
+      self.reprices = 0;
+    }
+    self.reprices = self.reprices
+      .checked_add(1).unwrap();
+    if exceeds(self.reprices, self.max_reprices_per_epoch) {
+      msg!("epoch reprices: {}", self.reprices);
+      return err!(VaultError::OrderBudgetExceeded);
+    }
+    Ok(())
+  }
+}
+
+fn exceeds(used: u64, limit: u64) -> bool {
+  limit > 0 && used > limit
+}
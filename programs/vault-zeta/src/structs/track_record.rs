@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+
+pub const EPOCH_HISTORY_LEN: usize = 16;
+
+
+/// Lifetime strategy statistics, updated once per settled expiry so a track
+/// record can be read from the vault account without an indexer. Share prices
+/// are at full NAV (locked profit included), `SHARE_PRICE_PRECISION`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct TrackRecord {
+  pub epochs: u64,
+  // epochs that settled above the share price of the previous settlement
+  pub winning_epochs: u64,
+  // premium received for the short options that reached settlement
+  pub premium_sold: u64,
+  // exercise value paid on them
+  pub assignment_losses: u64,
+  pub last_share_price: u64,
+}
+
+impl TrackRecord {
+  pub const MAXIMUM_SIZE: usize = 8 * 5;
+
+  /// `start_share_price` is only used for the first epoch, later ones start
+  /// where the previous settlement ended.
+  pub fn epoch_start(&self, start_share_price: u64) -> u64 {
+    if self.last_share_price == 0 {
+      start_share_price
+    } else {
+      self.last_share_price
+    }
+  }
+
+  pub fn record_epoch(
+    &mut self,
+    start_share_price: u64,
+    end_share_price: u64,
+    premium: u64,
+    assignment_loss: u64,
+  ) {
+    let start = self.epoch_start(start_share_price);
+    self.epochs = self.epochs
+      .checked_add(1).unwrap();
+    if end_share_price > start {
+      self.winning_epochs = self.winning_epochs
+        .checked_add(1).unwrap();
+    }
+    self.premium_sold = self.premium_sold
+      .checked_add(premium).unwrap();
+    self.assignment_losses = self.assignment_losses
+      .checked_add(assignment_loss).unwrap();
+    self.last_share_price = end_share_price;
+  }
+}
+
+/// One settled expiry of the `EpochHistory`.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct EpochRecord {
+  pub expiry_ts: u64,
+  pub start_share_price: u64,
+  pub end_share_price: u64,
+  pub premium: u64,
+  pub assignment_loss: u64,
+  // socialized since the previous record, see `Vault::after_socialized_loss`
+  pub haircut_bps: u64,
+}
+
+/// The last `EPOCH_HISTORY_LEN` settled expiries, oldest overwritten first.
+/// `TrackRecord` keeps the lifetime totals.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct EpochHistory {
+  // records written so far, the next one goes to `count % EPOCH_HISTORY_LEN`
+  pub count: u64,
+  pub records: [EpochRecord; EPOCH_HISTORY_LEN],
+}
+
+impl EpochHistory {
+  pub const MAXIMUM_SIZE: usize = 8 + 8 * 6 * EPOCH_HISTORY_LEN;
+
+  pub fn push(&mut self, record: EpochRecord) {
+    self.records[self.count as usize % EPOCH_HISTORY_LEN] = record;
+    self.count = self.count
+      .checked_add(1).unwrap();
+  }
+
+  /// Up to `limit` records newest first, skipping the `offset` newest ones.
+  pub fn page(&self, offset: u64, limit: u64) -> Vec<EpochRecord> {
+    let stored = self.count.min(EPOCH_HISTORY_LEN as u64);
+    (offset..stored.min(offset.saturating_add(limit)))
+      .map(|back| {
+        let index = (self.count - 1 - back) as usize % EPOCH_HISTORY_LEN;
+        self.records[index]
+      })
+      .collect()
+  }
+}
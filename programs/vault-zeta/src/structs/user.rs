@@ -0,0 +1,108 @@
+use anchor_lang::prelude::*;
+use crate::{ratio_floor, VaultError};
+use crate::structs::CLAIMS_INDEX_PRECISION;
+
+
+#[account]
+#[derive(Default)]
+pub struct UserState {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub owner: Pubkey,
+  // last deposit minted straight to the wallet, see `check_unlocked`
+  pub last_deposit_slot: u64,
+  // next nonce a signed deposit intent has to carry
+  pub permit_nonce: u64,
+  // USDC owed from an in-kind withdrawal, paid by `claim_usdc`
+  pub usdc_claim: u64,
+  // last deposit minted straight to the wallet, the lock and exit fee of
+  // escrowed deposits are kept on the `DepositReceipt`
+  pub last_deposit_ts: i64,
+  // wallet rewards and claims are attributed to when shares sit with an
+  // integrator PDA, default while the owner keeps them
+  pub beneficial_owner: Pubkey,
+  // `Vault::claims_index` `usdc_claim` was last scaled to, 0 before any haircut
+  pub claims_index: u64,
+}
+
+impl UserState {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 3 + 8 * 5;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.owner = owner;
+    Ok(())
+  }
+
+  /// Wallet the user's rewards and USDC claims are paid to.
+  pub fn beneficial_owner(&self) -> Pubkey {
+    if self.beneficial_owner == Pubkey::default() {
+      self.owner
+    } else {
+      self.beneficial_owner
+    }
+  }
+
+  /// Delegates attribution to `beneficial_owner`, the default key resets it
+  /// back to the owner.
+  pub fn set_beneficial_owner(&mut self, beneficial_owner: Pubkey) -> Result<()> {
+    self.beneficial_owner = beneficial_owner;
+    Ok(())
+  }
+
+  pub fn after_deposit(&mut self, slot: u64, now: i64) -> Result<()> {
+    self.last_deposit_slot = slot;
+    self.last_deposit_ts = now;
+    Ok(())
+  }
+
+  /// Shares can't leave the user within `lock_slots` slots after a deposit
+  /// minted to the wallet, the deposit slot itself included. That kills
+  /// flash-loan funded deposit/withdraw round trips.
+  pub fn check_unlocked(&self, slot: u64, lock_slots: u64) -> Result<()> {
+    let unlock_slot = self.last_deposit_slot
+      .checked_add(lock_slots).unwrap();
+    if self.last_deposit_slot != 0 && slot <= unlock_slot {
+      return err!(VaultError::DepositLocked);
+    }
+    Ok(())
+  }
+
+  pub fn after_permit(&mut self) -> Result<()> {
+    self.permit_nonce = self.permit_nonce
+      .checked_add(1).unwrap();
+    Ok(())
+  }
+
+  /// Scales `usdc_claim` down by the haircuts socialized since it was last
+  /// touched, call it with `Vault::current_claims_index` before using it.
+  pub fn apply_claims_index(&mut self, claims_index: u64) -> Result<()> {
+    let user_index = if self.claims_index == 0 {
+      CLAIMS_INDEX_PRECISION
+    } else {
+      self.claims_index
+    };
+    if claims_index != user_index {
+      self.usdc_claim = ratio_floor!(self.usdc_claim, claims_index, user_index).unwrap();
+    }
+    self.claims_index = claims_index;
+    Ok(())
+  }
+
+  pub fn after_usdc_claim(&mut self, amount: u64) -> Result<()> {
+    self.usdc_claim = self.usdc_claim
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  pub fn after_usdc_claim_paid(&mut self, amount: u64) -> Result<()> {
+    self.usdc_claim = self.usdc_claim
+      .checked_sub(amount).unwrap();
+    Ok(())
+  }
+}
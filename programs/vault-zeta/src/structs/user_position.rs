@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+
+/// Per-depositor ledger in underlying, kept by the deposit and withdraw
+/// handlers so realized PnL can be read without replaying history. Only
+/// shares minted and burned through the vault are seen, transferred shares
+/// carry no cost basis.
+#[account]
+#[derive(Default)]
+pub struct UserPosition {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub owner: Pubkey,
+  // shares the cost basis is for
+  pub shares: u64,
+  pub total_deposited: u64,
+  pub total_withdrawn: u64,
+  // weighted-average cost of `shares`
+  pub cost_basis: u64,
+  pub realized_pnl: i64,
+}
+
+impl UserPosition {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 5;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.owner = owner;
+    Ok(())
+  }
+
+  pub fn after_deposit(&mut self, amount: u64, shares: u64) -> Result<()> {
+    self.shares = self.shares
+      .checked_add(shares).unwrap();
+    self.cost_basis = self.cost_basis
+      .checked_add(amount).unwrap();
+    self.total_deposited = self.total_deposited
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// Realizes `amount` received for `shares` against their pro-rata cost
+  /// basis. Shares beyond the tracked ones (received by transfer) have no
+  /// cost basis and realize nothing.
+  pub fn after_withdraw(&mut self, amount: u64, shares: u64) -> Result<()> {
+    self.total_withdrawn = self.total_withdrawn
+      .checked_add(amount).unwrap();
+    if self.shares == 0 || shares == 0 {
+      return Ok(());
+    }
+    let tracked_shares = shares.min(self.shares);
+    let cost = (self.cost_basis as u128)
+      .checked_mul(tracked_shares as u128).unwrap()
+      .checked_div(self.shares as u128).unwrap() as u64;
+    let tracked_amount = (amount as u128)
+      .checked_mul(tracked_shares as u128).unwrap()
+      .checked_div(shares as u128).unwrap() as i64;
+    self.realized_pnl = self.realized_pnl
+      .checked_add(tracked_amount.checked_sub(cost as i64).unwrap()).unwrap();
+    self.cost_basis = self.cost_basis
+      .checked_sub(cost).unwrap();
+    self.shares = self.shares
+      .checked_sub(tracked_shares).unwrap();
+    Ok(())
+  }
+}
@@ -1,9 +1,27 @@
 use anchor_lang::prelude::*;
 use num_traits::{ToPrimitive};
-use crate::{ratio, VaultError};
-use crate::cpi_calls::solend::Reserve;
+use crate::{ratio, ratio_ceil, ratio_floor, VaultError};
+use crate::cpi_calls::solend::{CollateralExchangeRate, Decimal, ObligationState, Reserve, TryAdd, TryMul};
+use crate::cpi_calls::zeta::{
+  GreeksExposure, Kind, MarginParameters, OrderType, ZetaGroup, SECONDS_IN_A_YEAR,
+};
+use crate::constants::{ACTIVE_EXPIRIES, POSITION_PRECISION_DENOMINATOR};
+use crate::cpi_calls::zeta::pyth_client::{OracleSource, PriceFeedConfig};
+use crate::pda::ExecutorPda;
+use crate::structs::{
+  price_per_share, tiered_performance_fee_bps, validate_fee_tiers, Decimals, DeltaHedge, EpochSchedule, ExitFee, FeeTier,
+  EpochHistory, EpochRecord, InsuranceFund, KeeperSet, UserState, MarginBasis, MarginOverrides, MarketRegistry, OrderBudget, OrderThrottle, PremiumFloor, SharePriceAccumulator, TrackRecord, VaultConfigParams,
+  Venue, MAX_CLOSE_PRICE_BPS, MAX_EXIT_FEE_BPS, MAX_FEE_TIERS, MAX_INSTANT_EXIT_FEE_BPS, MAX_INSURANCE_BPS, MAX_IV_FLOOR_BPS, MAX_LTV_BPS, MAX_LADDER_LEGS, MAX_MANAGEMENT_FEE_BPS,
+  MAX_PERFORMANCE_FEE_BPS, MAX_SHARE_DECIMALS, MAX_STRATEGY_INSTRUCTIONS, SHARE_PRICE_PRECISION,
+};
 
 pub const DEGRADATION_COEFFICIENT: u64 = 1000000000000000000;
+// seconds
+pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;
+// seconds between proposing and executing an executor rotation
+pub const EXECUTOR_ROTATION_DELAY: i64 = 2 * 24 * 60 * 60;
+// `Vault::claims_index` before any haircut
+pub const CLAIMS_INDEX_PRECISION: u64 = 1_000_000_000_000;
 
 
 #[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
@@ -52,19 +70,351 @@ pub struct Vault {
   pub last_harvest: i64,
 
   pub created_at: i64,
+
+  pub share_price: SharePriceAccumulator,
+
+  // last opened principal/yield receipts epoch
+  pub receipt_epoch: u64,
+  pub receipt_epoch_live: bool,
+
+  // extra slots after a deposit during which the user shares are locked
+  pub deposit_lock_slots: u64,
+
+  pub oracle: PriceFeedConfig,
+
+  // false while a multi-step setup is in progress
+  pub setup_complete: bool,
+
+  pub epoch_schedule: EpochSchedule,
+
+  // deposits within this many slots after a NAV snapshot use the snapshot price
+  pub snapshot_slots: u64,
+
+  // solend obligation of the executor, default when leverage was never set up
+  pub obligation: Pubkey,
+  // 0 disables borrowing
+  pub max_ltv_bps: u64,
+  // cTokens on the obligation and its debt, interest included, read from
+  // the obligation at `debt_slot`
+  pub obligation_collateral: u64,
+  pub total_borrowed: u64,
+
+  // absolute greeks caps of the margin account, 0 disables the cap
+  pub max_abs_delta: u64,
+  pub max_abs_vega: u64,
+
+  // fee kept by the vault on `withdraw_instant`
+  pub instant_exit_fee_bps: u64,
+
+  // share price recorded at the last harvest, `drawdown_shortfall` measures
+  // the insurance cover against it
+  pub floor_share_price: u64,
+
+  // lets the authority park Solend liquidity in the Zeta margin account
+  // while no options are sold
+  pub idle_sweep_enabled: bool,
+  // USDC moved from Solend into the margin account by `sweep_to_margin`
+  pub parked_margin: u64,
+
+  // orders placed by the vault, the client order id of the last order
+  pub order_count: u64,
+
+  // charged on redeemed yield receipts, lowered by `fee_tiers` for locked shares
+  pub performance_fee_bps: u64,
+  pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+
+  // external program `execute_strategy` asks for keeper orders, it never
+  // gets the executor signature, default disables it
+  pub strategy_program: Pubkey,
+  // anchor discriminators of the strategy instructions allowed to be called
+  pub strategy_instructions: [[u8; 8]; MAX_STRATEGY_INSTRUCTIONS],
+
+  // lamports the executor keeps for rent, excluded from NAV like all PDA
+  // lamports, never below its own rent exemption, see `executor_rent_reserve`
+  pub rent_reserve: u64,
+
+  // share of the `place_ladder` amount per leg, unused legs are 0
+  pub ladder_weights_bps: [u64; MAX_LADDER_LEGS],
+
+  // expiry of the last series settled by `compute_settlement`
+  pub last_settled_expiry: u64,
+  // realized settlement losses, subtracted from NAV
+  pub total_loss: u64,
+
+  // smallest accepted deposit, in underlying
+  pub min_deposit: u64,
+
+  // underlying and zeta platform decimals, set with the token accounts
+  pub decimals: Decimals,
+
+  // can call `abort_epoch`, default disables it
+  pub guardian: Pubkey,
+  // set by `abort_epoch`, trading is halted and withdrawals open until then
+  pub aborted_until: i64,
+
+  // underlying of the zeta group options (SOL, BTC, ETH), set with the margin account
+  pub zeta_underlying_mint: Pubkey,
+
+  // keeper order flow limits, a stolen keeper key can't dump the vault at once
+  pub order_throttle: OrderThrottle,
+
+  // anyone can open withdrawals once the keeper was silent this long, 0 disables it
+  pub keeper_timeout_slots: u64,
+  pub last_keeper_slot: u64,
+
+  // zeta cross-margin account of the executor, used instead of `margin_account`
+  // by the dual-path instructions while `cross_margin` is set
+  pub cross_margin: bool,
+  pub cross_margin_account: Pubkey,
+
+  // keeper orders are post-only and expire after `order_tif_secs` (0 rests until cancelled)
+  pub post_only: bool,
+  pub order_tif_secs: u64,
+
+  // asks below the Black-Scholes price at this IV floor are rejected
+  pub premium_floor: PremiumFloor,
+
+  // orders further than this from the zeta mark price are rejected, 0 disables it
+  pub max_mark_deviation_bps: u64,
+
+  // executor derivation in use, version 0 is the original `[b"executor", vault]`
+  pub executor_version: u8,
+  // version proposed by the authority, rotation unlocks at `executor_rotation_at`
+  pub pending_executor_version: u8,
+  pub executor_rotation_at: i64,
+
+  // deposits the Solend reserve can't take stay idle instead of failing
+  pub reserve_fallback_idle: bool,
+  // user deposits held in `underlying_vault` that way, not a gain
+  pub idle_deposits: u64,
+
+  pub track_record: TrackRecord,
+
+  pub order_budget: OrderBudget,
+
+  pub venue: Venue,
+  // executor's drift user, sub account 0, the perp hedge and the orders of
+  // drift vaults trade from it
+  pub drift_user: Pubkey,
+
+  pub delta_hedge: DeltaHedge,
+
+  pub insurance: InsuranceFund,
+
+  // management fee accrual, the fee is minted as shares to the authority
+  pub last_fee_accrual_ts: i64,
+  // fee shares accrued but not minted yet, part of the supply shares are priced on
+  pub accrued_fee_shares: u64,
+
+  // notional cap of a single keeper order, 0 disables it
+  pub max_order_notional: u64,
+  // how far reprices can walk from the epoch's first order price of a market,
+  // tracked in the market's `OrderTracker`, 0 disables it
+  pub max_reprice_walk_bps: u64,
+
+  // address lookup table of the vault accounts, owned by the authority
+  pub lookup_table: Pubkey,
+
+  // pyth feed of the reserve liquidity, cross-checks the Solend market price
+  pub reserve_oracle: PriceFeedConfig,
+  // pricing halts while Solend and pyth are further apart, 0 disables it
+  pub max_reserve_price_divergence_bps: u64,
+
+  // shortfalls booked by `socialize_loss`, spread over all shares (queued
+  // ones included) and the USDC claims
+  pub socialized_loss: u64,
+  pub last_haircut_bps: u64,
+  pub last_haircut_epoch: u64,
+
+  // USDC margin of vaults with a non-USDC underlying, marked with `reserve_oracle`
+  pub margin_basis: MarginBasis,
+
+  // harvests below it only refresh NAV, the yield waits in `pending_yield`
+  pub min_harvest_amount: u64,
+  // unharvested Solend yield, kept out of NAV until a harvest redeems it and
+  // `reinvest_solend` books it through locked profit
+  pub pending_yield: u64,
+
+  // set for the duration of the strategy program CPI, vault instructions
+  // re-entered from it fail
+  pub in_progress: bool,
+
+  // crisis mode, `withdraw_in_kind` pays holders pro-rata without selling
+  pub in_kind_redemption: bool,
+  // USDC owed to in-kind withdrawals, a liability against the USDC held
+  pub usdc_claims: u64,
+
+  // decimals of the shares mint, set by `setup_shares_mint`
+  pub share_decimals: u8,
+
+  // NAV breakdown in underlying cached at `last_valuation_slot` by the
+  // harvest and deposit paths, idle + lending + zeta margin - total_borrowed
+  // is the NAV. `zeta_margin_value` is the USDC vault, the margin equity and
+  // the drift collateral net of USDC claims, `locked_in_orders` the part of it resting in orders
+  pub idle_underlying: u64,
+  pub lending_value: u64,
+  pub zeta_margin_value: u64,
+  pub locked_in_orders: u64,
+  pub last_valuation_slot: u64,
+  // deposits and withdrawals fail once `last_valuation_slot` is older, in
+  // slots, 0 disables the check
+  pub max_valuation_staleness: u64,
+
+  // `buy_to_close` bids pay at most this share of the mark, bps, 0 caps
+  // them at the mark
+  pub max_close_price_bps: u64,
+
+  // markets orders may be opened on, see `check_market_allowed`
+  pub market_registry: MarketRegistry,
+
+  // vaults created with operator bonds take deposits only while the bond
+  // covers the protocol minimum, see `check_bonded`
+  pub bond_required: bool,
+
+  // underlying escrowed by `deposit_pending`, outside the NAV until converted
+  pub pending_deposits: u64,
+  // share price pending deposits convert at during `entry_price_epoch`,
+  // recorded when the previous epoch settles
+  pub entry_share_price: u64,
+  pub entry_price_epoch: u64,
+
+  // Solend collateral per liquidity, WAD scaled, cached at
+  // `exchange_rate_slot` by `refresh_exchange_rate`. With a staleness bound,
+  // in slots, set the cTokens are valued at it and pricing paths need it fresh
+  pub reserve_exchange_rate: u128,
+  pub exchange_rate_slot: u64,
+  pub max_exchange_rate_staleness: u64,
+
+  // withdrawal fee by time held, left in the vault for the remaining holders
+  pub exit_fee: ExitFee,
+
+  // stricter put cap and dynamic short margin than the zeta group ones
+  pub margin_overrides: MarginOverrides,
+
+  // SPL Governance native treasury, signs the config instructions instead
+  // of the authority once set, default leaves them to the authority
+  pub governance: Pubkey,
+
+  // second keeper signature required for large orders
+  pub keeper_set: KeeperSet,
+
+  // per expiry records next to the `track_record` totals
+  pub epoch_history: EpochHistory,
+
+  // Solend yield above this APR since `yield_checkpoint_ts` is treated as a
+  // donation, 0 disables the check. The excess waits in `surplus`, outside
+  // NAV, until `skim_surplus` books it as locked profit
+  pub max_yield_apr_bps: u64,
+  pub yield_checkpoint_ts: i64,
+  pub surplus: u64,
+  pub total_surplus_skimmed: u64,
+
+  // slot `total_borrowed` was last read from the refreshed obligation,
+  // pricing paths need it fresh while the vault owes anything
+  pub debt_slot: u64,
+  // underlying redeemed by harvests, waiting in the underlying vault for
+  // `reinvest_solend` to book it
+  pub harvested_idle: u64,
+
+  // notional of the keeper orders placed without a cosigner in `uncosigned_epoch`
+  pub uncosigned_epoch: u64,
+  pub uncosigned_notional: u64,
+
+  // unrealized pnl of the zeta positions at mark, in underlying, cached with
+  // the NAV breakdown. Shares are priced with it, it is booked at settlement
+  pub marked_pnl: i64,
+
+  // scales the in-kind USDC claims down by the socialized haircuts, see
+  // `UserState::apply_claims_index`. 0 until the first haircut
+  pub claims_index: u64,
+  // haircut compounded since the last `EpochRecord`, in bps
+  pub epoch_haircut_bps: u64,
+
+  // expiry and platform pnl of the series booked by `compute_settlement`,
+  // by expiry index. The margin valuation carries the booked pnl instead of
+  // the mark until zeta settles the positions into the margin balance
+  pub settled_expiries: [u64; ACTIVE_EXPIRIES],
+  pub settled_pnl: [i64; ACTIVE_EXPIRIES],
+
+  // USDC moved into the drift user by `deposit_drift_collateral`, valued
+  // with the USDC vault and the margin
+  pub drift_collateral: u64,
+
+  // slot of the last deposit minted straight to a wallet, the shares may
+  // have moved to another wallet before its withdrawal
+  pub wallet_deposit_slot: u64,
 }
 
 impl Vault {
-  pub const MAXIMUM_SIZE: usize = 1 * 3 + 32 * 7 + 8 * 12;
+  // no realloc or migration, vaults of an older layout are redeployed
+  pub const MAXIMUM_SIZE: usize = 1 * 3 + 32 * 7 + 8 * 12
+    + SharePriceAccumulator::MAXIMUM_SIZE + 8 + 1 + 8
+    + PriceFeedConfig::MAXIMUM_SIZE + 1
+    + EpochSchedule::MAXIMUM_SIZE + 8
+    + 32 + 8 * 3
+    + 8 * 2 + 8 + 8
+    + 1 + 8
+    + 8
+    + 8 + FeeTier::MAXIMUM_SIZE * MAX_FEE_TIERS
+    + 32 + 8 * MAX_STRATEGY_INSTRUCTIONS
+    + 8
+    + 8 * MAX_LADDER_LEGS
+    + 8 + 8
+    + 8
+    + Decimals::MAXIMUM_SIZE
+    + 32 + 8
+    + 32
+    + OrderThrottle::MAXIMUM_SIZE
+    + 8 + 8
+    + 1 + 32
+    + 1 + 8
+    + PremiumFloor::MAXIMUM_SIZE
+    + 8
+    + 1 + 1 + 8
+    + 1 + 8
+    + TrackRecord::MAXIMUM_SIZE
+    + OrderBudget::MAXIMUM_SIZE
+    + Venue::MAXIMUM_SIZE + 32
+    + DeltaHedge::MAXIMUM_SIZE
+    + InsuranceFund::MAXIMUM_SIZE
+    + 8 + 8
+    + 8 + 8
+    + 32
+    + PriceFeedConfig::MAXIMUM_SIZE + 8
+    + 8 * 3
+    + MarginBasis::MAXIMUM_SIZE
+    + 8 + 8
+    + 1
+    + 1 + 8
+    + 1
+    + 8 * 5
+    + 8
+    + 8
+    + MarketRegistry::MAXIMUM_SIZE
+    + 1
+    + 8 * 3
+    + 16 + 8 + 8
+    + ExitFee::MAXIMUM_SIZE
+    + MarginOverrides::MAXIMUM_SIZE
+    + 32
+    + KeeperSet::MAXIMUM_SIZE
+    + EpochHistory::MAXIMUM_SIZE
+    + 8 + 8 + 8 + 8
+    + 8
+    + 8
+    + 8 + 8
+    + 8
+    + 8 + 8
+    + 8 * ACTIVE_EXPIRIES * 2
+    + 8
+    + 8;
 
   pub fn for_collateral(
     &self,
     underlying_value: u64,
     reserve: &Reserve,
   ) -> Option<u64> {
-    let total_supply = reserve.liquidity.total_supply().unwrap();
-    let rate = reserve.collateral
-      .exchange_rate(total_supply).unwrap();
+    let rate = self.exchange_rate(reserve);
     Some(rate.liquidity_to_collateral(underlying_value).unwrap())
   }
 
@@ -74,10 +424,31 @@ impl Vault {
     collateral_value: u64,
     reserve: &Reserve
   ) -> Option<u64> {
+    let rate = self.exchange_rate(reserve);
+    Some(rate.collateral_to_liquidity(collateral_value).unwrap())
+  }
+
+  // the cached rate once the staleness bound is set, the live one otherwise
+  fn exchange_rate(&self, reserve: &Reserve) -> CollateralExchangeRate {
+    if self.max_exchange_rate_staleness > 0 && self.reserve_exchange_rate > 0 {
+      return CollateralExchangeRate::from_scaled_val(self.reserve_exchange_rate);
+    }
+    let total_supply = reserve.liquidity.total_supply().unwrap();
+    reserve.collateral
+      .exchange_rate(total_supply).unwrap()
+  }
+
+  /// Caches the exchange rate of a reserve refreshed in this slot.
+  pub fn refresh_exchange_rate(&mut self, reserve: &Reserve, slot: u64) -> Result<()> {
+    if reserve.last_update.is_stale(slot)? {
+      return err!(VaultError::ReservePaused);
+    }
     let total_supply = reserve.liquidity.total_supply().unwrap();
     let rate = reserve.collateral
-      .exchange_rate(total_supply).unwrap();
-    Some(rate.collateral_to_liquidity(collateral_value).unwrap())
+      .exchange_rate(total_supply)?;
+    self.reserve_exchange_rate = rate.to_scaled_val();
+    self.exchange_rate_slot = slot;
+    Ok(())
   }
 
   pub fn current_locked_profit(&self, now: i64) -> Option<u64> {
@@ -100,16 +471,52 @@ impl Vault {
   }
 
 
+  pub fn record_valuation(
+    &mut self,
+    idle_underlying: u64,
+    lending_value: u64,
+    zeta_margin_value: u64,
+    locked_in_orders: u64,
+    marked_pnl: i64,
+    slot: u64,
+  ) {
+    self.idle_underlying = idle_underlying;
+    self.lending_value = lending_value;
+    self.zeta_margin_value = zeta_margin_value;
+    self.locked_in_orders = locked_in_orders;
+    self.marked_pnl = marked_pnl;
+    self.last_valuation_slot = slot;
+  }
+
+  /// Booked NAV the shares are priced off, marked with the FX pnl of the
+  /// USDC margin leg, see `MarginBasis`.
   pub fn total_assets(&self) -> Option<u64> {
     self.statistic.total_deposit
       .checked_add(self.statistic.total_gain)?
-      .checked_sub(self.statistic.total_withdraw)
+      .checked_add(self.margin_basis.unrealized_gain())?
+      .checked_sub(self.statistic.total_withdraw)?
+      .checked_sub(self.total_loss)?
+      .checked_sub(self.margin_basis.unrealized_loss())
+  }
+
+  /// Booked NAV with the open zeta positions at mark as of the last
+  /// valuation. Resting orders don't move it, their margin stays in the
+  /// equity the valuation reads.
+  pub fn marked_assets(&self) -> Option<u64> {
+    let total_assets = self.total_assets()?;
+    if self.marked_pnl >= 0 {
+      total_assets.checked_add(self.marked_pnl as u64)
+    } else {
+      Some(total_assets.saturating_sub(self.marked_pnl.unsigned_abs()))
+    }
   }
 
+  /// NAV the shares are priced off: `marked_assets` less the locked profit.
   pub fn free_funds(&self, now: i64) -> Option<u64> {
-    let locked_profit = self.current_locked_profit(now).unwrap();
-    msg!("locked_profit: {}, total_assets: {}", locked_profit, self.total_assets().unwrap());
-    self.total_assets().unwrap().checked_sub(locked_profit)
+    let locked_profit = self.current_locked_profit(now)?;
+    let marked_assets = self.marked_assets()?;
+    msg!("locked_profit: {}, marked_assets: {}", locked_profit, marked_assets);
+    marked_assets.checked_sub(locked_profit)
   }
 
   pub fn share_value(
@@ -121,7 +528,7 @@ impl Vault {
     if share_amount == 0 || total_share_supply == 0 {
       Some(0)
     } else {
-      ratio!(total_underlying, share_amount, total_share_supply)
+      ratio_floor!(total_underlying, share_amount, total_share_supply)
     }
   }
 
@@ -132,12 +539,59 @@ impl Vault {
     total_share_supply: u64,
   ) -> Option<u64> {
     if underlying_amount > 0 {
-      ratio!(total_share_supply, underlying_amount, total_underlying)
+      ratio_ceil!(total_share_supply, underlying_amount, total_underlying)
     } else {
       Some(0)
     }
   }
 
+  /// Shares minted for `assets`, priced off an active NAV snapshot if any.
+  /// The deposit paths and the `preview_deposit` instruction share it.
+  pub fn preview_deposit(
+    &self,
+    assets: u64,
+    snapshot_price: Option<u64>,
+    total_share_supply: u64,
+    now: i64,
+  ) -> Option<u64> {
+    if let Some(share_price) = snapshot_price {
+      return ratio_floor!(assets, SHARE_PRICE_PRECISION, share_price);
+    }
+    if total_share_supply > 0 {
+      let total_share_supply = self.share_supply(total_share_supply, now)?;
+      ratio_floor!(assets, total_share_supply, self.free_funds(now)?)
+    } else {
+      self.bootstrap_shares(assets)
+    }
+  }
+
+  /// Underlying paid out for `shares`, before any exit fee.
+  /// The withdraw paths and the `preview_withdraw` instruction share it.
+  pub fn preview_withdraw(&self, shares: u64, total_share_supply: u64, now: i64) -> Option<u64> {
+    let total_underlying = self.free_funds(now)?;
+    self.share_value(shares, total_underlying, self.share_supply(total_share_supply, now)?)
+  }
+
+  /// Supply shares are priced on: minted shares plus the management fee
+  /// shares accrued and not minted yet.
+  pub fn share_supply(&self, minted_supply: u64, now: i64) -> Option<u64> {
+    minted_supply
+      .checked_add(self.accrued_fee_shares)?
+      .checked_add(self.pending_fee_shares(minted_supply, now)?)
+  }
+
+  /// Fee shares accrued since `last_fee_accrual_ts`, diluting holders by
+  /// `management_fee_bps` a year pro-rata by time.
+  pub fn pending_fee_shares(&self, minted_supply: u64, now: i64) -> Option<u64> {
+    if now <= self.last_fee_accrual_ts {
+      return Some(0);
+    }
+    let elapsed = (now - self.last_fee_accrual_ts) as u64;
+    let supply = minted_supply.checked_add(self.accrued_fee_shares)?;
+    let fee_bps_seconds = self.management_fee_bps.checked_mul(elapsed)?;
+    ratio_floor!(supply, fee_bps_seconds, 10_000 * SECONDS_IN_A_YEAR)
+  }
+
   pub fn initialize(
     &mut self,
     now: i64,
@@ -151,19 +605,47 @@ impl Vault {
     underlying_vault: Pubkey,
     usdc_vault: Pubkey,
     margin_account: Pubkey,
+    price_feed: Pubkey,
+    zeta_underlying_mint: Pubkey,
+    underlying_decimals: u8,
+    share_decimals: u8,
     deposit_limit: u64,
     management_fee_bps: u64,
   ) -> Result<()> {
-    self.bump = bump;
+    self.begin_setup(
+      now,
+      bump,
+      executor_bump,
+      authority,
+      reserve,
+      zeta_group,
+      deposit_limit,
+      management_fee_bps,
+    )?;
     self.mint_bump = mint_bump;
+    self.set_token_accounts(collateral_vault, underlying_vault, usdc_vault, underlying_decimals)?;
+    self.set_share_decimals(share_decimals)?;
+    self.set_margin_account(margin_account, price_feed, zeta_underlying_mint)?;
+    self.setup_complete = true;
+    Ok(())
+  }
+
+  pub fn begin_setup(
+    &mut self,
+    now: i64,
+    bump: u8,
+    executor_bump: u8,
+    authority: Pubkey,
+    reserve: Pubkey,
+    zeta_group: Pubkey,
+    deposit_limit: u64,
+    management_fee_bps: u64,
+  ) -> Result<()> {
+    self.bump = bump;
     self.executor_bump = executor_bump;
     self.authority = authority;
     self.reserve = reserve;
     self.zeta_group = zeta_group;
-    self.collateral_vault = collateral_vault;
-    self.underlying_vault = underlying_vault;
-    self.usdc_vault = usdc_vault;
-    self.margin_account = margin_account;
     self.deposit_limit = deposit_limit;
     // 6 hours lock
     self.statistic.locked_profit_degradation = ratio!(
@@ -172,41 +654,1779 @@ impl Vault {
     // 7 days in ms
     self.harvest_interval = 604800000;
     self.management_fee_bps = management_fee_bps;
+    self.last_fee_accrual_ts = now;
     self.created_at = now;
+    self.bond_required = true;
     Ok(())
   }
 
-  pub fn after_deposit(&mut self, amount: u64) -> Result<()> {
-    self.statistic.total_deposit = self.statistic.total_deposit
-      .checked_add(amount).unwrap();
+  pub fn set_token_accounts(
+    &mut self,
+    collateral_vault: Pubkey,
+    underlying_vault: Pubkey,
+    usdc_vault: Pubkey,
+    underlying_decimals: u8,
+  ) -> Result<()> {
+    self.collateral_vault = collateral_vault;
+    self.underlying_vault = underlying_vault;
+    self.usdc_vault = usdc_vault;
+    self.decimals = Decimals::new(underlying_decimals);
     Ok(())
   }
 
-  pub fn after_withdraw(&mut self, amount: u64) -> Result<()> {
-    self.statistic.total_withdraw = self.statistic.total_withdraw
-      .checked_add(amount).unwrap();
+  /// Shares can't be coarser than the underlying, one base unit of it must
+  /// still buy a share at the bootstrap price.
+  pub fn set_share_decimals(&mut self, share_decimals: u8) -> Result<()> {
+    if share_decimals == 0
+      || share_decimals > MAX_SHARE_DECIMALS
+      || share_decimals < self.decimals.underlying {
+      return err!(VaultError::InvalidConfig);
+    }
+    self.share_decimals = share_decimals;
     Ok(())
   }
 
-  pub fn after_harvest(&mut self, amount: u64, now: i64) -> Result<()> {
-    let next_harvest = self.last_harvest
-      .checked_add(self.harvest_interval).unwrap();
-    if now < next_harvest {
-      return err!(VaultError::HarvestDenied)
+  /// Decimals the shares mint was created with.
+  pub fn share_mint_decimals(&self) -> u8 {
+    self.share_decimals
+  }
+
+  /// Shares minted for the first deposit into an empty vault.
+  fn bootstrap_shares(&self, assets: u64) -> Option<u64> {
+    self.decimals.assets_to_shares(assets, self.share_decimals)
+  }
+
+  /// Share price of an empty vault, the inverse of `bootstrap_shares`.
+  fn bootstrap_share_price(&self) -> Option<u64> {
+    self.decimals.shares_to_assets(SHARE_PRICE_PRECISION, self.share_decimals)
+  }
+
+  pub fn set_margin_account(
+    &mut self,
+    margin_account: Pubkey,
+    price_feed: Pubkey,
+    zeta_underlying_mint: Pubkey,
+  ) -> Result<()> {
+    self.margin_account = margin_account;
+    self.zeta_underlying_mint = zeta_underlying_mint;
+    self.oracle = PriceFeedConfig {
+      source: OracleSource::PythPush,
+      price_feed,
+      feed_id: [0; 32],
+      max_age: DEFAULT_MAX_PRICE_AGE,
+    };
+    Ok(())
+  }
+
+  pub fn set_cross_margin_account(&mut self, cross_margin_account: Pubkey) -> Result<()> {
+    self.cross_margin_account = cross_margin_account;
+    Ok(())
+  }
+
+  pub fn set_drift_user(&mut self, drift_user: Pubkey) -> Result<()> {
+    self.drift_user = drift_user;
+    Ok(())
+  }
+
+  /// For the instructions written against a single venue.
+  pub fn check_venue(&self, venue: Venue) -> Result<()> {
+    if self.venue != venue {
+      return err!(VaultError::VenueUnsupported);
     }
-    self.total_harvest = self.total_harvest
-      .checked_add(amount).unwrap();
-    self.last_harvest = now;
     Ok(())
   }
 
-  pub fn after_gain(&mut self, amount: u64, now: i64) -> Result<()> {
-    self.statistic.total_gain = self.statistic.total_gain
-      .checked_add(amount).unwrap();
-    self.statistic.locked_profit = self
-      .current_locked_profit(now).unwrap()
-      .checked_add(amount).unwrap();
-    self.statistic.last_gain = now;
+  pub fn set_lookup_table(&mut self, lookup_table: Pubkey) -> Result<()> {
+    self.lookup_table = lookup_table;
+    Ok(())
+  }
+
+  /// Accounts stored on the vault that trade and settle transactions pass,
+  /// the ones left unset are skipped.
+  pub fn static_accounts(&self) -> Vec<Pubkey> {
+    [
+      self.usdc_vault,
+      self.collateral_vault,
+      self.underlying_vault,
+      self.margin_account,
+      self.cross_margin_account,
+      self.reserve,
+      self.zeta_group,
+      self.oracle.price_feed,
+      self.obligation,
+      self.drift_user,
+      self.insurance.token_account,
+    ]
+      .into_iter()
+      .filter(|key| *key != Pubkey::default())
+      .collect()
+  }
+
+  /// Config instructions are signed by the governance treasury once it took
+  /// over from the authority, through a proposal executed by SPL Governance
+  /// that passes the realm's native treasury as their `authority` account.
+  /// The trading and rollover cranks stay with the authority.
+  pub fn check_config_authority(&self, signer: &Pubkey) -> Result<()> {
+    let config_authority = if self.governance != Pubkey::default() {
+      self.governance
+    } else {
+      self.authority
+    };
+    if *signer != config_authority {
+      return err!(VaultError::InvalidConfigAuthority);
+    }
+    Ok(())
+  }
+
+  /// Zeta account holding the margin under the selected account model.
+  pub fn active_margin_account(&self) -> Pubkey {
+    if self.cross_margin {
+      self.cross_margin_account
+    } else {
+      self.margin_account
+    }
+  }
+
+  /// Executor seed of the current executor version.
+  pub fn executor_version_seed(&self) -> &[u8] {
+    ExecutorPda::version_seed(&self.executor_version)
+  }
+
+  /// Starts the timelock for moving to the next executor derivation.
+  /// Proposing again restarts it.
+  pub fn propose_executor_rotation(&mut self, now: i64) -> Result<()> {
+    self.pending_executor_version = match self.executor_version.checked_add(1) {
+      Some(version) => version,
+      None => return err!(VaultError::InvalidConfig),
+    };
+    self.executor_rotation_at = now
+      .checked_add(EXECUTOR_ROTATION_DELAY).unwrap();
+    Ok(())
+  }
+
+  pub fn check_executor_rotation(&self, now: i64) -> Result<()> {
+    if self.pending_executor_version == 0 || now < self.executor_rotation_at {
+      msg!("pending version: {}, rotation at: {}", self.pending_executor_version, self.executor_rotation_at);
+      return err!(VaultError::ExecutorRotationLocked);
+    }
+    Ok(())
+  }
+
+  /// Switches to the pending executor. The cross-margin account and the drift
+  /// user belong to the old executor and have to be set up again, the vault
+  /// trades from the per-group margin account the balance moved to until then.
+  pub fn after_executor_rotation(&mut self, executor_bump: u8, margin_account: Pubkey) -> Result<()> {
+    self.executor_version = self.pending_executor_version;
+    self.executor_bump = executor_bump;
+    self.margin_account = margin_account;
+    self.cross_margin = false;
+    self.cross_margin_account = Pubkey::default();
+    self.drift_user = Pubkey::default();
+    self.delta_hedge.delta_band = 0;
+    self.pending_executor_version = 0;
+    self.executor_rotation_at = 0;
     Ok(())
   }
+
+  /// For the instructions that only know the per-group margin account.
+  pub fn check_group_margin(&self) -> Result<()> {
+    if self.cross_margin {
+      return err!(VaultError::CrossMarginUnsupported);
+    }
+    Ok(())
+  }
+
+  pub fn update_share_price(&mut self, total_share_supply: u64, now: i64) -> Result<()> {
+    let price = self.current_share_price(total_share_supply, now).unwrap();
+    self.share_price.update(price, now)
+  }
+
+  pub fn current_share_price(&self, total_share_supply: u64, now: i64) -> Option<u64> {
+    let total_underlying = self.free_funds(now)?;
+    let share_supply = self.share_supply(total_share_supply, now)?;
+    if share_supply == 0 {
+      return self.bootstrap_share_price();
+    }
+    price_per_share(total_underlying, share_supply)
+  }
+
+  /// Share price at full NAV, before profit locking.
+  pub fn nav_share_price(&self, total_share_supply: u64) -> Option<u64> {
+    if total_share_supply == 0 {
+      return self.bootstrap_share_price();
+    }
+    price_per_share(self.marked_assets()?, total_share_supply)
+  }
+
+  pub fn set_oracle(
+    &mut self,
+    source: OracleSource,
+    price_feed: Pubkey,
+    feed_id: [u8; 32],
+    max_age: u64,
+  ) -> Result<()> {
+    self.oracle = PriceFeedConfig {
+      source,
+      price_feed,
+      feed_id,
+      max_age,
+    };
+    Ok(())
+  }
+
+  pub fn set_reserve_oracle(
+    &mut self,
+    source: OracleSource,
+    price_feed: Pubkey,
+    feed_id: [u8; 32],
+    max_age: u64,
+  ) -> Result<()> {
+    self.reserve_oracle = PriceFeedConfig {
+      source,
+      price_feed,
+      feed_id,
+      max_age,
+    };
+    Ok(())
+  }
+
+  /// Validated against the zeta group margin parameters, see `MarginOverrides`.
+  pub fn set_margin_overrides(
+    &mut self,
+    overrides: MarginOverrides,
+    zeta_group: &ZetaGroup,
+  ) -> Result<()> {
+    if !overrides.is_valid(&zeta_group.margin_parameters) {
+      return err!(VaultError::InvalidMarginOverrides);
+    }
+    self.margin_overrides = overrides;
+    Ok(())
+  }
+
+  pub fn set_keeper_set(&mut self, keeper_set: KeeperSet) -> Result<()> {
+    if !keeper_set.is_valid(&self.authority) {
+      return err!(VaultError::InvalidKeeperSet);
+    }
+    self.keeper_set = keeper_set;
+    Ok(())
+  }
+
+  /// Zeta group margin parameters with the vault overrides applied.
+  pub fn margin_parameters(&self, zeta_group: &ZetaGroup) -> MarginParameters {
+    self.margin_overrides.apply(&zeta_group.margin_parameters)
+  }
+
+  pub fn update_config(&mut self, params: &VaultConfigParams) -> Result<()> {
+    self.check_not_in_progress()?;
+    if let Some(management_fee_bps) = params.management_fee_bps {
+      if management_fee_bps > MAX_MANAGEMENT_FEE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(harvest_interval) = params.harvest_interval {
+      if harvest_interval < 0 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(max_price_age) = params.max_price_age {
+      if max_price_age == 0 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    let epoch_schedule = EpochSchedule {
+      epoch_start: params.epoch_start.unwrap_or(self.epoch_schedule.epoch_start),
+      epoch_duration: params.epoch_duration.unwrap_or(self.epoch_schedule.epoch_duration),
+      deposit_window: params.deposit_window.unwrap_or(self.epoch_schedule.deposit_window),
+      withdraw_window: params.withdraw_window.unwrap_or(self.epoch_schedule.withdraw_window),
+    };
+    if let Some(max_ltv_bps) = params.max_ltv_bps {
+      if max_ltv_bps > MAX_LTV_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(instant_exit_fee_bps) = params.instant_exit_fee_bps {
+      if instant_exit_fee_bps > MAX_INSTANT_EXIT_FEE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(exit_fee_bps) = params.exit_fee_bps {
+      if exit_fee_bps > MAX_EXIT_FEE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(exit_fee_decay_secs) = params.exit_fee_decay_secs {
+      if exit_fee_decay_secs < 0 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(performance_fee_bps) = params.performance_fee_bps {
+      if performance_fee_bps > MAX_PERFORMANCE_FEE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if params.fee_tiers.is_some() || params.performance_fee_bps.is_some() {
+      let fee_tiers = params.fee_tiers.unwrap_or(self.fee_tiers);
+      let performance_fee_bps = params.performance_fee_bps.unwrap_or(self.performance_fee_bps);
+      if !validate_fee_tiers(&fee_tiers, performance_fee_bps) {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(ladder_weights_bps) = params.ladder_weights_bps {
+      if ladder_weights_bps.iter().sum::<u64>() > 10_000 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(order_tif_secs) = params.order_tif_secs {
+      if order_tif_secs > u16::MAX as u64 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(min_iv_bps) = params.min_iv_bps {
+      if min_iv_bps > MAX_IV_FLOOR_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(insurance_bps) = params.insurance_bps {
+      if insurance_bps > MAX_INSURANCE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(max_reprice_walk_bps) = params.max_reprice_walk_bps {
+      if max_reprice_walk_bps > 10_000 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(max_close_price_bps) = params.max_close_price_bps {
+      if max_close_price_bps > MAX_CLOSE_PRICE_BPS {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    let allowed_markets = match &params.allowed_markets {
+      Some(markets) => match MarketRegistry::allowlist(markets) {
+        Some(allowed_markets) => Some(allowed_markets),
+        None => return err!(VaultError::InvalidConfig),
+      },
+      None => None,
+    };
+    if let Some(max_expiries) = params.max_expiries {
+      if max_expiries as usize > ACTIVE_EXPIRIES {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(max_strike_distance_bps) = params.max_strike_distance_bps {
+      if max_strike_distance_bps > 10_000 {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if let Some(max_reserve_price_divergence_bps) = params.max_reserve_price_divergence_bps {
+      if max_reserve_price_divergence_bps > 10_000
+        || (max_reserve_price_divergence_bps > 0 && self.reserve_oracle.price_feed == Pubkey::default()) {
+        return err!(VaultError::InvalidConfig);
+      }
+    }
+    if params.priced_margin == Some(true) && self.reserve_oracle.price_feed == Pubkey::default() {
+      return err!(VaultError::InvalidConfig);
+    }
+    if params.cross_margin == Some(true) && self.cross_margin_account == Pubkey::default() {
+      return err!(VaultError::InvalidConfig);
+    }
+    if params.venue == Some(Venue::Drift) && self.drift_user == Pubkey::default() {
+      return err!(VaultError::InvalidConfig);
+    }
+    if params.hedge_delta_band.unwrap_or(0) > 0 && self.drift_user == Pubkey::default() {
+      return err!(VaultError::InvalidConfig);
+    }
+    if !epoch_schedule.validate() {
+      return err!(VaultError::InvalidConfig);
+    }
+
+    if let Some(deposit_limit) = params.deposit_limit {
+      self.deposit_limit = deposit_limit;
+    }
+    if let Some(management_fee_bps) = params.management_fee_bps {
+      self.management_fee_bps = management_fee_bps;
+    }
+    if let Some(harvest_interval) = params.harvest_interval {
+      self.harvest_interval = harvest_interval;
+    }
+    if let Some(deposit_lock_slots) = params.deposit_lock_slots {
+      self.deposit_lock_slots = deposit_lock_slots;
+    }
+    if let Some(max_price_age) = params.max_price_age {
+      self.oracle.max_age = max_price_age;
+    }
+    if let Some(snapshot_slots) = params.snapshot_slots {
+      self.snapshot_slots = snapshot_slots;
+    }
+    if let Some(max_ltv_bps) = params.max_ltv_bps {
+      self.max_ltv_bps = max_ltv_bps;
+    }
+    if let Some(max_abs_delta) = params.max_abs_delta {
+      self.max_abs_delta = max_abs_delta;
+    }
+    if let Some(max_abs_vega) = params.max_abs_vega {
+      self.max_abs_vega = max_abs_vega;
+    }
+    if let Some(instant_exit_fee_bps) = params.instant_exit_fee_bps {
+      self.instant_exit_fee_bps = instant_exit_fee_bps;
+    }
+    if let Some(idle_sweep_enabled) = params.idle_sweep_enabled {
+      self.idle_sweep_enabled = idle_sweep_enabled;
+    }
+    if let Some(performance_fee_bps) = params.performance_fee_bps {
+      self.performance_fee_bps = performance_fee_bps;
+    }
+    if let Some(fee_tiers) = params.fee_tiers {
+      self.fee_tiers = fee_tiers;
+    }
+    if let Some(strategy_program) = params.strategy_program {
+      self.strategy_program = strategy_program;
+    }
+    if let Some(strategy_instructions) = params.strategy_instructions {
+      self.strategy_instructions = strategy_instructions;
+    }
+    if let Some(ladder_weights_bps) = params.ladder_weights_bps {
+      self.ladder_weights_bps = ladder_weights_bps;
+    }
+    if let Some(min_deposit) = params.min_deposit {
+      self.min_deposit = min_deposit;
+    }
+    if let Some(guardian) = params.guardian {
+      self.guardian = guardian;
+    }
+    if let Some(governance) = params.governance {
+      self.governance = governance;
+    }
+    if params.clear_abort == Some(true) {
+      self.aborted_until = 0;
+    }
+    if let Some(max_lots_per_slot) = params.max_lots_per_slot {
+      self.order_throttle.max_lots_per_slot = max_lots_per_slot;
+    }
+    if let Some(max_notional_per_slot) = params.max_notional_per_slot {
+      self.order_throttle.max_notional_per_slot = max_notional_per_slot;
+    }
+    if let Some(max_notional_per_epoch) = params.max_notional_per_epoch {
+      self.order_throttle.max_notional_per_epoch = max_notional_per_epoch;
+    }
+    if let Some(keeper_timeout_slots) = params.keeper_timeout_slots {
+      self.keeper_timeout_slots = keeper_timeout_slots;
+    }
+    if let Some(cross_margin) = params.cross_margin {
+      self.cross_margin = cross_margin;
+    }
+    if let Some(post_only) = params.post_only {
+      self.post_only = post_only;
+    }
+    if let Some(order_tif_secs) = params.order_tif_secs {
+      self.order_tif_secs = order_tif_secs;
+    }
+    if let Some(min_iv_bps) = params.min_iv_bps {
+      self.premium_floor.min_iv_bps = min_iv_bps;
+    }
+    if let Some(max_mark_deviation_bps) = params.max_mark_deviation_bps {
+      self.max_mark_deviation_bps = max_mark_deviation_bps;
+    }
+    if let Some(reserve_fallback_idle) = params.reserve_fallback_idle {
+      self.reserve_fallback_idle = reserve_fallback_idle;
+    }
+    if let Some(max_resting_orders) = params.max_resting_orders {
+      self.order_budget.max_resting_orders = max_resting_orders;
+    }
+    if let Some(max_reprices_per_epoch) = params.max_reprices_per_epoch {
+      self.order_budget.max_reprices_per_epoch = max_reprices_per_epoch;
+    }
+    if let Some(venue) = params.venue {
+      self.venue = venue;
+    }
+    if let Some(hedge_perp_market_index) = params.hedge_perp_market_index {
+      self.delta_hedge.perp_market_index = hedge_perp_market_index;
+    }
+    if let Some(hedge_delta_band) = params.hedge_delta_band {
+      self.delta_hedge.delta_band = hedge_delta_band;
+    }
+    if let Some(insurance_bps) = params.insurance_bps {
+      self.insurance.carve_out_bps = insurance_bps;
+    }
+    if let Some(max_order_notional) = params.max_order_notional {
+      self.max_order_notional = max_order_notional;
+    }
+    if let Some(max_reprice_walk_bps) = params.max_reprice_walk_bps {
+      self.max_reprice_walk_bps = max_reprice_walk_bps;
+    }
+    if let Some(max_reserve_price_divergence_bps) = params.max_reserve_price_divergence_bps {
+      self.max_reserve_price_divergence_bps = max_reserve_price_divergence_bps;
+    }
+    if let Some(priced_margin) = params.priced_margin {
+      self.margin_basis.enabled = priced_margin;
+    }
+    if let Some(min_harvest_amount) = params.min_harvest_amount {
+      self.min_harvest_amount = min_harvest_amount;
+    }
+    if let Some(in_kind_redemption) = params.in_kind_redemption {
+      self.in_kind_redemption = in_kind_redemption;
+    }
+    if let Some(max_valuation_staleness) = params.max_valuation_staleness {
+      self.max_valuation_staleness = max_valuation_staleness;
+    }
+    if let Some(max_close_price_bps) = params.max_close_price_bps {
+      self.max_close_price_bps = max_close_price_bps;
+    }
+    if let Some(max_exchange_rate_staleness) = params.max_exchange_rate_staleness {
+      self.max_exchange_rate_staleness = max_exchange_rate_staleness;
+    }
+    if let Some(exit_fee_bps) = params.exit_fee_bps {
+      self.exit_fee.fee_bps = exit_fee_bps;
+    }
+    if let Some(exit_fee_decay_secs) = params.exit_fee_decay_secs {
+      self.exit_fee.decay_secs = exit_fee_decay_secs;
+    }
+    if let Some(max_yield_apr_bps) = params.max_yield_apr_bps {
+      self.max_yield_apr_bps = max_yield_apr_bps;
+    }
+    if let Some(allowed_markets) = allowed_markets {
+      self.market_registry.allowed_markets = allowed_markets;
+    }
+    if let Some(max_expiries) = params.max_expiries {
+      self.market_registry.max_expiries = max_expiries;
+    }
+    if let Some(max_strike_distance_bps) = params.max_strike_distance_bps {
+      self.market_registry.max_strike_distance_bps = max_strike_distance_bps;
+    }
+    self.epoch_schedule = epoch_schedule;
+    Ok(())
+  }
+
+  pub fn set_obligation(&mut self, obligation: Pubkey) -> Result<()> {
+    self.obligation = obligation;
+    Ok(())
+  }
+
+  pub fn after_obligation_deposit(&mut self, collateral_amount: u64) -> Result<()> {
+    self.obligation_collateral = self.obligation_collateral
+      .checked_add(collateral_amount).unwrap();
+    Ok(())
+  }
+
+  /// The obligation has to be the executor's on the vault lending market and
+  /// refreshed in this slot, so its debt includes the interest accrued up to now.
+  pub fn check_obligation(
+    &self,
+    obligation: &ObligationState,
+    executor: &Pubkey,
+    lending_market: &Pubkey,
+    slot: u64,
+  ) -> Result<()> {
+    if obligation.owner != *executor || obligation.lending_market != *lending_market {
+      return err!(VaultError::InvalidObligation);
+    }
+    if obligation.last_update.is_stale(slot)? {
+      return err!(VaultError::ObligationStale);
+    }
+    Ok(())
+  }
+
+  /// Debt of the obligation in `borrow_reserve` liquidity, interest accrued
+  /// up to the reserve's last refresh included. The vault borrows from a
+  /// single reserve, a borrow anywhere else fails rather than being missed.
+  pub fn obligation_debt(
+    &self,
+    obligation: &ObligationState,
+    borrow_reserve: &Account<Reserve>,
+  ) -> Result<u64> {
+    if obligation.borrows.iter().any(|borrow| borrow.borrow_reserve != borrow_reserve.key()) {
+      return err!(VaultError::InvalidObligation);
+    }
+    obligation.borrowed_amount(&borrow_reserve.key(), borrow_reserve)
+  }
+
+  /// Checks that the obligation stays under `max_ltv_bps` with `borrow_amount`
+  /// more debt and `withdraw_amount` fewer cTokens. Collateral and debt are
+  /// read from the refreshed obligation, not from the vault ledger.
+  pub fn check_ltv(
+    &self,
+    obligation: &ObligationState,
+    collateral_reserve: &Account<Reserve>,
+    borrow_reserve: &Account<Reserve>,
+    borrow_amount: u64,
+    withdraw_amount: u64,
+  ) -> Result<()> {
+    let debt = self.obligation_debt(obligation, borrow_reserve)?
+      .checked_add(borrow_amount).unwrap();
+    if debt == 0 {
+      return Ok(());
+    }
+    if self.max_ltv_bps == 0 {
+      return err!(VaultError::LeverageDisabled);
+    }
+    let collateral = obligation.deposited_amount(&collateral_reserve.key())
+      .checked_sub(withdraw_amount)
+      .ok_or_else(|| error!(VaultError::LtvExceeded))?;
+    let collateral_liquidity = self
+      .for_underlying(collateral, collateral_reserve).unwrap();
+    let collateral_value = collateral_reserve.liquidity
+      .market_value(collateral_liquidity)?
+      .try_mul(self.max_ltv_bps)?;
+    let borrow_value = borrow_reserve.liquidity
+      .market_value(debt)?
+      .try_mul(MAX_LTV_BPS)?;
+    if borrow_value > collateral_value {
+      return err!(VaultError::LtvExceeded);
+    }
+    Ok(())
+  }
+
+  /// Books the obligation as read after a borrow, repay or collateral
+  /// withdrawal, or by `refresh_valuation`. Debt growth the `borrowed` and
+  /// `repaid` USDC don't explain is interest and borrow fees, booked as a loss.
+  pub fn after_obligation_refreshed(
+    &mut self,
+    collateral: u64,
+    debt: u64,
+    borrowed: u64,
+    repaid: u64,
+    slot: u64,
+  ) -> Result<()> {
+    let expected_debt = self.total_borrowed
+      .checked_add(borrowed).unwrap()
+      .saturating_sub(repaid);
+    let borrow_cost = debt.saturating_sub(expected_debt);
+    if borrow_cost > 0 {
+      msg!("borrow cost: {}", borrow_cost);
+      self.total_loss = self.total_loss
+        .checked_add(self.decimals.to_underlying(borrow_cost).unwrap()).unwrap();
+    }
+    self.obligation_collateral = collateral;
+    self.total_borrowed = debt;
+    self.debt_slot = slot;
+    Ok(())
+  }
+
+  /// New shares wait in a `DepositReceipt` while there is an exit fee to
+  /// charge or a deposit lock to hold them for.
+  pub fn escrows_deposits(&self) -> bool {
+    self.exit_fee.fee_bps > 0 || self.deposit_lock_slots > 0
+  }
+
+  pub fn after_wallet_deposit(&mut self, slot: u64) -> Result<()> {
+    self.wallet_deposit_slot = slot;
+    Ok(())
+  }
+
+  /// Withdrawals of wallet shares. The user's own deposit stays locked for
+  /// its slot plus `deposit_lock_slots`, see `UserState::check_unlocked`, and
+  /// no withdrawal shares a slot with a deposit minted to any wallet, so
+  /// shares transferred or delegated out in the deposit slot can't leave
+  /// the vault either.
+  pub fn check_withdraw_unlocked(&self, user_state: &UserState, slot: u64) -> Result<()> {
+    user_state.check_unlocked(slot, self.deposit_lock_slots)?;
+    if slot == self.wallet_deposit_slot {
+      return err!(VaultError::DepositLocked);
+    }
+    Ok(())
+  }
+
+  pub fn instant_exit_fee(&self, amount: u64) -> Option<u64> {
+    ratio_ceil!(amount, self.instant_exit_fee_bps, 10_000_u64)
+  }
+
+  pub fn record_floor_price(&mut self, total_share_supply: u64, now: i64) -> Result<()> {
+    self.floor_share_price = self.current_share_price(total_share_supply, now).unwrap();
+    Ok(())
+  }
+
+  /// The assets the vault holds, `total_assets` of the `NavBreakdown`, have
+  /// to cover its liabilities: the `free_funds` the shares are priced off,
+  /// zeta positions at mark included on both sides. Solend debt and USDC
+  /// claims are netted out of the assets already.
+  pub fn assert_solvent(&self, total_assets: u64, now: i64) -> Result<()> {
+    self.check_not_in_progress()?;
+    let liabilities = self.free_funds(now)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    if total_assets < liabilities {
+      msg!("total_assets: {}, liabilities: {}", total_assets, liabilities);
+      return err!(VaultError::VaultInsolvent);
+    }
+    Ok(())
+  }
+
+  pub fn check_greeks(&self, exposure: &GreeksExposure) -> Result<()> {
+    if self.max_abs_delta > 0 && exposure.max_abs_delta > self.max_abs_delta {
+      msg!("delta: {}, max: {}", exposure.max_abs_delta, self.max_abs_delta);
+      return err!(VaultError::GreeksLimitExceeded);
+    }
+    if self.max_abs_vega > 0 && exposure.max_abs_vega > self.max_abs_vega {
+      msg!("vega: {}, max: {}", exposure.max_abs_vega, self.max_abs_vega);
+      return err!(VaultError::GreeksLimitExceeded);
+    }
+    Ok(())
+  }
+
+  /// Margin balance that belongs to parked liquidity, counted in NAV
+  /// next to the Solend collateral. The margin account is flat while funds
+  /// are parked, so its whole balance is parked principal plus yield.
+  pub fn parked_value(&self, margin_balance: u64) -> u64 {
+    if self.parked_margin == 0 {
+      0
+    } else {
+      self.decimals.to_underlying(margin_balance).unwrap()
+    }
+  }
+
+  /// Shares can't be priced off a NAV or a Solend debt older than
+  /// `max_valuation_staleness` slots, or a reserve exchange rate older than
+  /// `max_exchange_rate_staleness`. `refresh_valuation` in the same
+  /// transaction refreshes all three.
+  pub fn check_valuation_fresh(&self, slot: u64) -> Result<()> {
+    if self.max_valuation_staleness > 0
+      && slot.saturating_sub(self.last_valuation_slot) > self.max_valuation_staleness {
+      msg!("last valuation slot: {}, slot: {}", self.last_valuation_slot, slot);
+      return err!(VaultError::StaleValuation);
+    }
+    if self.max_exchange_rate_staleness > 0
+      && slot.saturating_sub(self.exchange_rate_slot) > self.max_exchange_rate_staleness {
+      msg!("exchange rate slot: {}, slot: {}", self.exchange_rate_slot, slot);
+      return err!(VaultError::StaleValuation);
+    }
+    if self.total_borrowed > 0
+      && self.max_valuation_staleness > 0
+      && slot.saturating_sub(self.debt_slot) > self.max_valuation_staleness {
+      msg!("debt slot: {}, slot: {}", self.debt_slot, slot);
+      return err!(VaultError::StaleValuation);
+    }
+    Ok(())
+  }
+
+  /// Guard of every mutating instruction, attached through `access_control`
+  /// in lib.rs so a strategy CPI can't re-enter the vault.
+  pub fn check_not_in_progress(&self) -> Result<()> {
+    if self.in_progress {
+      return err!(VaultError::Reentrancy);
+    }
+    Ok(())
+  }
+
+  /// Marks the vault busy before handing control to the strategy program.
+  /// The flag has to be written to the account before the CPI, nested
+  /// instructions load the vault from its data.
+  pub fn before_external_call(&mut self) -> Result<()> {
+    self.check_not_in_progress()?;
+    self.in_progress = true;
+    Ok(())
+  }
+
+  pub fn after_external_call(&mut self) {
+    self.in_progress = false;
+  }
+
+  pub fn check_not_parked(&self) -> Result<()> {
+    if self.parked_margin > 0 {
+      return err!(VaultError::IdleFundsParked);
+    }
+    Ok(())
+  }
+
+  pub fn after_park(&mut self, amount: u64) -> Result<()> {
+    if !self.idle_sweep_enabled {
+      return err!(VaultError::IdleSweepDisabled);
+    }
+    self.parked_margin = self.parked_margin
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  pub fn after_unpark(&mut self) -> Result<()> {
+    self.parked_margin = 0;
+    Ok(())
+  }
+
+  /// Receipts epoch a share lock taken now starts at, settled epochs can't be covered.
+  pub fn lock_epoch(&self) -> u64 {
+    if self.receipt_epoch_live {
+      self.receipt_epoch
+    } else {
+      self.receipt_epoch.checked_add(1).unwrap()
+    }
+  }
+
+  /// Fee shares kept out of `yield_shares`, of which `locked_yield_shares`
+  /// come from receipts credited to a lock of `lock_epochs`. The rest pays
+  /// the base fee.
+  pub fn performance_fee(
+    &self,
+    yield_shares: u64,
+    locked_yield_shares: u64,
+    lock_epochs: u64,
+  ) -> Option<u64> {
+    let tier_fee_bps = tiered_performance_fee_bps(
+      &self.fee_tiers, self.performance_fee_bps, lock_epochs,
+    );
+    let locked_yield_shares = locked_yield_shares.min(yield_shares);
+    let locked_fee = ratio_floor!(locked_yield_shares, tier_fee_bps, 10_000_u64)?;
+    let base_fee = ratio_floor!(yield_shares - locked_yield_shares, self.performance_fee_bps, 10_000_u64)?;
+    locked_fee.checked_add(base_fee)
+  }
+
+  /// Only allowlisted instructions of the configured strategy program may be
+  /// called for order intents.
+  pub fn check_strategy_instruction(&self, program: &Pubkey, data: &[u8]) -> Result<()> {
+    if self.strategy_program == Pubkey::default() || *program != self.strategy_program {
+      return err!(VaultError::StrategyDisabled);
+    }
+    let allowed = data.len() >= 8 && self.strategy_instructions
+      .iter()
+      .any(|discriminator| *discriminator != [0; 8] && discriminator[..] == data[..8]);
+    if !allowed {
+      return err!(VaultError::StrategyInstructionNotAllowed);
+    }
+    Ok(())
+  }
+
+  /// Configured ladder weights, in leg order, without the unused legs.
+  pub fn ladder_weights(&self) -> Vec<u64> {
+    self.ladder_weights_bps
+      .iter()
+      .copied()
+      .filter(|weight| *weight > 0)
+      .collect()
+  }
+
+  /// Books a keeper order against the throttle of the current slot and epoch.
+  /// `cosigned` when a keeper cosigner signed the transaction, see `KeeperSet`.
+  pub fn consume_order_budget(&mut self, size: u64, price: u64, cosigned: bool, slot: u64, now: i64) -> Result<()> {
+    self.check_order_notional(size, price)?;
+    self.consume_cosign_budget(size, price, cosigned, now)?;
+    let epoch = self.epoch_schedule.current_epoch(now);
+    self.order_throttle.consume(size, price, slot, epoch)
+  }
+
+  /// Orders placed without a keeper cosigner share `keeper_set.cosign_notional`
+  /// per epoch, splitting a large order across orders or slots still needs
+  /// the cosigner.
+  pub fn consume_cosign_budget(&mut self, size: u64, price: u64, cosigned: bool, now: i64) -> Result<()> {
+    if cosigned {
+      return Ok(());
+    }
+    let epoch = self.epoch_schedule.current_epoch(now);
+    if epoch != self.uncosigned_epoch {
+      self.uncosigned_epoch = epoch;
+      self.uncosigned_notional = 0;
+    }
+    let notional = ratio_floor!(size, price, POSITION_PRECISION_DENOMINATOR as u64).unwrap();
+    self.uncosigned_notional = self.uncosigned_notional
+      .checked_add(notional).unwrap();
+    if self.keeper_set.needs_cosigner(self.uncosigned_notional) {
+      msg!("uncosigned notional: {}", self.uncosigned_notional);
+      return err!(VaultError::CosignerRequired);
+    }
+    Ok(())
+  }
+
+  pub fn check_order_notional(&self, size: u64, price: u64) -> Result<()> {
+    if self.max_order_notional == 0 {
+      return Ok(());
+    }
+    let notional = ratio_floor!(size, price, POSITION_PRECISION_DENOMINATOR as u64).unwrap();
+    if notional > self.max_order_notional {
+      msg!("order notional: {}", notional);
+      return err!(VaultError::OrderNotionalExceeded);
+    }
+    Ok(())
+  }
+
+  pub fn check_resting_orders(&self, resting_orders: u64) -> Result<()> {
+    self.order_budget.check_resting(resting_orders)
+  }
+
+  /// Books a `cancel_replace` against the reprices of the current epoch.
+  pub fn consume_reprice(&mut self, now: i64) -> Result<()> {
+    let epoch = self.epoch_schedule.current_epoch(now);
+    self.order_budget.consume_reprice(epoch)
+  }
+
+  /// Order type of keeper orders, post-only ones are rejected instead of
+  /// crossing the spread.
+  pub fn order_type(&self) -> OrderType {
+    if self.post_only {
+      OrderType::PostOnly
+    } else {
+      OrderType::Limit
+    }
+  }
+
+  /// Rejects order prices more than `max_mark_deviation_bps` away from the
+  /// zeta mark price of the series.
+  pub fn check_price_band(&self, price: u64, mark_price: u64) -> Result<()> {
+    if self.max_mark_deviation_bps == 0 {
+      return Ok(());
+    }
+    let max_deviation = ratio_floor!(mark_price, self.max_mark_deviation_bps, 10_000_u64).unwrap();
+    let deviation = if price > mark_price { price - mark_price } else { mark_price - price };
+    if deviation > max_deviation {
+      msg!("price: {}, mark price: {}", price, mark_price);
+      return err!(VaultError::PriceOutsideMarkBand);
+    }
+    Ok(())
+  }
+
+  pub fn check_market_allowed(&self, zeta_group: &ZetaGroup, market_index: usize, spot: u64) -> Result<()> {
+    if !self.market_registry.is_allowed(zeta_group, market_index, spot)? {
+      msg!("market index: {}", market_index);
+      return err!(VaultError::MarketNotAllowed);
+    }
+    Ok(())
+  }
+
+  /// Caps a closing bid at `max_close_price_bps` of the mark, the value the
+  /// short has left.
+  pub fn check_close_price(&self, price: u64, mark_price: u64) -> Result<()> {
+    let max_close_price_bps = if self.max_close_price_bps == 0 {
+      10_000
+    } else {
+      self.max_close_price_bps
+    };
+    let max_price = ratio_floor!(mark_price, max_close_price_bps, 10_000_u64).unwrap();
+    if price > max_price {
+      msg!("price: {}, mark price: {}", price, mark_price);
+      return err!(VaultError::ClosePriceTooHigh);
+    }
+    Ok(())
+  }
+
+  /// Platform (USDC) amount in underlying. Vaults with a non-USDC underlying
+  /// convert at `underlying_price` (6.dp, the reserve oracle), the others
+  /// rescale 1:1 as their underlying is the quote asset.
+  pub fn platform_to_underlying(&self, amount: u64, underlying_price: Option<u64>) -> Option<u64> {
+    match underlying_price {
+      Some(price) => {
+        ratio_floor!(amount, self.decimals.underlying_unit()?, price)
+      }
+      _ => self.decimals.to_underlying(amount),
+    }
+  }
+
+  /// Fails when the Solend market price of the reserve liquidity is more
+  /// than `max_reserve_price_divergence_bps` away from the pyth `oracle_price`
+  /// (6.dp), cTokens can't be valued then.
+  pub fn check_reserve_price(&self, reserve: &Reserve, oracle_price: u64) -> Result<()> {
+    if self.max_reserve_price_divergence_bps == 0 {
+      return Ok(());
+    }
+    let reserve_price = reserve.liquidity.market_price
+      .try_mul(1_000_000_u64)?
+      .try_floor_u64()?;
+    let max_divergence = ratio_floor!(oracle_price, self.max_reserve_price_divergence_bps, 10_000_u64).unwrap();
+    let divergence = if reserve_price > oracle_price {
+      reserve_price - oracle_price
+    } else {
+      oracle_price - reserve_price
+    };
+    if divergence > max_divergence {
+      msg!("reserve price: {}, oracle price: {}", reserve_price, oracle_price);
+      return err!(VaultError::ReservePriceDiverged);
+    }
+    Ok(())
+  }
+
+  /// Rejects orders priced below the premium floor, on either side.
+  pub fn check_premium_floor(
+    &self,
+    price: u64,
+    kind: Kind,
+    spot: u64,
+    strike: u64,
+    seconds_to_expiry: u64,
+  ) -> Result<()> {
+    if !self.premium_floor.is_enabled() {
+      return Ok(());
+    }
+    let min_premium = self.premium_floor
+      .min_premium(kind, spot, strike, seconds_to_expiry).unwrap();
+    if price < min_premium {
+      msg!("price: {}, min premium: {}", price, min_premium);
+      return err!(VaultError::PremiumBelowFloor);
+    }
+    Ok(())
+  }
+
+  pub fn order_tif_offset(&self) -> Option<u16> {
+    if self.order_tif_secs == 0 {
+      None
+    } else {
+      Some(self.order_tif_secs as u16)
+    }
+  }
+
+  /// Client order id for the next order, starts at 1 as serum treats 0 as unset.
+  pub fn next_client_order_id(&mut self) -> u64 {
+    self.order_count = self.order_count
+      .checked_add(1).unwrap();
+    self.order_count
+  }
+
+  /// Lamports the executor keeps: `rent_reserve`, defaulting to the rent
+  /// exemption of the executor account itself so reclaiming never leaves it
+  /// to be purged.
+  pub fn executor_rent_reserve(&self, rent: &Rent) -> u64 {
+    self.rent_reserve.max(rent.minimum_balance(0))
+  }
+
+  pub fn after_rent_top_up(&mut self, lamports: u64) -> Result<()> {
+    self.rent_reserve = self.rent_reserve
+      .checked_add(lamports).unwrap();
+    Ok(())
+  }
+
+  /// Rejects deposits below `min_deposit` or too small to mint a share,
+  /// which would otherwise be donated to the holders.
+  pub fn check_deposit_shares(&self, amount: u64, shares: u64) -> Result<()> {
+    if amount < self.min_deposit || shares == 0 {
+      msg!("amount: {}, shares: {}", amount, shares);
+      return err!(VaultError::DepositTooSmall);
+    }
+    Ok(())
+  }
+
+  pub fn is_withdraw_open(&self, now: i64) -> bool {
+    now < self.aborted_until || self.epoch_schedule.is_withdraw_open(now)
+  }
+
+  pub fn check_not_aborted(&self, now: i64) -> Result<()> {
+    if now < self.aborted_until {
+      return err!(VaultError::EpochAborted);
+    }
+    Ok(())
+  }
+
+  /// Spreads the gap between the `marked_assets` and `real_assets` (what
+  /// the vault holds after a liquidation, net of the USDC claims) pro-rata
+  /// over the shares and the in-kind USDC claims worth `claims_value`, so
+  /// queued and later withdrawals take the same haircut instead of the first
+  /// exits draining what is left. Returns the part booked as a loss of the
+  /// shares. The floor price follows the haircut.
+  pub fn after_socialized_loss(
+    &mut self,
+    real_assets: u64,
+    claims_value: u64,
+    total_share_supply: u64,
+    now: i64,
+  ) -> Result<u64> {
+    // the real assets hold the zeta positions at mark, so does the book
+    let book_assets = self.marked_assets()
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    if real_assets >= book_assets {
+      return err!(VaultError::NoShortfall);
+    }
+    let shortfall = book_assets - real_assets;
+    let owed = book_assets.checked_add(claims_value)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    let kept = owed - shortfall;
+    let claims_cut = ratio_floor!(claims_value, shortfall, owed)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    let loss = shortfall - claims_cut;
+    self.usdc_claims = ratio_floor!(self.usdc_claims, kept, owed)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    self.claims_index = ratio_floor!(self.current_claims_index(), kept, owed)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+
+    self.total_loss = self.total_loss.checked_add(loss)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    self.socialized_loss = self.socialized_loss.checked_add(shortfall)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    let haircut_bps = ratio_floor!(shortfall, 10_000_u64, owed)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    // 1 - (1 - epoch haircut) * (1 - haircut)
+    let epoch_kept_bps = ratio_floor!(
+      10_000_u64.saturating_sub(self.epoch_haircut_bps),
+      10_000_u64 - haircut_bps,
+      10_000_u64
+    ).ok_or_else(|| error!(VaultError::MathOverflow))?;
+    self.epoch_haircut_bps = 10_000 - epoch_kept_bps;
+    self.last_haircut_bps = haircut_bps;
+    self.last_haircut_epoch = self.epoch_schedule.current_epoch(now);
+    let share_price = self.current_share_price(total_share_supply, now)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    self.floor_share_price = self.floor_share_price.min(share_price);
+    Ok(loss)
+  }
+
+  /// Index the USDC claims are scaled by, `CLAIMS_INDEX_PRECISION` until the
+  /// first haircut.
+  pub fn current_claims_index(&self) -> u64 {
+    if self.claims_index == 0 {
+      CLAIMS_INDEX_PRECISION
+    } else {
+      self.claims_index
+    }
+  }
+
+  /// Halts trading until the next epoch starts. Without an epoch schedule it
+  /// lasts until the authority clears it with `clear_abort`.
+  pub fn after_abort(&mut self, now: i64) -> Result<()> {
+    self.aborted_until = if self.epoch_schedule.is_enabled() {
+      self.epoch_schedule.current_epoch_start(now)
+        .checked_add(self.epoch_schedule.epoch_duration).unwrap()
+    } else {
+      i64::MAX
+    };
+    Ok(())
+  }
+
+  /// Restarts the dead-man switch, called by every keeper instruction and
+  /// the config updates of the authority.
+  pub fn record_keeper_activity(&mut self, slot: u64) {
+    self.last_keeper_slot = slot;
+  }
+
+  /// Dead-man switch: once no keeper instruction ran for
+  /// `keeper_timeout_slots`, trading is halted and the withdrawal window
+  /// stays open, see `is_withdraw_open`, until a config update with
+  /// `clear_abort` reopens trading. Withdrawals are paid from what the vault
+  /// holds like in any open window.
+  pub fn after_keeper_timeout(&mut self, slot: u64) -> Result<()> {
+    if self.keeper_timeout_slots == 0 {
+      return err!(VaultError::KeeperActive);
+    }
+    let deadline = self.last_keeper_slot
+      .checked_add(self.keeper_timeout_slots).unwrap();
+    if slot <= deadline {
+      return err!(VaultError::KeeperActive);
+    }
+    self.aborted_until = i64::MAX;
+    Ok(())
+  }
+
+  pub fn after_deposit(&mut self, amount: u64) -> Result<()> {
+    self.statistic.total_deposit = self.statistic.total_deposit
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// Pending deposits count against the deposit limit, they convert in the
+  /// next epoch so the vault needs an epoch schedule.
+  pub fn check_pending_deposit(&self, amount: u64) -> Result<()> {
+    if !self.epoch_schedule.is_enabled() {
+      return err!(VaultError::EpochScheduleDisabled);
+    }
+    if amount == 0 {
+      return err!(VaultError::ZeroDeposit);
+    }
+    if amount < self.min_deposit {
+      msg!("amount: {}", amount);
+      return err!(VaultError::DepositTooSmall);
+    }
+    self.check_deposit_limit(amount)
+  }
+
+  /// Deposits and the pending deposits waiting to convert count against the
+  /// deposit limit.
+  pub fn check_deposit_limit(&self, amount: u64) -> Result<()> {
+    let total = self.statistic.total_deposit
+      .checked_add(self.pending_deposits)
+      .and_then(|total| total.checked_add(amount))
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    if total > self.deposit_limit {
+      return err!(VaultError::VaultIsFull);
+    }
+    Ok(())
+  }
+
+  pub fn after_pending_deposit(&mut self, amount: u64) -> Result<()> {
+    self.pending_deposits = self.pending_deposits
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// Records the share price the pending deposits of the previous epoch
+  /// enter at, once its expiry is booked and before any of them converts.
+  pub fn snapshot_entry_price(&mut self, total_share_supply: u64, now: i64) -> Result<()> {
+    self.entry_share_price = self.current_share_price(total_share_supply, now).unwrap();
+    self.entry_price_epoch = self.epoch_schedule.current_epoch(now);
+    Ok(())
+  }
+
+  /// Share price of the pending deposits converted during the current epoch,
+  /// conversions wait for the rollover to record it.
+  pub fn entry_share_price(&self, now: i64) -> Result<u64> {
+    let epoch = self.epoch_schedule.current_epoch(now);
+    if self.entry_share_price == 0 || self.entry_price_epoch != epoch {
+      return err!(VaultError::EntryPriceNotRecorded);
+    }
+    Ok(self.entry_share_price)
+  }
+
+  pub fn after_pending_deposit_converted(&mut self, amount: u64) -> Result<()> {
+    self.pending_deposits = self.pending_deposits
+      .checked_sub(amount).unwrap();
+    self.after_deposit(amount)
+  }
+
+  pub fn after_pending_deposit_refunded(&mut self, amount: u64) -> Result<()> {
+    self.pending_deposits = self.pending_deposits
+      .checked_sub(amount).unwrap();
+    Ok(())
+  }
+
+  pub fn after_withdraw(&mut self, amount: u64) -> Result<()> {
+    self.statistic.total_withdraw = self.statistic.total_withdraw
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  pub fn after_usdc_claim(&mut self, amount: u64) -> Result<()> {
+    self.usdc_claims = self.usdc_claims
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// USDC of the USDC vault holding `usdc_vault_amount` that isn't owed to
+  /// the in-kind claims. Only `claim_usdc` pays out of the rest.
+  pub fn free_usdc(&self, usdc_vault_amount: u64) -> u64 {
+    usdc_vault_amount.saturating_sub(self.usdc_claims)
+  }
+
+  /// USDC of the USDC vault ring-fenced for the in-kind claims.
+  pub fn claimable_usdc(&self, usdc_vault_amount: u64) -> u64 {
+    usdc_vault_amount.min(self.usdc_claims)
+  }
+
+  // each claim is rounded down on its own when scaled, the total may keep dust
+  pub fn after_usdc_claim_paid(&mut self, amount: u64) -> Result<()> {
+    self.usdc_claims = self.usdc_claims.saturating_sub(amount);
+    Ok(())
+  }
+
+  /// Books the last holder's withdrawal, which takes all that is left once
+  /// only the lending leg holds assets. The difference to NAV (dust, locked
+  /// profit, unbooked yield or a shortfall) is booked first so NAV ends at 0.
+  pub fn after_withdraw_all(&mut self, amount: u64) -> Result<()> {
+    self.pending_yield = 0;
+    let total_assets = self.total_assets().unwrap();
+    if amount > total_assets {
+      self.statistic.total_gain = self.statistic.total_gain
+        .checked_add(amount - total_assets).unwrap();
+    } else {
+      self.total_loss = self.total_loss
+        .checked_add(total_assets - amount).unwrap();
+    }
+    self.statistic.locked_profit = 0;
+    self.idle_deposits = 0;
+    self.harvested_idle = 0;
+    self.after_withdraw(amount)
+  }
+
+  /// `harvest_interval` is the minimum time between harvests booking yield,
+  /// harvests before it only refresh the valuation.
+  pub fn is_harvest_due(&self, now: i64) -> bool {
+    let next_harvest = self.last_harvest
+      .checked_add(self.harvest_interval).unwrap();
+    now >= next_harvest
+  }
+
+  pub fn after_harvest(&mut self, amount: u64, now: i64) -> Result<()> {
+    if !self.is_harvest_due(now) {
+      return err!(VaultError::HarvestDenied)
+    }
+    self.total_harvest = self.total_harvest
+      .checked_add(amount).unwrap();
+    self.last_harvest = now;
+    // the redeemed yield includes what the skipped harvests left pending
+    self.pending_yield = 0;
+    self.harvested_idle = self.harvested_idle
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// Yield below `min_harvest_amount` isn't worth the redeem. Skipped
+  /// interest accumulates in `pending_yield` from one harvest to the next, it
+  /// stays out of NAV and the harvest that redeems it books it as locked
+  /// profit.
+  pub fn after_harvest_skipped(&mut self, accrued_yield: u64) -> Result<()> {
+    self.pending_yield = self.pending_yield
+      .checked_add(accrued_yield).unwrap();
+    Ok(())
+  }
+
+  /// Assets a harvest measures new yield against: NAV plus the yield and
+  /// surplus found by earlier harvests and not booked yet.
+  pub fn yield_base(&self) -> Option<u64> {
+    self.total_assets()?
+      .checked_add(self.pending_yield)?
+      .checked_add(self.surplus)
+  }
+
+  /// Splits yield accrued over the booked assets into the part a reserve
+  /// paying `max_yield_apr_bps` could have earned since the checkpoint and the
+  /// surplus above it, tokens sent to the vault accounts directly. The first
+  /// harvest after enabling the check only sets the checkpoint.
+  pub fn split_accrued_yield(&mut self, accrued_yield: u64, booked_assets: u64, now: i64) -> (u64, u64) {
+    if self.max_yield_apr_bps == 0 {
+      return (accrued_yield, 0);
+    }
+    if self.yield_checkpoint_ts == 0 {
+      self.yield_checkpoint_ts = now;
+      return (accrued_yield, 0);
+    }
+    let elapsed = now.saturating_sub(self.yield_checkpoint_ts).max(0) as u64;
+    let apr_bps_seconds = self.max_yield_apr_bps.checked_mul(elapsed).unwrap();
+    let expected_yield = ratio_floor!(booked_assets, apr_bps_seconds, 10_000 * SECONDS_IN_A_YEAR)
+      .unwrap_or(u64::MAX);
+    let surplus = accrued_yield.saturating_sub(expected_yield);
+    (accrued_yield - surplus, surplus)
+  }
+
+  /// The booked assets caught up with the reserve, the expected yield
+  /// restarts from `now`.
+  pub fn after_yield_booked(&mut self, now: i64) {
+    self.yield_checkpoint_ts = now;
+  }
+
+  /// Yield is measured against `yield_base`, which already holds the
+  /// surplus found so far, so each harvest adds only what is new.
+  pub fn after_surplus(&mut self, surplus: u64) -> Result<()> {
+    self.surplus = self.surplus
+      .checked_add(surplus).unwrap();
+    Ok(())
+  }
+
+  /// Books the surplus as locked profit, released to the share price over
+  /// the locked profit degradation instead of at once. Only the part still
+  /// held by the vault, `real_assets` above NAV and the pending yield, is
+  /// booked, the rest is dropped.
+  pub fn skim_surplus(&mut self, real_assets: u64, now: i64) -> Result<u64> {
+    let booked = self.total_assets().unwrap()
+      .checked_add(self.pending_yield).unwrap();
+    let surplus = self.surplus.min(real_assets.saturating_sub(booked));
+    if surplus < self.surplus {
+      msg!("unbacked surplus dropped: {}", self.surplus - surplus);
+    }
+    self.surplus = 0;
+    if surplus == 0 {
+      return Ok(0);
+    }
+    self.after_gain(surplus, now)?;
+    self.total_surplus_skimmed = self.total_surplus_skimmed
+      .checked_add(surplus).unwrap();
+    Ok(surplus)
+  }
+
+  /// Moves the management fee accrued up to `now` into `accrued_fee_shares`.
+  /// Runs before the share supply changes, the pending fee is priced on it.
+  pub fn accrue_fees(&mut self, minted_supply: u64, now: i64) -> Result<()> {
+    let pending = self.pending_fee_shares(minted_supply, now).unwrap();
+    self.accrued_fee_shares = self.accrued_fee_shares
+      .checked_add(pending).unwrap();
+    self.last_fee_accrual_ts = self.last_fee_accrual_ts.max(now);
+    Ok(())
+  }
+
+  pub fn after_fee_shares_minted(&mut self, fee_shares: u64) -> Result<()> {
+    self.accrued_fee_shares = self.accrued_fee_shares
+      .checked_sub(fee_shares).unwrap();
+    Ok(())
+  }
+
+  /// Books the realized pnl of an expired series, in underlying, each series
+  /// once. `platform_pnl` is kept for the margin valuation until zeta
+  /// settles the positions, see `booked_series_pnl`.
+  pub fn after_settlement(
+    &mut self,
+    pnl: i64,
+    platform_pnl: i64,
+    expiry_index: usize,
+    expiry_ts: u64,
+    now: i64,
+  ) -> Result<()> {
+    if expiry_index >= ACTIVE_EXPIRIES || self.settled_expiries[expiry_index] == expiry_ts {
+      return err!(VaultError::ExpiryAlreadySettled);
+    }
+    self.settled_expiries[expiry_index] = expiry_ts;
+    self.settled_pnl[expiry_index] = platform_pnl;
+    self.last_settled_expiry = expiry_ts;
+    // the next valuation marks what is still open, the booked series counts
+    // at its pnl until zeta settles it
+    self.marked_pnl = 0;
+    if pnl > 0 {
+      self.after_gain(pnl as u64, now)
+    } else {
+      self.total_loss = self.total_loss
+        .checked_add(pnl.unsigned_abs()).unwrap();
+      Ok(())
+    }
+  }
+
+  /// Platform pnl booked for the series at `expiry_index` while the margin
+  /// account still holds its positions, `series_expiry` being the expiry the
+  /// margin account has them under.
+  pub fn booked_series_pnl(&self, expiry_index: usize, series_expiry: u64) -> Option<i64> {
+    if series_expiry == 0
+      || expiry_index >= ACTIVE_EXPIRIES
+      || self.settled_expiries[expiry_index] != series_expiry {
+      return None;
+    }
+    Some(self.settled_pnl[expiry_index])
+  }
+
+  /// Routes the assets found above the booked NAV once a settlement is
+  /// booked into `surplus`: USDC sent to the vault accounts directly, the
+  /// margin balance holding more than the pnl booked. The idle underlying is
+  /// left to `after_idle_reinvested`, which books it the same way.
+  pub fn after_settlement_reconciled(&mut self, real_assets: u64, idle_underlying: u64) -> Result<u64> {
+    let booked = self.marked_assets().unwrap()
+      .saturating_sub(self.idle_deposits)
+      .checked_add(self.pending_yield).unwrap()
+      .checked_add(self.surplus).unwrap();
+    let excess = real_assets
+      .saturating_sub(idle_underlying)
+      .saturating_sub(booked);
+    if excess > 0 {
+      msg!("settlement surplus: {}", excess);
+      self.after_surplus(excess)?;
+    }
+    Ok(excess)
+  }
+
+  /// Adds a settled epoch to the track record and carves the insurance share
+  /// out of its premium, `start_share_price` is the NAV share price before its
+  /// pnl was booked.
+  pub fn after_epoch_settled(
+    &mut self,
+    start_share_price: u64,
+    total_share_supply: u64,
+    premium: u64,
+    assignment_loss: u64,
+  ) -> Result<()> {
+    let carve_out = self.insurance.carve_out(premium);
+    if carve_out > 0 {
+      self.total_loss = self.total_loss
+        .checked_add(self.decimals.to_underlying(carve_out).unwrap()).unwrap();
+      self.insurance.after_carve_out(carve_out);
+      msg!("insurance carve-out: {}", carve_out);
+    }
+    let end_share_price = self.nav_share_price(total_share_supply)
+      .ok_or_else(|| error!(VaultError::MathOverflow))?;
+    self.epoch_history.push(EpochRecord {
+      expiry_ts: self.last_settled_expiry,
+      start_share_price: self.track_record.epoch_start(start_share_price),
+      end_share_price,
+      premium,
+      assignment_loss,
+      haircut_bps: self.epoch_haircut_bps,
+    });
+    self.epoch_haircut_bps = 0;
+    self.track_record.record_epoch(start_share_price, end_share_price, premium, assignment_loss);
+    msg!("epochs: {}, winning: {}", self.track_record.epochs, self.track_record.winning_epochs);
+    Ok(())
+  }
+
+  pub fn set_insurance_account(&mut self, token_account: Pubkey) -> Result<()> {
+    self.insurance.token_account = token_account;
+    Ok(())
+  }
+
+  /// Books insurance funds paid back into the vault, they restore NAV
+  /// directly instead of going through locked profit.
+  pub fn after_insurance_cover(&mut self, amount: u64) -> Result<()> {
+    self.insurance.after_cover(amount);
+    self.statistic.total_gain = self.statistic.total_gain
+      .checked_add(self.decimals.to_underlying(amount).unwrap()).unwrap();
+    Ok(())
+  }
+
+  /// USDC needed to bring the NAV share price back to the floor price, 0
+  /// without a drawdown.
+  pub fn drawdown_shortfall(&self, total_share_supply: u64) -> Option<u64> {
+    if self.floor_share_price == 0 || total_share_supply == 0 {
+      return Some(0);
+    }
+    let floor_assets = ratio_floor!(
+      total_share_supply, self.floor_share_price, SHARE_PRICE_PRECISION
+    )?;
+    let shortfall = floor_assets.saturating_sub(self.total_assets()?);
+    self.decimals.to_platform(shortfall)
+  }
+
+  /// Pre-checks a Solend deposit of `amount` so a full or paused reserve fails
+  /// with a specific error. With `reserve_fallback_idle` set it returns false
+  /// instead and the caller keeps the funds in the underlying vault, booked
+  /// as `idle_deposits`.
+  pub fn check_reserve_deposit(&self, reserve: &Reserve, amount: u64, slot: u64) -> Result<bool> {
+    let deposit_limit = reserve.config.deposit_limit;
+    let error = if deposit_limit == 0 || reserve.last_update.is_stale(slot)? {
+      VaultError::ReservePaused
+    } else if reserve.liquidity.total_supply()?
+      .try_add(Decimal::from(amount))? > Decimal::from(deposit_limit) {
+      VaultError::ReserveFull
+    } else {
+      return Ok(true);
+    };
+    if self.reserve_fallback_idle {
+      msg!("reserve unavailable, {} stays idle", amount);
+      return Ok(false);
+    }
+    Err(error.into())
+  }
+
+  pub fn after_idle_deposit(&mut self, amount: u64) -> Result<()> {
+    self.idle_deposits = self.idle_deposits
+      .checked_add(amount).unwrap();
+    Ok(())
+  }
+
+  /// Caps the idle deposits at `underlying_left` once an in-kind withdrawal
+  /// or a swap took tokens out of the underlying vault.
+  pub fn after_underlying_spent(&mut self, underlying_left: u64) {
+    self.idle_deposits = self.idle_deposits.min(underlying_left);
+    self.harvested_idle = self.harvested_idle
+      .min(underlying_left - self.idle_deposits);
+  }
+
+  /// Books the underlying vault once `amount` of it is deposited into
+  /// Solend and returns the gain. Idle deposits are in NAV already and the
+  /// harvested yield is gain, anything above them was sent to the vault
+  /// directly and joins the surplus.
+  pub fn after_idle_reinvested(&mut self, amount: u64) -> Result<u64> {
+    if amount < self.idle_deposits {
+      return err!(VaultError::IdleDepositsMissing);
+    }
+    let unbooked = amount - self.idle_deposits;
+    let gain = unbooked.min(self.harvested_idle);
+    self.after_surplus(unbooked - gain)?;
+    self.idle_deposits = 0;
+    self.harvested_idle = 0;
+    Ok(gain)
+  }
+
+  pub fn after_gain(&mut self, amount: u64, now: i64) -> Result<()> {
+    self.statistic.total_gain = self.statistic.total_gain
+      .checked_add(amount).unwrap();
+    self.statistic.locked_profit = self
+      .current_locked_profit(now).unwrap()
+      .checked_add(amount).unwrap();
+    self.statistic.last_gain = now;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn vault_with_deposits(total_deposit: u64) -> Vault {
+    let mut vault = Vault::default();
+    vault.statistic.total_deposit = total_deposit;
+    vault
+  }
+
+  #[test]
+  fn solvency_nets_the_mark_out_of_the_liabilities() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.marked_pnl = -200;
+    assert!(vault.assert_solvent(800, 0).is_ok());
+    assert!(vault.assert_solvent(799, 0).is_err());
+  }
+
+  #[test]
+  fn withdrawals_wait_out_the_deposit_slot() {
+    let mut vault = vault_with_deposits(1_000);
+    let mut user_state = UserState::default();
+    user_state.after_deposit(100, 0).unwrap();
+    vault.after_wallet_deposit(100).unwrap();
+    assert!(vault.check_withdraw_unlocked(&user_state, 100).is_err());
+    assert!(vault.check_withdraw_unlocked(&user_state, 101).is_ok());
+    // shares moved to another wallet in the deposit slot
+    assert!(vault.check_withdraw_unlocked(&UserState::default(), 100).is_err());
+
+    vault.deposit_lock_slots = 5;
+    assert!(vault.check_withdraw_unlocked(&user_state, 105).is_err());
+    assert!(vault.check_withdraw_unlocked(&user_state, 106).is_ok());
+  }
+
+  #[test]
+  fn pending_deposits_count_against_the_deposit_limit() {
+    let mut vault = vault_with_deposits(600);
+    vault.deposit_limit = 1_000;
+    vault.pending_deposits = 300;
+    assert!(vault.check_deposit_limit(100).is_ok());
+    assert!(vault.check_deposit_limit(101).is_err());
+  }
+
+  #[test]
+  fn drift_venue_needs_the_drift_user() {
+    let mut vault = vault_with_deposits(1_000);
+    let params = VaultConfigParams {
+      venue: Some(Venue::Drift),
+      ..VaultConfigParams::default()
+    };
+    assert!(vault.update_config(&params).is_err());
+    assert!(vault.check_venue(Venue::Zeta).is_ok());
+
+    vault.drift_user = Pubkey::new_unique();
+    vault.update_config(&params).unwrap();
+    assert!(vault.check_venue(Venue::Zeta).is_err());
+    assert!(vault.check_venue(Venue::Drift).is_ok());
+  }
+
+  #[test]
+  fn socialized_loss_leaves_the_mark_out_of_the_shortfall() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.marked_pnl = -200;
+    assert!(vault.after_socialized_loss(800, 0, 1_000, 0).is_err());
+    assert_eq!(vault.after_socialized_loss(700, 0, 1_000, 0).unwrap(), 100);
+    assert_eq!(vault.marked_assets(), Some(700));
+  }
+
+  #[test]
+  fn platform_amounts_convert_to_underlying_decimals() {
+    let mut vault = vault_with_deposits(0);
+    vault.decimals = Decimals::new(9);
+    assert_eq!(vault.platform_to_underlying(1_000_000, None), Some(1_000_000_000));
+    assert_eq!(vault.platform_to_underlying(1_000_000, Some(20_000_000)), Some(50_000_000));
+    assert_eq!(Decimals::platform_amount(100_000), Some(100_000_000_000));
+  }
+
+  #[test]
+  fn split_accrued_yield_caps_yield_at_max_apr() {
+    let mut vault = vault_with_deposits(1_000_000);
+    assert_eq!(vault.split_accrued_yield(150_000, 1_000_000, 100), (150_000, 0));
+
+    vault.max_yield_apr_bps = 1_000;
+    // the first harvest only sets the checkpoint
+    assert_eq!(vault.split_accrued_yield(150_000, 1_000_000, 100), (150_000, 0));
+    assert_eq!(vault.yield_checkpoint_ts, 100);
+
+    let year_later = 100 + SECONDS_IN_A_YEAR as i64;
+    assert_eq!(vault.split_accrued_yield(150_000, 1_000_000, year_later), (100_000, 50_000));
+    assert_eq!(vault.split_accrued_yield(80_000, 1_000_000, year_later), (80_000, 0));
+  }
+
+  #[test]
+  fn surplus_accumulates_into_yield_base() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.after_surplus(30).unwrap();
+    vault.after_harvest_skipped(5).unwrap();
+    vault.after_surplus(20).unwrap();
+    vault.after_harvest_skipped(5).unwrap();
+    assert_eq!(vault.surplus, 50);
+    assert_eq!(vault.pending_yield, 10);
+    assert_eq!(vault.yield_base(), Some(1_060));
+  }
+
+  #[test]
+  fn reinvest_books_harvested_yield_and_surplus() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.idle_deposits = 100;
+    vault.after_harvest(50, 0).unwrap();
+    assert_eq!(vault.after_idle_reinvested(180).unwrap(), 50);
+    assert_eq!(vault.surplus, 30);
+    assert_eq!(vault.idle_deposits, 0);
+    assert_eq!(vault.harvested_idle, 0);
+  }
+
+  #[test]
+  fn reinvest_fails_below_idle_deposits() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.idle_deposits = 100;
+    vault.after_harvest(50, 0).unwrap();
+    assert!(vault.after_idle_reinvested(80).is_err());
+    vault.after_underlying_spent(120);
+    assert_eq!(vault.idle_deposits, 100);
+    assert_eq!(vault.harvested_idle, 20);
+    assert_eq!(vault.after_idle_reinvested(120).unwrap(), 20);
+  }
+
+  #[test]
+  fn skim_surplus_books_only_backed_surplus() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.after_surplus(100).unwrap();
+    vault.after_harvest_skipped(10).unwrap();
+    assert_eq!(vault.skim_surplus(1_070, 1).unwrap(), 60);
+    assert_eq!(vault.surplus, 0);
+    assert_eq!(vault.statistic.total_gain, 60);
+    assert_eq!(vault.statistic.locked_profit, 60);
+    assert_eq!(vault.total_surplus_skimmed, 60);
+
+    let mut vault = vault_with_deposits(1_000);
+    vault.after_surplus(100).unwrap();
+    assert_eq!(vault.skim_surplus(1_200, 1).unwrap(), 100);
+    assert_eq!(vault.total_assets(), Some(1_100));
+    assert_eq!(vault.skim_surplus(1_200, 2).unwrap(), 0);
+  }
+
+  #[test]
+  fn shares_are_priced_with_positions_at_mark() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.record_valuation(0, 0, 0, 400, -200, 1);
+    assert_eq!(vault.preview_withdraw(100, 1_000, 0), Some(80));
+    assert_eq!(vault.preview_deposit(80, None, 1_000, 0), Some(100));
+
+    vault.record_valuation(0, 0, 0, 0, 200, 2);
+    assert_eq!(vault.preview_withdraw(100, 1_000, 0), Some(120));
+
+    vault.after_settlement(200, 200, 0, 10, 0).unwrap();
+    assert_eq!(vault.marked_pnl, 0);
+    assert_eq!(vault.preview_withdraw(100, 1_000, 0), Some(120));
+  }
+
+  #[test]
+  fn socialized_loss_haircuts_shares_and_usdc_claims() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.usdc_claims = 250;
+    assert!(vault.after_socialized_loss(1_000, 250, 1_000, 0).is_err());
+
+    assert_eq!(vault.after_socialized_loss(800, 250, 1_000, 0).unwrap(), 160);
+    assert_eq!(vault.total_assets(), Some(840));
+    assert_eq!(vault.usdc_claims, 210);
+    assert_eq!(vault.current_claims_index(), 840_000_000_000);
+    assert_eq!(vault.last_haircut_bps, 1_600);
+
+    vault.after_socialized_loss(420, 210, 1_000, 0).unwrap();
+    assert_eq!(vault.last_haircut_bps, 4_000);
+    assert_eq!(vault.epoch_haircut_bps, 4_960);
+  }
+
+  #[test]
+  fn performance_fee_discounts_only_locked_yield() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.performance_fee_bps = 2_000;
+    vault.fee_tiers[0] = FeeTier { min_lock_epochs: 4, performance_fee_bps: 1_000 };
+    assert_eq!(vault.performance_fee(1_000, 0, 4), Some(200));
+    assert_eq!(vault.performance_fee(1_000, 400, 4), Some(160));
+    assert_eq!(vault.performance_fee(1_000, 2_000, 4), Some(100));
+    assert_eq!(vault.performance_fee(1_000, 1_000, 3), Some(200));
+
+    let params = VaultConfigParams {
+      performance_fee_bps: Some(500),
+      ..VaultConfigParams::default()
+    };
+    assert!(vault.update_config(&params).is_err());
+  }
+
+  #[test]
+  fn settlement_is_booked_once_per_series() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.after_settlement(-100, -100_000, 1, 20, 0).unwrap();
+    // an earlier expiry of the other series still books
+    vault.after_settlement(50, 50_000, 0, 10, 0).unwrap();
+    assert!(vault.after_settlement(-100, -100_000, 1, 20, 0).is_err());
+    assert_eq!(vault.total_assets(), Some(950));
+    assert_eq!(vault.last_settled_expiry, 10);
+
+    assert_eq!(vault.booked_series_pnl(1, 20), Some(-100_000));
+    // zeta settled it, the margin balance holds the pnl now
+    assert_eq!(vault.booked_series_pnl(1, 0), None);
+    assert_eq!(vault.booked_series_pnl(0, 30), None);
+  }
+
+  #[test]
+  fn settlement_reconciliation_routes_excess_to_surplus() {
+    let mut vault = vault_with_deposits(1_000);
+    vault.idle_deposits = 100;
+    assert_eq!(vault.after_settlement_reconciled(1_000, 100).unwrap(), 0);
+    // the idle underlying is left to `after_idle_reinvested`
+    assert_eq!(vault.after_settlement_reconciled(1_000, 150).unwrap(), 0);
+    assert_eq!(vault.after_settlement_reconciled(1_030, 100).unwrap(), 30);
+    assert_eq!(vault.surplus, 30);
+    assert_eq!(vault.after_settlement_reconciled(1_030, 100).unwrap(), 0);
+  }
 }
@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+
+
+/// Derivatives venue the vault's strategy trades on. Zeta vaults sell the
+/// options books, Drift vaults trade perps from the executor's drift user
+/// once `setup_drift_user` ran, see `place_drift_order`.
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, PartialEq, Eq)]
+pub enum Venue {
+  Zeta,
+  Drift,
+}
+
+impl Venue {
+  pub const MAXIMUM_SIZE: usize = 1;
+}
+
+impl Default for Venue {
+  fn default() -> Self {
+    Venue::Zeta
+  }
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+
+/// Shares of a user waiting for idle liquidity, held in the vault queue escrow.
+#[account]
+#[derive(Default)]
+pub struct WithdrawRequest {
+  pub bump: u8,
+  pub vault: Pubkey,
+  pub owner: Pubkey,
+  pub shares: u64,
+  pub queued_at: i64,
+}
+
+impl WithdrawRequest {
+  pub const MAXIMUM_SIZE: usize = 1 + 32 * 2 + 8 * 2;
+
+  pub fn is_initialized(&self) -> bool {
+    self.owner != Pubkey::default()
+  }
+
+  pub fn initialize(&mut self, bump: u8, vault: Pubkey, owner: Pubkey) -> Result<()> {
+    self.bump = bump;
+    self.vault = vault;
+    self.owner = owner;
+    Ok(())
+  }
+
+  pub fn take(&mut self) -> u64 {
+    let shares = self.shares;
+    self.shares = 0;
+    shares
+  }
+
+  pub fn queue(&mut self, shares: u64, now: i64) -> Result<()> {
+    if shares == 0 {
+      return Ok(());
+    }
+    self.shares = self.shares
+      .checked_add(shares).unwrap();
+    self.queued_at = now;
+    Ok(())
+  }
+}
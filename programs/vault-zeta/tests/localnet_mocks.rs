@@ -0,0 +1,263 @@
+#![cfg(feature = "localnet-mocks")]
+//! `cargo test -p vault-zeta --features localnet-mocks`
+//!
+//! Boots the vault next to the mock Zeta and Solend programs, deployed at the
+//! ids the vault's CPI modules target, with a USDC mint, reserve, oracle and
+//! Zeta vault seeded as fixtures.
+use anchor_lang::{AccountDeserialize, InstructionData};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use mock_solend::fixtures::ReserveFixture;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+use vault_zeta::cpi_calls as cpi;
+
+const USDC_DECIMALS: u8 = 6;
+
+struct Fixtures {
+  reserve: Pubkey,
+  reserve_fixture: ReserveFixture,
+  zeta_group: Pubkey,
+  zeta_vault: Pubkey,
+  user: Keypair,
+  user_usdc: Pubkey,
+  user_collateral: Pubkey,
+}
+
+fn mint_account(authority: &Pubkey, decimals: u8) -> Account {
+  let mut data = vec![0u8; spl_token::state::Mint::LEN];
+  spl_token::state::Mint {
+    mint_authority: Some(*authority).into(),
+    supply: u64::MAX / 2,
+    decimals,
+    is_initialized: true,
+    freeze_authority: None.into(),
+  }.pack_into_slice(&mut data);
+  Account { lamports: 1_000_000_000, data, owner: spl_token::ID, ..Account::default() }
+}
+
+fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+  let mut data = vec![0u8; spl_token::state::Account::LEN];
+  spl_token::state::Account {
+    mint: *mint,
+    owner: *owner,
+    amount,
+    state: spl_token::state::AccountState::Initialized,
+    ..spl_token::state::Account::default()
+  }.pack_into_slice(&mut data);
+  Account { lamports: 1_000_000_000, data, owner: spl_token::ID, ..Account::default() }
+}
+
+fn program_test() -> (ProgramTest, Fixtures) {
+  let mut test = ProgramTest::new("vault_zeta", vault_zeta::ID, processor!(vault_zeta::entry));
+  test.add_program("mock_zeta", cpi::zeta::zeta_program::ID, processor!(mock_zeta::entry));
+  test.add_program(
+    "mock_solend",
+    cpi::solend::solend_program::ID,
+    processor!(mock_solend::process_instruction),
+  );
+
+  let usdc = cpi::zeta::USDC;
+  let user = Keypair::new();
+  let lending_market = Pubkey::new_unique();
+  let reserve_fixture = ReserveFixture {
+    lending_market,
+    liquidity_mint: usdc,
+    liquidity_decimals: USDC_DECIMALS,
+    liquidity_supply: Pubkey::new_unique(),
+    pyth_oracle: Pubkey::new_unique(),
+    collateral_mint: Pubkey::new_unique(),
+    collateral_supply: Pubkey::new_unique(),
+    fee_receiver: Pubkey::new_unique(),
+    market_price: 1,
+  };
+  let market_authority = reserve_fixture.market_authority();
+  let zeta_group = Pubkey::new_unique();
+  let (zeta_vault, _) = mock_zeta::vault_address(&zeta_group);
+
+  let reserve = Pubkey::new_unique();
+  test.add_account(reserve, Account {
+    lamports: 1_000_000_000,
+    data: reserve_fixture.data(),
+    owner: cpi::solend::solend_program::ID,
+    ..Account::default()
+  });
+  test.add_account(lending_market, Account {
+    lamports: 1_000_000_000,
+    owner: cpi::solend::solend_program::ID,
+    ..Account::default()
+  });
+  test.add_account(usdc, mint_account(&Pubkey::new_unique(), USDC_DECIMALS));
+  test.add_account(
+    reserve_fixture.collateral_mint,
+    mint_account(&market_authority, USDC_DECIMALS),
+  );
+  test.add_account(
+    reserve_fixture.liquidity_supply,
+    token_account(&usdc, &market_authority, 0),
+  );
+  test.add_account(zeta_vault, token_account(&usdc, &zeta_vault, 0));
+  test.add_account(reserve_fixture.pyth_oracle, Account {
+    lamports: 1_000_000_000,
+    data: vec![0u8; 3312],
+    owner: cpi::zeta::pyth_client::pyth_program::ID,
+    ..Account::default()
+  });
+
+  let user_usdc = Pubkey::new_unique();
+  let user_collateral = Pubkey::new_unique();
+  test.add_account(user_usdc, token_account(&usdc, &user.pubkey(), 1_000_000_000));
+  test.add_account(
+    user_collateral,
+    token_account(&reserve_fixture.collateral_mint, &user.pubkey(), 0),
+  );
+  test.add_account(user.pubkey(), Account {
+    lamports: 10_000_000_000,
+    ..Account::default()
+  });
+
+  (test, Fixtures {
+    reserve,
+    reserve_fixture,
+    zeta_group,
+    zeta_vault,
+    user,
+    user_usdc,
+    user_collateral,
+  })
+}
+
+async fn send(context: &mut ProgramTestContext, signer: &Keypair, instructions: &[Instruction]) {
+  let transaction = Transaction::new_signed_with_payer(
+    instructions,
+    Some(&signer.pubkey()),
+    &[signer],
+    context.last_blockhash,
+  );
+  context.banks_client.process_transaction(transaction).await.unwrap();
+}
+
+async fn token_balance(context: &mut ProgramTestContext, address: Pubkey) -> u64 {
+  let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+  spl_token::state::Account::unpack(&account.data).unwrap().amount
+}
+
+fn solend_instruction(tag: u8, amount: u64, accounts: Vec<AccountMeta>) -> Instruction {
+  let mut data = vec![tag];
+  data.extend_from_slice(&amount.to_le_bytes());
+  Instruction { program_id: cpi::solend::solend_program::ID, accounts, data }
+}
+
+#[tokio::test]
+async fn reserve_fixture_deserializes() {
+  let (test, fixtures) = program_test();
+  let mut context = test.start_with_context().await;
+  let account = context.banks_client.get_account(fixtures.reserve).await.unwrap().unwrap();
+  let reserve = cpi::solend::Reserve::try_deserialize(&mut account.data.as_slice()).unwrap();
+  assert_eq!(reserve.liquidity.mint_pubkey, cpi::zeta::USDC);
+  assert_eq!(reserve.collateral.mint_pubkey, fixtures.reserve_fixture.collateral_mint);
+  let exchange_rate = reserve.collateral
+    .exchange_rate(reserve.liquidity.total_supply().unwrap())
+    .unwrap();
+  assert_eq!(exchange_rate.collateral_to_liquidity(1_000_000).unwrap(), 1_000_000);
+}
+
+#[tokio::test]
+async fn solend_deposit_and_redeem() {
+  let (test, fixtures) = program_test();
+  let mut context = test.start_with_context().await;
+  let user = fixtures.user.pubkey();
+  let reserve = &fixtures.reserve_fixture;
+  let market_authority = reserve.market_authority();
+
+  send(&mut context, &fixtures.user, &[solend_instruction(4, 250_000_000, vec![
+    AccountMeta::new(fixtures.user_usdc, false),
+    AccountMeta::new(fixtures.user_collateral, false),
+    AccountMeta::new(fixtures.reserve, false),
+    AccountMeta::new(reserve.liquidity_supply, false),
+    AccountMeta::new(reserve.collateral_mint, false),
+    AccountMeta::new_readonly(reserve.lending_market, false),
+    AccountMeta::new_readonly(market_authority, false),
+    AccountMeta::new_readonly(user, true),
+    AccountMeta::new_readonly(spl_token::ID, false),
+  ])]).await;
+  assert_eq!(token_balance(&mut context, fixtures.user_collateral).await, 250_000_000);
+
+  send(&mut context, &fixtures.user, &[solend_instruction(5, 250_000_000, vec![
+    AccountMeta::new(fixtures.user_collateral, false),
+    AccountMeta::new(fixtures.user_usdc, false),
+    AccountMeta::new(fixtures.reserve, false),
+    AccountMeta::new(reserve.collateral_mint, false),
+    AccountMeta::new(reserve.liquidity_supply, false),
+    AccountMeta::new_readonly(reserve.lending_market, false),
+    AccountMeta::new_readonly(market_authority, false),
+    AccountMeta::new_readonly(user, true),
+    AccountMeta::new_readonly(spl_token::ID, false),
+  ])]).await;
+  assert_eq!(token_balance(&mut context, fixtures.user_collateral).await, 0);
+  assert_eq!(token_balance(&mut context, fixtures.user_usdc).await, 1_000_000_000);
+}
+
+#[tokio::test]
+async fn zeta_margin_deposit_and_withdraw() {
+  let (test, fixtures) = program_test();
+  let mut context = test.start_with_context().await;
+  let user = fixtures.user.pubkey();
+  let (margin_account, _) = mock_zeta::margin_account_address(&fixtures.zeta_group, &user);
+
+  let initialize = Instruction {
+    program_id: mock_zeta::ID,
+    accounts: vec![
+      AccountMeta::new(margin_account, false),
+      AccountMeta::new_readonly(user, true),
+      AccountMeta::new(user, true),
+      AccountMeta::new_readonly(mock_zeta::ID, false),
+      AccountMeta::new_readonly(system_program::ID, false),
+      AccountMeta::new_readonly(fixtures.zeta_group, false),
+    ],
+    data: mock_zeta::instruction::InitializeMarginAccount {}.data(),
+  };
+  let deposit = Instruction {
+    program_id: mock_zeta::ID,
+    accounts: vec![
+      AccountMeta::new_readonly(fixtures.zeta_group, false),
+      AccountMeta::new(margin_account, false),
+      AccountMeta::new(fixtures.zeta_vault, false),
+      AccountMeta::new(fixtures.user_usdc, false),
+      AccountMeta::new(Pubkey::new_unique(), false),
+      AccountMeta::new_readonly(user, true),
+      AccountMeta::new_readonly(spl_token::ID, false),
+    ],
+    data: mock_zeta::instruction::Deposit { amount: 400_000_000 }.data(),
+  };
+  send(&mut context, &fixtures.user, &[initialize, deposit]).await;
+  assert_eq!(token_balance(&mut context, fixtures.zeta_vault).await, 400_000_000);
+
+  let account = context.banks_client.get_account(margin_account).await.unwrap().unwrap();
+  let margin = cpi::zeta::MarginAccount::try_deserialize(&mut account.data.as_slice()).unwrap();
+  // packed struct, copy the fields out before comparing
+  assert_eq!({ margin.authority }, user);
+  assert_eq!({ margin.balance }, 400_000_000);
+
+  let withdraw = Instruction {
+    program_id: mock_zeta::ID,
+    accounts: vec![
+      AccountMeta::new_readonly(Pubkey::new_unique(), false),
+      AccountMeta::new_readonly(fixtures.zeta_group, false),
+      AccountMeta::new(fixtures.zeta_vault, false),
+      AccountMeta::new(margin_account, false),
+      AccountMeta::new(fixtures.user_usdc, false),
+      AccountMeta::new_readonly(spl_token::ID, false),
+      AccountMeta::new_readonly(user, true),
+    ],
+    data: mock_zeta::instruction::Withdraw { amount: 400_000_000 }.data(),
+  };
+  send(&mut context, &fixtures.user, &[withdraw]).await;
+  assert_eq!(token_balance(&mut context, fixtures.zeta_vault).await, 0);
+  assert_eq!(token_balance(&mut context, fixtures.user_usdc).await, 1_000_000_000);
+}
@@ -0,0 +1,58 @@
+use proptest::prelude::*;
+use vault_zeta::{ratio_ceil, ratio_floor};
+
+#[test]
+fn rounding_direction() {
+  assert_eq!(ratio_floor!(10_u64, 1_u64, 3_u64), Some(3));
+  assert_eq!(ratio_ceil!(10_u64, 1_u64, 3_u64), Some(4));
+  assert_eq!(ratio_floor!(9_u64, 1_u64, 3_u64), Some(3));
+  assert_eq!(ratio_ceil!(9_u64, 1_u64, 3_u64), Some(3));
+}
+
+#[test]
+fn zero_divisor_and_overflow() {
+  assert_eq!(ratio_floor!(1_u64, 1_u64, 0_u64), None);
+  assert_eq!(ratio_ceil!(1_u64, 1_u64, 0_u64), None);
+  assert_eq!(ratio_floor!(u64::MAX, u64::MAX, 1_u64), None);
+  assert_eq!(ratio_ceil!(u64::MAX, u64::MAX, 1_u64), None);
+}
+
+proptest! {
+  #[test]
+  fn ceil_is_floor_or_next(x in any::<u64>(), a in any::<u64>(), b in 1..u64::MAX) {
+    let floor = ratio_floor!(x, a, b);
+    let ceil = ratio_ceil!(x, a, b);
+    if let (Some(floor), Some(ceil)) = (floor, ceil) {
+      prop_assert!(ceil == floor || ceil == floor + 1);
+    }
+  }
+
+  // deposit then withdraw everything never returns more than was deposited
+  #[test]
+  fn deposit_withdraw_round_trip(
+    total_assets in 1..1_000_000_000_000_000_u64,
+    total_supply in 1..1_000_000_000_000_000_u64,
+    amount in 1..1_000_000_000_000_u64,
+  ) {
+    let shares = ratio_floor!(amount, total_supply, total_assets).unwrap();
+    let total_assets = total_assets + amount;
+    let total_supply = total_supply + shares;
+    let withdrawn = ratio_floor!(total_assets, shares, total_supply).unwrap();
+    prop_assert!(withdrawn <= amount);
+  }
+
+  // burned shares always cover the withdrawn value
+  #[test]
+  fn withdraw_burns_enough_shares(
+    total_assets in 1..1_000_000_000_000_000_u64,
+    total_supply in 1..1_000_000_000_000_000_u64,
+    shares in 1..1_000_000_000_000_u64,
+  ) {
+    prop_assume!(shares <= total_supply);
+    let value = ratio_floor!(total_assets, shares, total_supply).unwrap();
+    let burned = ratio_ceil!(total_supply, value, total_assets).unwrap();
+    prop_assert!(burned <= shares);
+    let burned_value = ratio_floor!(total_assets, burned, total_supply).unwrap();
+    prop_assert!(value <= burned_value);
+  }
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "localnet-mocks")]
+//! `cargo test -p vault-zeta --features localnet-mocks --test zeta_guard`
+//!
+//! Margin account checks run after every Zeta withdrawal and order, amounts
+//! 6.dp and sizes 3.dp.
+use vault_zeta::cpi_calls::zeta::Side;
+use vault_zeta::MarginSnapshot;
+
+const SPOT: u64 = 100_000_000; // $100
+
+fn snapshot(balance: u64, position: i64, bids: u64, asks: u64) -> MarginSnapshot {
+  MarginSnapshot { balance, position, opening_orders: [bids, asks] }
+}
+
+#[test]
+fn withdraw_takes_exactly_the_amount() {
+  let before = snapshot(1_000_000_000, 0, 0, 0);
+  assert!(before.check_withdraw(&snapshot(900_000_000, 0, 0, 0), 100_000_000).is_ok());
+  assert!(before.check_withdraw(&snapshot(899_999_999, 0, 0, 0), 100_000_000).is_err());
+  assert!(before.check_withdraw(&snapshot(900_000_001, 0, 0, 0), 100_000_000).is_err());
+  assert!(before.check_withdraw(&before, 2_000_000_000).is_err());
+}
+
+#[test]
+fn resting_order_moves_no_balance() {
+  let before = snapshot(1_000_000_000, 0, 0, 0);
+  assert!(before.check_order(&snapshot(1_000_000_000, 0, 5_000, 0), Side::Bid, 2_000_000, 5_000, SPOT).is_ok());
+  // more lots than asked for
+  assert!(before.check_order(&snapshot(1_000_000_000, 0, 6_000, 0), Side::Bid, 2_000_000, 5_000, SPOT).is_err());
+  // lots on the other side
+  assert!(before.check_order(&snapshot(1_000_000_000, 0, 0, 5_000), Side::Bid, 2_000_000, 5_000, SPOT).is_err());
+  // nothing filled, nothing to pay
+  assert!(before.check_order(&snapshot(999_999_999, 0, 5_000, 0), Side::Bid, 2_000_000, 5_000, SPOT).is_err());
+}
+
+#[test]
+fn filled_bid_pays_premium_and_fees() {
+  let before = snapshot(1_000_000_000, 0, 0, 0);
+  // 2 lots filled at $2, 3 resting: $4 premium, fees up to 1% of $200
+  let filled = |balance| snapshot(balance, 2_000, 3_000, 0);
+  assert!(before.check_order(&filled(996_000_000), Side::Bid, 2_000_000, 5_000, SPOT).is_ok());
+  assert!(before.check_order(&filled(994_000_000), Side::Bid, 2_000_000, 5_000, SPOT).is_ok());
+  assert!(before.check_order(&filled(993_999_999), Side::Bid, 2_000_000, 5_000, SPOT).is_err());
+}
+
+#[test]
+fn closing_ask_pays_fees_only() {
+  let before = snapshot(1_000_000_000, 5_000, 0, 0);
+  let closed = |balance| snapshot(balance, 0, 0, 0);
+  assert!(before.check_order(&closed(1_010_000_000), Side::Ask, 2_000_000, 5_000, SPOT).is_ok());
+  assert!(before.check_order(&closed(995_000_000), Side::Ask, 2_000_000, 5_000, SPOT).is_ok());
+  assert!(before.check_order(&closed(994_999_999), Side::Ask, 2_000_000, 5_000, SPOT).is_err());
+  assert!(before.check_order(&closed(1_000_000_000), Side::Uninitialized, 2_000_000, 5_000, SPOT).is_err());
+}
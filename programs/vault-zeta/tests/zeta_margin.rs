@@ -0,0 +1,133 @@
+#![cfg(feature = "localnet-mocks")]
+//! `cargo test -p vault-zeta --features localnet-mocks --test zeta_margin`
+//!
+//! Parity vectors for the vendored Zeta margin math. Expected values are
+//! worked by hand from Zeta's margin formulas at its mainnet parameters, all
+//! amounts 6.dp, so a refactor that changes any of them diverges from the
+//! exchange.
+use proptest::prelude::*;
+use vault_zeta::cpi_calls::zeta::{
+  get_initial_margin_per_lot, get_maintenance_margin_per_lot, get_otm_amount, Kind,
+  MarginParameters, Side,
+};
+
+const SPOT: u64 = 100_000_000; // $100
+
+fn margin_parameters() -> MarginParameters {
+  MarginParameters {
+    future_margin_initial: 15_000_000,
+    future_margin_maintenance: 7_500_000,
+    option_mark_percentage_long_initial: 100_000_000,
+    option_spot_percentage_long_initial: 15_000_000,
+    option_spot_percentage_short_initial: 10_000_000,
+    option_dynamic_percentage_short_initial: 25_000_000,
+    option_mark_percentage_long_maintenance: 100_000_000,
+    option_spot_percentage_long_maintenance: 7_500_000,
+    option_spot_percentage_short_maintenance: 5_000_000,
+    option_dynamic_percentage_short_maintenance: 12_500_000,
+    option_short_put_cap_percentage: 100_000_000,
+    padding: [0; 32],
+  }
+}
+
+fn initial(strike: u64, mark: u64, kind: Kind, side: Side) -> u64 {
+  get_initial_margin_per_lot(SPOT, strike, mark, kind, side, &margin_parameters()).unwrap()
+}
+
+fn maintenance(strike: u64, mark: u64, kind: Kind, long: bool) -> u64 {
+  get_maintenance_margin_per_lot(SPOT, strike, mark, kind, long, &margin_parameters()).unwrap()
+}
+
+#[test]
+fn otm_amount() {
+  assert_eq!(get_otm_amount(SPOT, 110_000_000, Kind::Call).unwrap(), 10_000_000);
+  assert_eq!(get_otm_amount(SPOT, 90_000_000, Kind::Call).unwrap(), 0);
+  assert_eq!(get_otm_amount(SPOT, 90_000_000, Kind::Put).unwrap(), 10_000_000);
+  assert_eq!(get_otm_amount(SPOT, 110_000_000, Kind::Put).unwrap(), 0);
+  assert!(get_otm_amount(SPOT, 0, Kind::Future).is_err());
+}
+
+#[test]
+fn future_margin() {
+  // 15% / 7.5% of spot, either side
+  assert_eq!(initial(0, 0, Kind::Future, Side::Bid), 15_000_000);
+  assert_eq!(initial(0, 0, Kind::Future, Side::Ask), 15_000_000);
+  assert_eq!(maintenance(0, 0, Kind::Future, true), 7_500_000);
+  assert_eq!(maintenance(0, 0, Kind::Future, false), 7_500_000);
+}
+
+#[test]
+fn long_option_margin() {
+  // min(15% spot, 100% mark)
+  assert_eq!(initial(110_000_000, 5_000_000, Kind::Call, Side::Bid), 5_000_000);
+  assert_eq!(initial(110_000_000, 20_000_000, Kind::Call, Side::Bid), 15_000_000);
+  assert_eq!(initial(90_000_000, 5_000_000, Kind::Put, Side::Bid), 5_000_000);
+  // min(7.5% spot, 100% mark)
+  assert_eq!(maintenance(110_000_000, 5_000_000, Kind::Call, true), 5_000_000);
+  assert_eq!(maintenance(110_000_000, 20_000_000, Kind::Call, true), 7_500_000);
+}
+
+#[test]
+fn short_call_margin() {
+  // max(25% - otm%, 10%) of spot
+  assert_eq!(initial(90_000_000, 0, Kind::Call, Side::Ask), 25_000_000);
+  assert_eq!(initial(SPOT, 0, Kind::Call, Side::Ask), 25_000_000);
+  assert_eq!(initial(110_000_000, 0, Kind::Call, Side::Ask), 15_000_000);
+  assert_eq!(initial(200_000_000, 0, Kind::Call, Side::Ask), 10_000_000);
+  // max(12.5% - otm%, 5%) of spot
+  assert_eq!(maintenance(SPOT, 0, Kind::Call, false), 12_500_000);
+  assert_eq!(maintenance(110_000_000, 0, Kind::Call, false), 5_000_000);
+}
+
+#[test]
+fn short_put_margin() {
+  assert_eq!(initial(120_000_000, 0, Kind::Put, Side::Ask), 25_000_000);
+  assert_eq!(initial(90_000_000, 0, Kind::Put, Side::Ask), 15_000_000);
+  assert_eq!(maintenance(95_000_000, 0, Kind::Put, false), 7_500_000);
+}
+
+#[test]
+fn short_put_cap() {
+  // deep otm puts are capped at 100% of the strike
+  assert_eq!(initial(5_000_000, 0, Kind::Put, Side::Ask), 5_000_000);
+  assert_eq!(maintenance(4_000_000, 0, Kind::Put, false), 4_000_000);
+  // the cap never applies to calls or to the long side
+  assert_eq!(initial(5_000_000, 0, Kind::Call, Side::Ask), 25_000_000);
+  assert_eq!(initial(5_000_000, 20_000_000, Kind::Put, Side::Bid), 15_000_000);
+}
+
+#[test]
+fn invalid_inputs_fail_without_panicking() {
+  let params = margin_parameters();
+  for kind in [Kind::Call, Kind::Put] {
+    assert!(get_initial_margin_per_lot(SPOT, SPOT, 0, kind, Side::Uninitialized, &params).is_err());
+    // zero spot used to divide by zero in the otm percentage
+    assert!(get_initial_margin_per_lot(0, SPOT, 0, kind, Side::Ask, &params).is_err());
+    assert!(get_maintenance_margin_per_lot(0, SPOT, 0, kind, false, &params).is_err());
+  }
+  assert!(get_initial_margin_per_lot(SPOT, 0, 0, Kind::Uninitialized, Side::Bid, &params).is_err());
+}
+
+proptest! {
+  #[test]
+  fn long_margin_never_exceeds_mark(strike in 1..1_000_000_000_u64, mark in 0..1_000_000_000_u64) {
+    for kind in [Kind::Call, Kind::Put] {
+      prop_assert!(initial(strike, mark, kind, Side::Bid) <= mark);
+      prop_assert!(maintenance(strike, mark, kind, true) <= mark);
+    }
+  }
+
+  #[test]
+  fn short_put_margin_never_exceeds_strike(strike in 1..1_000_000_000_u64) {
+    prop_assert!(initial(strike, 0, Kind::Put, Side::Ask) <= strike);
+    prop_assert!(maintenance(strike, 0, Kind::Put, false) <= strike);
+  }
+
+  #[test]
+  fn maintenance_never_exceeds_initial(strike in 1..1_000_000_000_u64, mark in 0..1_000_000_000_u64) {
+    for kind in [Kind::Call, Kind::Put] {
+      prop_assert!(maintenance(strike, mark, kind, true) <= initial(strike, mark, kind, Side::Bid));
+      prop_assert!(maintenance(strike, mark, kind, false) <= initial(strike, mark, kind, Side::Ask));
+    }
+  }
+}
@@ -1,3 +1,6 @@
+//! Typed CPI interface to the Zeta dex: the `ZetaInterface` client, account
+//! contexts, zero-copy account layouts, margin calculations and the pyth
+//! readers they depend on. Programs depend on it with `no-entrypoint`.
 pub mod zeta_program {
   // anchor_lang::prelude::declare_id!("ZETAxsqBRek56DhiGXrn75yj2NHU3aYUnxvHXpkf3aD"); // mainnet
   anchor_lang::prelude::declare_id!("BG3oRikW8d16YjUEmX3ZxHm9SiJzrGtMhsSR8aCw1Cd7"); // devnet
@@ -10,6 +13,7 @@ mod zeta_usdc {
 }
 
 pub mod pyth_client;
+pub mod pyth_receiver;
 pub mod zeta_client;
 mod zeta_calculations;
 mod zeta_account;
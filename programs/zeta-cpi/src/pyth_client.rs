@@ -1,7 +1,49 @@
 // Taken from https://github.com/project-serum/anchor/tree/master/examples/pyth
 use anchor_lang::prelude::*;
-use bytemuck::{cast_slice_mut, from_bytes_mut, try_cast_slice_mut, Pod, Zeroable};
+use bytemuck::{from_bytes_mut, try_from_bytes, Pod, Zeroable};
 use std::cell::RefMut;
+use crate::FuzeErrorCode;
+
+pub mod pyth_program {
+    // anchor_lang::prelude::declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH"); // mainnet
+    anchor_lang::prelude::declare_id!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s"); // devnet
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, PartialEq)]
+pub enum OracleSource {
+    PythPush,
+    PythPull,
+}
+
+impl Default for OracleSource {
+    fn default() -> Self {
+        OracleSource::PythPush
+    }
+}
+
+/// Price feed used by the vault for its own pricing.
+/// `price_feed` is the legacy push account (also the one Zeta expects),
+/// `feed_id` and `max_age` are only used by pull oracle updates.
+#[derive(AnchorDeserialize, AnchorSerialize, Default, Copy, Clone)]
+pub struct PriceFeedConfig {
+    pub source: OracleSource,
+    pub price_feed: Pubkey,
+    pub feed_id: [u8; 32],
+    pub max_age: u64,
+}
+
+impl PriceFeedConfig {
+    pub const MAXIMUM_SIZE: usize = 1 + 32 + 32 + 8;
+
+    /// Owner of the account the price is read from: the push feed itself, or
+    /// a PriceUpdateV2 posted by the pull receiver. Zeta always takes `price_feed`.
+    pub fn price_account_owner(&self) -> Pubkey {
+        match self.source {
+            OracleSource::PythPush => pyth_program::ID,
+            OracleSource::PythPull => crate::pyth_receiver::pyth_receiver_program::ID,
+        }
+    }
+}
 
 #[derive(Default, Copy, Clone)]
 #[repr(C)]
@@ -102,13 +144,26 @@ impl Price {
         let account_data: RefMut<'a, [u8]>;
         let state: RefMut<'a, Self>;
 
-        account_data = RefMut::map(price_feed.try_borrow_mut_data().unwrap(), |data| *data);
+        account_data = RefMut::map(price_feed.try_borrow_mut_data()?, |data| *data);
+        if try_from_bytes::<Self>(&account_data).is_err() {
+            return err!(FuzeErrorCode::InvalidPriceFeed);
+        }
 
-        state = RefMut::map(account_data, |data| {
-            from_bytes_mut(cast_slice_mut::<u8, u8>(try_cast_slice_mut(data).unwrap()))
-        });
+        state = RefMut::map(account_data, |data| from_bytes_mut(data));
         Ok(state)
     }
+
+    /// Same as `load`, but makes sure the account is the expected Pyth feed.
+    #[inline]
+    pub fn load_checked<'a>(
+        price_feed: &'a AccountInfo,
+        expected_feed: &Pubkey,
+    ) -> Result<RefMut<'a, Price>> {
+        if price_feed.owner != &pyth_program::ID || price_feed.key != expected_feed {
+            return err!(FuzeErrorCode::InvalidPriceFeed);
+        }
+        Self::load(price_feed)
+    }
 }
 
 #[cfg(target_endian = "little")]
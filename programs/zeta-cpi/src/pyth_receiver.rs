@@ -0,0 +1,160 @@
+// Layout of `pyth-solana-receiver` PriceUpdateV2 accounts (Pyth pull oracle)
+use anchor_lang::prelude::*;
+use crate::FuzeErrorCode;
+
+pub mod pyth_receiver_program {
+    anchor_lang::prelude::declare_id!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+}
+
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, PartialEq, Debug)]
+pub enum VerificationLevel {
+    Partial { num_signatures: u8 },
+    Full,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, Debug)]
+pub struct PriceFeedMessage {
+    pub feed_id: [u8; 32],
+    pub price: i64,
+    pub conf: u64,
+    pub exponent: i32,
+    pub publish_time: i64,
+    pub prev_publish_time: i64,
+    pub ema_price: i64,
+    pub ema_conf: u64,
+}
+
+#[derive(AnchorDeserialize, AnchorSerialize, Copy, Clone, Debug)]
+pub struct PriceUpdateV2 {
+    pub write_authority: Pubkey,
+    pub verification_level: VerificationLevel,
+    pub price_message: PriceFeedMessage,
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    pub fn load(price_update: &AccountInfo) -> Result<PriceUpdateV2> {
+        if price_update.owner != &pyth_receiver_program::ID {
+            return err!(FuzeErrorCode::InvalidPriceFeed);
+        }
+        let data = price_update.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != PRICE_UPDATE_V2_DISCRIMINATOR {
+            return err!(FuzeErrorCode::InvalidPriceFeed);
+        }
+        let mut payload: &[u8] = &data[8..];
+        Ok(PriceUpdateV2::deserialize(&mut payload)?)
+    }
+
+    /// Returns the price message if it is fully verified, matches `feed_id`
+    /// and was published no more than `max_age` seconds before `now`.
+    pub fn get_price_no_older_than(
+        &self,
+        feed_id: &[u8; 32],
+        max_age: u64,
+        now: i64,
+    ) -> Result<PriceFeedMessage> {
+        if self.verification_level != VerificationLevel::Full {
+            return err!(FuzeErrorCode::UnverifiedPrice);
+        }
+        if &self.price_message.feed_id != feed_id {
+            return err!(FuzeErrorCode::InvalidPriceFeed);
+        }
+        let age = now.saturating_sub(self.price_message.publish_time);
+        if age < 0 || age as u64 > max_age {
+            return err!(FuzeErrorCode::StalePrice);
+        }
+        Ok(self.price_message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED_ID: [u8; 32] = [7; 32];
+    const PUBLISH_TIME: i64 = 1_700_000_000;
+
+    fn price_update(verification_level: VerificationLevel) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level,
+            price_message: PriceFeedMessage {
+                feed_id: FEED_ID,
+                price: 2_512_345_678,
+                conf: 1_000_000,
+                exponent: -8,
+                publish_time: PUBLISH_TIME,
+                prev_publish_time: PUBLISH_TIME - 1,
+                ema_price: 2_500_000_000,
+                ema_conf: 1_000_000,
+            },
+            posted_slot: 42,
+        }
+    }
+
+    fn account_data(update: &PriceUpdateV2) -> Vec<u8> {
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        data.extend(update.try_to_vec().unwrap());
+        data
+    }
+
+    fn load(data: &mut [u8], owner: &Pubkey) -> Result<PriceUpdateV2> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        PriceUpdateV2::load(&account)
+    }
+
+    #[test]
+    fn decodes_receiver_accounts() {
+        let update = price_update(VerificationLevel::Full);
+        let mut data = account_data(&update);
+        let loaded = load(&mut data, &pyth_receiver_program::ID).unwrap();
+        assert_eq!(loaded.write_authority, update.write_authority);
+        assert_eq!(loaded.verification_level, VerificationLevel::Full);
+        assert_eq!(loaded.price_message.price, 2_512_345_678);
+        assert_eq!(loaded.price_message.exponent, -8);
+        assert_eq!(loaded.posted_slot, 42);
+    }
+
+    #[test]
+    fn rejects_foreign_or_mistyped_accounts() {
+        let mut data = account_data(&price_update(VerificationLevel::Full));
+        assert!(load(&mut data, &Pubkey::new_unique()).is_err());
+        data[0] ^= 1;
+        assert!(load(&mut data, &pyth_receiver_program::ID).is_err());
+        assert!(load(&mut [0u8; 4], &pyth_receiver_program::ID).is_err());
+    }
+
+    #[test]
+    fn enforces_max_age() {
+        let update = price_update(VerificationLevel::Full);
+        assert!(update.get_price_no_older_than(&FEED_ID, 60, PUBLISH_TIME).is_ok());
+        assert!(update.get_price_no_older_than(&FEED_ID, 60, PUBLISH_TIME + 60).is_ok());
+        assert_eq!(
+            update.get_price_no_older_than(&FEED_ID, 60, PUBLISH_TIME + 61).unwrap_err(),
+            error!(FuzeErrorCode::StalePrice),
+        );
+        // published after the clock, the validator is behind the update
+        assert_eq!(
+            update.get_price_no_older_than(&FEED_ID, 60, PUBLISH_TIME - 1).unwrap_err(),
+            error!(FuzeErrorCode::StalePrice),
+        );
+    }
+
+    #[test]
+    fn checks_feed_id_and_verification() {
+        let update = price_update(VerificationLevel::Full);
+        assert_eq!(
+            update.get_price_no_older_than(&[8; 32], 60, PUBLISH_TIME).unwrap_err(),
+            error!(FuzeErrorCode::InvalidPriceFeed),
+        );
+        let partial = price_update(VerificationLevel::Partial { num_signatures: 5 });
+        assert_eq!(
+            partial.get_price_no_older_than(&FEED_ID, 60, PUBLISH_TIME).unwrap_err(),
+            error!(FuzeErrorCode::UnverifiedPrice),
+        );
+    }
+}
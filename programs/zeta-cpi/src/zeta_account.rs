@@ -104,6 +104,28 @@ impl Greeks {
     }
 }
 
+// Written by zeta on expiration of a series, strikes are kept as the
+// zeta group products are reused by the next series.
+#[account(zero_copy)]
+pub struct SettlementAccount {
+    pub settlement_price: u64,
+    pub strikes: [u64; 23], // NUM_PRODUCTS_PER_SERIES
+} // 192
+
+impl SettlementAccount {
+    pub fn get_settlement_address(underlying_mint: &Pubkey, expiry_ts: u64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                SETTLEMENT_SEED.as_bytes(),
+                underlying_mint.as_ref(),
+                &expiry_ts.to_le_bytes(),
+            ],
+            &zeta_program::ID,
+        )
+        .0
+    }
+}
+
 
 #[account(zero_copy)]
 pub struct ZetaGroup {
@@ -141,6 +163,12 @@ pub struct HaltState {
     _market_cleaned_padding: [bool; 92],
 } // 1 + 8 + 8 + 6 + 6 + 46 + 92 = 167
 
+impl HaltState {
+    pub fn is_halted(&self) -> bool {
+        self._halted
+    }
+}
+
 #[zero_copy]
 #[derive(Default)]
 pub struct PricingParameters {
@@ -197,7 +225,7 @@ impl ZetaGroup {
 
         match index {
             Err(_) => wrap_error!(Err(error!(FuzeErrorCode::InvalidProductMarketKey))),
-            Ok(i) => Ok((i, self.get_expiry_index_by_product_index(i))),
+            Ok(i) => Ok((i, self.get_expiry_index_by_product_index(i)?)),
         }
     }
 
@@ -219,19 +247,20 @@ impl ZetaGroup {
 
         match index {
             Err(_) => wrap_error!(Err(error!(FuzeErrorCode::InvalidProductMarketKey))),
-            Ok(i) => Ok(self.get_expiry_series_by_product_index(i)),
+            Ok(i) => self.get_expiry_series_by_product_index(i),
         }
     }
 
-    pub fn get_expiry_series_by_product_index(&self, index: usize) -> &ExpirySeries {
-        &self.expiry_series[self.get_expiry_index_by_product_index(index)]
+    pub fn get_expiry_series_by_product_index(&self, index: usize) -> Result<&ExpirySeries> {
+        Ok(&self.expiry_series[self.get_expiry_index_by_product_index(index)?])
     }
 
-    pub fn get_expiry_index_by_product_index(&self, index: usize) -> usize {
-        assert!(index < self.products.len());
-        let expiry_index = index.checked_div(NUM_PRODUCTS_PER_SERIES).unwrap();
-        assert!(expiry_index < self.expiry_series.len());
-        expiry_index
+    pub fn get_expiry_index_by_product_index(&self, index: usize) -> Result<usize> {
+        let expiry_index = index / NUM_PRODUCTS_PER_SERIES;
+        if index >= self.products.len() || expiry_index >= self.expiry_series.len() {
+            return wrap_error!(Err(error!(FuzeErrorCode::InvalidProductIndex)));
+        }
+        Ok(expiry_index)
     }
 
     /// This function should validate an expiry index is:
@@ -362,30 +391,23 @@ impl Position {
         self.size.abs() as u64
     }
 
-    pub fn get_unrealized_pnl(&self, mark_price: u64) -> i64 {
+    pub fn get_unrealized_pnl(&self, mark_price: u64) -> Result<i64> {
         if self.size == 0 {
-            0
-        } else if self.size > 0 {
-            (self.size as i128)
-                .checked_mul(mark_price as i128)
-                .unwrap()
-                .checked_div(POSITION_PRECISION_DENOMINATOR as i128)
-                .unwrap()
-                .checked_sub(self.cost_of_trades as i128)
-                .unwrap()
-                .try_into()
-                .unwrap()
-        } else {
-            (self.size as i128)
-                .checked_mul(mark_price as i128)
-                .unwrap()
-                .checked_div(POSITION_PRECISION_DENOMINATOR as i128)
-                .unwrap()
-                .checked_add(self.cost_of_trades as i128)
-                .unwrap()
-                .try_into()
-                .unwrap()
+            return Ok(0);
         }
+        let value = (self.size as i128)
+            .checked_mul(mark_price as i128)
+            .ok_or_else(math_error)?
+            .checked_div(POSITION_PRECISION_DENOMINATOR as i128)
+            .ok_or_else(math_error)?;
+        let pnl = if self.size > 0 {
+            value.checked_sub(self.cost_of_trades as i128)
+        } else {
+            value.checked_add(self.cost_of_trades as i128)
+        };
+        pnl.ok_or_else(math_error)?
+            .try_into()
+            .map_err(|_| math_error())
     }
 }
 
@@ -428,18 +450,18 @@ impl ProductLedger {
         product: &Product,
         spot: u64,
         margin_parameters: &MarginParameters,
-    ) -> u64 {
+    ) -> Result<u64> {
         let strike: u64 = match product.strike.get_strike() {
             Ok(strike) => strike,
-            Err(_) => return 0,
+            Err(_) => return Ok(0),
         };
 
         let mut long_lots: u64 = self.order_state.opening_orders[BID_ORDERS_INDEX];
         let mut short_lots: u64 = self.order_state.opening_orders[ASK_ORDERS_INDEX];
         if self.position.size > 0 {
-            long_lots = long_lots.checked_add(self.position.size_abs()).unwrap();
+            long_lots = long_lots.checked_add(self.position.size_abs()).ok_or_else(math_error)?;
         } else if self.position.size < 0 {
-            short_lots = short_lots.checked_add(self.position.size_abs()).unwrap();
+            short_lots = short_lots.checked_add(self.position.size_abs()).ok_or_else(math_error)?;
         }
 
         let mut long_initial_margin: u128 = 0;
@@ -455,12 +477,11 @@ impl ProductLedger {
                         product.kind,
                         Side::Bid,
                         margin_parameters,
-                    )
-                    .unwrap()
+                    )?
                     .try_into()
-                    .unwrap(),
+                    .map_err(|_| math_error())?,
                 )
-                .unwrap();
+                .ok_or_else(math_error)?;
         }
 
         if short_lots > 0 {
@@ -473,21 +494,20 @@ impl ProductLedger {
                         product.kind,
                         Side::Ask,
                         margin_parameters,
-                    )
-                    .unwrap()
+                    )?
                     .try_into()
-                    .unwrap(),
+                    .map_err(|_| math_error())?,
                 )
-                .unwrap();
+                .ok_or_else(math_error)?;
         }
 
         long_initial_margin
             .checked_add(short_initial_margin)
-            .unwrap()
+            .ok_or_else(math_error)?
             .checked_div(POSITION_PRECISION_DENOMINATOR)
-            .unwrap()
+            .ok_or_else(math_error)?
             .try_into()
-            .unwrap()
+            .map_err(|_| math_error())
     }
 
     pub fn get_maintenance_margin(
@@ -496,14 +516,14 @@ impl ProductLedger {
         product: &Product,
         spot: u64,
         margin_parameters: &MarginParameters,
-    ) -> u64 {
+    ) -> Result<u64> {
         if self.position.size == 0 {
-            return 0;
+            return Ok(0);
         }
 
         let strike: u64 = match product.strike.get_strike() {
             Ok(strike) => strike,
-            Err(_) => return 0,
+            Err(_) => return Ok(0),
         };
 
         let maintenance_margin_per_lot = get_maintenance_margin_per_lot(
@@ -513,14 +533,13 @@ impl ProductLedger {
             product.kind,
             self.position.size >= 0,
             margin_parameters,
-        )
-        .unwrap();
+        )?;
 
-        (self.position.size_abs() as u128)
+        Ok((self.position.size_abs() as u128)
             .checked_mul(maintenance_margin_per_lot as u128)
-            .unwrap()
+            .ok_or_else(math_error)?
             .checked_div(POSITION_PRECISION_DENOMINATOR)
-            .unwrap() as u64
+            .ok_or_else(math_error)? as u64)
     }
 
     pub fn get_maintenance_margin_including_orders(
@@ -529,10 +548,10 @@ impl ProductLedger {
         product: &Product,
         spot: u64,
         margin_parameters: &MarginParameters,
-    ) -> u64 {
+    ) -> Result<u64> {
         let strike: u64 = match product.strike.get_strike() {
             Ok(strike) => strike,
-            Err(_) => return 0,
+            Err(_) => return Ok(0),
         };
 
         let mut long_lots: u128 = self.order_state.opening_orders[BID_ORDERS_INDEX].into();
@@ -541,11 +560,11 @@ impl ProductLedger {
         if self.position.size > 0 {
             long_lots = long_lots
                 .checked_add(self.position.size_abs() as u128)
-                .unwrap();
+                .ok_or_else(math_error)?;
         } else {
             short_lots = long_lots
                 .checked_add(self.position.size_abs() as u128)
-                .unwrap();
+                .ok_or_else(math_error)?;
         }
 
         let mut maintenance_margin_long = 0;
@@ -561,12 +580,11 @@ impl ProductLedger {
                         product.kind,
                         true,
                         margin_parameters,
-                    )
-                    .unwrap() as u128,
+                    )? as u128,
                 )
-                .unwrap()
+                .ok_or_else(math_error)?
                 .checked_div(POSITION_PRECISION_DENOMINATOR)
-                .unwrap() as u64;
+                .ok_or_else(math_error)? as u64;
         }
 
         if short_lots > 0 {
@@ -579,17 +597,16 @@ impl ProductLedger {
                         product.kind,
                         false,
                         margin_parameters,
-                    )
-                    .unwrap() as u128,
+                    )? as u128,
                 )
-                .unwrap()
+                .ok_or_else(math_error)?
                 .checked_div(POSITION_PRECISION_DENOMINATOR)
-                .unwrap() as u64;
+                .ok_or_else(math_error)? as u64;
         }
 
         maintenance_margin_long
             .checked_add(maintenance_margin_short)
-            .unwrap()
+            .ok_or_else(math_error)
     }
 
     pub fn get_margin_market_maker_concession(
@@ -599,10 +616,10 @@ impl ProductLedger {
         spot: u64,
         margin_parameters: &MarginParameters,
         concession_percentage: u8,
-    ) -> u64 {
+    ) -> Result<u64> {
         let strike: u64 = match product.strike.get_strike() {
             Ok(strike) => strike,
-            Err(_) => return 0,
+            Err(_) => return Ok(0),
         };
 
         let long_lots: u64 = self.order_state.opening_orders[BID_ORDERS_INDEX];
@@ -620,12 +637,11 @@ impl ProductLedger {
                         product.kind,
                         Side::Bid,
                         margin_parameters,
-                    )
-                    .unwrap()
+                    )?
                     .try_into()
-                    .unwrap(),
+                    .map_err(|_| math_error())?,
                 )
-                .unwrap();
+                .ok_or_else(math_error)?;
         }
 
         if short_lots > 0 {
@@ -638,22 +654,21 @@ impl ProductLedger {
                         product.kind,
                         Side::Ask,
                         margin_parameters,
-                    )
-                    .unwrap()
+                    )?
                     .try_into()
-                    .unwrap(),
+                    .map_err(|_| math_error())?,
                 )
-                .unwrap();
+                .ok_or_else(math_error)?;
         }
 
         // Apply the concession
         let total_initial_margin = long_initial_margin
             .checked_add(short_initial_margin)
-            .unwrap()
+            .ok_or_else(math_error)?
             .checked_mul(concession_percentage.into())
-            .unwrap()
+            .ok_or_else(math_error)?
             .checked_div(100)
-            .unwrap();
+            .ok_or_else(math_error)?;
 
         let maintenance_margin_per_lot = get_maintenance_margin_per_lot(
             spot,
@@ -662,21 +677,20 @@ impl ProductLedger {
             product.kind,
             self.position.size >= 0,
             margin_parameters,
-        )
-        .unwrap();
+        )?;
 
         // Normalize it with the initial_margin
         let maintenance_margin = (self.position.size_abs() as u128)
             .checked_mul(maintenance_margin_per_lot as u128)
-            .unwrap();
+            .ok_or_else(math_error)?;
 
         maintenance_margin
             .checked_add(total_initial_margin)
-            .unwrap()
+            .ok_or_else(math_error)?
             .checked_div(POSITION_PRECISION_DENOMINATOR)
-            .unwrap()
+            .ok_or_else(math_error)?
             .try_into()
-            .unwrap()
+            .map_err(|_| math_error())
     }
 }
 
@@ -709,22 +723,30 @@ impl MarginAccount {
     }
 
     // Calculates the total initial margin for all open orders and positions.
-    pub fn get_initial_margin(&self, greeks: &Greeks, zeta_group: &ZetaGroup, spot: u64) -> u64 {
-        let initial_margin_requirement = self
-            .product_ledgers
-            .iter()
-            .enumerate()
-            .map(|(i, ledger)| {
-                ledger.get_initial_margin(
-                    greeks.mark_prices[i],
-                    &zeta_group.products[i],
-                    spot,
-                    &zeta_group.margin_parameters,
-                )
-            })
-            .sum();
+    pub fn get_initial_margin(&self, greeks: &Greeks, zeta_group: &ZetaGroup, spot: u64) -> Result<u64> {
+        self.get_initial_margin_with_parameters(
+            greeks,
+            zeta_group,
+            spot,
+            &zeta_group.margin_parameters,
+        )
+    }
 
-        initial_margin_requirement
+    // `get_initial_margin` under margin parameters other than the zeta group ones.
+    pub fn get_initial_margin_with_parameters(
+        &self,
+        greeks: &Greeks,
+        zeta_group: &ZetaGroup,
+        spot: u64,
+        margin_parameters: &MarginParameters,
+    ) -> Result<u64> {
+        get_ledgers_initial_margin(
+            &self.product_ledgers,
+            greeks,
+            zeta_group,
+            spot,
+            margin_parameters,
+        )
     }
 
     // Calculates the total maintenance margin for all positions only.
@@ -733,34 +755,34 @@ impl MarginAccount {
         greeks: &Greeks,
         zeta_group: &ZetaGroup,
         spot: u64,
-    ) -> u64 {
-        let maintenance_margin_requirement = self
-            .product_ledgers
-            .iter()
-            .enumerate()
-            .map(|(i, product_ledgers)| {
-                product_ledgers.get_maintenance_margin(
-                    greeks.mark_prices[i],
-                    &zeta_group.products[i],
-                    spot,
-                    &zeta_group.margin_parameters,
-                )
-            })
-            .sum();
+    ) -> Result<u64> {
+        self.get_maintenance_margin_with_parameters(
+            greeks,
+            zeta_group,
+            spot,
+            &zeta_group.margin_parameters,
+        )
+    }
 
-        maintenance_margin_requirement
+    // `get_maintenance_margin` under margin parameters other than the zeta group ones.
+    pub fn get_maintenance_margin_with_parameters(
+        &self,
+        greeks: &Greeks,
+        zeta_group: &ZetaGroup,
+        spot: u64,
+        margin_parameters: &MarginParameters,
+    ) -> Result<u64> {
+        get_ledgers_maintenance_margin(
+            &self.product_ledgers,
+            greeks,
+            zeta_group,
+            spot,
+            margin_parameters,
+        )
     }
 
-    pub fn get_unrealized_pnl(&self, greeks: &Greeks) -> i64 {
-        self.product_ledgers
-            .iter()
-            .enumerate()
-            .map(|(i, product_ledger)| {
-                (product_ledger
-                    .position
-                    .get_unrealized_pnl(greeks.mark_prices[i]) as i128) as i64
-            })
-            .sum()
+    pub fn get_unrealized_pnl(&self, greeks: &Greeks) -> Result<i64> {
+        get_ledgers_unrealized_pnl(&self.product_ledgers, greeks)
     }
 
     pub fn get_maintenance_margin_including_orders(
@@ -768,7 +790,7 @@ impl MarginAccount {
         greeks: &Greeks,
         zeta_group: &ZetaGroup,
         spot: u64,
-    ) -> u64 {
+    ) -> Result<u64> {
         let maintenance_margin_requirement = self
             .product_ledgers
             .iter()
@@ -781,9 +803,9 @@ impl MarginAccount {
                     &zeta_group.margin_parameters,
                 )
             })
-            .sum();
+            .sum::<Result<u64>>()?;
 
-        maintenance_margin_requirement
+        Ok(maintenance_margin_requirement)
     }
 
     pub fn is_market_maker(&self) -> bool {
@@ -796,7 +818,7 @@ impl MarginAccount {
         zeta_group: &ZetaGroup,
         spot: u64,
         concession: u8,
-    ) -> u64 {
+    ) -> Result<u64> {
         let maintenance_margin_requirement = self
             .product_ledgers
             .iter()
@@ -810,9 +832,9 @@ impl MarginAccount {
                     concession,
                 )
             })
-            .sum();
+            .sum::<Result<u64>>()?;
 
-        maintenance_margin_requirement
+        Ok(maintenance_margin_requirement)
     }
 
     pub fn get_margin_requirement(
@@ -822,7 +844,7 @@ impl MarginAccount {
         zeta_group: &ZetaGroup,
         native_spot: u64,
         margin_concession_percentage: Option<u8>,
-    ) -> u64 {
+    ) -> Result<u64> {
         match margin_type {
             MarginRequirement::Initial => {
                 self.get_initial_margin(&greeks, &zeta_group, native_spot)
@@ -834,10 +856,14 @@ impl MarginAccount {
                 self.get_maintenance_margin_including_orders(&greeks, &zeta_group, native_spot)
             }
             MarginRequirement::MarketMakerConcession => {
-                assert!(self.is_market_maker());
-                assert!(margin_concession_percentage.is_some());
-                let margin_concession_percentage = margin_concession_percentage.unwrap();
-                assert!(margin_concession_percentage > 0 && margin_concession_percentage <= 100);
+                let margin_concession_percentage = match margin_concession_percentage {
+                    Some(percentage)
+                        if self.is_market_maker() && percentage > 0 && percentage <= 100 =>
+                    {
+                        percentage
+                    }
+                    _ => return wrap_error!(Err(error!(FuzeErrorCode::InvalidMarginConcession))),
+                };
                 self.get_margin_market_maker_concession(
                     &greeks,
                     &zeta_group,
@@ -849,6 +875,143 @@ impl MarginAccount {
     }
 }
 
+// Initial margin of the open orders and positions of `product_ledgers`,
+// indexed like the zeta group products.
+fn get_ledgers_initial_margin(
+    product_ledgers: &[ProductLedger],
+    greeks: &Greeks,
+    zeta_group: &ZetaGroup,
+    spot: u64,
+    margin_parameters: &MarginParameters,
+) -> Result<u64> {
+    product_ledgers
+        .iter()
+        .enumerate()
+        .map(|(i, ledger)| {
+            ledger.get_initial_margin(
+                greeks.mark_prices[i],
+                &zeta_group.products[i],
+                spot,
+                margin_parameters,
+            )
+        })
+        .sum::<Result<u64>>()
+}
+
+// Maintenance margin of the positions of `product_ledgers`.
+fn get_ledgers_maintenance_margin(
+    product_ledgers: &[ProductLedger],
+    greeks: &Greeks,
+    zeta_group: &ZetaGroup,
+    spot: u64,
+    margin_parameters: &MarginParameters,
+) -> Result<u64> {
+    product_ledgers
+        .iter()
+        .enumerate()
+        .map(|(i, ledger)| {
+            ledger.get_maintenance_margin(
+                greeks.mark_prices[i],
+                &zeta_group.products[i],
+                spot,
+                margin_parameters,
+            )
+        })
+        .sum::<Result<u64>>()
+}
+
+// Unrealized pnl of the positions of `product_ledgers` at the mark prices.
+fn get_ledgers_unrealized_pnl(product_ledgers: &[ProductLedger], greeks: &Greeks) -> Result<i64> {
+    product_ledgers
+        .iter()
+        .enumerate()
+        .try_fold(0i64, |pnl, (i, product_ledger)| {
+            let position = product_ledger.position;
+            pnl.checked_add(position.get_unrealized_pnl(greeks.mark_prices[i])?)
+                .ok_or_else(math_error)
+        })
+}
+
+// Margin account of the cross-margin model, one per authority and subaccount
+// index instead of one per zeta group. Product ledgers share the zeta group
+// product indexing.
+#[account(zero_copy)]
+#[repr(packed)]
+pub struct CrossMarginAccount {
+    pub authority: Pubkey,                             // 32
+    pub delegated_pubkey: Pubkey,                      // 32
+    pub balance: u64,                                  // 8
+    pub subaccount_index: u8,                          // 1
+    pub nonce: u8,                                     // 1
+    pub force_cancel_flag: bool,                       // 1
+    pub account_type: MarginAccountType,               // 1
+    pub open_orders_nonce: [u8; 138],                  // 138
+    pub product_ledgers: [ProductLedger; 46],          // 138 * 40 = 5520
+    pub _product_ledgers_padding: [ProductLedger; 92], //
+    pub rebalance_amount: i64,                         // 8
+    pub _padding: [u8; 338],                           // 338
+} // 6144
+
+impl CrossMarginAccount {
+    pub fn get_address(authority: &Pubkey, subaccount_index: u8) -> Pubkey {
+        Pubkey::find_program_address(
+            &[
+                CROSS_MARGIN_SEED.as_bytes(),
+                authority.as_ref(),
+                &[subaccount_index],
+            ],
+            &zeta_program::ID,
+        )
+        .0
+    }
+
+    pub fn get_manager_address(authority: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[CROSS_MARGIN_ACCOUNT_MANAGER_SEED.as_bytes(), authority.as_ref()],
+            &zeta_program::ID,
+        )
+        .0
+    }
+
+    // Initial margin of the open orders and positions under `margin_parameters`.
+    pub fn get_initial_margin_with_parameters(
+        &self,
+        greeks: &Greeks,
+        zeta_group: &ZetaGroup,
+        spot: u64,
+        margin_parameters: &MarginParameters,
+    ) -> Result<u64> {
+        get_ledgers_initial_margin(
+            &self.product_ledgers,
+            greeks,
+            zeta_group,
+            spot,
+            margin_parameters,
+        )
+    }
+
+    // Maintenance margin of the positions under `margin_parameters`.
+    pub fn get_maintenance_margin_with_parameters(
+        &self,
+        greeks: &Greeks,
+        zeta_group: &ZetaGroup,
+        spot: u64,
+        margin_parameters: &MarginParameters,
+    ) -> Result<u64> {
+        get_ledgers_maintenance_margin(
+            &self.product_ledgers,
+            greeks,
+            zeta_group,
+            spot,
+            margin_parameters,
+        )
+    }
+
+    pub fn get_unrealized_pnl(&self, greeks: &Greeks) -> Result<i64> {
+        get_ledgers_unrealized_pnl(&self.product_ledgers, greeks)
+    }
+}
+
 // Enum Types
 #[repr(u8)]
 #[derive(PartialEq, Debug)]
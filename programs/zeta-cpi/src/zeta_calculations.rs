@@ -0,0 +1,392 @@
+use super::*;
+use anchor_lang::prelude::*;
+use rust_decimal::prelude::*;
+
+#[derive(Debug)]
+pub struct MarginAccountState {
+    pub balance: u64,                       // Balance of the margin account.
+    pub initial_margin: u64,                // Initial margin requirements of orders and positions.
+    pub maintenance_margin: u64,            // Maintenance margin requirements of positions.
+    pub unrealized_pnl: i64,                // Unrealized pnl of positions.
+    pub available_balance_initial: i64,     // Available balance remaining for trading.
+    pub available_balance_maintenance: i64, // Available balance before being at risk of liquidation
+    pub account_equity: i64,                // Effective value of account
+}
+
+pub fn calculate_margin_account_state(
+    zeta_group: &ZetaGroup,
+    margin_account: &MarginAccount,
+    greeks: &Greeks,
+    oracle: &AccountInfo,
+    oracle_config: &pyth_client::PriceFeedConfig,
+) -> Result<MarginAccountState> {
+    calculate_margin_account_state_with_parameters(
+        zeta_group,
+        &zeta_group.margin_parameters,
+        margin_account,
+        greeks,
+        oracle,
+        oracle_config,
+    )
+}
+
+/// `calculate_margin_account_state` with the margin requirements computed
+/// under `margin_parameters` instead of the zeta group ones.
+pub fn calculate_margin_account_state_with_parameters(
+    zeta_group: &ZetaGroup,
+    margin_parameters: &MarginParameters,
+    margin_account: &MarginAccount,
+    greeks: &Greeks,
+    oracle: &AccountInfo,
+    oracle_config: &pyth_client::PriceFeedConfig,
+) -> Result<MarginAccountState> {
+    let spot_price = get_native_oracle_price(oracle, oracle_config)?;
+    let initial_margin = margin_account.get_initial_margin_with_parameters(
+        greeks,
+        zeta_group,
+        spot_price,
+        margin_parameters,
+    )?;
+    let maintenance_margin = margin_account.get_maintenance_margin_with_parameters(
+        greeks,
+        zeta_group,
+        spot_price,
+        margin_parameters,
+    )?;
+    let unrealized_pnl = margin_account.get_unrealized_pnl(greeks)?;
+    build_margin_account_state(
+        margin_account.balance,
+        initial_margin,
+        maintenance_margin,
+        unrealized_pnl,
+    )
+}
+
+/// `calculate_margin_account_state_with_parameters` for the cross-margin
+/// account model.
+pub fn calculate_cross_margin_account_state_with_parameters(
+    zeta_group: &ZetaGroup,
+    margin_parameters: &MarginParameters,
+    cross_margin_account: &CrossMarginAccount,
+    greeks: &Greeks,
+    oracle: &AccountInfo,
+    oracle_config: &pyth_client::PriceFeedConfig,
+) -> Result<MarginAccountState> {
+    let spot_price = get_native_oracle_price(oracle, oracle_config)?;
+    let initial_margin = cross_margin_account.get_initial_margin_with_parameters(
+        greeks,
+        zeta_group,
+        spot_price,
+        margin_parameters,
+    )?;
+    let maintenance_margin = cross_margin_account.get_maintenance_margin_with_parameters(
+        greeks,
+        zeta_group,
+        spot_price,
+        margin_parameters,
+    )?;
+    let unrealized_pnl = cross_margin_account.get_unrealized_pnl(greeks)?;
+    build_margin_account_state(
+        cross_margin_account.balance,
+        initial_margin,
+        maintenance_margin,
+        unrealized_pnl,
+    )
+}
+
+/// State of whichever account model `margin_account` is, a `CrossMarginAccount`
+/// when `cross_margin` is set and a `MarginAccount` otherwise.
+pub fn load_margin_account_state(
+    zeta_group: &ZetaGroup,
+    margin_parameters: &MarginParameters,
+    margin_account: &AccountInfo,
+    cross_margin: bool,
+    greeks: &Greeks,
+    oracle: &AccountInfo,
+    oracle_config: &pyth_client::PriceFeedConfig,
+) -> Result<MarginAccountState> {
+    if cross_margin {
+        let cross_margin_account = load_cross_margin_account(margin_account)?;
+        calculate_cross_margin_account_state_with_parameters(
+            zeta_group,
+            margin_parameters,
+            &cross_margin_account,
+            greeks,
+            oracle,
+            oracle_config,
+        )
+    } else {
+        let margin_account = load_margin_account(margin_account)?;
+        calculate_margin_account_state_with_parameters(
+            zeta_group,
+            margin_parameters,
+            &margin_account,
+            greeks,
+            oracle,
+            oracle_config,
+        )
+    }
+}
+
+fn build_margin_account_state(
+    balance: u64,
+    initial_margin: u64,
+    maintenance_margin: u64,
+    unrealized_pnl: i64,
+) -> Result<MarginAccountState> {
+    let available_balance_initial = (balance as i64)
+        .checked_add(unrealized_pnl)
+        .ok_or_else(math_error)?
+        .checked_sub(initial_margin as i64)
+        .ok_or_else(math_error)?;
+    let available_balance_maintenance = (balance as i64)
+        .checked_add(unrealized_pnl)
+        .ok_or_else(math_error)?
+        .checked_sub(maintenance_margin as i64)
+        .ok_or_else(math_error)?;
+    let account_equity = (balance as i64)
+        .checked_add(unrealized_pnl)
+        .ok_or_else(math_error)?;
+
+    Ok(MarginAccountState {
+        balance,
+        initial_margin,
+        maintenance_margin,
+        unrealized_pnl,
+        available_balance_initial,
+        available_balance_maintenance,
+        account_equity,
+    })
+}
+
+/// Balance plus unrealized pnl of the positions at the greeks mark prices.
+/// Resting orders don't move the balance, zeta mints the order funds on
+/// placement, so their value only shows up once fills are cranked into
+/// positions.
+pub fn calculate_margin_account_equity(margin_account: &MarginAccount, greeks: &Greeks) -> Result<i64> {
+    (margin_account.balance as i64)
+        .checked_add(margin_account.get_unrealized_pnl(greeks)?)
+        .ok_or_else(math_error)
+}
+
+/// `calculate_margin_account_equity` for the cross-margin account model.
+pub fn calculate_cross_margin_account_equity(
+    cross_margin_account: &CrossMarginAccount,
+    greeks: &Greeks,
+) -> Result<i64> {
+    (cross_margin_account.balance as i64)
+        .checked_add(cross_margin_account.get_unrealized_pnl(greeks)?)
+        .ok_or_else(math_error)
+}
+
+/// Mark value of the resting orders, opening bids and asks and the closing
+/// orders together. Not part of the equity, see
+/// `calculate_margin_account_equity`.
+pub fn calculate_open_orders_value(
+    product_ledgers: &[ProductLedger],
+    greeks: &Greeks,
+) -> Result<u64> {
+    let mut value: u128 = 0;
+    for (i, ledger) in product_ledgers.iter().enumerate() {
+        let order_state = &ledger.order_state;
+        let lots = (order_state.opening_orders[BID_ORDERS_INDEX] as u128)
+            .checked_add(order_state.opening_orders[ASK_ORDERS_INDEX] as u128)
+            .ok_or_else(math_error)?
+            .checked_add(order_state.closing_orders as u128)
+            .ok_or_else(math_error)?;
+        let lots_value = lots
+            .checked_mul(greeks.mark_prices[i] as u128)
+            .ok_or_else(math_error)?
+            .checked_div(POSITION_PRECISION_DENOMINATOR)
+            .ok_or_else(math_error)?;
+        value = value.checked_add(lots_value).ok_or_else(math_error)?;
+    }
+    u64::try_from(value).map_err(|_| math_error())
+}
+
+/// Realized pnl of the series positions at the settlement price, the same
+/// amount zeta credits to the balance when the positions are settled.
+pub fn calculate_settlement_pnl(
+    zeta_group: &ZetaGroup,
+    margin_account: &MarginAccount,
+    settlement: &SettlementAccount,
+    expiry_index: usize,
+) -> Result<i64> {
+    let settlement_price = settlement.settlement_price;
+    let products = zeta_group.get_products_slice(expiry_index);
+    let ledgers = margin_account.get_product_ledgers_slice(expiry_index);
+
+    let mut pnl: i64 = 0;
+    for (i, ledger) in ledgers.iter().enumerate() {
+        let strike = settlement.strikes[i];
+        let exercise_value = match products[i].kind {
+            Kind::Call => settlement_price.saturating_sub(strike),
+            Kind::Put => strike.saturating_sub(settlement_price),
+            Kind::Future => settlement_price,
+            Kind::Uninitialized => continue,
+        };
+        let position = ledger.position;
+        pnl = pnl
+            .checked_add(position.get_unrealized_pnl(exercise_value)?)
+            .ok_or_else(math_error)?;
+    }
+    Ok(pnl)
+}
+
+/// Unrealized pnl of the positions of a series at the greeks mark prices,
+/// its part of `calculate_margin_account_equity`.
+pub fn calculate_series_unrealized_pnl(
+    margin_account: &MarginAccount,
+    greeks: &Greeks,
+    expiry_index: usize,
+) -> Result<i64> {
+    let mark_prices = greeks.get_mark_prices_slice(expiry_index);
+    let ledgers = margin_account.get_product_ledgers_slice(expiry_index);
+
+    let mut pnl: i64 = 0;
+    for (i, ledger) in ledgers.iter().enumerate() {
+        let position = ledger.position;
+        pnl = pnl
+            .checked_add(position.get_unrealized_pnl(mark_prices[i])?)
+            .ok_or_else(math_error)?;
+    }
+    Ok(pnl)
+}
+
+#[derive(Debug, Default)]
+pub struct ShortSettlement {
+    pub premium: u64,         // Premium received for the short option positions.
+    pub assignment_loss: u64, // Their exercise value at the settlement price.
+}
+
+/// The short option leg of `calculate_settlement_pnl`, split into the premium
+/// collected and the exercise value owed.
+pub fn calculate_short_settlement(
+    zeta_group: &ZetaGroup,
+    margin_account: &MarginAccount,
+    settlement: &SettlementAccount,
+    expiry_index: usize,
+) -> Result<ShortSettlement> {
+    let settlement_price = settlement.settlement_price;
+    let products = zeta_group.get_products_slice(expiry_index);
+    let ledgers = margin_account.get_product_ledgers_slice(expiry_index);
+
+    let mut short = ShortSettlement::default();
+    for (i, ledger) in ledgers.iter().enumerate() {
+        let position = ledger.position;
+        if position.size >= 0 {
+            continue;
+        }
+        let strike = settlement.strikes[i];
+        let exercise_value = match products[i].kind {
+            Kind::Call => settlement_price.saturating_sub(strike),
+            Kind::Put => strike.saturating_sub(settlement_price),
+            Kind::Future | Kind::Uninitialized => continue,
+        };
+        let assignment_loss = (position.size_abs() as u128)
+            .checked_mul(exercise_value as u128)
+            .ok_or_else(math_error)?
+            .checked_div(POSITION_PRECISION_DENOMINATOR as u128)
+            .ok_or_else(math_error)? as u64;
+        short.premium = short.premium
+            .checked_add(position.cost_of_trades)
+            .ok_or_else(math_error)?;
+        short.assignment_loss = short.assignment_loss
+            .checked_add(assignment_loss)
+            .ok_or_else(math_error)?;
+    }
+    Ok(short)
+}
+
+#[derive(Debug, Default)]
+pub struct GreeksExposure {
+    pub max_abs_delta: u64, // Underlying units, 3.dp (POSITION_PRECISION).
+    pub max_abs_vega: u64,  // 6.dp (PLATFORM_PRECISION).
+    pub net_delta: i64,     // Filled positions only, 3.dp (POSITION_PRECISION).
+}
+
+/// Aggregate delta and vega of the margin account positions.
+/// Open orders are assumed to fill, bids and asks are taken as separate
+/// scenarios and the worst absolute value of each greek is returned.
+pub fn calculate_greeks_exposure(
+    zeta_group: &ZetaGroup,
+    margin_account: &MarginAccount,
+    greeks: &Greeks,
+) -> Result<GreeksExposure> {
+    calculate_ledgers_greeks_exposure(zeta_group, &margin_account.product_ledgers, greeks)
+}
+
+/// `calculate_greeks_exposure` for the cross-margin account model.
+pub fn calculate_cross_margin_greeks_exposure(
+    zeta_group: &ZetaGroup,
+    cross_margin_account: &CrossMarginAccount,
+    greeks: &Greeks,
+) -> Result<GreeksExposure> {
+    calculate_ledgers_greeks_exposure(zeta_group, &cross_margin_account.product_ledgers, greeks)
+}
+
+fn calculate_ledgers_greeks_exposure(
+    zeta_group: &ZetaGroup,
+    product_ledgers: &[ProductLedger],
+    greeks: &Greeks,
+) -> Result<GreeksExposure> {
+    let delta_unit = 10i128.pow(PRICING_PRECISION);
+    let mut delta = [0i128; 2];
+    let mut vega = [Decimal::ZERO; 2];
+    let mut net_delta = 0i128;
+
+    for (i, ledger) in product_ledgers.iter().enumerate() {
+        let product = &zeta_group.products[i];
+        let position = ledger.position.size as i128;
+        let bids = ledger.order_state.opening_orders[BID_ORDERS_INDEX] as i128;
+        let asks = ledger.order_state.opening_orders[ASK_ORDERS_INDEX] as i128;
+        let sizes = [position + bids, position - asks];
+
+        let expiry_index = i / NUM_PRODUCTS_PER_SERIES;
+        let strike_index = (i % NUM_PRODUCTS_PER_SERIES) % NUM_STRIKES;
+        let product_greeks = &greeks.product_greeks[
+            expiry_index * NUM_STRIKES + strike_index
+        ];
+        let (product_delta, product_vega) = match product.kind {
+            Kind::Call => (product_greeks.delta as i128, Decimal::from(product_greeks.vega)),
+            Kind::Put => (
+                (product_greeks.delta as i128).checked_sub(delta_unit).ok_or_else(math_error)?,
+                Decimal::from(product_greeks.vega),
+            ),
+            Kind::Future => (delta_unit, Decimal::ZERO),
+            Kind::Uninitialized => continue,
+        };
+
+        let position_delta = position.checked_mul(product_delta).ok_or_else(math_error)? / delta_unit;
+        net_delta = net_delta.checked_add(position_delta).ok_or_else(math_error)?;
+        for (scenario, size) in sizes.iter().enumerate() {
+            let size_delta = size.checked_mul(product_delta).ok_or_else(math_error)? / delta_unit;
+            delta[scenario] = delta[scenario].checked_add(size_delta).ok_or_else(math_error)?;
+            let size_vega = product_vega
+                .checked_mul(Decimal::from(*size as i64))
+                .ok_or_else(math_error)?;
+            vega[scenario] = vega[scenario].checked_add(size_vega).ok_or_else(math_error)?;
+        }
+    }
+
+    let vega_scale = Decimal::from(10u64.pow(PLATFORM_PRECISION))
+        .checked_div(Decimal::from(POSITION_PRECISION_DENOMINATOR as u64))
+        .ok_or_else(math_error)?;
+    let mut max_abs_vega = 0u64;
+    for v in vega.iter() {
+        let abs_vega = v.abs()
+            .checked_mul(vega_scale)
+            .and_then(|v| v.to_u64())
+            .ok_or_else(math_error)?;
+        max_abs_vega = max_abs_vega.max(abs_vega);
+    }
+    let max_abs_delta = delta[0].unsigned_abs().max(delta[1].unsigned_abs())
+        .try_into()
+        .map_err(|_| math_error())?;
+
+    Ok(GreeksExposure {
+        max_abs_delta,
+        max_abs_vega,
+        net_delta: net_delta.try_into().map_err(|_| math_error())?,
+    })
+}
@@ -0,0 +1,252 @@
+use std::num::NonZeroU64;
+use super::zeta_context::*;
+use super::*;
+use cpi_interface::global_interface;
+use anchor_lang::prelude::*;
+
+/// Zeta Program Client
+/// Defines a clean interface and set of helper functions to make CPI calls to the Zeta Program
+
+#[global_interface]
+pub trait ZetaInterface<'info, T: Accounts<'info>> {
+    fn initialize_margin_account(ctx: Context<T>) -> Result<()>;
+    fn deposit(ctx: Context<T>, amount: u64) -> Result<()>;
+    fn withdraw(ctx: Context<T>, amount: u64) -> Result<()>;
+    fn initialize_open_orders(ctx: Context<T>) -> Result<()>;
+    fn close_open_orders(ctx: Context<T>, map_nonce: u8) -> Result<()>;
+    fn place_order(
+        ctx: Context<T>,
+        price: u64,
+        size: u64,
+        side: Side,
+        client_order_id: Option<u64>,
+    ) -> Result<()>;
+    fn place_order_v3(
+        ctx: Context<T>,
+        price: u64,
+        size: u64,
+        side: Side,
+        order_type: OrderType,
+        client_order_id: Option<u64>,
+        tag: Option<String>,
+    ) -> Result<()>;
+    fn place_order_v4(
+        ctx: Context<T>,
+        price: u64,
+        size: u64,
+        side: Side,
+        order_type: OrderType,
+        client_order_id: Option<u64>,
+        tag: Option<String>,
+        tif_offset: Option<u16>,
+    ) -> Result<()>;
+    fn cancel_order(ctx: Context<T>, side: Side, order_id: u128) -> Result<()>;
+    fn cancel_order_by_client_order_id(ctx: Context<T>, client_order_id: u64) -> Result<()>;
+    fn initialize_cross_margin_account_manager(ctx: Context<T>) -> Result<()>;
+    fn initialize_cross_margin_account(ctx: Context<T>, subaccount_index: u8) -> Result<()>;
+    fn deposit_v2(ctx: Context<T>, amount: u64) -> Result<()>;
+    fn withdraw_v2(ctx: Context<T>, amount: u64) -> Result<()>;
+    fn place_cross_margin_order(
+        ctx: Context<T>,
+        price: u64,
+        size: u64,
+        side: Side,
+        order_type: OrderType,
+        client_order_id: Option<u64>,
+        tag: Option<String>,
+        tif_offset: Option<u16>,
+    ) -> Result<()>;
+}
+
+pub fn initialize_margin_account<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: InitializeMarginAccount<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::initialize_margin_account(cpi_ctx)
+}
+
+pub fn deposit<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: Deposit<'info>,
+    amount: u64,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::deposit(cpi_ctx, amount)
+}
+
+pub fn withdraw<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: Withdraw<'info>,
+    amount: u64,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::withdraw(cpi_ctx, amount)
+}
+
+pub fn initialize_open_orders<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: InitializeOpenOrders<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::initialize_open_orders(cpi_ctx)
+}
+
+pub fn close_open_orders<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: CloseOpenOrders<'info>,
+    map_nonce: u8,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::close_open_orders(cpi_ctx, map_nonce)
+}
+
+pub fn place_order<'info>(
+  zeta_program: AccountInfo<'info>,
+  cpi_accounts: PlaceOrder<'info>,
+  price: u64,
+  size: u64,
+  side: Side,
+  client_order_id: Option<u64>,
+  seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::place_order(cpi_ctx, price, size, side, client_order_id)
+}
+
+pub fn place_order_v3<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: PlaceOrder<'info>,
+    price: u64,
+    size: u64,
+    side: Side,
+    order_type: OrderType,
+    client_order_id: Option<u64>,
+    tag: Option<String>, // Not stored, only used when sniffing the transactions
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::place_order_v3(cpi_ctx, price, size, side, order_type, client_order_id, tag)
+}
+
+pub fn place_order_v4<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: PlaceOrder<'info>,
+    price: u64,
+    size: u64,
+    side: Side,
+    order_type: OrderType,
+    client_order_id: Option<u64>,
+    tag: Option<String>,
+    tif_offset: Option<u16>, // Seconds until zeta expires the order, None rests until cancelled
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::place_order_v4(cpi_ctx, price, size, side, order_type, client_order_id, tag, tif_offset)
+}
+
+pub fn cancel_order<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: CancelOrder<'info>,
+    side: Side,
+    order_id: u128,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::cancel_order(cpi_ctx, side, order_id)
+}
+
+pub fn cancel_order_by_client_order_id<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: CancelOrder<'info>,
+    client_order_id: u64,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::cancel_order_by_client_order_id(cpi_ctx, client_order_id)
+}
+
+pub fn initialize_cross_margin_account_manager<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: InitializeCrossMarginAccountManager<'info>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::initialize_cross_margin_account_manager(cpi_ctx)
+}
+
+pub fn initialize_cross_margin_account<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: InitializeCrossMarginAccount<'info>,
+    subaccount_index: u8,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::initialize_cross_margin_account(cpi_ctx, subaccount_index)
+}
+
+pub fn deposit_v2<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: DepositV2<'info>,
+    amount: u64,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::deposit_v2(cpi_ctx, amount)
+}
+
+pub fn withdraw_v2<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: WithdrawV2<'info>,
+    amount: u64,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::withdraw_v2(cpi_ctx, amount)
+}
+
+// Same accounts as `place_order_v4`, with the cross-margin account as `margin_account`
+pub fn place_cross_margin_order<'info>(
+    zeta_program: AccountInfo<'info>,
+    cpi_accounts: PlaceOrder<'info>,
+    price: u64,
+    size: u64,
+    side: Side,
+    order_type: OrderType,
+    client_order_id: Option<u64>,
+    tag: Option<String>,
+    tif_offset: Option<u16>,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(zeta_program, cpi_accounts, signer);
+    zeta_interface::place_cross_margin_order(
+        cpi_ctx,
+        price,
+        size,
+        side,
+        order_type,
+        client_order_id,
+        tag,
+        tif_offset,
+    )
+}
@@ -20,6 +20,8 @@ pub const QUOTE_MINT_SEED: &str = "quote-mint";
 pub const MARKET_SEED: &str = "market";
 pub const MARKET_INDEXES_SEED: &str = "market-indexes";
 pub const SOCIALIZED_LOSS_SEED: &str = "socialized-loss";
+pub const CROSS_MARGIN_SEED: &str = "cross-margin";
+pub const CROSS_MARGIN_ACCOUNT_MANAGER_SEED: &str = "cross-margin-manager";
 
 pub const PLATFORM_PRECISION: u32 = 6;
 pub const HALT_SPOT_PRICE_PRECISION: u32 = 6;
@@ -31,6 +33,8 @@ pub const DEFAULT_MINT_LOT_SIZE: u64 = 1;
 pub const DISCRIMINATOR_SIZE: usize = 8;
 pub const MARK_PRICE_PERCENTAGE: u128 = 100;
 pub const PRICE_BAND_MULTIPLE: u64 = 10;
+// Approximate slot time, used to convert slot ages to seconds
+pub const MS_PER_SLOT: u64 = 400;
 
 pub const NUM_STRIKES: usize = 11;
 pub const NUM_PRODUCTS_PER_SERIES: usize = NUM_STRIKES * 2 + 1;
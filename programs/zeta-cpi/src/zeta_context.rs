@@ -106,6 +106,32 @@ pub struct InitializeOpenOrders<'info> {
   pub rent: Sysvar<'info, Rent>,
 }
 
+#[derive(Accounts, Clone)]
+pub struct CloseOpenOrders<'info> {
+  /// CHECK: checked via external program
+  pub state: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub zeta_group: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub dex_program: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub open_orders: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: Receives the rent. checked via external program
+  #[account(mut, signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub market: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub serum_authority: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub open_orders_map: AccountInfo<'info>,
+}
+
 // Market accounts are the accounts used to place orders against the dex minus
 // common accounts, i.e., program ids, sysvars, and the `pc_wallet`.
 #[derive(Accounts, Clone)]
@@ -252,3 +278,86 @@ pub struct Liquidate<'info> {
   #[account(mut)]
   pub liquidated_margin_account: AccountInfo<'info>,
 }
+
+#[derive(Accounts, Clone)]
+pub struct InitializeCrossMarginAccountManager<'info> {
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub cross_margin_account_manager: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub payer: Signer<'info>,
+  /// CHECK: checked via external program
+  pub zeta_program: AccountInfo<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts, Clone)]
+pub struct InitializeCrossMarginAccount<'info> {
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub cross_margin_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub cross_margin_account_manager: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  #[account(mut)]
+  pub payer: Signer<'info>,
+  /// CHECK: checked via external program
+  pub zeta_program: AccountInfo<'info>,
+  pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts, Clone)]
+pub struct DepositV2<'info> {
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub vault: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub user_token_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  /// CHECK: checked via external program
+  pub state: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub greeks: AccountInfo<'info>,
+}
+
+#[derive(Accounts, Clone)]
+pub struct WithdrawV2<'info> {
+  /// CHECK: checked via external program
+  pub state: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub greeks: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub vault: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub margin_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub user_token_account: AccountInfo<'info>,
+  pub token_program: Program<'info, Token>,
+  /// CHECK: checked via external program
+  #[account(signer)]
+  pub authority: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  #[account(mut)]
+  pub socialized_loss_account: AccountInfo<'info>,
+  /// CHECK: checked via external program
+  pub oracle: AccountInfo<'info>,
+}
@@ -0,0 +1,475 @@
+use super::*;
+use anchor_lang::prelude::*;
+use std::cell::RefMut;
+use std::convert::{TryFrom, TryInto};
+use std::ops::DerefMut;
+
+#[macro_export]
+macro_rules! wrap_error {
+    ($err:expr) => {{
+        msg!("Error thrown at {}:{}", file!(), line!());
+        $err
+    }};
+}
+
+#[error_code]
+pub enum FuzeErrorCode {
+  #[msg("Account not mutable")]
+  AccountNotMutable,
+  #[msg("Unsupported kind")]
+  UnsupportedKind,
+  #[msg("Product strike uninitialized")]
+  ProductStrikeUninitialized,
+  #[msg("Invalid product market key")]
+  InvalidProductMarketKey,
+  #[msg("Market not live")]
+  MarketNotLive,
+  #[msg("Product dirty")]
+  ProductDirty,
+  #[msg("Invalid option kind, must be Call or Put")]
+  InvalidOptionKind,
+  #[msg("Greeks account doesn't belong to the zeta group")]
+  InvalidGreeksAccount,
+  #[msg("Margin account doesn't belong to the zeta group")]
+  InvalidMarginAccount,
+  #[msg("Oracle doesn't match the zeta group oracle")]
+  InvalidOracle,
+  #[msg("Oracle account doesn't match the price feed")]
+  InvalidPriceFeed,
+  #[msg("Price update is not fully verified")]
+  UnverifiedPrice,
+  #[msg("Oracle price is stale")]
+  StalePrice,
+  #[msg("Invalid side, must be Bid or Ask")]
+  InvalidSide,
+  #[msg("Math overflow")]
+  MathOverflow,
+  #[msg("Oracle price is not positive")]
+  InvalidOraclePrice,
+  #[msg("Margin concession only applies to market makers, between 1 and 100%")]
+  InvalidMarginConcession,
+  #[msg("Account is not owned by the zeta program")]
+  InvalidAccountOwner,
+  #[msg("Account discriminator doesn't match the zeta account type")]
+  InvalidAccountDiscriminator,
+  #[msg("Product index out of the zeta group products")]
+  InvalidProductIndex,
+}
+
+/// Margin and oracle math fails with an error code instead of panicking on
+/// bad zeta or oracle data.
+pub(crate) fn math_error() -> Error {
+    error!(FuzeErrorCode::MathOverflow)
+}
+
+/// Casts the account data without checking owner or discriminator, zeta
+/// state goes through `load_zeta_account` and the typed loaders instead.
+pub fn deserialize_account_info_zerocopy<'a, T: bytemuck::Pod>(
+    account_info: &'a AccountInfo,
+) -> Result<RefMut<'a, T>> {
+    let data = account_info.try_borrow_mut_data()?;
+    let size = std::mem::size_of::<T>();
+    if data.len() != 8 + size {
+        return wrap_error!(Err(error!(FuzeErrorCode::InvalidAccountDiscriminator)));
+    }
+    Ok(RefMut::map(data, |data| {
+        bytemuck::from_bytes_mut(&mut data.deref_mut()[8..])
+    }))
+}
+
+/// Zero-copy view of a zeta account, after checking it is owned by the zeta
+/// program and starts with the discriminator of `T`.
+pub fn load_zeta_account<'a, T: bytemuck::Pod + Discriminator>(
+    account_info: &'a AccountInfo,
+) -> Result<RefMut<'a, T>> {
+    if *account_info.owner != zeta_program::ID {
+        msg!("account {} owner {}", account_info.key, account_info.owner);
+        return wrap_error!(Err(error!(FuzeErrorCode::InvalidAccountOwner)));
+    }
+    let data = account_info.try_borrow_mut_data()?;
+    let size = std::mem::size_of::<T>();
+    if data.len() < 8 + size || data[..8] != T::discriminator() {
+        return wrap_error!(Err(error!(FuzeErrorCode::InvalidAccountDiscriminator)));
+    }
+    Ok(RefMut::map(data, |data| {
+        bytemuck::from_bytes_mut(&mut data.deref_mut()[8..8 + size])
+    }))
+}
+
+pub fn load_zeta_group<'a>(account_info: &'a AccountInfo) -> Result<RefMut<'a, ZetaGroup>> {
+    load_zeta_account::<ZetaGroup>(account_info)
+}
+
+pub fn load_margin_account<'a>(account_info: &'a AccountInfo) -> Result<RefMut<'a, MarginAccount>> {
+    load_zeta_account::<MarginAccount>(account_info)
+}
+
+pub fn load_cross_margin_account<'a>(account_info: &'a AccountInfo) -> Result<RefMut<'a, CrossMarginAccount>> {
+    load_zeta_account::<CrossMarginAccount>(account_info)
+}
+
+pub fn load_settlement_account<'a>(account_info: &'a AccountInfo) -> Result<RefMut<'a, SettlementAccount>> {
+    load_zeta_account::<SettlementAccount>(account_info)
+}
+
+/// Deserializes the greeks account, failing for the greeks of another zeta group.
+///
+/// # Arguments
+///
+/// * `zeta_group` - Zeta group the greeks belong to.
+/// * `greeks` - Greeks account info.
+pub fn deserialize_greeks<'a>(
+    zeta_group: &ZetaGroup,
+    greeks: &'a AccountInfo,
+) -> Result<RefMut<'a, Greeks>> {
+    if greeks.key() != zeta_group.greeks {
+        return wrap_error!(Err(error!(FuzeErrorCode::InvalidGreeksAccount)));
+    }
+    load_zeta_account::<Greeks>(greeks)
+}
+
+#[inline(never)]
+pub fn deserialize_account_info<'a, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
+    account_info: &AccountInfo<'a>,
+) -> Result<T> {
+    let mut data: &[u8] = &account_info.try_borrow_data()?;
+    Ok(T::try_deserialize_unchecked(&mut data)?)
+}
+
+pub fn get_otm_amount(spot: u64, strike: u64, product: Kind) -> Result<u64> {
+    match product {
+        Kind::Call => (strike as i128)
+            .checked_sub(spot as i128)
+            .ok_or_else(math_error)?
+            .max(0)
+            .try_into()
+            .map_err(|_| math_error()),
+        Kind::Put => (spot as i128)
+            .checked_sub(strike as i128)
+            .ok_or_else(math_error)?
+            .max(0)
+            .try_into()
+            .map_err(|_| math_error()),
+        _ => return wrap_error!(Err(error!(FuzeErrorCode::UnsupportedKind))),
+    }
+}
+
+/// Initial margin for single product
+pub fn get_initial_margin_per_lot(
+    spot: u64,
+    strike: u64,
+    mark: u64,
+    product: Kind,
+    side: Side,
+    margin_parameters: &MarginParameters,
+) -> Result<u64> {
+    let initial_margin: u128 = match product {
+        Kind::Future => (spot as u128)
+            .checked_mul(margin_parameters.future_margin_initial.into())
+            .ok_or_else(math_error)?
+            .checked_div(NATIVE_PRECISION_DENOMINATOR)
+            .ok_or_else(math_error)?,
+        Kind::Call | Kind::Put => match side {
+            Side::Bid => (spot as u128)
+                .checked_mul(margin_parameters.option_spot_percentage_long_initial.into())
+                .ok_or_else(math_error)?
+                .checked_div(NATIVE_PRECISION_DENOMINATOR)
+                .ok_or_else(math_error)?
+                .min(
+                    (mark as u128)
+                        .checked_mul(margin_parameters.option_mark_percentage_long_initial.into())
+                        .ok_or_else(math_error)?
+                        .checked_div(NATIVE_PRECISION_DENOMINATOR)
+                        .ok_or_else(math_error)?,
+                ),
+            Side::Ask => {
+                let otm_amount: u128 = get_otm_amount(spot, strike, product)?.into();
+                let otm_pct = otm_amount
+                    .checked_mul(NATIVE_PRECISION_DENOMINATOR)
+                    .ok_or_else(math_error)?
+                    .checked_div(spot.into())
+                    .ok_or_else(math_error)?;
+
+                let dynamic_margin_pct = (margin_parameters.option_dynamic_percentage_short_initial
+                    as u128)
+                    .checked_sub(otm_pct)
+                    .unwrap_or(0);
+
+                let margin_pct = dynamic_margin_pct.max(
+                    margin_parameters
+                        .option_spot_percentage_short_initial
+                        .into(),
+                );
+                margin_pct
+                    .checked_mul(spot.into())
+                    .ok_or_else(math_error)?
+                    .checked_div(NATIVE_PRECISION_DENOMINATOR)
+                    .ok_or_else(math_error)?
+            }
+            Side::Uninitialized => return wrap_error!(Err(error!(FuzeErrorCode::InvalidSide))),
+        },
+        _ => return wrap_error!(Err(error!(FuzeErrorCode::UnsupportedKind))),
+    };
+
+    if product == Kind::Put && side == Side::Ask {
+        let sell_put_cap_margin = (strike as u128)
+            .checked_mul(margin_parameters.option_short_put_cap_percentage as u128)
+            .ok_or_else(math_error)?
+            .checked_div(NATIVE_PRECISION_DENOMINATOR)
+            .ok_or_else(math_error)?;
+
+        return u64::try_from(initial_margin.min(sell_put_cap_margin)).map_err(|_| math_error());
+    }
+
+    u64::try_from(initial_margin).map_err(|_| math_error())
+}
+
+/// Maintenance margin for single product
+pub fn get_maintenance_margin_per_lot(
+    spot: u64,
+    strike: u64,
+    mark: u64,
+    product: Kind,
+    long: bool,
+    margin_parameters: &MarginParameters,
+) -> Result<u64> {
+    let maintenance_margin: u128 = match product {
+        Kind::Future => (spot as u128)
+            .checked_mul(margin_parameters.future_margin_maintenance.into())
+            .ok_or_else(math_error)?
+            .checked_div(NATIVE_PRECISION_DENOMINATOR)
+            .ok_or_else(math_error)?,
+        Kind::Call | Kind::Put => {
+            if long {
+                (spot as u128)
+                    .checked_mul(
+                        margin_parameters
+                            .option_spot_percentage_long_maintenance
+                            .into(),
+                    )
+                    .ok_or_else(math_error)?
+                    .checked_div(NATIVE_PRECISION_DENOMINATOR)
+                    .ok_or_else(math_error)?
+                    .min(
+                        (mark as u128)
+                            .checked_mul(
+                                margin_parameters
+                                    .option_mark_percentage_long_maintenance
+                                    .into(),
+                            )
+                            .ok_or_else(math_error)?
+                            .checked_div(NATIVE_PRECISION_DENOMINATOR)
+                            .ok_or_else(math_error)?,
+                    )
+            } else {
+                let otm_amount: u128 = get_otm_amount(spot, strike, product)?.into();
+                let otm_pct = otm_amount
+                    .checked_mul(NATIVE_PRECISION_DENOMINATOR)
+                    .ok_or_else(math_error)?
+                    .checked_div(spot.into())
+                    .ok_or_else(math_error)?;
+
+                let dynamic_margin_pct: u128 =
+                    (margin_parameters.option_dynamic_percentage_short_maintenance as u128)
+                        .checked_sub(otm_pct)
+                        .unwrap_or(0);
+
+                let margin_pct = dynamic_margin_pct.max(
+                    margin_parameters
+                        .option_spot_percentage_short_maintenance
+                        .into(),
+                );
+                margin_pct
+                    .checked_mul(spot.into())
+                    .ok_or_else(math_error)?
+                    .checked_div(NATIVE_PRECISION_DENOMINATOR)
+                    .ok_or_else(math_error)?
+            }
+        }
+        _ => return wrap_error!(Err(error!(FuzeErrorCode::UnsupportedKind))),
+    };
+
+    if product == Kind::Put && !long {
+        let sell_put_cap_margin = (strike as u128)
+            .checked_mul(margin_parameters.option_short_put_cap_percentage as u128)
+            .ok_or_else(math_error)?
+            .checked_div(NATIVE_PRECISION_DENOMINATOR)
+            .ok_or_else(math_error)?;
+
+        return u64::try_from(maintenance_margin.min(sell_put_cap_margin)).map_err(|_| math_error());
+    }
+
+    u64::try_from(maintenance_margin).map_err(|_| math_error())
+}
+
+/// Returns the native oracle price (6.dp)
+///
+/// # Arguments
+///
+/// * `oracle` - Oracle account.
+/// * `config` - Price feed configured for the vault.
+pub fn get_native_oracle_price(oracle: &AccountInfo, config: &pyth_client::PriceFeedConfig) -> Result<u64> {
+    get_oracle_price(oracle, config, PLATFORM_PRECISION)?
+        .try_into()
+        .map_err(|_| math_error())
+}
+
+pub fn get_oracle_price(
+    oracle: &AccountInfo,
+    config: &pyth_client::PriceFeedConfig,
+    precision: u32,
+) -> Result<i128> {
+    let (price, expo) = match config.source {
+        pyth_client::OracleSource::PythPush => {
+            let oracle_price = pyth_client::Price::load_checked(&oracle, &config.price_feed)?;
+            (oracle_price.agg.price, oracle_price.expo)
+        }
+        pyth_client::OracleSource::PythPull => {
+            let clock = Clock::get()?;
+            let price_update = pyth_receiver::PriceUpdateV2::load(&oracle)?;
+            let message = price_update.get_price_no_older_than(
+                &config.feed_id,
+                config.max_age,
+                clock.unix_timestamp,
+            )?;
+            (message.price, message.exponent)
+        }
+    };
+    if price <= 0 {
+        return wrap_error!(Err(error!(FuzeErrorCode::InvalidOraclePrice)));
+    }
+    let expo: u32 = (-expo).try_into().map_err(|_| math_error())?;
+    (price as u128)
+        .checked_mul(10u128.checked_pow(precision).ok_or_else(math_error)?)
+        .ok_or_else(math_error)?
+        .checked_div(10u128.checked_pow(expo).ok_or_else(math_error)?)
+        .ok_or_else(math_error)?
+        .try_into()
+        .map_err(|_| math_error())
+}
+
+/// Returns the age of the latest oracle price in seconds.
+/// Push feeds only expose the publish slot, so slots are converted with `MS_PER_SLOT`.
+pub fn get_oracle_age(
+    oracle: &AccountInfo,
+    config: &pyth_client::PriceFeedConfig,
+    clock: &Clock,
+) -> Result<u64> {
+    match config.source {
+        pyth_client::OracleSource::PythPush => {
+            let oracle_price = pyth_client::Price::load_checked(&oracle, &config.price_feed)?;
+            let slots = clock.slot.saturating_sub(oracle_price.agg.pub_slot);
+            Ok(slots.checked_mul(MS_PER_SLOT).ok_or_else(math_error)? / 1000)
+        }
+        pyth_client::OracleSource::PythPull => {
+            let price_update = pyth_receiver::PriceUpdateV2::load(&oracle)?;
+            Ok(clock.unix_timestamp
+                .saturating_sub(price_update.price_message.publish_time)
+                .max(0) as u64)
+        }
+    }
+}
+
+/// Checks that the market belongs to a live expiry series, is not dirty
+/// and has its strike set. Returns the product index of the market.
+///
+/// # Arguments
+///
+/// * `zeta_group` - Zeta group the market belongs to.
+/// * `market` - Serum market of the product.
+pub fn validate_market_tradeable(zeta_group: &ZetaGroup, market: &Pubkey) -> Result<usize> {
+    let (product_index, expiry_index) = zeta_group.get_product_and_expiry_index_by_key(market)?;
+    zeta_group.validate_series_tradeable(expiry_index)?;
+
+    let product = &zeta_group.products[product_index];
+    if product.dirty {
+        return wrap_error!(Err(error!(FuzeErrorCode::ProductDirty)));
+    }
+    if !product.strike.is_set() {
+        return wrap_error!(Err(error!(FuzeErrorCode::ProductStrikeUninitialized)));
+    }
+    Ok(product_index)
+}
+
+/// Returns true if the margin account has no positions and no open orders.
+///
+/// # Arguments
+///
+/// * `margin_account` - Margin account to check.
+pub fn is_margin_account_flat(margin_account: &MarginAccount) -> bool {
+    are_product_ledgers_flat(&margin_account.product_ledgers)
+}
+
+/// Returns true if the cross-margin account has no positions and no open orders.
+///
+/// # Arguments
+///
+/// * `cross_margin_account` - Cross-margin account to check.
+pub fn is_cross_margin_account_flat(cross_margin_account: &CrossMarginAccount) -> bool {
+    are_product_ledgers_flat(&cross_margin_account.product_ledgers)
+}
+
+fn are_product_ledgers_flat(product_ledgers: &[ProductLedger]) -> bool {
+    product_ledgers
+        .iter()
+        .all(|ledger| {
+            let order_state = ledger.order_state;
+            ledger.size() == 0 && !order_state.has_active_orders()
+        })
+}
+
+/// Returns, per active expiry series, whether the ledgers hold positions or
+/// open orders in it.
+///
+/// # Arguments
+///
+/// * `product_ledgers` - Margin account product ledgers.
+pub fn get_series_exposure(product_ledgers: &[ProductLedger]) -> [bool; ACTIVE_EXPIRIES] {
+    let mut exposure = [false; ACTIVE_EXPIRIES];
+    for (i, ledger) in product_ledgers.iter().enumerate().take(ACTIVE_MARKETS) {
+        if !ledger.empty() {
+            exposure[i / NUM_PRODUCTS_PER_SERIES] = true;
+        }
+    }
+    exposure
+}
+
+/// Checks the margin account is the one zeta derives for `authority` in the zeta group.
+///
+/// # Arguments
+///
+/// * `zeta_group` - Zeta group key.
+/// * `authority` - Margin account authority.
+/// * `margin_account` - Margin account key.
+pub fn validate_margin_account(
+    zeta_group: &Pubkey,
+    authority: &Pubkey,
+    margin_account: &Pubkey,
+) -> Result<()> {
+    let (address, _) = Pubkey::find_program_address(
+        &[
+            MARGIN_SEED.as_bytes(),
+            zeta_group.as_ref(),
+            authority.as_ref(),
+        ],
+        &zeta_program::ID,
+    );
+    if address != *margin_account {
+        return wrap_error!(Err(error!(FuzeErrorCode::InvalidMarginAccount)));
+    }
+    Ok(())
+}
+
+/// Returns the market index given an expiry index and index into the slice.
+///
+/// # Arguments
+///
+/// * `expiry_index` - Expiry series index.
+/// * `product_index` - Index into the products slice. [0..NUM_PRODUCTS_PER_SERIES).
+pub fn get_products_slice_market_index(expiry_index: usize, product_index: usize) -> Result<usize> {
+    expiry_index
+        .checked_mul(NUM_PRODUCTS_PER_SERIES)
+        .ok_or_else(math_error)?
+        .checked_add(product_index)
+        .ok_or_else(math_error)
+}
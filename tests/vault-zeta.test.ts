@@ -10,7 +10,8 @@ import BN from "bn.js";
 import {getVault, getVaultInfo} from "../app/src/pda/vault";
 import {getOrCreateATA} from "./util";
 import {SOL_MINT, USDC_MINT} from "../app/src/pubkeys";
-import {mintTo, syncNative} from "@solana/spl-token";
+import {getAccount, mintTo, syncNative} from "@solana/spl-token";
+import {assert} from "chai";
 import {Vault} from "../app/src/structs/vault";
 
 const anchor = require('@project-serum/anchor');
@@ -135,6 +136,32 @@ describe('Vault Zeta Markets + Solend', function () {
       );
       console.log(data);
     });
+    it('should withdraw from cUSDC vault with the Zeta margin fallback', async () => {
+      const {sharesMint} = await getVaultInfo(vaultUSDC);
+      const userAccount = await getOrCreateATA(
+        USDC_MINT,
+        provider,
+        user.publicKey
+      );
+      const userShares = await getOrCreateATA(
+        sharesMint,
+        provider,
+        user.publicKey
+      );
+      // the reserve pays what its liquidity covers, the margin account the rest
+      const data = await manager.withdraw(
+        new BN(userShares.amount.toString()).divn(2),
+        user,
+        userAccount.address,
+        userShares.address,
+        vaultUSDC,
+        false,
+        true
+      );
+      console.log(data);
+      const after = await getAccount(provider.connection, userAccount.address);
+      assert.isTrue(after.amount > userAccount.amount);
+    });
     it('should place bets on Zeta Markets', async () => {
       const data = await manager.bidOrder(
         new BN(1300 * 10 ** 6), // 1 ETH -> 1300 usd